@@ -303,6 +303,7 @@ export interface TerminalExecResponse {
   stdout: string;
   stderr: string;
   timedOut: boolean;
+  truncated?: boolean;
   durationMs: number;
 }
 
@@ -342,6 +343,7 @@ export interface GitStatusFile {
   staged: boolean;
   unstaged: boolean;
   untracked: boolean;
+  ignored?: boolean;
 }
 
 export interface GitDiffResponse {
@@ -440,6 +442,7 @@ export interface GitCommitResponse {
   stdout: string;
   stderr: string;
   committed: boolean;
+  hash?: string | null;
   cwd?: string;
 }
 
@@ -492,6 +495,7 @@ export interface PendingApproval {
   cwd?: string;
   grantRoot?: string;
   proposedExecpolicyAmendment?: string[];
+  diffPreview?: string;
 }
 
 export interface ResolveApprovalRequest {
@@ -701,6 +705,7 @@ export interface BridgeStatus {
   uptimeSec: number;
   connectedClients: number;
   devices: BridgeDeviceConnection[];
+  gitRoot?: string | null;
 }
 
 export interface BrowserPreviewSession {
@@ -44,6 +44,7 @@ interface RpcError {
 interface ReplayEventsResponse {
   events?: unknown[];
   hasMore?: boolean;
+  gapDetected?: boolean;
   earliestEventId?: number;
   latestEventId?: number;
 }
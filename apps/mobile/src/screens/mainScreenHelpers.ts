@@ -3036,5 +3036,6 @@ export function toPendingApproval(value: unknown): PendingApproval | null {
     cwd: readString(record.cwd) ?? undefined,
     grantRoot: readString(record.grantRoot) ?? undefined,
     proposedExecpolicyAmendment: readStringArray(record.proposedExecpolicyAmendment) ?? undefined,
+    diffPreview: readString(record.diffPreview) ?? undefined,
   };
 }
@@ -1,7 +1,7 @@
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     env,
     hash::{Hash, Hasher},
     io::{SeekFrom, Write},
@@ -9,7 +9,7 @@ use std::{
     process::Stdio,
     sync::{
         atomic::{AtomicBool, AtomicU64, Ordering},
-        Arc, OnceLock, RwLock as StdRwLock,
+        Arc, Mutex as StdMutex, OnceLock, RwLock as StdRwLock,
     },
     time::{Duration, Instant, SystemTime},
 };
@@ -17,13 +17,15 @@ use std::{
 use axum::{
     body::{to_bytes, Body},
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
         FromRequestParts, Query, Request, State,
     },
     http::{
         header::{
-            CACHE_CONTROL, CONNECTION, CONTENT_ENCODING, CONTENT_TYPE, COOKIE, HOST, LOCATION,
-            ORIGIN, REFERER, SET_COOKIE, UPGRADE, VARY,
+            ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+            ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, CACHE_CONTROL, CONNECTION,
+            CONTENT_ENCODING, CONTENT_TYPE, COOKIE, HOST, LOCATION, ORIGIN, REFERER, SET_COOKIE,
+            UPGRADE, VARY,
         },
         HeaderMap, HeaderValue, Method, StatusCode, Uri,
     },
@@ -33,21 +35,25 @@ use axum::{
 };
 use base64::{engine::general_purpose, Engine as _};
 use chrono::{DateTime, Utc};
-use futures_util::{stream, SinkExt, StreamExt};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use futures_util::{future::join_all, stream, SinkExt, StreamExt};
 use reqwest::{Client as HttpClient, Method as HttpMethod, Url};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use services::{GitService, TerminalService, UpdateService};
+use services::{GitService, GitStashAction, ReapingChild, TerminalService, UpdateService};
 use tokio::{
     fs,
     io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader},
     process::{Child, ChildStdin, ChildStdout, Command},
-    sync::{broadcast, mpsc, oneshot, watch, Mutex, RwLock},
-    time::{sleep, timeout},
+    sync::{broadcast, mpsc, oneshot, watch, Mutex, OnceCell, RwLock},
+    time::{sleep, timeout, timeout_at},
 };
 use tokio_tungstenite::{
     connect_async,
-    tungstenite::{client::IntoClientRequest, Message as UpstreamWsMessage},
+    tungstenite::{
+        client::IntoClientRequest, error::CapacityError, Error as TungsteniteError,
+        Message as UpstreamWsMessage,
+    },
 };
 
 mod services;
@@ -61,13 +67,73 @@ const REQUEST_USER_INPUT_METHOD_ALT: &str = "tool/requestUserInput";
 const DYNAMIC_TOOL_CALL_METHOD: &str = "item/tool/call";
 const ACCOUNT_CHATGPT_TOKENS_REFRESH_METHOD: &str = "account/chatgptAuthTokens/refresh";
 const BRIDGE_CHATGPT_AUTH_CACHE_FILE_NAME: &str = "chatgpt-auth.json";
+const CHATGPT_REFRESH_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+const CHATGPT_REFRESH_EXPIRY_SKEW: Duration = Duration::from_secs(60);
 const MOBILE_ATTACHMENTS_DIR: &str = ".clawdex-mobile-attachments";
 const MAX_ATTACHMENT_BYTES: usize = 20 * 1024 * 1024;
+const MAX_FS_WRITE_BYTES: usize = 10 * 1024 * 1024;
+const THREAD_EXPORT_MAX_ATTACHMENTS: usize = 200;
+const THREAD_EXPORT_INLINE_FILE_MAX_BYTES: u64 = 2 * 1024 * 1024;
+const THREAD_EXPORT_INLINE_TOTAL_MAX_BYTES: u64 = 8 * 1024 * 1024;
+/// Room for a `data:<mime>;base64,` header and padding when pre-checking encoded lengths.
+const BASE64_LENGTH_CHECK_SLACK: usize = 256;
+const MAX_FS_LIST_ENTRIES: usize = 1_000;
+const ATTACHMENT_IDEMPOTENCY_TTL: Duration = Duration::from_secs(10 * 60);
+const ATTACHMENT_IDEMPOTENCY_MAX_KEYS: usize = 256;
+const ATTACHMENT_IDEMPOTENCY_KEY_MAX_LEN: usize = 128;
 const DEFAULT_MAX_VOICE_TRANSCRIPTION_BYTES: usize = 100 * 1024 * 1024;
+const DEFAULT_TRANSCRIPTION_MODEL: &str = "gpt-4o-transcribe";
+const DEFAULT_TRANSCRIPTION_TIMEOUT_MS: u64 = 120_000;
+const GIT_WATCH_DEBOUNCE: Duration = Duration::from_millis(750);
+const GIT_WATCH_MAX_DELAY: Duration = Duration::from_secs(5);
+const MAX_GIT_STATUS_SUBSCRIPTIONS_PER_CLIENT: usize = 8;
+const MAX_GIT_STATUS_MULTI_CWDS: usize = 16;
+const GIT_WATCH_MAX_BATCH_PATHS: usize = 512;
 const NOTIFICATION_REPLAY_BUFFER_SIZE: usize = 2_000;
+/// Serialized size below which `BRIDGE_REPLAY_COMPRESS` keeps a notification as JSON; most
+/// notifications are small deltas that gzip barely shrinks.
+const REPLAY_COMPRESS_MIN_BYTES: usize = 4 * 1024;
 const NOTIFICATION_REPLAY_MAX_LIMIT: usize = 1_000;
+const NOTIFICATION_REPLAY_MAX_METHOD_FILTERS: usize = 32;
 const INTERNAL_NOTIFICATION_CHANNEL_CAPACITY: usize = 1_024;
-const WS_CLIENT_QUEUE_CAPACITY: usize = 256;
+const DEFAULT_WS_CLIENT_QUEUE_CAPACITY: usize = 256;
+const MIN_WS_CLIENT_QUEUE_CAPACITY: usize = 16;
+const MAX_WS_CLIENT_QUEUE_CAPACITY: usize = 4_096;
+const WS_CLIENT_SEND_TIMEOUT_BASE_MS: u64 = 250;
+const WS_CLIENT_SEND_TIMEOUT_MAX_MS: u64 = 4_000;
+const RECONNECT_RETRY_AFTER_BASE_MS: u64 = 1_000;
+const RECONNECT_RETRY_AFTER_MAX_MS: u64 = 30_000;
+const PRESENCE_BROADCAST_DEBOUNCE: Duration = Duration::from_millis(500);
+const DEFAULT_WS_MAX_FRAME_BYTES: usize = 32 * 1024 * 1024;
+const DEFAULT_APP_SERVER_MAX_LINE_BYTES: usize = 64 * 1024 * 1024;
+const APP_SERVER_SLOW_MESSAGE_THRESHOLD: Duration = Duration::from_millis(250);
+const HEALTH_DEEP_SLOW_THRESHOLD: Duration = Duration::from_secs(1);
+const HEALTH_DEEP_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_TERMINAL_HISTORY_SIZE: usize = 50;
+const DEFAULT_TERMINAL_MAX_OUTPUT_BYTES: usize = 10 * 1024 * 1024;
+const DEFAULT_DECLINE_ON_NO_CLIENTS_GRACE_MS: u64 = 120_000;
+const DEFAULT_CLIENT_RATE_LIMIT_BURST: u32 = 60;
+const MAX_CLIENT_BATCH_SIZE: usize = 50;
+/// Rate-limit bucket shared by all `POST /rpc` callers; WebSocket client ids start at 1.
+const HTTP_RPC_RATE_BUCKET_ID: u64 = 0;
+/// Bridge methods that stream notifications or keep per-connection state, which a
+/// one-shot `POST /rpc` request can't receive or clean up.
+const HTTP_RPC_UNSUPPORTED_METHODS: &[&str] = &[
+    "bridge/client/subscriptions/set",
+    "bridge/client/subscriptions/read",
+    "bridge/git/statusSubscribe",
+    "bridge/git/statusUnsubscribe",
+    "bridge/thread/list/stream/start",
+    "bridge/thread/list/stream/cancel",
+];
+const CLIENT_SESSION_TTL: Duration = Duration::from_secs(10 * 60);
+const MAX_PARKED_CLIENT_SESSIONS: usize = 256;
+const CLIENT_KEY_MAX_LEN: usize = 128;
+const MAX_CLIENT_SUBSCRIPTIONS: usize = 64;
+const CLIENT_SUBSCRIPTION_TOPIC_MAX_LEN: usize = 256;
+const DEBUG_TRACE_CAPACITY: usize = 200;
+const DEBUG_TRACE_PAYLOAD_MAX_CHARS: usize = 2_000;
+const DEBUG_TRACE_REDACTED: &str = "[redacted]";
 const BRIDGE_THREAD_LIST_CURSOR_PREFIX: &str = "bridge:";
 const THREAD_LIST_STREAM_BATCH_METHOD: &str = "bridge/thread/list/stream/batch";
 const THREAD_LIST_STREAM_ERROR_METHOD: &str = "bridge/thread/list/stream/error";
@@ -78,10 +144,42 @@ const THREAD_LIST_STREAM_MAX_DELAY_MS: u64 = 5_000;
 const APP_SERVER_TRANSIENT_THREAD_READ_RETRY_DELAYS_MS: [u64; 5] = [50, 100, 200, 400, 800];
 const ROLLOUT_LIVE_SYNC_POLL_INTERVAL_MS: u64 = 900;
 const ROLLOUT_LIVE_SYNC_DISCOVERY_INTERVAL_TICKS: u64 = 1;
-const ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES: usize = 64;
+const DEFAULT_ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES: usize = 64;
 const ROLLOUT_LIVE_SYNC_MAX_FILE_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 2);
 const ROLLOUT_LIVE_SYNC_INITIAL_TAIL_BYTES: u64 = 64 * 1024;
 const ROLLOUT_LIVE_SYNC_DEDUP_CAPACITY: usize = 8_192;
+/// Live sync skips a poll when at least this many notifications went out since the last
+/// tick while most connected clients are behind.
+const ROLLOUT_THROTTLE_MIN_EVENTS_PER_TICK: u64 = 200;
+/// Consecutive polls live sync may skip, so throttled files still advance.
+const ROLLOUT_THROTTLE_MAX_SKIPPED_TICKS: u32 = 4;
+const DEFAULT_ROLLOUT_DEDUP_WINDOW: Duration = Duration::from_secs(3);
+const LIVE_EVENT_DEDUP_CAPACITY: usize = 4_096;
+/// A `BRIDGE_GUARD_CONCURRENT_TURNS` claim older than this no longer blocks the thread, in
+/// case the turn's completion notification never arrives.
+const TURN_START_GUARD_MAX_AGE: Duration = Duration::from_secs(15 * 60);
+/// Fields each path adds to a `codex/event/*` `msg` on its own, ignored when matching.
+const CODEX_EVENT_ENVELOPE_KEYS: &[&str] = &[
+    "thread_id",
+    "threadId",
+    "conversation_id",
+    "conversationId",
+    "timestamp",
+];
+/// `exec_command` calls per rollout file remembered until their output record arrives.
+const ROLLOUT_PENDING_EXEC_CALLS_CAPACITY: usize = 64;
+/// Output bytes carried in a rollout-derived `exec_command_end` preview.
+const ROLLOUT_EXEC_OUTPUT_PREVIEW_MAX_BYTES: usize = 2_048;
+const APPROVAL_DIFF_PREVIEW_MAX_BYTES: usize = 4_096;
+const REJECTED_METHODS_MAX_TRACKED: usize = 256;
+const REJECTED_METHOD_NAME_MAX_LEN: usize = 128;
+/// Unknown method names longer than this get no "did you mean" suggestion, so an oversized
+/// name cannot make the edit-distance search expensive.
+const SUGGEST_METHOD_NAME_MAX_LEN: usize = 128;
+/// Bucket for rejected method names seen after `REJECTED_METHODS_MAX_TRACKED` distinct ones.
+const REJECTED_METHODS_OVERFLOW_KEY: &str = "(other)";
+/// Rollout `event_msg` types skipped by live sync unless named in `BRIDGE_ROLLOUT_INCLUDE_EVENTS`.
+const ROLLOUT_DROPPED_EVENT_TYPES: [&str; 2] = ["user_message", "context_compacted"];
 const OPENCODE_HEALTH_TIMEOUT: Duration = Duration::from_secs(20);
 const OPENCODE_HEALTH_POLL_INTERVAL: Duration = Duration::from_millis(250);
 const OPENCODE_EVENT_RECONNECT_DELAY: Duration = Duration::from_secs(1);
@@ -102,6 +200,13 @@ const GITHUB_CREDENTIALS_FILE_NAME: &str = "github-credentials";
 const GITHUB_GIT_CONFIG_FILE_NAME: &str = "github-git-auth.gitconfig";
 const CURSOR_API_BASE_URL: &str = "https://api.cursor.com";
 
+/// Token-bucket limits applied to each client's requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ClientRateLimit {
+    per_second: u32,
+    burst: u32,
+}
+
 #[derive(Clone)]
 struct BridgeConfig {
     host: String,
@@ -125,8 +230,36 @@ struct BridgeConfig {
     allow_query_token_auth: bool,
     allow_outside_root_cwd: bool,
     disable_terminal_exec: bool,
+    terminal_readonly: bool,
     terminal_allowed_commands: HashSet<String>,
     show_pairing_qr: bool,
+    ws_max_frame_bytes: usize,
+    terminal_history_size: usize,
+    decline_on_no_clients_grace: Option<Duration>,
+    terminal_blocked_args: HashMap<String, HashSet<String>>,
+    enable_debug_trace: bool,
+    app_server_args: Vec<String>,
+    app_server_env: Vec<(String, String)>,
+    rollout_max_tracked_files: usize,
+    git_watch: bool,
+    client_rate_limit: Option<ClientRateLimit>,
+    max_clients: Option<usize>,
+    cleanup_attachments_on_archive: bool,
+    ws_client_queue_capacity: usize,
+    attachments_dir: PathBuf,
+    rollout_include_events: HashSet<String>,
+    rollout_extra_record_types: HashSet<String>,
+    extra_forwarded_methods: HashSet<String>,
+    cors_origins: HashSet<String>,
+    disable_rollout_sync: bool,
+    replay_compress: bool,
+    command_signing_key: Option<String>,
+    app_server_max_line_bytes: usize,
+    reject_legacy_approvals: bool,
+    rollout_dedup_window: Duration,
+    terminal_max_output_bytes: usize,
+    strict_jsonrpc: bool,
+    guard_concurrent_turns: bool,
 }
 
 impl BridgeConfig {
@@ -203,12 +336,46 @@ impl BridgeConfig {
         let allow_outside_root_cwd =
             parse_bool_env_with_default("BRIDGE_ALLOW_OUTSIDE_ROOT_CWD", true);
         let disable_terminal_exec = parse_bool_env("BRIDGE_DISABLE_TERMINAL_EXEC");
+        let terminal_readonly = parse_bool_env("BRIDGE_TERMINAL_READONLY");
         let show_pairing_qr = parse_bool_env_with_default("BRIDGE_SHOW_PAIRING_QR", true);
+        let ws_max_frame_bytes = resolve_ws_max_frame_bytes();
 
         let terminal_allowed_commands = parse_csv_env(
             "BRIDGE_TERMINAL_ALLOWED_COMMANDS",
             &["pwd", "ls", "cat", "git"],
         );
+        let terminal_history_size = resolve_terminal_history_size();
+        let decline_on_no_clients_grace = resolve_decline_on_no_clients_grace();
+        let terminal_blocked_args = parse_terminal_blocked_args(env::vars());
+        let enable_debug_trace = parse_bool_env("BRIDGE_ENABLE_DEBUG_TRACE");
+        let app_server_args =
+            parse_app_server_args(&env::var("BRIDGE_APP_SERVER_ARGS").unwrap_or_default())?;
+        let app_server_env =
+            parse_app_server_env(&env::var("BRIDGE_APP_SERVER_ENV").unwrap_or_default())?;
+        let rollout_max_tracked_files = resolve_rollout_max_tracked_files();
+        let git_watch = parse_bool_env("BRIDGE_GIT_WATCH");
+        let client_rate_limit = resolve_client_rate_limit();
+        let max_clients = read_non_empty_env("BRIDGE_MAX_CLIENTS")
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|value| *value > 0);
+        let cleanup_attachments_on_archive =
+            parse_bool_env("BRIDGE_CLEANUP_ATTACHMENTS_ON_ARCHIVE");
+        let ws_client_queue_capacity = resolve_ws_client_queue_capacity();
+        let attachments_dir =
+            resolve_attachments_dir(&workdir, read_non_empty_env("BRIDGE_ATTACHMENTS_DIR"))?;
+        let rollout_include_events = parse_csv_env("BRIDGE_ROLLOUT_INCLUDE_EVENTS", &[]);
+        let rollout_extra_record_types = parse_csv_env("BRIDGE_ROLLOUT_EXTRA_RECORD_TYPES", &[]);
+        let extra_forwarded_methods = parse_csv_env("BRIDGE_EXTRA_FORWARDED_METHODS", &[]);
+        let cors_origins = parse_csv_env("BRIDGE_CORS_ORIGINS", &[]);
+        let disable_rollout_sync = parse_bool_env("BRIDGE_DISABLE_ROLLOUT_SYNC");
+        let replay_compress = parse_bool_env("BRIDGE_REPLAY_COMPRESS");
+        let command_signing_key = read_non_empty_env("BRIDGE_COMMAND_SIGNING_KEY");
+        let app_server_max_line_bytes = resolve_app_server_max_line_bytes();
+        let reject_legacy_approvals = parse_bool_env("BRIDGE_REJECT_LEGACY_APPROVALS");
+        let rollout_dedup_window = resolve_rollout_dedup_window();
+        let terminal_max_output_bytes = resolve_terminal_max_output_bytes();
+        let strict_jsonrpc = parse_bool_env("BRIDGE_STRICT_JSONRPC");
+        let guard_concurrent_turns = parse_bool_env("BRIDGE_GUARD_CONCURRENT_TURNS");
 
         Ok(Self {
             host,
@@ -232,8 +399,36 @@ impl BridgeConfig {
             allow_query_token_auth,
             allow_outside_root_cwd,
             disable_terminal_exec,
+            terminal_readonly,
             terminal_allowed_commands,
             show_pairing_qr,
+            ws_max_frame_bytes,
+            terminal_history_size,
+            decline_on_no_clients_grace,
+            terminal_blocked_args,
+            enable_debug_trace,
+            app_server_args,
+            app_server_env,
+            rollout_max_tracked_files,
+            git_watch,
+            client_rate_limit,
+            max_clients,
+            cleanup_attachments_on_archive,
+            ws_client_queue_capacity,
+            attachments_dir,
+            rollout_include_events,
+            rollout_extra_record_types,
+            extra_forwarded_methods,
+            cors_origins,
+            disable_rollout_sync,
+            replay_compress,
+            command_signing_key,
+            app_server_max_line_bytes,
+            reject_legacy_approvals,
+            rollout_dedup_window,
+            terminal_max_output_bytes,
+            strict_jsonrpc,
+            guard_concurrent_turns,
         })
     }
 
@@ -263,11 +458,18 @@ impl BridgeConfig {
 
         false
     }
+
+    /// Attachments root whose per-thread folders are removed when a thread is archived
+    /// or deleted, when `BRIDGE_CLEANUP_ATTACHMENTS_ON_ARCHIVE` is on.
+    fn attachment_cleanup_root(&self) -> Option<PathBuf> {
+        self.cleanup_attachments_on_archive
+            .then(|| self.attachments_dir.clone())
+    }
 }
 
-fn extract_bearer_token<'a>(headers: &'a HeaderMap) -> Option<&'a str> {
+fn extract_bearer_token(headers: &HeaderMap) -> Option<&str> {
     let raw = headers.get("authorization")?.to_str().ok()?;
-    let mut parts = raw.trim().split_whitespace();
+    let mut parts = raw.split_whitespace();
     let scheme = parts.next()?;
     let token = parts.next()?;
     if !scheme.eq_ignore_ascii_case("bearer") || parts.next().is_some() {
@@ -671,8 +873,16 @@ struct AppState {
     backend: Arc<RuntimeBackend>,
     queue: Arc<BridgeQueueService>,
     thread_list_streams: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    /// Recent uploads keyed by (thread scope, idempotency key) so retried uploads reuse the file.
+    attachment_uploads: Arc<Mutex<AttachmentUploadCache>>,
     terminal: Arc<TerminalService>,
     git: Arc<GitService>,
+    git_status_subscriptions: Arc<GitStatusSubscriptions>,
+    rejected_methods: Arc<RejectedMethodCounter>,
+    /// Work tree root containing `BRIDGE_WORKDIR`, detected once at startup.
+    workdir_git_root: Option<PathBuf>,
+    /// `FORWARDED_METHODS` plus `BRIDGE_EXTRA_FORWARDED_METHODS`.
+    forwarded_methods: HashSet<String>,
     updater: Arc<UpdateService>,
     preview: Arc<BrowserPreviewService>,
 }
@@ -706,7 +916,100 @@ struct BridgeCapabilitySupport {
     generic_ui_surface: bool,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeCapabilityDescriptor {
+    version: &'static str,
+    capabilities: BridgeCapabilities,
+    methods: &'static [BridgeMethodSchema],
+    forwarded_methods: Vec<String>,
+    limits: BridgeCapabilityLimits,
+    features: BridgeCapabilityFeatures,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeCapabilityLimits {
+    max_attachment_bytes: usize,
+    max_voice_transcription_bytes: usize,
+    max_ws_frame_bytes: usize,
+    notification_replay_max_limit: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeCapabilityFeatures {
+    terminal_exec: bool,
+    /// `bridge/terminal/exec` only runs the read-only subset (`BRIDGE_TERMINAL_READONLY`).
+    terminal_readonly: bool,
+    query_token_auth: bool,
+    allow_outside_root_cwd: bool,
+}
+
 impl AppState {
+    fn bridge_capability_descriptor(&self) -> BridgeCapabilityDescriptor {
+        BridgeCapabilityDescriptor {
+            version: env!("CARGO_PKG_VERSION"),
+            capabilities: self.bridge_capabilities(),
+            methods: BRIDGE_METHOD_SCHEMAS,
+            forwarded_methods: self.forwarded_method_list(),
+            limits: BridgeCapabilityLimits {
+                max_attachment_bytes: MAX_ATTACHMENT_BYTES,
+                max_voice_transcription_bytes: resolve_max_voice_transcription_bytes(),
+                max_ws_frame_bytes: self.config.ws_max_frame_bytes,
+                notification_replay_max_limit: NOTIFICATION_REPLAY_MAX_LIMIT,
+            },
+            features: BridgeCapabilityFeatures {
+                terminal_exec: !self.config.disable_terminal_exec,
+                terminal_readonly: !self.config.disable_terminal_exec
+                    && self.config.terminal_readonly,
+                query_token_auth: self.config.allow_query_token_auth,
+                allow_outside_root_cwd: self.config.allow_outside_root_cwd,
+            },
+        }
+    }
+
+    /// Every method a client may call: bridge methods from `BRIDGE_METHOD_SCHEMAS` and the
+    /// forwarded allowlist. Used to suggest a name when a call misses.
+    fn known_method_names(&self) -> impl Iterator<Item = &str> {
+        BRIDGE_METHOD_SCHEMAS
+            .iter()
+            .map(|schema| schema.method)
+            .chain(self.forwarded_methods.iter().map(String::as_str))
+    }
+
+    fn unknown_method_data(&self, method: &str) -> Option<Value> {
+        suggest_method_name(method, self.known_method_names())
+            .map(|suggestion| json!({ "suggestion": suggestion }))
+    }
+
+    /// `-32601` for a method outside the forwarded allowlist, counted per method name.
+    fn method_not_allowed_payload(&self, id: Value, method: &str) -> Value {
+        self.rejected_methods.record(method);
+        rpc_error_payload(
+            id,
+            -32601,
+            &format!("Method not allowed: {method}"),
+            self.unknown_method_data(method),
+        )
+    }
+
+    /// Built-in forwarded methods in declaration order, then operator extras sorted.
+    fn forwarded_method_list(&self) -> Vec<String> {
+        let mut extras = self
+            .forwarded_methods
+            .iter()
+            .filter(|method| !FORWARDED_METHODS.contains(&method.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        extras.sort();
+        FORWARDED_METHODS
+            .iter()
+            .map(|method| method.to_string())
+            .chain(extras)
+            .collect()
+    }
+
     fn bridge_capabilities(&self) -> BridgeCapabilities {
         let mut capabilities = self.backend.capabilities();
         capabilities.supports.self_update = self.updater.is_self_update_supported();
@@ -723,6 +1026,7 @@ impl AppState {
             uptime_sec: self.started_at.elapsed().as_secs(),
             connected_clients: devices.len(),
             devices,
+            git_root: self.workdir_git_root.as_deref().map(path_to_string),
         }
     }
 
@@ -847,6 +1151,7 @@ async fn start_cursor_app_server_from_config(
         &config.cursor_app_server_bin,
         &credential.api_key,
         &config.workdir,
+        AppServerOptions::from_config(config),
         hub,
     )
     .await
@@ -1078,7 +1383,7 @@ impl BrowserPreviewService {
         let candidate_ports =
             discover_loopback_listening_ports(&[self.bridge_port, self.preview_port]).await;
         let http = self.http.clone();
-        let mut suggestions = stream::iter(candidate_ports.into_iter())
+        let mut suggestions = stream::iter(candidate_ports)
             .map(|port| {
                 let http = http.clone();
                 async move {
@@ -1142,10 +1447,18 @@ struct RuntimeBackend {
     codex: Arc<StdRwLock<Option<Arc<AppServerBridge>>>>,
     opencode: Option<Arc<OpencodeBackend>>,
     cursor: Arc<StdRwLock<Option<Arc<AppServerBridge>>>>,
+    rollout_sync: Arc<Mutex<RolloutLiveSyncState>>,
+    /// Whether the rollout tailing task was started; false when codex is not running or
+    /// `BRIDGE_DISABLE_ROLLOUT_SYNC` is set.
+    rollout_sync_started: bool,
 }
 
 impl RuntimeBackend {
-    async fn start(config: &Arc<BridgeConfig>, hub: Arc<ClientHub>) -> Result<Arc<Self>, String> {
+    async fn start(
+        config: &Arc<BridgeConfig>,
+        hub: Arc<ClientHub>,
+        shutdown_rx: watch::Receiver<bool>,
+    ) -> Result<Arc<Self>, String> {
         let preferred_engine = config.active_engine;
         let codex_enabled = config.enabled_engines.contains(&BridgeRuntimeEngine::Codex);
         let opencode_enabled = config
@@ -1157,13 +1470,23 @@ impl RuntimeBackend {
         let codex = Arc::new(StdRwLock::new(None));
         let mut opencode = None;
         let cursor = Arc::new(StdRwLock::new(None));
+        let rollout_sync = Arc::new(Mutex::new(
+            RolloutLiveSyncState::new(config.rollout_max_tracked_files)
+                .with_included_events(config.rollout_include_events.clone())
+                .with_extra_record_types(config.rollout_extra_record_types.clone()),
+        ));
+        let mut rollout_sync_started = false;
 
         match preferred_engine {
             BridgeRuntimeEngine::Codex => {
                 if codex_enabled {
-                    let app_server =
-                        AppServerBridge::start_codex(&config.cli_bin, hub.clone()).await?;
-                    spawn_rollout_live_sync(hub.clone());
+                    let app_server = AppServerBridge::start_codex(config, hub.clone()).await?;
+                    rollout_sync_started = start_rollout_live_sync(
+                        config,
+                        hub.clone(),
+                        rollout_sync.clone(),
+                        shutdown_rx.clone(),
+                    );
                     Self::store_codex_backend(&codex, app_server);
                 }
 
@@ -1192,14 +1515,17 @@ impl RuntimeBackend {
                 }
 
                 if codex_enabled {
-                    match AppServerBridge::start_codex(
-                        &config.cli_bin,
-                        hub.clone(),
+                    match AppServerBridge::start_codex(config, hub.clone(),
                     )
                     .await
                     {
                         Ok(app_server) => {
-                            spawn_rollout_live_sync(hub.clone());
+                            rollout_sync_started = start_rollout_live_sync(
+                                config,
+                                hub.clone(),
+                                rollout_sync.clone(),
+                                shutdown_rx.clone(),
+                            );
                             Self::store_codex_backend(&codex, app_server);
                         }
                         Err(error) => eprintln!(
@@ -1225,9 +1551,14 @@ impl RuntimeBackend {
                 }
 
                 if codex_enabled {
-                    match AppServerBridge::start_codex(&config.cli_bin, hub.clone()).await {
+                    match AppServerBridge::start_codex(config, hub.clone()).await {
                         Ok(app_server) => {
-                            spawn_rollout_live_sync(hub.clone());
+                            rollout_sync_started = start_rollout_live_sync(
+                                config,
+                                hub.clone(),
+                                rollout_sync.clone(),
+                                shutdown_rx.clone(),
+                            );
                             Self::store_codex_backend(&codex, app_server);
                         }
                         Err(error) => eprintln!(
@@ -1252,6 +1583,8 @@ impl RuntimeBackend {
             codex,
             opencode,
             cursor,
+            rollout_sync,
+            rollout_sync_started,
         }))
     }
 
@@ -1259,6 +1592,49 @@ impl RuntimeBackend {
         self.cursor.read().ok().and_then(|guard| guard.clone())
     }
 
+    fn app_server_stdout_lag(&self) -> Vec<AppServerLagSnapshot> {
+        [self.codex_backend(), self.cursor_backend()]
+            .into_iter()
+            .flatten()
+            .map(|bridge| bridge.stdout_lag.snapshot(bridge.engine))
+            .collect()
+    }
+
+    fn app_server_method_traffic(&self) -> Vec<AppServerMethodTrafficEntry> {
+        [self.codex_backend(), self.cursor_backend()]
+            .into_iter()
+            .flatten()
+            .flat_map(|bridge| bridge.method_traffic.snapshot(bridge.engine))
+            .collect()
+    }
+
+    /// Round-trips a cheap request to the active engine for `bridge/health/deep`.
+    async fn probe_health(&self, slow_after: Duration, give_up_after: Duration) -> AppServerProbe {
+        let engine = self.engine();
+        let started = Instant::now();
+        let outcome = match self.backend_for_engine(engine) {
+            Ok(RuntimeBackendRef::Codex(bridge)) | Ok(RuntimeBackendRef::Cursor(bridge)) => {
+                bridge.probe(give_up_after).await
+            }
+            Ok(RuntimeBackendRef::Opencode(backend)) => {
+                match timeout(
+                    give_up_after,
+                    backend.request_json(HttpMethod::GET, "global/health", None, None, None),
+                )
+                .await
+                {
+                    Ok(result) => result.map(|_| ()),
+                    Err(_) => Err(format!(
+                        "no opencode response within {}ms",
+                        give_up_after.as_millis()
+                    )),
+                }
+            }
+            Err(error) => Err(error),
+        };
+        AppServerProbe::from_outcome(engine, started.elapsed(), slow_after, outcome)
+    }
+
     fn codex_backend(&self) -> Option<Arc<AppServerBridge>> {
         self.codex.read().ok().and_then(|guard| guard.clone())
     }
@@ -1290,7 +1666,7 @@ impl RuntimeBackend {
             return Err("codex backend is not enabled".to_string());
         }
 
-        let next_backend = AppServerBridge::start_codex(&config.cli_bin, hub).await?;
+        let next_backend = AppServerBridge::start_codex(config, hub).await?;
         let previous_backend = self
             .codex
             .write()
@@ -1609,6 +1985,17 @@ impl RuntimeBackend {
         approvals
     }
 
+    async fn drop_client_pending_requests(&self, client_id: u64) -> usize {
+        let mut dropped = 0;
+        if let Some(codex) = self.codex_backend() {
+            dropped += codex.drop_client_pending_requests(client_id).await;
+        }
+        if let Some(cursor) = self.cursor_backend() {
+            dropped += cursor.drop_client_pending_requests(client_id).await;
+        }
+        dropped
+    }
+
     async fn list_pending_user_inputs(&self) -> Vec<PendingUserInputRequest> {
         let mut requests = Vec::new();
         if let Some(codex) = self.codex_backend() {
@@ -1809,12 +2196,278 @@ struct ClientHub {
     next_client_id: AtomicU64,
     next_event_id: AtomicU64,
     replay_capacity: usize,
-    clients: RwLock<HashMap<u64, mpsc::Sender<Message>>>,
+    clients: RwLock<HashMap<u64, ClientSender>>,
     client_infos: RwLock<HashMap<u64, BridgeDeviceConnection>>,
+    client_full_queue_streaks: Mutex<HashMap<u64, u32>>,
+    /// First notification event id each client failed to receive because its queue was
+    /// full, kept until a replay covers it so the client can be pointed at the gap.
+    client_missed_from: Mutex<HashMap<u64, u64>>,
+    /// Stable `clientKey` per live client, and per-client subscriptions. When a keyed client
+    /// disconnects its subscriptions are parked under the key for `CLIENT_SESSION_TTL`.
+    client_keys: Mutex<HashMap<u64, String>>,
+    client_subscriptions: Mutex<HashMap<u64, BTreeSet<String>>>,
+    parked_client_sessions: Mutex<HashMap<String, ParkedClientSession>>,
+    rate_limit: Option<ClientRateLimit>,
+    client_rate_buckets: Mutex<HashMap<u64, ClientRateBucket>>,
+    client_count_tx: watch::Sender<usize>,
+    live_event_dedup: LiveEventDedup,
+    /// Set when `BRIDGE_GUARD_CONCURRENT_TURNS` is on.
+    turn_start_guard: Option<TurnStartGuard>,
+    /// `bridge/workspace/setCwd` defaults keyed by unqualified thread id, used by git and
+    /// terminal requests that name a `threadId` but no `cwd`.
+    thread_cwds: Mutex<HashMap<String, PathBuf>>,
+    /// `BRIDGE_MAX_CLIENTS`, used to scale the `retryAfterMs` reconnect hint with load.
+    max_clients: Option<usize>,
+    /// `BRIDGE_STRICT_JSONRPC`: stamp `"jsonrpc": "2.0"` on every outbound message.
+    strict_jsonrpc: bool,
+    replay_compress: bool,
     notification_replay: RwLock<VecDeque<ReplayableNotification>>,
     notification_tx: broadcast::Sender<HubNotification>,
 }
 
+struct ParkedClientSession {
+    subscriptions: BTreeSet<String>,
+    last_event_id: u64,
+    missed_from: Option<u64>,
+    rate_bucket: Option<ClientRateBucket>,
+    parked_at: Instant,
+}
+
+/// Result of binding a `clientKey` to a new connection.
+#[derive(Debug, Default)]
+struct ClientKeyClaim {
+    resumed: bool,
+    subscriptions: Vec<String>,
+    last_event_id: Option<u64>,
+    /// Oldest event id the previous connection under this key never received.
+    missed_from: Option<u64>,
+    replaced_client_id: Option<u64>,
+}
+
+struct ClientRateBucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl ClientRateBucket {
+    fn full(limit: ClientRateLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.burst),
+            refilled_at: now,
+        }
+    }
+
+    /// Takes one token, or returns how long until one is available.
+    fn try_take(&mut self, limit: ClientRateLimit, now: Instant) -> Result<(), Duration> {
+        let elapsed = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * f64::from(limit.per_second)).min(f64::from(limit.burst));
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - self.tokens) / f64::from(limit.per_second),
+        ))
+    }
+}
+
+/// Per-client outbound queue. Every enqueue happens while holding `send_order`, so a
+/// direct response that is waiting on a full queue can't be overtaken by a later
+/// broadcast; broadcasts skip that client instead and leave the gap to replay.
+#[derive(Clone)]
+struct ClientSender {
+    tx: mpsc::Sender<Message>,
+    send_order: Arc<Mutex<()>>,
+}
+
+impl ClientSender {
+    fn new(tx: mpsc::Sender<Message>) -> Self {
+        Self {
+            tx,
+            send_order: Arc::new(Mutex::new(())),
+        }
+    }
+}
+
+/// Threads whose client `turn/start` has not finished yet, keyed by unqualified thread id,
+/// for `BRIDGE_GUARD_CONCURRENT_TURNS`.
+#[derive(Default)]
+struct TurnStartGuard {
+    claimed_at: StdMutex<HashMap<String, Instant>>,
+}
+
+impl TurnStartGuard {
+    /// Claims `thread_id` for a new turn; `false` while an earlier claim is still live.
+    fn try_claim(&self, thread_id: &str, now: Instant) -> bool {
+        let Ok(mut claimed_at) = self.claimed_at.lock() else {
+            return true;
+        };
+        let busy = claimed_at
+            .get(thread_id)
+            .is_some_and(|at| now.saturating_duration_since(*at) < TURN_START_GUARD_MAX_AGE);
+        if !busy {
+            claimed_at.insert(thread_id.to_string(), now);
+        }
+        !busy
+    }
+
+    fn release(&self, thread_id: &str) {
+        if let Ok(mut claimed_at) = self.claimed_at.lock() {
+            claimed_at.remove(thread_id);
+        }
+    }
+}
+
+/// Unqualified thread id a `turn/start` targets.
+fn turn_start_thread_id(params: Option<&Value>) -> Option<String> {
+    let thread_id = read_string(params?.get("threadId"))?;
+    let thread_id = decode_engine_qualified_id(&thread_id);
+    (!thread_id.is_empty()).then_some(thread_id)
+}
+
+/// Thread id a `thread/archive` request targets, for cleanup once the archive succeeds.
+fn archived_thread_id(method: &str, params: Option<&Value>) -> Option<String> {
+    if method != "thread/archive" {
+        return None;
+    }
+    read_string(params?.get("threadId"))
+}
+
+/// Unqualified thread id of a notification that ends a turn: `turn/completed`, or a
+/// terminal `codex/event/*` such as `task_complete`.
+fn finished_turn_thread_id(method: &str, params: &Value) -> Option<String> {
+    let thread_id = if method == "turn/completed" {
+        read_string(params.get("threadId"))
+    } else {
+        let event_type = method.strip_prefix("codex/event/")?;
+        if !matches!(
+            event_type,
+            "task_complete" | "task_failed" | "turn_failed" | "task_interrupted" | "turn_aborted"
+        ) {
+            return None;
+        }
+        let msg = params.get("msg");
+        [
+            msg.and_then(|msg| msg.get("thread_id")),
+            msg.and_then(|msg| msg.get("threadId")),
+            params.get("conversationId"),
+            params.get("threadId"),
+        ]
+        .into_iter()
+        .find_map(read_string)
+    }?;
+    Some(decode_engine_qualified_id(&thread_id))
+}
+
+/// Content keys of live `codex/event/*` notifications seen within `window`. A zero window
+/// disables matching.
+struct LiveEventDedup {
+    window: Duration,
+    seen: StdMutex<LiveEventDedupEntries>,
+}
+
+#[derive(Default)]
+struct LiveEventDedupEntries {
+    order: VecDeque<(u64, Instant)>,
+    last_seen: HashMap<u64, Instant>,
+}
+
+impl LiveEventDedup {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: StdMutex::new(LiveEventDedupEntries::default()),
+        }
+    }
+
+    fn record(&self, key: u64, now: Instant) {
+        if self.window.is_zero() {
+            return;
+        }
+        let Ok(mut seen) = self.seen.lock() else {
+            return;
+        };
+        seen.order.push_back((key, now));
+        seen.last_seen.insert(key, now);
+        while let Some(&(oldest_key, seen_at)) = seen.order.front() {
+            let expired = now.duration_since(seen_at) > self.window;
+            if !expired && seen.order.len() <= LIVE_EVENT_DEDUP_CAPACITY {
+                break;
+            }
+            seen.order.pop_front();
+            if seen.last_seen.get(&oldest_key) == Some(&seen_at) {
+                seen.last_seen.remove(&oldest_key);
+            }
+        }
+    }
+
+    fn contains(&self, key: u64, now: Instant) -> bool {
+        if self.window.is_zero() {
+            return false;
+        }
+        self.seen.lock().is_ok_and(|seen| {
+            seen.last_seen
+                .get(&key)
+                .is_some_and(|seen_at| now.duration_since(*seen_at) <= self.window)
+        })
+    }
+}
+
+/// Matching key for a `codex/event/*` notification from either the live app-server or
+/// rollout sync: the method, the unqualified thread id, and the `msg` body without
+/// `CODEX_EVENT_ENVELOPE_KEYS`.
+fn codex_event_dedup_key(method: &str, params: &Value) -> Option<u64> {
+    if !method.starts_with("codex/event/") {
+        return None;
+    }
+    let msg = params.get("msg")?.as_object()?;
+    let thread_id = [
+        msg.get("thread_id"),
+        msg.get("threadId"),
+        msg.get("conversation_id"),
+        params.get("conversationId"),
+        params.get("threadId"),
+    ]
+    .into_iter()
+    .find_map(read_string)?;
+    let mut body = msg.clone();
+    for key in CODEX_EVENT_ENVELOPE_KEYS {
+        body.remove(*key);
+    }
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    method.hash(&mut hasher);
+    decode_engine_qualified_id(&thread_id).hash(&mut hasher);
+    serde_json::to_string(&body).ok()?.hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+/// Reconnect backoff hint: the base delay with no `BRIDGE_MAX_CLIENTS`, otherwise scaled
+/// linearly with how full the bridge is, up to `RECONNECT_RETRY_AFTER_MAX_MS` at the cap.
+fn reconnect_retry_after_ms(connected: usize, max_clients: Option<usize>) -> u64 {
+    let Some(max_clients) = max_clients.filter(|max| *max > 0) else {
+        return RECONNECT_RETRY_AFTER_BASE_MS;
+    };
+    let load_permille = (connected.min(max_clients) * 1_000 / max_clients) as u64;
+    RECONNECT_RETRY_AFTER_BASE_MS
+        + (RECONNECT_RETRY_AFTER_MAX_MS - RECONNECT_RETRY_AFTER_BASE_MS) * load_permille / 1_000
+}
+
+/// Grows the wait for a full client queue with each consecutive full-queue send so bursty
+/// clients get time to drain, while dead clients are still evicted once the cap is hit.
+fn slow_client_send_timeout(full_queue_streak: u32) -> Duration {
+    let exponent = full_queue_streak.saturating_sub(1).min(16);
+    let wait_ms = WS_CLIENT_SEND_TIMEOUT_BASE_MS
+        .saturating_mul(1u64 << exponent)
+        .min(WS_CLIENT_SEND_TIMEOUT_MAX_MS);
+    Duration::from_millis(wait_ms)
+}
+
 #[derive(Debug, Clone)]
 struct ClientConnectionMetadata {
     client_type: String,
@@ -1861,12 +2514,64 @@ struct BridgeStatus {
     uptime_sec: u64,
     connected_clients: usize,
     devices: Vec<BridgeDeviceConnection>,
+    /// Top-level directory of the git work tree holding `BRIDGE_WORKDIR`. Kept off the
+    /// unauthenticated `/health`, which only reports whether there is one.
+    git_root: Option<String>,
 }
 
+/// One replay buffer entry. The method stays uncompressed so filtered replays can skip
+/// entries without inflating them.
 #[derive(Clone)]
 struct ReplayableNotification {
     event_id: u64,
-    payload: Value,
+    method: String,
+    payload: ReplayPayload,
+}
+
+/// A replayed notification, gzip-compressed when `BRIDGE_REPLAY_COMPRESS` is set.
+#[derive(Clone)]
+enum ReplayPayload {
+    Json(Value),
+    Gzip(Vec<u8>),
+}
+
+impl ReplayPayload {
+    /// Compresses payloads of at least `REPLAY_COMPRESS_MIN_BYTES` serialized; smaller ones,
+    /// and any that fail to compress, are stored as-is.
+    fn compressed(payload: Value) -> Self {
+        let serialized = match serde_json::to_vec(&payload) {
+            Ok(serialized) if serialized.len() >= REPLAY_COMPRESS_MIN_BYTES => serialized,
+            Ok(_) => return Self::Json(payload),
+            Err(error) => {
+                eprintln!("failed to compress replay payload: {error}");
+                return Self::Json(payload);
+            }
+        };
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        match encoder
+            .write_all(&serialized)
+            .and_then(|()| encoder.finish())
+        {
+            Ok(bytes) => Self::Gzip(bytes),
+            Err(error) => {
+                eprintln!("failed to compress replay payload: {error}");
+                Self::Json(payload)
+            }
+        }
+    }
+
+    fn to_value(&self) -> Option<Value> {
+        match self {
+            Self::Json(payload) => Some(payload.clone()),
+            Self::Gzip(bytes) => match serde_json::from_reader(GzDecoder::new(bytes.as_slice())) {
+                Ok(payload) => Some(payload),
+                Err(error) => {
+                    eprintln!("failed to decompress replay payload: {error}");
+                    None
+                }
+            },
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -1890,29 +2595,163 @@ impl ClientHub {
             replay_capacity,
             clients: RwLock::new(HashMap::new()),
             client_infos: RwLock::new(HashMap::new()),
+            client_full_queue_streaks: Mutex::new(HashMap::new()),
+            client_missed_from: Mutex::new(HashMap::new()),
+            client_keys: Mutex::new(HashMap::new()),
+            client_subscriptions: Mutex::new(HashMap::new()),
+            parked_client_sessions: Mutex::new(HashMap::new()),
+            rate_limit: None,
+            client_rate_buckets: Mutex::new(HashMap::new()),
+            client_count_tx: watch::channel(0).0,
+            live_event_dedup: LiveEventDedup::new(Duration::ZERO),
+            turn_start_guard: None,
+            thread_cwds: Mutex::new(HashMap::new()),
+            max_clients: None,
+            strict_jsonrpc: false,
+            replay_compress: false,
             notification_replay: RwLock::new(VecDeque::new()),
             notification_tx,
         }
     }
 
+    fn with_rate_limit(mut self, rate_limit: Option<ClientRateLimit>) -> Self {
+        self.rate_limit = rate_limit;
+        self
+    }
+
+    fn with_replay_compression(mut self, replay_compress: bool) -> Self {
+        self.replay_compress = replay_compress;
+        self
+    }
+
+    fn with_max_clients(mut self, max_clients: Option<usize>) -> Self {
+        self.max_clients = max_clients;
+        self
+    }
+
+    fn with_strict_jsonrpc(mut self, strict_jsonrpc: bool) -> Self {
+        self.strict_jsonrpc = strict_jsonrpc;
+        self
+    }
+
+    fn with_rollout_dedup_window(mut self, window: Duration) -> Self {
+        self.live_event_dedup = LiveEventDedup::new(window);
+        self
+    }
+
+    fn with_turn_start_guard(mut self, enabled: bool) -> Self {
+        self.turn_start_guard = enabled.then(TurnStartGuard::default);
+        self
+    }
+
+    /// Claims the thread for a client `turn/start`. `false` only when the guard is on and a
+    /// turn started earlier on that thread has not finished.
+    fn try_claim_turn_start(&self, thread_id: &str) -> bool {
+        self.turn_start_guard
+            .as_ref()
+            .is_none_or(|guard| guard.try_claim(thread_id, Instant::now()))
+    }
+
+    /// Frees the thread after its `turn/start` failed or its turn finished.
+    fn release_turn_start(&self, thread_id: &str) {
+        if let Some(guard) = &self.turn_start_guard {
+            guard.release(thread_id);
+        }
+    }
+
+    async fn store_thread_cwd(&self, thread_id: &str, cwd: PathBuf) {
+        self.thread_cwds
+            .lock()
+            .await
+            .insert(decode_engine_qualified_id(thread_id), cwd);
+    }
+
+    async fn thread_cwd(&self, thread_id: &str) -> Option<PathBuf> {
+        self.thread_cwds
+            .lock()
+            .await
+            .get(&decode_engine_qualified_id(thread_id))
+            .cloned()
+    }
+
+    /// Drops the stored cwd of a thread that was cleared, archived or deleted.
+    async fn forget_thread_cwd(&self, thread_id: &str) {
+        self.thread_cwds
+            .lock()
+            .await
+            .remove(&decode_engine_qualified_id(thread_id));
+    }
+
+    /// Remembers a live app-server `codex/event/*` notification (raw params, before id
+    /// qualification) so rollout sync can skip its copy.
+    fn record_live_event(&self, method: &str, params: &Value) {
+        if let Some(key) = codex_event_dedup_key(method, params) {
+            self.live_event_dedup.record(key, Instant::now());
+        }
+    }
+
+    fn is_recent_live_event(&self, method: &str, params: &Value) -> bool {
+        codex_event_dedup_key(method, params)
+            .is_some_and(|key| self.live_event_dedup.contains(key, Instant::now()))
+    }
+
+    /// How long a disconnected client should wait before reconnecting, given current load.
+    fn reconnect_retry_after_ms(&self) -> u64 {
+        reconnect_retry_after_ms(*self.client_count_tx.borrow(), self.max_clients)
+    }
+
+    /// Charges one request to the client's bucket. Returns the wait before the next
+    /// request would be accepted when the bucket is empty.
+    async fn check_rate_limit(&self, client_id: u64, now: Instant) -> Result<(), Duration> {
+        let Some(limit) = self.rate_limit else {
+            return Ok(());
+        };
+        self.client_rate_buckets
+            .lock()
+            .await
+            .entry(client_id)
+            .or_insert_with(|| ClientRateBucket::full(limit, now))
+            .try_take(limit, now)
+    }
+
     fn subscribe_notifications(&self) -> broadcast::Receiver<HubNotification> {
         self.notification_tx.subscribe()
     }
 
+    fn subscribe_client_count(&self) -> watch::Receiver<usize> {
+        self.client_count_tx.subscribe()
+    }
+
+    fn allocate_client_id(&self) -> u64 {
+        self.next_client_id.fetch_add(1, Ordering::Relaxed)
+    }
+
     #[cfg(test)]
     async fn add_client(&self, tx: mpsc::Sender<Message>) -> u64 {
         self.add_client_with_metadata(tx, ClientConnectionMetadata::default())
             .await
+            .expect("client slot available")
     }
 
+    /// Registers a client, or returns `None` when `max_clients` are already connected.
+    /// The count check and the insert share one lock so concurrent upgrades can't both
+    /// take the last slot.
     async fn add_client_with_metadata(
         &self,
         tx: mpsc::Sender<Message>,
         metadata: ClientConnectionMetadata,
-    ) -> u64 {
-        let id = self.next_client_id.fetch_add(1, Ordering::Relaxed);
+    ) -> Option<u64> {
+        let id = {
+            let mut clients = self.clients.write().await;
+            if self.max_clients.is_some_and(|max| clients.len() >= max) {
+                return None;
+            }
+            let id = self.allocate_client_id();
+            clients.insert(id, ClientSender::new(tx));
+            self.client_count_tx.send_replace(clients.len());
+            id
+        };
         let now = now_iso();
-        self.clients.write().await.insert(id, tx);
         self.client_infos.write().await.insert(
             id,
             BridgeDeviceConnection {
@@ -1923,25 +2762,214 @@ impl ClientHub {
                 last_seen_at: now,
             },
         );
-        id
+        Some(id)
     }
 
     async fn remove_client(&self, client_id: u64) {
-        self.clients.write().await.remove(&client_id);
+        {
+            let mut clients = self.clients.write().await;
+            clients.remove(&client_id);
+            self.client_count_tx.send_replace(clients.len());
+        }
         self.client_infos.write().await.remove(&client_id);
-    }
+        self.client_full_queue_streaks
+            .lock()
+            .await
+            .remove(&client_id);
+        let rate_bucket = self.client_rate_buckets.lock().await.remove(&client_id);
+        let missed_from = self.client_missed_from.lock().await.remove(&client_id);
 
-    async fn mark_client_seen(&self, client_id: u64) {
-        let mut clients = self.client_infos.write().await;
-        if let Some(client) = clients.get_mut(&client_id) {
-            client.last_seen_at = now_iso();
+        let subscriptions = self
+            .client_subscriptions
+            .lock()
+            .await
+            .remove(&client_id)
+            .unwrap_or_default();
+        if let Some(key) = self.client_keys.lock().await.remove(&client_id) {
+            let mut parked = self.parked_client_sessions.lock().await;
+            if parked.len() >= MAX_PARKED_CLIENT_SESSIONS {
+                if let Some(oldest) = parked
+                    .iter()
+                    .min_by_key(|(_, session)| session.parked_at)
+                    .map(|(key, _)| key.clone())
+                {
+                    parked.remove(&oldest);
+                }
+            }
+            parked.insert(
+                key,
+                ParkedClientSession {
+                    subscriptions,
+                    last_event_id: self.latest_event_id(),
+                    missed_from,
+                    rate_bucket,
+                    parked_at: Instant::now(),
+                },
+            );
         }
     }
 
-    async fn client_connections(&self) -> Vec<BridgeDeviceConnection> {
-        let mut clients = self
-            .client_infos
-            .read()
+    /// Binds `key` to `client_id`, restoring subscriptions and the rate bucket parked by an
+    /// earlier connection with the same key. A live connection already holding the key hands
+    /// its state over and is reported in `replaced_client_id` so the caller can close it.
+    async fn claim_client_key(&self, client_id: u64, key: String) -> ClientKeyClaim {
+        let mut claim = ClientKeyClaim::default();
+        let mut rate_bucket = None;
+        let subscriptions = {
+            let mut keys = self.client_keys.lock().await;
+            let previous = keys
+                .iter()
+                .find(|(_, existing)| **existing == key)
+                .map(|(id, _)| *id);
+            let subscriptions = if let Some(previous) = previous {
+                keys.remove(&previous);
+                claim.replaced_client_id = Some(previous);
+                claim.resumed = true;
+                claim.missed_from = self.client_missed_from.lock().await.remove(&previous);
+                rate_bucket = self.client_rate_buckets.lock().await.remove(&previous);
+                self.client_subscriptions
+                    .lock()
+                    .await
+                    .remove(&previous)
+                    .unwrap_or_default()
+            } else {
+                let mut parked = self.parked_client_sessions.lock().await;
+                parked.retain(|_, session| session.parked_at.elapsed() < CLIENT_SESSION_TTL);
+                match parked.remove(&key) {
+                    Some(session) => {
+                        claim.resumed = true;
+                        claim.last_event_id = Some(session.last_event_id);
+                        claim.missed_from = session.missed_from.or_else(|| {
+                            (self.latest_event_id() > session.last_event_id)
+                                .then_some(session.last_event_id + 1)
+                        });
+                        rate_bucket = session.rate_bucket;
+                        session.subscriptions
+                    }
+                    None => BTreeSet::new(),
+                }
+            };
+            keys.insert(client_id, key);
+            subscriptions
+        };
+
+        if let Some(bucket) = rate_bucket {
+            self.client_rate_buckets
+                .lock()
+                .await
+                .insert(client_id, bucket);
+        }
+        claim.subscriptions = subscriptions.iter().cloned().collect();
+        if !subscriptions.is_empty() {
+            self.client_subscriptions
+                .lock()
+                .await
+                .insert(client_id, subscriptions);
+        }
+        claim
+    }
+
+    async fn set_client_subscriptions(&self, client_id: u64, topics: BTreeSet<String>) {
+        let mut subscriptions = self.client_subscriptions.lock().await;
+        if topics.is_empty() {
+            subscriptions.remove(&client_id);
+        } else {
+            subscriptions.insert(client_id, topics);
+        }
+    }
+
+    async fn client_subscriptions(&self, client_id: u64) -> Vec<String> {
+        self.client_subscriptions
+            .lock()
+            .await
+            .get(&client_id)
+            .map(|topics| topics.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Closes a connection superseded by a newer one with the same client key. No retry hint
+    /// is sent: reconnecting would evict the replacement in turn.
+    async fn close_replaced_client(&self, client_id: u64) {
+        self.disconnect_client(client_id, "replaced", close_code::NORMAL, None)
+            .await;
+    }
+
+    /// Closes every connection, e.g. on shutdown, with one shared reconnect hint.
+    async fn disconnect_all_clients(&self, reason: &str, code: u16) {
+        let retry_after_ms = self.reconnect_retry_after_ms();
+        let client_ids = self
+            .clients
+            .read()
+            .await
+            .keys()
+            .copied()
+            .collect::<Vec<_>>();
+        for client_id in client_ids {
+            self.disconnect_client(client_id, reason, code, Some(retry_after_ms))
+                .await;
+        }
+    }
+
+    /// Sends a `degraded` connection state carrying `retryAfterMs`, then a close frame whose
+    /// reason repeats the hint. Without a hint the state is a terminal `closed`. Both are
+    /// best-effort: a full queue drops them.
+    async fn disconnect_client(
+        &self,
+        client_id: u64,
+        reason: &str,
+        code: u16,
+        retry_after_ms: Option<u64>,
+    ) {
+        let sender = self.clients.read().await.get(&client_id).cloned();
+        if let Some(sender) = sender {
+            let (mut state, close_reason) = match retry_after_ms {
+                Some(retry_after_ms) => (
+                    json!({
+                        "method": "bridge/connection/state",
+                        "params": {
+                            "status": "degraded",
+                            "at": now_iso(),
+                            "reason": reason,
+                            "retryAfterMs": retry_after_ms,
+                        },
+                    }),
+                    format!("{reason}; retryAfterMs={retry_after_ms}"),
+                ),
+                None => (
+                    json!({
+                        "method": "bridge/connection/state",
+                        "params": {
+                            "status": "closed",
+                            "at": now_iso(),
+                            "reason": reason,
+                        },
+                    }),
+                    reason.to_string(),
+                ),
+            };
+            if self.strict_jsonrpc {
+                add_jsonrpc_version(&mut state);
+            }
+            let _ = sender.tx.try_send(Message::Text(state.to_string().into()));
+            let _ = sender.tx.try_send(Message::Close(Some(CloseFrame {
+                code,
+                reason: close_reason.into(),
+            })));
+        }
+        self.remove_client(client_id).await;
+    }
+
+    async fn mark_client_seen(&self, client_id: u64) {
+        let mut clients = self.client_infos.write().await;
+        if let Some(client) = clients.get_mut(&client_id) {
+            client.last_seen_at = now_iso();
+        }
+    }
+
+    async fn client_connections(&self) -> Vec<BridgeDeviceConnection> {
+        let mut clients = self
+            .client_infos
+            .read()
             .await
             .values()
             .cloned()
@@ -1950,7 +2978,10 @@ impl ClientHub {
         clients
     }
 
-    async fn send_json(&self, client_id: u64, value: Value) {
+    async fn send_json(&self, client_id: u64, mut value: Value) {
+        if self.strict_jsonrpc {
+            add_jsonrpc_version(&mut value);
+        }
         let text = match serde_json::to_string(&value) {
             Ok(v) => v,
             Err(error) => {
@@ -1959,20 +2990,34 @@ impl ClientHub {
             }
         };
 
-        let tx = {
+        let sender = {
             let clients = self.clients.read().await;
             clients.get(&client_id).cloned()
         };
-        let Some(tx) = tx else {
+        let Some(sender) = sender else {
             return;
         };
+        let tx = &sender.tx;
+        let _ordered = sender.send_order.lock().await;
 
         let message = Message::Text(text.into());
         let should_remove = match tx.try_send(message) {
-            Ok(()) => false,
+            Ok(()) => {
+                self.client_full_queue_streaks
+                    .lock()
+                    .await
+                    .remove(&client_id);
+                false
+            }
             Err(mpsc::error::TrySendError::Closed(_)) => true,
             Err(mpsc::error::TrySendError::Full(message)) => {
-                match timeout(Duration::from_millis(250), tx.send(message)).await {
+                let streak = {
+                    let mut streaks = self.client_full_queue_streaks.lock().await;
+                    let streak = streaks.entry(client_id).or_insert(0);
+                    *streak = streak.saturating_add(1);
+                    *streak
+                };
+                match timeout(slow_client_send_timeout(streak), tx.send(message)).await {
                     Ok(Ok(())) => false,
                     Ok(Err(_)) | Err(_) => true,
                 }
@@ -1984,7 +3029,15 @@ impl ClientHub {
         }
     }
 
+    #[cfg(test)]
     async fn broadcast_json(&self, value: Value) {
+        self.broadcast_payload(value, None).await;
+    }
+
+    async fn broadcast_payload(&self, mut value: Value, event_id: Option<u64>) {
+        if self.strict_jsonrpc {
+            add_jsonrpc_version(&mut value);
+        }
         let text = match serde_json::to_string(&value) {
             Ok(v) => v,
             Err(error) => {
@@ -1994,34 +3047,95 @@ impl ClientHub {
         };
 
         let mut stale_clients = Vec::new();
-        {
-            let clients = self.clients.read().await;
-            for (client_id, tx) in clients.iter() {
-                match tx.try_send(Message::Text(text.clone().into())) {
-                    Ok(()) => {}
-                    Err(mpsc::error::TrySendError::Closed(_)) => {
-                        stale_clients.push(*client_id);
-                    }
-                    Err(mpsc::error::TrySendError::Full(_)) => {
-                        // Keep the client and rely on replay to catch up dropped notifications.
-                    }
+        let mut dropped_clients = Vec::new();
+        // Snapshot the senders so waiting on one client's `send_order` doesn't hold the
+        // client map lock.
+        let senders = self
+            .clients
+            .read()
+            .await
+            .iter()
+            .map(|(client_id, sender)| (*client_id, sender.clone()))
+            .collect::<Vec<_>>();
+        // Clients that set subscription topics only get the notifications those topics
+        // match; responses and clients without topics get everything.
+        let excluded = match value.get("method").and_then(Value::as_str) {
+            Some(method) => self
+                .client_subscriptions
+                .lock()
+                .await
+                .iter()
+                .filter(|(_, topics)| {
+                    !topics.is_empty() && !topics.iter().any(|topic| topic_matches(method, topic))
+                })
+                .map(|(client_id, _)| *client_id)
+                .collect::<HashSet<_>>(),
+            None => HashSet::new(),
+        };
+        for (client_id, sender) in senders {
+            if excluded.contains(&client_id) {
+                continue;
+            }
+            // A direct send blocked on this client's full queue holds the lock for up to the
+            // slow-client backoff. Waiting here would stall every other client, so the
+            // notification is recorded as missed and the client catches up via replay.
+            let Ok(_ordered) = sender.send_order.try_lock() else {
+                dropped_clients.push(client_id);
+                continue;
+            };
+            match sender.tx.try_send(Message::Text(text.clone().into())) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Closed(_)) => {
+                    stale_clients.push(client_id);
+                }
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    // Keep the client and rely on replay to catch up dropped notifications.
+                    dropped_clients.push(client_id);
                 }
             }
         }
 
+        if let Some(event_id) = event_id.filter(|_| !dropped_clients.is_empty()) {
+            let mut missed = self.client_missed_from.lock().await;
+            for client_id in dropped_clients {
+                missed.entry(client_id).or_insert_with(|| {
+                    eprintln!(
+                        "client {client_id} missed notification {event_id}; it can catch up via bridge/events/replay"
+                    );
+                    event_id
+                });
+            }
+        }
+
         if !stale_clients.is_empty() {
             {
                 let mut clients = self.clients.write().await;
                 for client_id in &stale_clients {
                     clients.remove(client_id);
                 }
+                self.client_count_tx.send_replace(clients.len());
             }
             {
                 let mut client_infos = self.client_infos.write().await;
-                for client_id in stale_clients {
-                    client_infos.remove(&client_id);
+                for client_id in &stale_clients {
+                    client_infos.remove(client_id);
                 }
             }
+            let mut missed = self.client_missed_from.lock().await;
+            for client_id in stale_clients {
+                missed.remove(&client_id);
+            }
+        }
+    }
+
+    /// Forgets a client's gap once it has replayed everything after `after_event_id`.
+    async fn clear_client_missed_from(&self, client_id: u64, after_event_id: u64) {
+        let mut missed = self.client_missed_from.lock().await;
+        if missed
+            .get(&client_id)
+            .is_some_and(|missed_from| after_event_id < *missed_from)
+        {
+            missed.remove(&client_id);
         }
     }
 
@@ -2033,61 +3147,144 @@ impl ClientHub {
             "params": params
         });
         let params = payload.get("params").cloned().unwrap_or(Value::Null);
+        if let Some(thread_id) = finished_turn_thread_id(method, &params) {
+            self.release_turn_start(&thread_id);
+        }
 
-        self.push_replay(event_id, payload.clone()).await;
+        self.push_replay(event_id, method, payload.clone()).await;
         let _ = self.notification_tx.send(HubNotification {
             event_id,
             method: method.to_string(),
             params,
         });
-        self.broadcast_json(payload).await;
+        self.broadcast_payload(payload, Some(event_id)).await;
     }
 
-    async fn push_replay(&self, event_id: u64, payload: Value) {
+    async fn push_replay(&self, event_id: u64, method: &str, payload: Value) {
         if self.replay_capacity == 0 {
             return;
         }
 
+        let payload = if self.replay_compress {
+            ReplayPayload::compressed(payload)
+        } else {
+            ReplayPayload::Json(payload)
+        };
         let mut replay = self.notification_replay.write().await;
-        replay.push_back(ReplayableNotification { event_id, payload });
+        replay.push_back(ReplayableNotification {
+            event_id,
+            method: method.to_string(),
+            payload,
+        });
         while replay.len() > self.replay_capacity {
             replay.pop_front();
         }
     }
 
-    async fn replay_since(&self, after_event_id: Option<u64>, limit: usize) -> (Vec<Value>, bool) {
+    #[cfg(test)]
+    async fn replay_since(
+        &self,
+        after_event_id: Option<u64>,
+        limit: usize,
+    ) -> (Vec<Value>, bool, bool) {
+        self.replay_matching_since(after_event_id, limit, &[]).await
+    }
+
+    /// Like `replay_since`, but only notifications whose method matches one of `methods`
+    /// (see `notification_method_matches`) count toward `limit` and `hasMore`.
+    ///
+    /// Returns `(events, has_more, gap_detected)`; `gap_detected` means events after the
+    /// cursor were already evicted from the buffer and the client must resync.
+    async fn replay_matching_since(
+        &self,
+        after_event_id: Option<u64>,
+        limit: usize,
+        methods: &[String],
+    ) -> (Vec<Value>, bool, bool) {
         let after = after_event_id.unwrap_or(0);
-        let replay = self.notification_replay.read().await;
-        let mut events = Vec::new();
+        // Payloads are cloned under the lock and inflated after it is released, so a large
+        // compressed replay does not hold up `push_replay` and every broadcast behind it.
+        let mut payloads = Vec::new();
         let mut has_more = false;
+        let mut gap_detected;
+        {
+            let replay = self.notification_replay.read().await;
+            gap_detected = after_event_id.is_some_and(|after| {
+                let oldest_retained = Self::oldest_retained_event_id(&replay)
+                    .unwrap_or_else(|| self.latest_event_id().saturating_add(1));
+                after.saturating_add(1) < oldest_retained
+            });
 
-        for entry in replay.iter() {
-            if entry.event_id <= after {
-                continue;
-            }
+            for entry in replay.iter() {
+                if entry.event_id <= after {
+                    continue;
+                }
+                if !methods.is_empty() && !notification_method_matches(&entry.method, methods) {
+                    continue;
+                }
 
-            if events.len() >= limit {
-                has_more = true;
-                break;
+                if payloads.len() >= limit {
+                    has_more = true;
+                    break;
+                }
+
+                payloads.push((entry.event_id, entry.payload.clone()));
             }
+        }
 
-            events.push(entry.payload.clone());
+        let mut events = Vec::with_capacity(payloads.len());
+        for (event_id, payload) in payloads {
+            match payload.to_value() {
+                Some(payload) => events.push(payload),
+                None => {
+                    eprintln!("replay event {event_id} could not be inflated; reporting a gap");
+                    gap_detected = true;
+                }
+            }
         }
 
-        (events, has_more)
+        (events, has_more, gap_detected)
     }
 
     async fn earliest_event_id(&self) -> Option<u64> {
-        self.notification_replay
-            .read()
-            .await
-            .front()
-            .map(|entry| entry.event_id)
+        Self::oldest_retained_event_id(&*self.notification_replay.read().await)
+    }
+
+    /// Lowest event id still in the replay buffer. Not `front()`: ids are taken before the
+    /// buffer lock, so concurrent broadcasts can push them out of order.
+    fn oldest_retained_event_id(replay: &VecDeque<ReplayableNotification>) -> Option<u64> {
+        replay.iter().map(|entry| entry.event_id).min()
     }
 
     fn latest_event_id(&self) -> u64 {
         self.next_event_id.load(Ordering::Relaxed).saturating_sub(1)
     }
+
+    /// One retained notification by event id, or `None` once it has been evicted. Ids are
+    /// taken before the buffer lock, so concurrent broadcasts can push them out of order.
+    async fn replay_event(&self, event_id: u64) -> Option<Value> {
+        let payload = self
+            .notification_replay
+            .read()
+            .await
+            .iter()
+            .find(|entry| entry.event_id == event_id)?
+            .payload
+            .clone();
+        payload.to_value()
+    }
+
+    /// Returns `(connected, lagging)`: live clients, and how many of them dropped
+    /// notifications and have not yet caught up via replay.
+    async fn client_backlog(&self) -> (usize, usize) {
+        let clients = self.clients.read().await;
+        let missed = self.client_missed_from.lock().await;
+        let lagging = missed
+            .keys()
+            .filter(|client_id| clients.contains_key(client_id))
+            .count();
+        (clients.len(), lagging)
+    }
 }
 
 impl BridgeQueuedMessageEntry {
@@ -2416,20 +3613,21 @@ impl BridgeQueueService {
         let approvals = self.backend.list_pending_approvals().await;
         let user_inputs = self.backend.list_pending_user_inputs().await;
 
-        let mut runtime = BridgeThreadQueueRuntime::default();
-        runtime.active_turn_id = read_active_turn_id_from_thread(thread);
-        runtime.thread_running = thread_has_running_turn(thread);
-        runtime.pending_approval_ids = approvals
-            .into_iter()
-            .filter(|entry| entry.thread_id == thread_id)
-            .map(|entry| entry.id)
-            .collect();
-        runtime.pending_user_input_ids = user_inputs
-            .into_iter()
-            .filter(|entry| entry.thread_id == thread_id)
-            .map(|entry| entry.id)
-            .collect();
-        Ok(runtime)
+        Ok(BridgeThreadQueueRuntime {
+            active_turn_id: read_active_turn_id_from_thread(thread),
+            thread_running: thread_has_running_turn(thread),
+            pending_approval_ids: approvals
+                .into_iter()
+                .filter(|entry| entry.thread_id == thread_id)
+                .map(|entry| entry.id)
+                .collect(),
+            pending_user_input_ids: user_inputs
+                .into_iter()
+                .filter(|entry| entry.thread_id == thread_id)
+                .map(|entry| entry.id)
+                .collect(),
+            ..Default::default()
+        })
     }
 
     async fn dispatch_turn_start(
@@ -2771,121 +3969,497 @@ struct AppServerBridge {
     child_pid: u32,
     writer: Mutex<ChildStdin>,
     pending_requests: Mutex<HashMap<u64, PendingRequest>>,
+    /// Internal request ids per client so a disconnect can drop that client's forwards.
+    /// Locked after `pending_requests` whenever both are held.
+    pending_requests_by_client: Mutex<HashMap<u64, HashSet<u64>>>,
     internal_waiters: Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>,
     pending_approvals: Mutex<HashMap<String, PendingApprovalEntry>>,
     pending_user_inputs: Mutex<HashMap<String, PendingUserInputEntry>>,
     next_request_id: AtomicU64,
     approval_counter: AtomicU64,
     user_input_counter: AtomicU64,
+    server_info: Mutex<Option<AppServerInfo>>,
+    /// True once `initialize` completes; cleared when a write to the child's stdin fails.
+    ready: AtomicBool,
+    debug_trace: Option<Mutex<VecDeque<DebugTraceEntry>>>,
+    /// Set when attachment folders should be removed after a successful `thread/archive`.
+    attachment_cleanup_root: Option<PathBuf>,
+    /// `BRIDGE_REJECT_LEGACY_APPROVALS`: answer `applyPatchApproval`/`execCommandApproval`
+    /// with `-32601` instead of queueing them.
+    reject_legacy_approvals: bool,
+    stdout_lag: AppServerStdoutLag,
+    method_traffic: AppServerMethodTraffic,
     hub: Arc<ClientHub>,
 }
 
-struct PendingRequest {
-    client_id: u64,
-    client_request_id: Value,
-    method: String,
-    cached_chatgpt_auth: Option<BridgeChatGptAuthBundle>,
-    clear_cached_chatgpt_auth_on_success: bool,
+/// Settings from `BridgeConfig` shared by every app-server engine the bridge spawns.
+struct AppServerOptions {
+    debug_trace: bool,
+    attachment_cleanup_root: Option<PathBuf>,
+    max_line_bytes: usize,
+    reject_legacy_approvals: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-struct BridgeChatGptAuthBundle {
-    access_token: String,
-    account_id: String,
-    plan_type: Option<String>,
+impl AppServerOptions {
+    fn from_config(config: &BridgeConfig) -> Self {
+        Self {
+            debug_trace: config.enable_debug_trace,
+            attachment_cleanup_root: config.attachment_cleanup_root(),
+            max_line_bytes: config.app_server_max_line_bytes,
+            reject_legacy_approvals: config.reject_legacy_approvals,
+        }
+    }
 }
 
-#[derive(Clone, Copy)]
-enum ApprovalResponseFormat {
-    Modern,
-    Legacy,
-}
+/// One newline-delimited record from app-server stdout.
+#[derive(Debug, PartialEq, Eq)]
+enum BoundedLine {
+    Line(String),
+    /// The line was longer than the cap and was discarded; holds its length in bytes.
+    TooLong(usize),
+}
+
+/// Reads up to the next `\n` without buffering more than `max_bytes` of it, so one
+/// runaway line cannot grow memory without bound. Returns `None` at EOF.
+async fn read_bounded_line<R>(
+    reader: &mut R,
+    max_bytes: usize,
+) -> std::io::Result<Option<BoundedLine>>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    let mut line = Vec::new();
+    let mut line_bytes = 0usize;
+    let mut read_any = false;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            break;
+        }
+        read_any = true;
+        let newline = available.iter().position(|byte| *byte == b'\n');
+        let chunk = &available[..newline.unwrap_or(available.len())];
+        line_bytes = line_bytes.saturating_add(chunk.len());
+        if line_bytes <= max_bytes {
+            line.extend_from_slice(chunk);
+        } else if !line.is_empty() {
+            line = Vec::new();
+        }
+        let consumed = chunk.len() + usize::from(newline.is_some());
+        reader.consume(consumed);
+        if newline.is_some() {
+            break;
+        }
+    }
 
-#[derive(Clone)]
-struct PendingApprovalEntry {
-    app_server_request_id: Value,
-    response_format: ApprovalResponseFormat,
-    approval: PendingApproval,
+    if !read_any {
+        return Ok(None);
+    }
+    if line_bytes > max_bytes {
+        return Ok(Some(BoundedLine::TooLong(line_bytes)));
+    }
+    Ok(Some(BoundedLine::Line(
+        String::from_utf8_lossy(&line).into_owned(),
+    )))
 }
 
-#[derive(Clone)]
-struct PendingUserInputEntry {
-    app_server_request_id: Value,
-    request: PendingUserInputRequest,
+fn oversized_app_server_line_warning(
+    engine: BridgeRuntimeEngine,
+    line_bytes: usize,
+    max_bytes: usize,
+) -> String {
+    format!(
+        "skipped oversized app-server line: {} sent {line_bytes} bytes, over BRIDGE_APP_SERVER_MAX_LINE_BYTES={max_bytes}",
+        engine.as_str()
+    )
 }
 
-impl AppServerBridge {
-    async fn start_codex(cli_bin: &str, hub: Arc<ClientHub>) -> Result<Arc<Self>, String> {
-        let mut command = Command::new(cli_bin);
-        command
-            .arg("app-server")
-            .arg("--listen")
-            .arg("stdio://")
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-        Self::start_with_command(command, BridgeRuntimeEngine::Codex, hub).await
+/// Counts app-server stdout messages whose handling took longer than
+/// `APP_SERVER_SLOW_MESSAGE_THRESHOLD`, since later lines queue up behind them.
+#[derive(Debug, Default)]
+struct AppServerStdoutLag {
+    handled_messages: AtomicU64,
+    slow_messages: AtomicU64,
+    max_handle_ms: AtomicU64,
+}
+
+impl AppServerStdoutLag {
+    /// Runs `handler` for one dequeued line and records how long it took. Returns the
+    /// elapsed time so the caller can log slow messages.
+    async fn measure<F>(&self, threshold: Duration, handler: F) -> Option<Duration>
+    where
+        F: std::future::Future<Output = ()>,
+    {
+        let dequeued_at = Instant::now();
+        handler.await;
+        let elapsed = dequeued_at.elapsed();
+
+        self.handled_messages.fetch_add(1, Ordering::Relaxed);
+        let elapsed_ms = u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX);
+        self.max_handle_ms.fetch_max(elapsed_ms, Ordering::Relaxed);
+        if elapsed < threshold {
+            return None;
+        }
+        self.slow_messages.fetch_add(1, Ordering::Relaxed);
+        Some(elapsed)
     }
 
-    async fn start_cursor(
-        cursor_app_server_bin: &str,
-        api_key: &str,
-        workdir: &Path,
-        hub: Arc<ClientHub>,
-    ) -> Result<Arc<Self>, String> {
-        let mut command = Command::new(cursor_app_server_bin);
-        command
-            .env("CURSOR_API_KEY", api_key)
-            .env("CURSOR_WORKDIR", workdir)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-        Self::start_with_command(command, BridgeRuntimeEngine::Cursor, hub).await
+    fn snapshot(&self, engine: BridgeRuntimeEngine) -> AppServerLagSnapshot {
+        AppServerLagSnapshot {
+            engine,
+            handled_messages: self.handled_messages.load(Ordering::Relaxed),
+            slow_messages: self.slow_messages.load(Ordering::Relaxed),
+            max_handle_ms: self.max_handle_ms.load(Ordering::Relaxed),
+        }
     }
+}
 
-    async fn start_with_command(
-        mut command: Command,
-        engine: BridgeRuntimeEngine,
-        hub: Arc<ClientHub>,
-    ) -> Result<Arc<Self>, String> {
-        configure_managed_child_command(&mut command);
+#[derive(Debug, Clone, Copy)]
+struct AppServerLagSnapshot {
+    engine: BridgeRuntimeEngine,
+    handled_messages: u64,
+    slow_messages: u64,
+    max_handle_ms: u64,
+}
 
-        let mut child = command
-            .spawn()
-            .map_err(|error| format!("failed to start app-server: {error}"))?;
-        let child_pid = child
-            .id()
-            .ok_or_else(|| "app-server pid unavailable".to_string())?;
+/// Cumulative bytes exchanged with the app-server per forwarded method, so the
+/// operations that dominate bandwidth can be found.
+#[derive(Debug, Default)]
+struct AppServerMethodTraffic {
+    by_method: StdMutex<HashMap<String, AppServerMethodTrafficCounts>>,
+}
 
-        let stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| "app-server stdin unavailable".to_string())?;
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| "app-server stdout unavailable".to_string())?;
-        let stderr = child
-            .stderr
-            .take()
-            .ok_or_else(|| "app-server stderr unavailable".to_string())?;
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppServerMethodTrafficCounts {
+    requests: u64,
+    request_bytes: u64,
+    responses: u64,
+    response_bytes: u64,
+}
+
+impl AppServerMethodTraffic {
+    fn record_request(&self, method: &str, bytes: usize) {
+        self.update(method, |counts| {
+            counts.requests += 1;
+            counts.request_bytes += bytes as u64;
+        });
+    }
 
-        let bridge = Arc::new(Self {
-            engine,
-            child: Mutex::new(child),
-            child_pid,
-            writer: Mutex::new(stdin),
-            pending_requests: Mutex::new(HashMap::new()),
-            internal_waiters: Mutex::new(HashMap::new()),
-            pending_approvals: Mutex::new(HashMap::new()),
-            pending_user_inputs: Mutex::new(HashMap::new()),
-            next_request_id: AtomicU64::new(1),
-            approval_counter: AtomicU64::new(1),
-            user_input_counter: AtomicU64::new(1),
-            hub,
+    fn record_response(&self, method: &str, bytes: usize) {
+        self.update(method, |counts| {
+            counts.responses += 1;
+            counts.response_bytes += bytes as u64;
         });
+    }
 
-        bridge.spawn_stdout_loop(stdout);
+    fn update(&self, method: &str, apply: impl FnOnce(&mut AppServerMethodTrafficCounts)) {
+        let Ok(mut by_method) = self.by_method.lock() else {
+            return;
+        };
+        if let Some(counts) = by_method.get_mut(method) {
+            apply(counts);
+        } else {
+            let mut counts = AppServerMethodTrafficCounts::default();
+            apply(&mut counts);
+            by_method.insert(method.to_string(), counts);
+        }
+    }
+
+    fn snapshot(&self, engine: BridgeRuntimeEngine) -> Vec<AppServerMethodTrafficEntry> {
+        let Ok(by_method) = self.by_method.lock() else {
+            return Vec::new();
+        };
+        let mut entries = by_method
+            .iter()
+            .map(|(method, counts)| AppServerMethodTrafficEntry {
+                engine,
+                method: method.clone(),
+                counts: *counts,
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|left, right| left.method.cmp(&right.method));
+        entries
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppServerMethodTrafficEntry {
+    engine: BridgeRuntimeEngine,
+    method: String,
+    #[serde(flatten)]
+    counts: AppServerMethodTrafficCounts,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppServerStatsResponse {
+    methods: Vec<AppServerMethodTrafficEntry>,
+}
+
+/// Counts client calls to methods outside the forwarded allowlist, so operators can see
+/// what to add to `BRIDGE_EXTRA_FORWARDED_METHODS`.
+#[derive(Default)]
+struct RejectedMethodCounter {
+    by_method: StdMutex<HashMap<String, u64>>,
+}
+
+impl RejectedMethodCounter {
+    fn record(&self, method: &str) {
+        let Ok(mut by_method) = self.by_method.lock() else {
+            return;
+        };
+        let mut name = method.to_string();
+        if name.len() > REJECTED_METHOD_NAME_MAX_LEN {
+            let mut end = REJECTED_METHOD_NAME_MAX_LEN;
+            while !name.is_char_boundary(end) {
+                end -= 1;
+            }
+            name.truncate(end);
+        }
+        if !by_method.contains_key(&name) {
+            if by_method.len() >= REJECTED_METHODS_MAX_TRACKED {
+                name = REJECTED_METHODS_OVERFLOW_KEY.to_string();
+            } else {
+                eprintln!(
+                    "rejected non-allowlisted method {name:?}; add it to BRIDGE_EXTRA_FORWARDED_METHODS to forward it"
+                );
+            }
+        }
+        *by_method.entry(name).or_insert(0) += 1;
+    }
+
+    fn snapshot(&self) -> Vec<RejectedMethodEntry> {
+        let Ok(by_method) = self.by_method.lock() else {
+            return Vec::new();
+        };
+        let mut entries = by_method
+            .iter()
+            .map(|(method, count)| RejectedMethodEntry {
+                method: method.clone(),
+                count: *count,
+            })
+            .collect::<Vec<_>>();
+        entries.sort_by(|left, right| left.method.cmp(&right.method));
+        entries
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RejectedMethodEntry {
+    method: String,
+    count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RejectedMethodsResponse {
+    methods: Vec<RejectedMethodEntry>,
+}
+
+/// `bridge/health/deep` result: one round-trip to the active engine's backend.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppServerProbe {
+    engine: BridgeRuntimeEngine,
+    /// `ok`, `slow` (answered after `slow_after`) or `down` (no answer in time).
+    app_server: &'static str,
+    latency_ms: u64,
+    error: Option<String>,
+}
+
+impl AppServerProbe {
+    fn from_outcome(
+        engine: BridgeRuntimeEngine,
+        elapsed: Duration,
+        slow_after: Duration,
+        outcome: Result<(), String>,
+    ) -> Self {
+        let app_server = match &outcome {
+            Err(_) => "down",
+            Ok(()) if elapsed > slow_after => "slow",
+            Ok(()) => "ok",
+        };
+        Self {
+            engine,
+            app_server,
+            latency_ms: u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX),
+            error: outcome.err(),
+        }
+    }
+}
+
+/// One app-server protocol message kept for `bridge/debug/trace`. The payload is
+/// redacted and truncated before it is stored.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DebugTraceEntry {
+    engine: BridgeRuntimeEngine,
+    direction: &'static str,
+    method: String,
+    request_id: Value,
+    payload: String,
+    truncated: bool,
+    at: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DebugTraceResponse {
+    entries: Vec<DebugTraceEntry>,
+    capacity: usize,
+}
+
+/// What the app-server reported from `initialize`, kept so clients can adapt to
+/// the underlying codex version.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppServerInfo {
+    engine: BridgeRuntimeEngine,
+    version: Option<String>,
+    user_agent: Option<String>,
+    server_info: Option<Value>,
+    capabilities: Option<Value>,
+    initialized_at: String,
+    ready: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AppServerInfoResponse {
+    servers: Vec<AppServerInfo>,
+}
+
+struct PendingRequest {
+    client_id: u64,
+    client_request_id: Value,
+    method: String,
+    cached_chatgpt_auth: Option<BridgeChatGptAuthBundle>,
+    clear_cached_chatgpt_auth_on_success: bool,
+    archived_thread_id: Option<String>,
+    /// Thread claimed under `BRIDGE_GUARD_CONCURRENT_TURNS`, freed if `turn/start` fails.
+    turn_start_thread_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+struct BridgeChatGptAuthBundle {
+    access_token: String,
+    account_id: String,
+    plan_type: Option<String>,
+}
+
+#[derive(Clone, Copy)]
+enum ApprovalResponseFormat {
+    Modern,
+    Legacy,
+}
+
+#[derive(Clone)]
+struct PendingApprovalEntry {
+    app_server_request_id: Value,
+    response_format: ApprovalResponseFormat,
+    approval: PendingApproval,
+}
+
+#[derive(Clone)]
+struct PendingUserInputEntry {
+    app_server_request_id: Value,
+    request: PendingUserInputRequest,
+}
+
+impl AppServerBridge {
+    async fn start_codex(config: &BridgeConfig, hub: Arc<ClientHub>) -> Result<Arc<Self>, String> {
+        let command = build_codex_app_server_command(
+            &config.cli_bin,
+            &config.app_server_args,
+            &config.app_server_env,
+        );
+        Self::start_with_command(
+            command,
+            BridgeRuntimeEngine::Codex,
+            AppServerOptions::from_config(config),
+            hub,
+        )
+        .await
+    }
+
+    async fn start_cursor(
+        cursor_app_server_bin: &str,
+        api_key: &str,
+        workdir: &Path,
+        options: AppServerOptions,
+        hub: Arc<ClientHub>,
+    ) -> Result<Arc<Self>, String> {
+        let mut command = Command::new(cursor_app_server_bin);
+        command
+            .env("CURSOR_API_KEY", api_key)
+            .env("CURSOR_WORKDIR", workdir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        Self::start_with_command(command, BridgeRuntimeEngine::Cursor, options, hub).await
+    }
+
+    async fn start_with_command(
+        mut command: Command,
+        engine: BridgeRuntimeEngine,
+        options: AppServerOptions,
+        hub: Arc<ClientHub>,
+    ) -> Result<Arc<Self>, String> {
+        configure_managed_child_command(&mut command);
+
+        let mut child = ReapingChild::new(
+            command
+                .spawn()
+                .map_err(|error| format!("failed to start app-server: {error}"))?,
+        );
+        let child_pid = child
+            .child_mut()
+            .id()
+            .ok_or_else(|| "app-server pid unavailable".to_string())?;
+
+        let stdin = child
+            .child_mut()
+            .stdin
+            .take()
+            .ok_or_else(|| "app-server stdin unavailable".to_string())?;
+        let stdout = child
+            .child_mut()
+            .stdout
+            .take()
+            .ok_or_else(|| "app-server stdout unavailable".to_string())?;
+        let stderr = child
+            .child_mut()
+            .stderr
+            .take()
+            .ok_or_else(|| "app-server stderr unavailable".to_string())?;
+
+        let bridge = Arc::new(Self {
+            engine,
+            child: Mutex::new(child.into_inner()),
+            child_pid,
+            writer: Mutex::new(stdin),
+            pending_requests: Mutex::new(HashMap::new()),
+            pending_requests_by_client: Mutex::new(HashMap::new()),
+            internal_waiters: Mutex::new(HashMap::new()),
+            pending_approvals: Mutex::new(HashMap::new()),
+            pending_user_inputs: Mutex::new(HashMap::new()),
+            next_request_id: AtomicU64::new(1),
+            approval_counter: AtomicU64::new(1),
+            user_input_counter: AtomicU64::new(1),
+            server_info: Mutex::new(None),
+            ready: AtomicBool::new(false),
+            debug_trace: options.debug_trace.then(|| Mutex::new(VecDeque::new())),
+            attachment_cleanup_root: options.attachment_cleanup_root,
+            reject_legacy_approvals: options.reject_legacy_approvals,
+            stdout_lag: AppServerStdoutLag::default(),
+            method_traffic: AppServerMethodTraffic::default(),
+            hub,
+        });
+
+        bridge.spawn_stdout_loop(stdout, options.max_line_bytes);
         bridge.spawn_stderr_loop(stderr);
         bridge.spawn_wait_loop();
 
@@ -2926,11 +4500,11 @@ impl AppServerBridge {
             .await
             .map_err(|_| "app-server initialize timed out".to_string())?;
 
-        match init_result {
-            Ok(Ok(_)) => {}
+        let init_value = match init_result {
+            Ok(Ok(value)) => value,
             Ok(Err(message)) => return Err(format!("app-server initialize failed: {message}")),
             Err(_) => return Err("app-server initialize waiter dropped".to_string()),
-        }
+        };
 
         self.write_json(json!({
             "method": "initialized",
@@ -2939,26 +4513,153 @@ impl AppServerBridge {
         .await
         .map_err(|error| format!("initialized write failed: {error}"))?;
 
+        self.record_server_info(&init_value).await;
+
         Ok(())
     }
 
-    fn spawn_stdout_loop(self: &Arc<Self>, stdout: ChildStdout) {
+    async fn record_server_info(&self, init_value: &Value) {
+        let server_info = init_value.get("serverInfo").cloned();
+        let user_agent = read_string(init_value.get("userAgent"));
+        let version = server_info
+            .as_ref()
+            .and_then(|info| read_string(info.get("version")))
+            .or_else(|| user_agent.as_deref().and_then(parse_user_agent_version));
+        let info = AppServerInfo {
+            engine: self.engine,
+            version,
+            user_agent,
+            server_info,
+            capabilities: init_value.get("capabilities").cloned(),
+            initialized_at: now_iso(),
+            ready: true,
+        };
+
+        *self.server_info.lock().await = Some(info.clone());
+        self.ready.store(true, Ordering::Relaxed);
+        self.hub
+            .broadcast_notification(
+                "bridge/appServer/ready",
+                json!({
+                    "engine": info.engine,
+                    "version": info.version,
+                    "userAgent": info.user_agent,
+                }),
+            )
+            .await;
+    }
+
+    async fn server_info(&self) -> Option<AppServerInfo> {
+        let mut info = self.server_info.lock().await.clone()?;
+        info.ready = self.ready.load(Ordering::Relaxed);
+        Some(info)
+    }
+
+    async fn record_trace(
+        &self,
+        direction: &'static str,
+        method: &str,
+        request_id: Value,
+        payload: Value,
+    ) {
+        let Some(trace) = self.debug_trace.as_ref() else {
+            return;
+        };
+
+        let redacted = redact_debug_trace_value(payload);
+        let serialized = serde_json::to_string(&redacted).unwrap_or_default();
+        let truncated = serialized.chars().count() > DEBUG_TRACE_PAYLOAD_MAX_CHARS;
+        let payload = if truncated {
+            serialized
+                .chars()
+                .take(DEBUG_TRACE_PAYLOAD_MAX_CHARS)
+                .collect()
+        } else {
+            serialized
+        };
+
+        let mut trace = trace.lock().await;
+        while trace.len() >= DEBUG_TRACE_CAPACITY {
+            trace.pop_front();
+        }
+        trace.push_back(DebugTraceEntry {
+            engine: self.engine,
+            direction,
+            method: method.to_string(),
+            request_id,
+            payload,
+            truncated,
+            at: now_iso(),
+        });
+    }
+
+    async fn debug_trace_entries(&self) -> Vec<DebugTraceEntry> {
+        match self.debug_trace.as_ref() {
+            Some(trace) => trace.lock().await.iter().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn spawn_stdout_loop(self: &Arc<Self>, stdout: ChildStdout, max_line_bytes: usize) {
         let this = Arc::clone(self);
         tokio::spawn(async move {
-            let mut lines = BufReader::new(stdout).lines();
+            let mut reader = BufReader::new(stdout);
 
             loop {
-                match lines.next_line().await {
-                    Ok(Some(line)) => {
+                match read_bounded_line(&mut reader, max_line_bytes).await {
+                    Ok(Some(BoundedLine::TooLong(line_bytes))) => {
+                        eprintln!(
+                            "{}",
+                            oversized_app_server_line_warning(
+                                this.engine,
+                                line_bytes,
+                                max_line_bytes
+                            )
+                        );
+                    }
+                    Ok(Some(BoundedLine::Line(line))) => {
                         let trimmed = line.trim();
                         if trimmed.is_empty() {
                             continue;
                         }
 
-                        match serde_json::from_str::<Value>(trimmed) {
-                            Ok(value) => this.handle_incoming(value).await,
-                            Err(error) => {
-                                eprintln!("invalid app-server json: {error} | line={trimmed}");
+                        // A line may carry several concatenated JSON values; handle each in turn
+                        // and stop at the first one that does not parse.
+                        let mut values =
+                            serde_json::Deserializer::from_str(trimmed).into_iter::<Value>();
+                        while let Some(parsed) = values.next() {
+                            let value = match parsed {
+                                Ok(value) => value,
+                                Err(error) => {
+                                    eprintln!(
+                                        "invalid app-server json: {error} | line={}",
+                                        &trimmed[values.byte_offset()..]
+                                    );
+                                    break;
+                                }
+                            };
+                            let label = if value.is_array() {
+                                "batch".to_string()
+                            } else {
+                                value
+                                    .get("method")
+                                    .and_then(Value::as_str)
+                                    .unwrap_or("response")
+                                    .to_string()
+                            };
+                            let slow = this
+                                .stdout_lag
+                                .measure(
+                                    APP_SERVER_SLOW_MESSAGE_THRESHOLD,
+                                    this.handle_incoming(value),
+                                )
+                                .await;
+                            if let Some(elapsed) = slow {
+                                eprintln!(
+                                    "slow app-server message: {} {label} took {}ms; later output is queued behind it",
+                                    this.engine.as_str(),
+                                    elapsed.as_millis()
+                                );
                             }
                         }
                     }
@@ -3011,13 +4712,58 @@ impl AppServerBridge {
         });
     }
 
+    async fn take_pending_request(&self, internal_id: u64) -> Option<PendingRequest> {
+        let mut pending = self.pending_requests.lock().await;
+        let entry = pending.remove(&internal_id)?;
+        let mut by_client = self.pending_requests_by_client.lock().await;
+        if let Some(ids) = by_client.get_mut(&entry.client_id) {
+            ids.remove(&internal_id);
+            if ids.is_empty() {
+                by_client.remove(&entry.client_id);
+            }
+        }
+        Some(entry)
+    }
+
+    /// Frees the `BRIDGE_GUARD_CONCURRENT_TURNS` claim of a `turn/start` that will never
+    /// get its response routed.
+    fn release_pending_turn_start(&self, pending: &PendingRequest) {
+        if let Some(thread_id) = pending.turn_start_thread_id.as_deref() {
+            self.hub.release_turn_start(thread_id);
+        }
+    }
+
+    /// Forgets every request forwarded for a disconnected client. Late responses
+    /// for those ids are then ignored instead of routed to a missing socket.
+    async fn drop_client_pending_requests(&self, client_id: u64) -> usize {
+        let mut pending = self.pending_requests.lock().await;
+        let Some(ids) = self
+            .pending_requests_by_client
+            .lock()
+            .await
+            .remove(&client_id)
+        else {
+            return 0;
+        };
+        let dropped = ids
+            .iter()
+            .filter_map(|internal_id| pending.remove(internal_id))
+            .collect::<Vec<_>>();
+        for entry in &dropped {
+            self.release_pending_turn_start(entry);
+        }
+        dropped.len()
+    }
+
     async fn fail_all_pending(&self, message: &str) {
         let pending_entries = {
             let mut pending = self.pending_requests.lock().await;
+            self.pending_requests_by_client.lock().await.clear();
             pending.drain().map(|(_, entry)| entry).collect::<Vec<_>>()
         };
 
         for pending in pending_entries {
+            self.release_pending_turn_start(&pending);
             self.hub
                 .send_json(
                     pending.client_id,
@@ -3044,20 +4790,61 @@ impl AppServerBridge {
         let cached_chatgpt_auth =
             extract_chatgpt_auth_tokens_from_account_login_start(params.as_ref());
         let clear_cached_chatgpt_auth_on_success = method == "account/logout";
+        let archived_thread_id = archived_thread_id(method, params.as_ref());
+        let turn_start_thread_id = if method == "turn/start" {
+            turn_start_thread_id(params.as_ref())
+        } else {
+            None
+        };
 
         {
             let mut pending = self.pending_requests.lock().await;
-            pending.insert(
-                internal_id,
-                PendingRequest {
-                    client_id,
-                    client_request_id,
-                    method: method.to_string(),
-                    cached_chatgpt_auth,
-                    clear_cached_chatgpt_auth_on_success,
-                },
-            );
-        }
+            let mut by_client = self.pending_requests_by_client.lock().await;
+            // Responses route by internal id, but a client holding two outstanding
+            // requests with one id could not tell their answers apart.
+            let duplicate = by_client.get(&client_id).is_some_and(|internal_ids| {
+                internal_ids.iter().any(|internal_id| {
+                    pending
+                        .get(internal_id)
+                        .is_some_and(|entry| entry.client_request_id == client_request_id)
+                })
+            });
+            if duplicate {
+                drop(by_client);
+                drop(pending);
+                if let Some(thread_id) = turn_start_thread_id.as_deref() {
+                    self.hub.release_turn_start(thread_id);
+                }
+                let message = format!(
+                    "Duplicate request id: {client_request_id} is still awaiting a response"
+                );
+                self.hub
+                    .send_json(
+                        client_id,
+                        rpc_error_payload(
+                            client_request_id,
+                            -32600,
+                            &message,
+                            Some(json!({ "duplicateId": true })),
+                        ),
+                    )
+                    .await;
+                return Ok(());
+            }
+            pending.insert(
+                internal_id,
+                PendingRequest {
+                    client_id,
+                    client_request_id,
+                    method: method.to_string(),
+                    cached_chatgpt_auth,
+                    clear_cached_chatgpt_auth_on_success,
+                    archived_thread_id,
+                    turn_start_thread_id,
+                },
+            );
+            by_client.entry(client_id).or_default().insert(internal_id);
+        }
 
         let mut payload = json!({
             "id": internal_id,
@@ -3066,12 +4853,21 @@ impl AppServerBridge {
         if let Some(params) = params {
             payload["params"] = params;
         }
+        let trace_params = payload.get("params").cloned().unwrap_or(Value::Null);
 
-        if let Err(error) = self.write_json(payload).await {
-            self.pending_requests.lock().await.remove(&internal_id);
-            return Err(format!("failed forwarding request to app-server: {error}"));
+        match self.write_json_line(payload).await {
+            Ok(bytes) => self.method_traffic.record_request(method, bytes),
+            Err(error) => {
+                if let Some(pending) = self.take_pending_request(internal_id).await {
+                    self.release_pending_turn_start(&pending);
+                }
+                return Err(format!("failed forwarding request to app-server: {error}"));
+            }
         }
 
+        self.record_trace("request", method, json!(internal_id), trace_params)
+            .await;
+
         Ok(())
     }
 
@@ -3101,6 +4897,24 @@ impl AppServerBridge {
         method: &str,
         params: Option<Value>,
     ) -> Result<Value, String> {
+        let (internal_id, rx) = self.send_internal_request(method, params).await?;
+        match timeout(Duration::from_secs(20), rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(message))) => Err(message),
+            Ok(Err(_)) => Err("internal app-server waiter dropped".to_string()),
+            Err(_) => {
+                self.internal_waiters.lock().await.remove(&internal_id);
+                Err(format!("internal app-server request timed out: {method}"))
+            }
+        }
+    }
+
+    /// Writes a bridge-originated request and returns the waiter its response resolves.
+    async fn send_internal_request(
+        &self,
+        method: &str,
+        params: Option<Value>,
+    ) -> Result<(u64, oneshot::Receiver<Result<Value, String>>), String> {
         let internal_id = self.next_request_id.fetch_add(1, Ordering::Relaxed);
         let (tx, rx) = oneshot::channel::<Result<Value, String>>();
         self.internal_waiters.lock().await.insert(internal_id, tx);
@@ -3120,13 +4934,22 @@ impl AppServerBridge {
             ));
         }
 
-        match timeout(Duration::from_secs(20), rx).await {
-            Ok(Ok(Ok(result))) => Ok(result),
-            Ok(Ok(Err(message))) => Err(message),
+        Ok((internal_id, rx))
+    }
+
+    /// Sends `model/list` and waits up to `give_up_after`. Any response, including a
+    /// JSON-RPC error, means the app-server is alive.
+    async fn probe(&self, give_up_after: Duration) -> Result<(), String> {
+        let (internal_id, rx) = self.send_internal_request("model/list", None).await?;
+        match timeout(give_up_after, rx).await {
+            Ok(Ok(_)) => Ok(()),
             Ok(Err(_)) => Err("internal app-server waiter dropped".to_string()),
             Err(_) => {
                 self.internal_waiters.lock().await.remove(&internal_id);
-                Err(format!("internal app-server request timed out: {method}"))
+                Err(format!(
+                    "no app-server response within {}ms",
+                    give_up_after.as_millis()
+                ))
             }
         }
     }
@@ -3231,22 +5054,48 @@ impl AppServerBridge {
             return Err(format!("failed to send requestUserInput response: {error}"));
         }
 
+        if self.debug_trace.is_some() {
+            let answers = redact_secret_user_input_answers(&pending.request.questions, answers);
+            self.record_trace(
+                "userInputResponse",
+                "item/tool/requestUserInput",
+                pending.app_server_request_id.clone(),
+                json!({ "answers": answers }),
+            )
+            .await;
+        }
+
         self.hub
             .broadcast_notification(
                 "bridge/userInput.resolved",
-                json!({
-                    "id": pending.request.id,
-                    "threadId": pending.request.thread_id,
-                    "turnId": pending.request.turn_id,
-                    "resolvedAt": now_iso(),
-                }),
+                user_input_resolved_params(&pending.request),
             )
             .await;
 
         Ok(Some(pending.request))
     }
 
-    async fn handle_incoming(&self, value: Value) {
+    async fn handle_incoming(self: &Arc<Self>, value: Value) {
+        match value {
+            Value::Array(batch) => {
+                let skipped = batch.iter().filter(|entry| !entry.is_object()).count();
+                if skipped > 0 {
+                    eprintln!(
+                        "app-server batch from {} has {skipped} non-object entries; skipping them",
+                        self.engine.as_str()
+                    );
+                }
+                for entry in batch {
+                    self.handle_incoming_message(entry).await;
+                }
+            }
+            // Scalars (e.g. keep-alives) carry nothing to route.
+            Value::Object(_) => self.handle_incoming_message(value).await,
+            _ => {}
+        }
+    }
+
+    async fn handle_incoming_message(self: &Arc<Self>, value: Value) {
         let Some(object) = value.as_object() else {
             return;
         };
@@ -3273,7 +5122,26 @@ impl AppServerBridge {
         }
     }
 
-    async fn handle_server_request(&self, method: &str, id: Value, params: Option<Value>) {
+    async fn handle_server_request(
+        self: &Arc<Self>,
+        method: &str,
+        id: Value,
+        params: Option<Value>,
+    ) {
+        if self.reject_legacy_approvals
+            && matches!(
+                method,
+                LEGACY_APPROVAL_PATCH_METHOD | LEGACY_APPROVAL_COMMAND_METHOD
+            )
+        {
+            eprintln!(
+                "rejected legacy approval request {method} from {} (BRIDGE_REJECT_LEGACY_APPROVALS=true); the app-server should send {APPROVAL_COMMAND_METHOD} or {APPROVAL_FILE_METHOD}",
+                self.engine.as_str()
+            );
+            let _ = self.write_json(legacy_approval_rejection(id, method)).await;
+            return;
+        }
+
         if matches!(
             method,
             APPROVAL_COMMAND_METHOD
@@ -3365,6 +5233,14 @@ impl AppServerBridge {
                         None
                     },
                 ),
+                diff_preview: if matches!(
+                    method,
+                    APPROVAL_FILE_METHOD | LEGACY_APPROVAL_PATCH_METHOD
+                ) {
+                    params_obj.and_then(parse_approval_diff_preview)
+                } else {
+                    None
+                },
             };
 
             self.pending_approvals.lock().await.insert(
@@ -3455,49 +5331,12 @@ impl AppServerBridge {
         }
 
         if method == ACCOUNT_CHATGPT_TOKENS_REFRESH_METHOD {
-            if let Some(auth) = resolve_bridge_chatgpt_auth_bundle_for_refresh() {
-                let mut result = json!({
-                    "accessToken": auth.access_token,
-                    "chatgptAccountId": auth.account_id,
-                    "chatgptPlanType": Value::Null,
-                });
-
-                if let Some(plan_type) = auth.plan_type {
-                    result["chatgptPlanType"] = json!(plan_type);
-                }
-
-                let _ = self
-                    .write_json(json!({
-                        "id": id,
-                        "result": result
-                    }))
-                    .await;
-            } else {
-                self.hub
-                    .broadcast_notification(
-                        "bridge/account.chatgptAuthTokens.refresh.required",
-                        json!({
-                            "requestedAt": now_iso(),
-                            "reason": params
-                                .as_ref()
-                                .and_then(Value::as_object)
-                                .and_then(|raw| raw.get("reason"))
-                                .and_then(Value::as_str)
-                                .unwrap_or("unauthorized"),
-                        }),
-                    )
-                    .await;
-
-                let _ = self
-                    .write_json(json!({
-                        "id": id,
-                        "error": {
-                            "code": -32001,
-                            "message": "account/chatgptAuthTokens/refresh is not configured (set BRIDGE_CHATGPT_ACCESS_TOKEN and BRIDGE_CHATGPT_ACCOUNT_ID, or use Codex-managed ChatGPT login instead)"
-                        }
-                    }))
-                    .await;
-            }
+            // The refresh command can take seconds; run it off the stdout loop so other
+            // app-server messages keep flowing.
+            let bridge = Arc::clone(self);
+            tokio::spawn(async move {
+                bridge.respond_to_chatgpt_tokens_refresh(id, params).await;
+            });
             return;
         }
 
@@ -3512,9 +5351,53 @@ impl AppServerBridge {
             .await;
     }
 
+    async fn respond_to_chatgpt_tokens_refresh(&self, id: Value, params: Option<Value>) {
+        let refreshed = resolve_bridge_chatgpt_auth_for_refresh_request().await;
+        if let Ok(Some(auth)) = refreshed {
+            let _ = self
+                .write_json(json!({
+                    "id": id,
+                    "result": chatgpt_tokens_refresh_result(auth)
+                }))
+                .await;
+        } else {
+            let message = match refreshed {
+                Err(error) => format!("account/chatgptAuthTokens/refresh failed: {error}"),
+                _ => "account/chatgptAuthTokens/refresh is not configured (set BRIDGE_CHATGPT_REFRESH_COMMAND, or BRIDGE_CHATGPT_ACCESS_TOKEN and BRIDGE_CHATGPT_ACCOUNT_ID, or use Codex-managed ChatGPT login instead)".to_string(),
+            };
+            self.hub
+                .broadcast_notification(
+                    "bridge/account.chatgptAuthTokens.refresh.required",
+                    json!({
+                        "requestedAt": now_iso(),
+                        "reason": params
+                            .as_ref()
+                            .and_then(Value::as_object)
+                            .and_then(|raw| raw.get("reason"))
+                            .and_then(Value::as_str)
+                            .unwrap_or("unauthorized"),
+                    }),
+                )
+                .await;
+
+            let _ = self
+                .write_json(json!({
+                    "id": id,
+                    "error": {
+                        "code": -32001,
+                        "message": message
+                    }
+                }))
+                .await;
+        }
+    }
+
     async fn handle_notification(&self, method: &str, params: Option<Value>) {
-        let normalized_params =
-            normalize_forwarded_notification(method, params.unwrap_or(Value::Null), self.engine);
+        let params = params.unwrap_or(Value::Null);
+        if self.engine == BridgeRuntimeEngine::Codex {
+            self.hub.record_live_event(method, &params);
+        }
+        let normalized_params = normalize_forwarded_notification(method, params, self.engine);
         self.hub
             .broadcast_notification(method, normalized_params)
             .await;
@@ -3529,7 +5412,7 @@ impl AppServerBridge {
             return;
         };
 
-        let pending = self.pending_requests.lock().await.remove(&internal_id);
+        let pending = self.take_pending_request(internal_id).await;
         if pending.is_none() {
             let waiter = self.internal_waiters.lock().await.remove(&internal_id);
             if let Some(waiter) = waiter {
@@ -3550,14 +5433,40 @@ impl AppServerBridge {
         let Some(pending) = pending else {
             return;
         };
+        if let Ok(raw) = serde_json::to_vec(&response) {
+            self.method_traffic
+                .record_response(&pending.method, raw.len() + 1);
+        }
+
+        let trace_payload = object
+            .get("error")
+            .map(|error| json!({ "error": error }))
+            .unwrap_or_else(|| object.get("result").cloned().unwrap_or(Value::Null));
+        self.record_trace(
+            "response",
+            &pending.method,
+            json!(internal_id),
+            trace_payload,
+        )
+        .await;
 
-        if object.get("error").is_none() {
+        if object.get("error").is_some() {
+            self.release_pending_turn_start(&pending);
+        } else {
             if pending.clear_cached_chatgpt_auth_on_success {
                 clear_cached_bridge_chatgpt_auth();
             }
             if let Some(auth) = pending.cached_chatgpt_auth.clone() {
                 cache_bridge_chatgpt_auth(auth);
             }
+            if let Some(thread_id) = pending.archived_thread_id.as_deref() {
+                cleanup_removed_thread(
+                    &self.hub,
+                    self.attachment_cleanup_root.as_deref(),
+                    thread_id,
+                )
+                .await;
+            }
         }
 
         let client_payload = if let Some(error) = object.get("error") {
@@ -3581,11 +5490,43 @@ impl AppServerBridge {
     }
 
     async fn write_json(&self, payload: Value) -> Result<(), std::io::Error> {
+        self.write_json_line(payload).await.map(|_| ())
+    }
+
+    /// Writes one newline-terminated message and returns how many bytes went out.
+    async fn write_json_line(&self, payload: Value) -> Result<usize, std::io::Error> {
         let line = serde_json::to_string(&payload).map_err(std::io::Error::other)?;
-        let mut writer = self.writer.lock().await;
-        writer.write_all(line.as_bytes()).await?;
-        writer.write_all(b"\n").await?;
-        writer.flush().await
+        let result = async {
+            let mut writer = self.writer.lock().await;
+            writer.write_all(line.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+            writer.flush().await
+        }
+        .await
+        .map(|()| line.len() + 1);
+        if let Err(error) = &result {
+            self.mark_unavailable(error).await;
+        }
+        result
+    }
+
+    /// Records that the child stopped accepting input, so callers that drop write errors
+    /// still leave the failure visible to clients.
+    async fn mark_unavailable(&self, error: &std::io::Error) {
+        if !self.ready.swap(false, Ordering::Relaxed) {
+            return;
+        }
+        eprintln!("app-server stdin write failed: {error}");
+        self.hub
+            .broadcast_notification(
+                "bridge/appServer/unavailable",
+                json!({
+                    "engine": self.engine,
+                    "error": error.to_string(),
+                    "at": now_iso(),
+                }),
+            )
+            .await;
     }
 }
 
@@ -3639,18 +5580,23 @@ impl OpencodeBackend {
             command.env("OPENCODE_SERVER_USERNAME", &config.opencode_server_username);
         }
 
-        let mut child = command
-            .spawn()
-            .map_err(|error| format!("failed to start opencode serve: {error}"))?;
+        let mut child = ReapingChild::new(
+            command
+                .spawn()
+                .map_err(|error| format!("failed to start opencode serve: {error}"))?,
+        );
         let child_pid = child
+            .child_mut()
             .id()
             .ok_or_else(|| "opencode pid unavailable".to_string())?;
 
         let stdout = child
+            .child_mut()
             .stdout
             .take()
             .ok_or_else(|| "opencode stdout unavailable".to_string())?;
         let stderr = child
+            .child_mut()
             .stderr
             .take()
             .ok_or_else(|| "opencode stderr unavailable".to_string())?;
@@ -3662,7 +5608,7 @@ impl OpencodeBackend {
         .map_err(|error| format!("invalid opencode base url: {error}"))?;
 
         let backend = Arc::new(Self {
-            child: Mutex::new(child),
+            child: Mutex::new(child.into_inner()),
             child_pid,
             hub,
             http: HttpClient::builder()
@@ -4212,6 +6158,7 @@ impl OpencodeBackend {
             cwd: Some(directory.clone()),
             grant_root: None,
             proposed_execpolicy_amendment: None,
+            diff_preview: None,
         };
 
         self.pending_approvals.lock().await.insert(
@@ -4361,12 +6308,7 @@ impl OpencodeBackend {
 
         self.broadcast_json_notification(
             "bridge/userInput.resolved",
-            json!({
-                "id": pending.request.id,
-                "threadId": pending.request.thread_id,
-                "turnId": pending.request.turn_id,
-                "resolvedAt": now_iso(),
-            }),
+            user_input_resolved_params(&pending.request),
         )
         .await;
     }
@@ -4404,6 +6346,11 @@ impl OpencodeBackend {
         method: &str,
         params: Option<Value>,
     ) -> Result<(), String> {
+        let turn_start_thread_id = if method == "turn/start" {
+            turn_start_thread_id(params.as_ref())
+        } else {
+            None
+        };
         match self.dispatch_request(method, params).await {
             Ok(result) => {
                 let normalized =
@@ -4417,6 +6364,9 @@ impl OpencodeBackend {
                 Ok(())
             }
             Err(error) => {
+                if let Some(thread_id) = turn_start_thread_id.as_deref() {
+                    self.hub.release_turn_start(thread_id);
+                }
                 let code = if error.starts_with("unsupported opencode backend method:") {
                     -32601
                 } else {
@@ -4567,12 +6517,7 @@ impl OpencodeBackend {
 
         self.broadcast_json_notification(
             "bridge/userInput.resolved",
-            json!({
-                "id": pending.request.id,
-                "threadId": pending.request.thread_id,
-                "turnId": pending.request.turn_id,
-                "resolvedAt": now_iso(),
-            }),
+            user_input_resolved_params(&pending.request),
         )
         .await;
 
@@ -5182,10 +7127,139 @@ impl OpencodeBackend {
     }
 }
 
-#[derive(Default)]
 struct RolloutLiveSyncState {
+    sessions_root: Option<PathBuf>,
+    max_tracked_files: usize,
     files: HashMap<PathBuf, RolloutTrackedFile>,
+    included_events: HashSet<String>,
+    /// `BRIDGE_ROLLOUT_EXTRA_RECORD_TYPES`: record types besides `event_msg` and
+    /// `response_item` that are forwarded as `codex/rollout/<type>`.
+    extra_record_types: HashSet<String>,
+    /// Last `rolloutSeq` per raw thread id and when it was assigned. Kept here rather than
+    /// on the tracked file so numbering continues when discovery drops and re-tracks the
+    /// same file; threads idle past `ROLLOUT_LIVE_SYNC_MAX_FILE_AGE` are pruned.
+    thread_seqs: HashMap<String, (u64, Instant)>,
+    /// Line boundary each dropped file had been read up to, so re-tracking it resumes
+    /// there instead of replaying its tail.
+    resume_offsets: HashMap<PathBuf, u64>,
+    tick: u64,
+    /// Hub event id seen at the previous tick, to measure notification volume per tick.
+    last_tick_event_id: u64,
+    /// Polls skipped in a row by back-pressure throttling.
+    throttled_ticks: u32,
+}
+
+impl RolloutLiveSyncState {
+    fn new(max_tracked_files: usize) -> Self {
+        Self {
+            sessions_root: None,
+            max_tracked_files,
+            files: HashMap::new(),
+            included_events: HashSet::new(),
+            extra_record_types: HashSet::new(),
+            thread_seqs: HashMap::new(),
+            resume_offsets: HashMap::new(),
+            tick: 0,
+            last_tick_event_id: 0,
+            throttled_ticks: 0,
+        }
+    }
+
+    fn with_included_events(mut self, included_events: HashSet<String>) -> Self {
+        self.included_events = included_events;
+        self
+    }
+
+    fn with_extra_record_types(mut self, extra_record_types: HashSet<String>) -> Self {
+        self.extra_record_types = extra_record_types;
+        self
+    }
+
+    fn track(&mut self, mut tracked: RolloutTrackedFile) {
+        if self.files.contains_key(&tracked.path) {
+            return;
+        }
+        if let Some(offset) = self.resume_offsets.remove(&tracked.path) {
+            tracked.resume_from(offset);
+        }
+        self.files.insert(tracked.path.clone(), tracked);
+    }
+
+    fn untrack(&mut self, path: &Path) -> Option<RolloutTrackedFile> {
+        let tracked = self.files.remove(path)?;
+        if let Some(offset) = tracked.line_offset() {
+            self.resume_offsets.insert(path.to_path_buf(), offset);
+        }
+        Some(tracked)
+    }
+
+    fn next_thread_seq(&mut self, thread_id: &str, now: Instant) -> u64 {
+        let (seq, last_used) = self
+            .thread_seqs
+            .entry(thread_id.to_string())
+            .or_insert((0, now));
+        *seq += 1;
+        *last_used = now;
+        *seq
+    }
+
+    /// Forgets sequence counters of idle threads and resume offsets of files discovery no
+    /// longer considers recent, so neither map grows for the life of the process.
+    fn prune(&mut self, recent_paths: &HashSet<PathBuf>, now: Instant) {
+        self.thread_seqs.retain(|_, (_, last_used)| {
+            now.saturating_duration_since(*last_used) < ROLLOUT_LIVE_SYNC_MAX_FILE_AGE
+        });
+        self.resume_offsets
+            .retain(|path, _| recent_paths.contains(path));
+    }
+
+    fn status(&self) -> RolloutSyncStatusResponse {
+        let mut files = self
+            .files
+            .values()
+            .map(|tracked| RolloutTrackedFileStatus {
+                path: tracked.path.to_string_lossy().to_string(),
+                thread_id: tracked.thread_id.clone(),
+                originator: tracked.originator.clone(),
+                include_for_live_sync: tracked.include_for_live_sync,
+                offset: tracked.offset,
+                lines_processed: tracked.lines_processed,
+                last_polled_at: tracked.last_polled_at.clone(),
+                last_line_at: tracked.last_line_at.clone(),
+            })
+            .collect::<Vec<_>>();
+        files.sort_by(|left, right| left.path.cmp(&right.path));
+
+        RolloutSyncStatusResponse {
+            sessions_root: self
+                .sessions_root
+                .as_ref()
+                .map(|root| root.to_string_lossy().to_string()),
+            tick: self.tick,
+            files,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RolloutTrackedFileStatus {
+    path: String,
+    thread_id: Option<String>,
+    originator: Option<String>,
+    include_for_live_sync: bool,
+    offset: u64,
+    lines_processed: u64,
+    last_polled_at: Option<String>,
+    last_line_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RolloutSyncStatusResponse {
+    sessions_root: Option<String>,
     tick: u64,
+    files: Vec<RolloutTrackedFileStatus>,
 }
 
 struct RolloutTrackedFile {
@@ -5197,8 +7271,14 @@ struct RolloutTrackedFile {
     originator: Option<String>,
     include_for_live_sync: bool,
     last_seen: Instant,
+    lines_processed: u64,
+    last_polled_at: Option<String>,
+    last_line_at: Option<String>,
     recent_line_hashes: VecDeque<u64>,
     recent_line_hash_set: HashSet<u64>,
+    /// `(call_id, command)` of synthesized `exec_command_begin` events still waiting for
+    /// their `function_call_output`.
+    pending_exec_calls: VecDeque<(String, Value)>,
 }
 
 impl RolloutTrackedFile {
@@ -5226,24 +7306,44 @@ impl RolloutTrackedFile {
             originator,
             include_for_live_sync,
             last_seen: Instant::now(),
+            lines_processed: 0,
+            last_polled_at: None,
+            last_line_at: None,
             recent_line_hashes: VecDeque::new(),
             recent_line_hash_set: HashSet::new(),
+            pending_exec_calls: VecDeque::new(),
         })
     }
 
-    async fn poll(&mut self, hub: &Arc<ClientHub>) -> Result<(), std::io::Error> {
-        let mut file = match fs::File::open(&self.path).await {
-            Ok(file) => file,
-            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
-                return Err(error);
-            }
-            Err(error) => return Err(error),
-        };
+    /// Offset of the first byte not yet consumed as a whole line, or `None` while the
+    /// initial tail is still looking for its first line boundary.
+    fn line_offset(&self) -> Option<u64> {
+        if self.drop_first_partial_line {
+            return None;
+        }
+        Some(self.offset.saturating_sub(self.partial_line.len() as u64))
+    }
+
+    /// Continues from where an earlier tracker of this file stopped, unless the file has
+    /// since grown past the initial tail window.
+    fn resume_from(&mut self, offset: u64) {
+        if offset >= self.offset {
+            self.offset = offset;
+            self.drop_first_partial_line = false;
+        }
+    }
 
-        let metadata = file.metadata().await?;
-        let len = metadata.len();
+    /// Applies bytes read past the tracked offset and returns the notifications to broadcast
+    /// once the sync state lock is released.
+    fn apply_read(
+        &mut self,
+        read: RolloutFileRead,
+        included_events: &HashSet<String>,
+        extra_record_types: &HashSet<String>,
+    ) -> Vec<RolloutNotification> {
+        self.last_polled_at = Some(now_iso());
 
-        if len < self.offset {
+        if read.start < self.offset {
             self.offset = 0;
             self.partial_line.clear();
             self.drop_first_partial_line = false;
@@ -5251,21 +7351,14 @@ impl RolloutTrackedFile {
             self.recent_line_hash_set.clear();
         }
 
-        if len == self.offset {
-            return Ok(());
+        let mut notifications = Vec::new();
+        if read.bytes.is_empty() {
+            return notifications;
         }
-
-        file.seek(SeekFrom::Start(self.offset)).await?;
-        let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes).await?;
-        self.offset = len;
+        self.offset = read.start + read.bytes.len() as u64;
         self.last_seen = Instant::now();
 
-        if bytes.is_empty() {
-            return Ok(());
-        }
-
-        let chunk = String::from_utf8_lossy(&bytes);
+        let chunk = String::from_utf8_lossy(&read.bytes);
         let mut combined = String::with_capacity(self.partial_line.len() + chunk.len());
         combined.push_str(&self.partial_line);
         combined.push_str(&chunk);
@@ -5277,7 +7370,7 @@ impl RolloutTrackedFile {
                 self.drop_first_partial_line = false;
             } else {
                 self.partial_line = combined;
-                return Ok(());
+                return notifications;
             }
         }
 
@@ -5297,19 +7390,30 @@ impl RolloutTrackedFile {
             if !self.remember_line_hash(line_hash) {
                 continue;
             }
+            self.lines_processed = self.lines_processed.saturating_add(1);
+            self.last_line_at = Some(now_iso());
 
-            if let Some((method, params)) = self.to_notification(trimmed) {
+            if let Some((method, params)) =
+                self.line_to_notification(trimmed, included_events, extra_record_types)
+            {
                 if let Some(status_payload) =
                     build_rollout_thread_status_notification(&method, &params)
                 {
-                    hub.broadcast_notification("thread/status/changed", status_payload)
-                        .await;
+                    notifications.push(RolloutNotification {
+                        method: "thread/status/changed".to_string(),
+                        params: status_payload,
+                        seq_thread_id: None,
+                    });
                 }
-                hub.broadcast_notification(&method, params).await;
+                notifications.push(RolloutNotification {
+                    method,
+                    params,
+                    seq_thread_id: self.thread_id.clone(),
+                });
             }
         }
 
-        Ok(())
+        notifications
     }
 
     fn remember_line_hash(&mut self, line_hash: u64) -> bool {
@@ -5328,7 +7432,12 @@ impl RolloutTrackedFile {
         true
     }
 
-    fn to_notification(&mut self, line: &str) -> Option<(String, Value)> {
+    fn line_to_notification(
+        &mut self,
+        line: &str,
+        included_events: &HashSet<String>,
+        extra_record_types: &HashSet<String>,
+    ) -> Option<(String, Value)> {
         let parsed = serde_json::from_str::<Value>(line).ok()?;
         let parsed_object = parsed.as_object()?;
         let record_type = read_string(parsed_object.get("type"))?;
@@ -5353,215 +7462,979 @@ impl RolloutTrackedFile {
             self.thread_id = Some(payload_thread_id);
         }
 
-        let thread_id = self.thread_id.as_deref()?;
+        let thread_id = self.thread_id.clone()?;
+        let thread_id = thread_id.as_str();
         if record_type == "event_msg" {
-            return build_rollout_event_msg_notification(payload, thread_id, timestamp.as_deref());
+            return build_rollout_event_msg_notification(
+                payload,
+                thread_id,
+                timestamp.as_deref(),
+                included_events,
+            );
         }
 
         if record_type == "response_item" {
-            return build_rollout_response_item_notification(
+            if let Some(command) = self.take_pending_exec_call(payload) {
+                return build_rollout_exec_command_end_notification(
+                    payload,
+                    thread_id,
+                    timestamp.as_deref(),
+                    command,
+                );
+            }
+            let notification =
+                build_rollout_response_item_notification(payload, thread_id, timestamp.as_deref());
+            if let Some((method, params)) = &notification {
+                if method == "codex/event/exec_command_begin" {
+                    self.remember_pending_exec_call(&params["msg"]);
+                }
+            }
+            return notification;
+        }
+
+        if extra_record_types.contains(&record_type) {
+            return Some(build_rollout_passthrough_notification(
+                &record_type,
                 payload,
                 thread_id,
                 timestamp.as_deref(),
-            );
+            ));
         }
 
         None
     }
-}
 
-fn spawn_rollout_live_sync(hub: Arc<ClientHub>) {
-    tokio::spawn(async move {
-        let Some(sessions_root) = resolve_codex_sessions_root() else {
+    fn remember_pending_exec_call(&mut self, msg: &Value) {
+        let Some(call_id) = read_string(msg.get("call_id")) else {
             return;
         };
+        if self.pending_exec_calls.len() >= ROLLOUT_PENDING_EXEC_CALLS_CAPACITY {
+            self.pending_exec_calls.pop_front();
+        }
+        self.pending_exec_calls
+            .push_back((call_id, msg.get("command").cloned().unwrap_or(Value::Null)));
+    }
 
-        let mut state = RolloutLiveSyncState::default();
-        let mut ticker =
-            tokio::time::interval(Duration::from_millis(ROLLOUT_LIVE_SYNC_POLL_INTERVAL_MS));
-        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    fn take_pending_exec_call(
+        &mut self,
+        payload: &serde_json::Map<String, Value>,
+    ) -> Option<Value> {
+        if read_string(payload.get("type")).as_deref() != Some("function_call_output") {
+            return None;
+        }
+        let call_id = read_string(payload.get("call_id"))?;
+        let index = self
+            .pending_exec_calls
+            .iter()
+            .position(|(pending_id, _)| *pending_id == call_id)?;
+        self.pending_exec_calls
+            .remove(index)
+            .map(|(_, command)| command)
+    }
+}
 
+/// Declines approvals and answers user-input prompts with no answers once no client has been
+/// connected for `grace`, so turns do not hang waiting on a device that is gone.
+fn spawn_no_client_pending_decline(
+    backend: Arc<RuntimeBackend>,
+    hub: Arc<ClientHub>,
+    grace: Duration,
+) {
+    let mut client_count = hub.subscribe_client_count();
+    let mut notifications = hub.subscribe_notifications();
+    tokio::spawn(async move {
         loop {
-            ticker.tick().await;
-            state.tick = state.tick.wrapping_add(1);
-
-            if should_run_rollout_discovery_tick(
-                state.tick,
-                ROLLOUT_LIVE_SYNC_DISCOVERY_INTERVAL_TICKS,
-            ) {
-                if let Err(error) =
-                    rollout_live_sync_discover_files(&sessions_root, &mut state).await
-                {
-                    eprintln!("rollout live sync discovery failed: {error}");
+            let has_clients = *client_count.borrow_and_update() > 0;
+            if has_clients {
+                if client_count.changed().await.is_err() {
+                    return;
                 }
+                continue;
             }
 
-            if let Err(error) = rollout_live_sync_poll_files(&hub, &mut state).await {
-                eprintln!("rollout live sync poll failed: {error}");
+            // The grace period runs once per window without clients, from when the last
+            // client left.
+            let reconnected = timeout(grace, client_count.wait_for(|count| *count > 0))
+                .await
+                .map(|result| result.is_ok());
+            match reconnected {
+                Ok(true) => continue,
+                Ok(false) => return,
+                Err(_) => decline_pending_requests_without_clients(&backend, &hub).await,
+            }
+
+            // Past the grace period, requests that arrive before a client reconnects are
+            // declined right away.
+            notifications = notifications.resubscribe();
+            loop {
+                tokio::select! {
+                    reconnected = async {
+                        client_count.wait_for(|count| *count > 0).await.is_ok()
+                    } => {
+                        if !reconnected {
+                            return;
+                        }
+                        break;
+                    }
+                    notification = notifications.recv() => match notification {
+                        Ok(notification)
+                            if !matches!(
+                                notification.method.as_str(),
+                                "bridge/approval.requested" | "bridge/userInput.requested"
+                            ) => {}
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                            decline_pending_requests_without_clients(&backend, &hub).await;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    },
+                }
             }
         }
     });
 }
 
-fn resolve_codex_sessions_root() -> Option<PathBuf> {
-    if let Some(codex_home) = read_non_empty_env("CODEX_HOME") {
-        let root = PathBuf::from(codex_home).join("sessions");
-        if root.is_dir() {
-            return Some(root);
+/// Broadcasts `bridge/presence/changed` with the connected-client count. Changes are
+/// coalesced for `debounce`, so a quick drop-and-reconnect produces no notification.
+fn spawn_presence_broadcast(hub: Arc<ClientHub>, debounce: Duration) {
+    let mut client_count = hub.subscribe_client_count();
+    let mut last_count = *client_count.borrow_and_update();
+    tokio::spawn(async move {
+        loop {
+            if client_count.changed().await.is_err() {
+                return;
+            }
+            sleep(debounce).await;
+
+            let count = *client_count.borrow_and_update();
+            if count == last_count {
+                continue;
+            }
+            last_count = count;
+            hub.broadcast_notification(
+                "bridge/presence/changed",
+                json!({
+                    "connectedClients": count,
+                    "at": now_iso(),
+                }),
+            )
+            .await;
+        }
+    });
+}
+
+async fn decline_pending_requests_without_clients(backend: &RuntimeBackend, hub: &ClientHub) {
+    let decline = json!("decline");
+    let mut approval_ids = Vec::new();
+    for approval in backend.list_pending_approvals().await {
+        match backend.resolve_approval(&approval.id, &decline).await {
+            Ok(Some(_)) => approval_ids.push(approval.id),
+            Ok(None) => {}
+            Err(error) => eprintln!("failed to auto-decline approval {}: {error}", approval.id),
         }
     }
 
-    let home = read_non_empty_env("HOME")?;
-    let root = PathBuf::from(home).join(".codex").join("sessions");
-    if root.is_dir() {
-        Some(root)
-    } else {
-        None
+    let no_answers = HashMap::new();
+    let mut user_input_ids = Vec::new();
+    for request in backend.list_pending_user_inputs().await {
+        match backend.resolve_user_input(&request.id, &no_answers).await {
+            Ok(Some(_)) => user_input_ids.push(request.id),
+            Ok(None) => {}
+            Err(error) => eprintln!("failed to auto-decline user input {}: {error}", request.id),
+        }
+    }
+
+    if approval_ids.is_empty() && user_input_ids.is_empty() {
+        return;
     }
+
+    hub.broadcast_notification(
+        "bridge/pending.autoDeclined",
+        json!({
+            "reason": "no_clients",
+            "approvalIds": approval_ids,
+            "userInputIds": user_input_ids,
+            "at": now_iso(),
+        }),
+    )
+    .await;
 }
 
-async fn rollout_live_sync_discover_files(
-    sessions_root: &Path,
-    state: &mut RolloutLiveSyncState,
-) -> Result<(), std::io::Error> {
-    let discovered_paths = discover_recent_rollout_files(sessions_root).await?;
-    let discovered_set = discovered_paths.iter().cloned().collect::<HashSet<_>>();
+/// Coalesces bursts of filesystem events into one status refresh. A batch is due once
+/// `window` passes without another change, or after `max_delay` during continuous edits.
+struct GitWatchDebounce {
+    window: Duration,
+    max_delay: Duration,
+    first_change: Option<Instant>,
+    last_change: Option<Instant>,
+    paths: HashSet<String>,
+}
 
-    for path in discovered_paths {
-        if state.files.contains_key(&path) {
-            continue;
+impl GitWatchDebounce {
+    fn new(window: Duration, max_delay: Duration) -> Self {
+        Self {
+            window,
+            max_delay,
+            first_change: None,
+            last_change: None,
+            paths: HashSet::new(),
         }
+    }
 
-        match RolloutTrackedFile::new(path.clone()).await {
-            Ok(tracked) => {
-                state.files.insert(path, tracked);
-            }
-            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
-            Err(error) => return Err(error),
+    fn record(&mut self, path: String, now: Instant) {
+        if self.paths.len() < GIT_WATCH_MAX_BATCH_PATHS {
+            self.paths.insert(path);
         }
+        self.first_change.get_or_insert(now);
+        self.last_change = Some(now);
     }
 
-    state.files.retain(|path, tracked| {
-        discovered_set.contains(path)
-            || tracked.last_seen.elapsed() < ROLLOUT_LIVE_SYNC_MAX_FILE_AGE
-    });
+    fn deadline(&self) -> Option<Instant> {
+        let first_change = self.first_change?;
+        let last_change = self.last_change?;
+        Some((last_change + self.window).min(first_change + self.max_delay))
+    }
+
+    fn take_due(&mut self, now: Instant) -> Option<Vec<String>> {
+        if self.deadline()? > now {
+            return None;
+        }
+        self.first_change = None;
+        self.last_change = None;
+        let mut paths = self.paths.drain().collect::<Vec<_>>();
+        paths.sort();
+        Some(paths)
+    }
+}
+
+/// Maps a watcher path to a root-relative path, keeping only `.git/index` and `.git/HEAD`
+/// from inside `.git` so object writes and lock files do not count as changes.
+fn git_watch_relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let mut components = relative.components();
+    let first = components.next()?.as_os_str();
+    if first == ".git" {
+        let rest = components.as_path();
+        if rest != Path::new("index") && rest != Path::new("HEAD") {
+            return None;
+        }
+    }
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
 
+fn spawn_git_watch(git: Arc<GitService>, hub: Arc<ClientHub>, root: PathBuf) -> Result<(), String> {
+    let (watcher, events_rx) = start_git_watcher(&root)?;
+    tokio::spawn(async move {
+        // The watcher stops delivering events once dropped, so it lives with the task.
+        let _watcher = watcher;
+        run_git_watch(
+            events_rx,
+            git,
+            hub,
+            root,
+            GitWatchDebounce::new(GIT_WATCH_DEBOUNCE, GIT_WATCH_MAX_DELAY),
+            GitWatchAudience::Everyone,
+        )
+        .await;
+    });
     Ok(())
 }
 
-async fn rollout_live_sync_poll_files(
-    hub: &Arc<ClientHub>,
-    state: &mut RolloutLiveSyncState,
-) -> Result<(), std::io::Error> {
-    let tracked_paths = state.files.keys().cloned().collect::<Vec<_>>();
-    let mut removed_paths = Vec::new();
+fn start_git_watcher(
+    root: &Path,
+) -> Result<(notify::RecommendedWatcher, mpsc::UnboundedReceiver<PathBuf>), String> {
+    let (events_tx, events_rx) = mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if matches!(event.kind, notify::EventKind::Access(_)) {
+            return;
+        }
+        for path in event.paths {
+            let _ = events_tx.send(path);
+        }
+    })
+    .map_err(|error| format!("failed to start git watcher: {error}"))?;
+    notify::Watcher::watch(&mut watcher, root, notify::RecursiveMode::Recursive)
+        .map_err(|error| format!("failed to watch {}: {error}", root.display()))?;
+    Ok((watcher, events_rx))
+}
 
-    for path in tracked_paths {
-        let Some(tracked) = state.files.get_mut(&path) else {
-            continue;
+/// Who receives the `bridge/git/updated` notifications produced by a watch loop.
+#[derive(Clone)]
+enum GitWatchAudience {
+    /// Every connected client, for the workdir watch enabled by `BRIDGE_GIT_WATCH`.
+    Everyone,
+    /// Only the clients subscribed to the watched repository via `bridge/git/statusSubscribe`.
+    Subscribers(Arc<GitStatusSubscriptions>),
+}
+
+async fn run_git_watch(
+    mut events: mpsc::UnboundedReceiver<PathBuf>,
+    git: Arc<GitService>,
+    hub: Arc<ClientHub>,
+    root: PathBuf,
+    mut debounce: GitWatchDebounce,
+    audience: GitWatchAudience,
+) {
+    loop {
+        let next = match debounce.deadline() {
+            Some(deadline) => {
+                timeout_at(tokio::time::Instant::from_std(deadline), events.recv()).await
+            }
+            None => Ok(events.recv().await),
         };
+        match next {
+            Ok(Some(path)) => {
+                if let Some(relative) = git_watch_relative_path(&root, &path) {
+                    debounce.record(relative, Instant::now());
+                }
+            }
+            Ok(None) => return,
+            Err(_) => {}
+        }
+        // Checked after every event too: a queued event wins over an elapsed timeout, so
+        // a steady stream would otherwise hold the batch past `max_delay`.
+        if let Some(paths) = debounce.take_due(Instant::now()) {
+            broadcast_git_watch_status(&git, &hub, &root, &paths, &audience).await;
+        }
+    }
+}
 
-        match tracked.poll(hub).await {
-            Ok(()) => {}
-            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
-                removed_paths.push(path.clone());
+async fn broadcast_git_watch_status(
+    git: &GitService,
+    hub: &ClientHub,
+    root: &Path,
+    paths: &[String],
+    audience: &GitWatchAudience,
+) {
+    let git_state_changed = paths.iter().any(|path| path.starts_with(".git/"));
+    if !git_state_changed {
+        match git.filter_unignored_paths(root, paths).await {
+            Ok(unignored) if unignored.is_empty() => return,
+            Ok(_) => {}
+            Err(error) => {
+                eprintln!("git watch ignore check failed: {}", error.message);
+                return;
             }
-            Err(error) => return Err(error),
         }
     }
 
-    for path in removed_paths {
-        state.files.remove(&path);
+    let subscribers = match audience {
+        GitWatchAudience::Everyone => None,
+        GitWatchAudience::Subscribers(subscriptions) => {
+            let subscribers = subscriptions.subscribers(root).await;
+            if subscribers.is_empty() {
+                return;
+            }
+            Some(subscribers)
+        }
+    };
+    let cwd = subscribers
+        .as_ref()
+        .map(|_| root.to_string_lossy().to_string());
+
+    match git.get_status(cwd.as_deref()).await {
+        Ok(status) => match serde_json::to_value(status) {
+            Ok(status_value) => match subscribers {
+                None => {
+                    hub.broadcast_notification("bridge/git/updated", status_value)
+                        .await;
+                }
+                Some(subscribers) => {
+                    let payload = json!({
+                        "method": "bridge/git/updated",
+                        "params": status_value,
+                    });
+                    for client_id in subscribers {
+                        hub.send_json(client_id, payload.clone()).await;
+                    }
+                }
+            },
+            Err(error) => eprintln!("git watch status serialization failed: {error}"),
+        },
+        Err(error) => eprintln!("git watch status failed: {}", error.message),
     }
+}
 
-    Ok(())
+/// Per-client git status subscriptions keyed by repository root. Each watched root runs one
+/// file watcher, stopped once its last subscriber unsubscribes or disconnects.
+struct GitStatusSubscriptions {
+    git: Arc<GitService>,
+    hub: Arc<ClientHub>,
+    window: Duration,
+    /// Repository the `BRIDGE_GIT_WATCH` watch already broadcasts to every client.
+    /// Subscribing to it starts no second watcher, so subscribers are not notified twice.
+    global_watch_root: Option<PathBuf>,
+    watches: Mutex<HashMap<PathBuf, GitStatusWatch>>,
 }
 
-async fn discover_recent_rollout_files(root: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
-    let now = SystemTime::now();
-    let mut stack = vec![root.to_path_buf()];
-    let mut matches = Vec::<(PathBuf, SystemTime)>::new();
+struct GitStatusWatch {
+    subscribers: BTreeSet<u64>,
+    /// `None` when the root is covered by the global watch.
+    task: Option<tokio::task::JoinHandle<()>>,
+}
 
-    while let Some(dir) = stack.pop() {
-        let mut entries = match fs::read_dir(&dir).await {
-            Ok(entries) => entries,
-            Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
-            Err(error) => return Err(error),
-        };
+impl GitStatusWatch {
+    fn stop(&self) {
+        if let Some(task) = &self.task {
+            task.abort();
+        }
+    }
+}
 
-        while let Some(entry) = entries.next_entry().await? {
-            let path = entry.path();
-            let metadata = entry.metadata().await?;
+impl GitStatusSubscriptions {
+    fn new(git: Arc<GitService>, hub: Arc<ClientHub>, window: Duration) -> Self {
+        Self {
+            git,
+            hub,
+            window,
+            global_watch_root: None,
+            watches: Mutex::new(HashMap::new()),
+        }
+    }
 
-            if metadata.is_dir() {
-                stack.push(path);
-                continue;
-            }
+    fn with_global_watch_root(mut self, root: PathBuf) -> Self {
+        self.global_watch_root = Some(root);
+        self
+    }
 
-            if !metadata.is_file() || !is_rollout_file_path(&path) {
-                continue;
+    async fn subscribe(
+        self: &Arc<Self>,
+        client_id: u64,
+        raw_cwd: Option<&str>,
+    ) -> Result<PathBuf, BridgeError> {
+        let root = self.git.repo_root(raw_cwd).await?;
+        let mut watches = self.watches.lock().await;
+        // The client may have disconnected while the root was resolved; its cleanup already
+        // ran, so a watch inserted now would never be stopped.
+        if !self.hub.clients.read().await.contains_key(&client_id) {
+            return Err(BridgeError::server("client disconnected"));
+        }
+        if let Some(watch) = watches.get_mut(&root) {
+            watch.subscribers.insert(client_id);
+            return Ok(root);
+        }
+
+        let client_watches = watches
+            .values()
+            .filter(|watch| watch.subscribers.contains(&client_id))
+            .count();
+        if client_watches >= MAX_GIT_STATUS_SUBSCRIPTIONS_PER_CLIENT {
+            return Err(BridgeError::invalid_params(&format!(
+                "at most {MAX_GIT_STATUS_SUBSCRIPTIONS_PER_CLIENT} git status subscriptions are allowed"
+            )));
+        }
+
+        let task = if self.global_watch_root.as_ref() == Some(&root) {
+            None
+        } else {
+            let (watcher, events_rx) =
+                start_git_watcher(&root).map_err(|error| BridgeError::server(&error))?;
+            let (git, hub, watch_root, window, audience) = (
+                self.git.clone(),
+                self.hub.clone(),
+                root.clone(),
+                self.window,
+                GitWatchAudience::Subscribers(self.clone()),
+            );
+            Some(tokio::spawn(async move {
+                // Aborting the task drops the watcher along with the loop.
+                let _watcher = watcher;
+                let debounce = GitWatchDebounce::new(window, GIT_WATCH_MAX_DELAY);
+                run_git_watch(events_rx, git, hub, watch_root, debounce, audience).await;
+            }))
+        };
+        watches.insert(
+            root.clone(),
+            GitStatusWatch {
+                subscribers: BTreeSet::from([client_id]),
+                task,
+            },
+        );
+        Ok(root)
+    }
+
+    async fn unsubscribe(
+        &self,
+        client_id: u64,
+        raw_cwd: Option<&str>,
+    ) -> Result<(PathBuf, bool), BridgeError> {
+        let root = self.git.repo_root(raw_cwd).await?;
+        let mut watches = self.watches.lock().await;
+        let removed = watches
+            .get_mut(&root)
+            .is_some_and(|watch| watch.subscribers.remove(&client_id));
+        if watches
+            .get(&root)
+            .is_some_and(|watch| watch.subscribers.is_empty())
+        {
+            if let Some(watch) = watches.remove(&root) {
+                watch.stop();
             }
+        }
+        Ok((root, removed))
+    }
 
-            let modified = metadata.modified().unwrap_or(now);
-            if now
-                .duration_since(modified)
-                .unwrap_or_else(|_| Duration::from_secs(0))
-                > ROLLOUT_LIVE_SYNC_MAX_FILE_AGE
-            {
-                continue;
+    async fn remove_client(&self, client_id: u64) {
+        let mut watches = self.watches.lock().await;
+        watches.retain(|_, watch| {
+            watch.subscribers.remove(&client_id);
+            if watch.subscribers.is_empty() {
+                watch.stop();
+                return false;
             }
+            true
+        });
+    }
 
-            matches.push((path, modified));
-        }
+    async fn subscribers(&self, root: &Path) -> Vec<u64> {
+        self.watches
+            .lock()
+            .await
+            .get(root)
+            .map(|watch| watch.subscribers.iter().copied().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Spawns rollout tailing unless `BRIDGE_DISABLE_ROLLOUT_SYNC` turned it off. Returns
+/// whether the task was started.
+fn start_rollout_live_sync(
+    config: &BridgeConfig,
+    hub: Arc<ClientHub>,
+    sync_state: Arc<Mutex<RolloutLiveSyncState>>,
+    shutdown_rx: watch::Receiver<bool>,
+) -> bool {
+    if config.disable_rollout_sync {
+        return false;
     }
+    spawn_rollout_live_sync(hub, sync_state, shutdown_rx);
+    true
+}
 
-    matches.sort_by(|left, right| right.1.cmp(&left.1));
-    matches.truncate(ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES);
+fn spawn_rollout_live_sync(
+    hub: Arc<ClientHub>,
+    sync_state: Arc<Mutex<RolloutLiveSyncState>>,
+    shutdown_rx: watch::Receiver<bool>,
+) {
+    tokio::spawn(async move {
+        let Some(sessions_root) = resolve_codex_sessions_root() else {
+            return;
+        };
+        run_rollout_live_sync(
+            hub,
+            sync_state,
+            sessions_root,
+            Duration::from_millis(ROLLOUT_LIVE_SYNC_POLL_INTERVAL_MS),
+            shutdown_rx,
+        )
+        .await;
+    });
+}
 
-    Ok(matches.into_iter().map(|(path, _)| path).collect())
+/// A notification built from rollout lines, broadcast after the sync state lock is released.
+struct RolloutNotification {
+    method: String,
+    params: Value,
+    /// Raw thread id that numbers this notification with `rolloutSeq`; `None` for derived
+    /// notifications such as `thread/status/changed`.
+    seq_thread_id: Option<String>,
 }
 
-fn is_rollout_file_path(path: &Path) -> bool {
-    path.file_name()
-        .and_then(|name| name.to_str())
-        .map(|name| name.starts_with("rollout-") && name.ends_with(".jsonl"))
-        .unwrap_or(false)
+/// Bytes appended to a rollout file past a tracked offset. `start` is 0 when the file
+/// shrank below the offset, i.e. it was truncated or replaced.
+struct RolloutFileRead {
+    start: u64,
+    bytes: Vec<u8>,
 }
 
-async fn read_rollout_session_meta(
+async fn read_rollout_file_from(
     path: &Path,
-) -> Result<Option<(String, Option<String>)>, std::io::Error> {
-    let file = match fs::File::open(path).await {
-        Ok(file) => file,
-        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
-        Err(error) => return Err(error),
-    };
+    offset: u64,
+) -> Result<RolloutFileRead, std::io::Error> {
+    let mut file = fs::File::open(path).await?;
+    let len = file.metadata().await?.len();
+    let start = if len < offset { 0 } else { offset };
+    let mut bytes = Vec::new();
+    if len > start {
+        file.seek(SeekFrom::Start(start)).await?;
+        file.take(len - start).read_to_end(&mut bytes).await?;
+    }
+    Ok(RolloutFileRead { start, bytes })
+}
 
-    let mut lines = BufReader::new(file).lines();
-    let Some(first_line) = lines.next_line().await? else {
-        return Ok(None);
-    };
+/// Polls rollout files until `shutdown_rx` flips to `true`, then runs one last poll so lines
+/// already on disk are broadcast before the task exits.
+async fn run_rollout_live_sync(
+    hub: Arc<ClientHub>,
+    sync_state: Arc<Mutex<RolloutLiveSyncState>>,
+    sessions_root: PathBuf,
+    poll_interval: Duration,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    sync_state.lock().await.sessions_root = Some(sessions_root.clone());
 
-    let parsed = match serde_json::from_str::<Value>(&first_line) {
-        Ok(parsed) => parsed,
-        Err(_) => return Ok(None),
-    };
+    let mut ticker = tokio::time::interval(poll_interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
-    let parsed_object = match parsed.as_object() {
-        Some(object) => object,
-        None => return Ok(None),
-    };
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = wait_for_shutdown_trigger(&mut shutdown_rx) => {
+                if let Err(error) = rollout_live_sync_poll_files(&hub, &sync_state).await {
+                    eprintln!("rollout live sync final poll failed: {error}");
+                }
+                return;
+            }
+        }
 
-    if read_string(parsed_object.get("type")).as_deref() != Some("session_meta") {
-        return Ok(None);
-    }
+        let tick = {
+            let mut state = sync_state.lock().await;
+            state.tick = state.tick.wrapping_add(1);
+            state.tick
+        };
 
-    let payload = match parsed_object.get("payload").and_then(Value::as_object) {
-        Some(payload) => payload,
-        None => return Ok(None),
-    };
+        if should_run_rollout_discovery_tick(tick, ROLLOUT_LIVE_SYNC_DISCOVERY_INTERVAL_TICKS) {
+            if let Err(error) =
+                rollout_live_sync_discover_files(&hub, &sessions_root, &sync_state).await
+            {
+                eprintln!("rollout live sync discovery failed: {error}");
+            }
+        }
 
-    let thread_id = match extract_rollout_thread_id(payload, true) {
+        let (connected_clients, lagging_clients) = hub.client_backlog().await;
+        let latest_event_id = hub.latest_event_id();
+        let (events_since_last_tick, throttled_ticks) = {
+            let mut state = sync_state.lock().await;
+            let events_since_last_tick = latest_event_id.saturating_sub(state.last_tick_event_id);
+            state.last_tick_event_id = latest_event_id;
+            (events_since_last_tick, state.throttled_ticks)
+        };
+        if should_throttle_rollout_poll(
+            events_since_last_tick,
+            connected_clients,
+            lagging_clients,
+            throttled_ticks,
+        ) {
+            if throttled_ticks == 0 {
+                hub.broadcast_notification(
+                    "bridge/rollout/throttled",
+                    json!({
+                        "connectedClients": connected_clients,
+                        "laggingClients": lagging_clients,
+                        "eventsSinceLastTick": events_since_last_tick,
+                        "maxSkippedTicks": ROLLOUT_THROTTLE_MAX_SKIPPED_TICKS,
+                    }),
+                )
+                .await;
+            }
+            sync_state.lock().await.throttled_ticks += 1;
+            continue;
+        }
+        sync_state.lock().await.throttled_ticks = 0;
+
+        if let Err(error) = rollout_live_sync_poll_files(&hub, &sync_state).await {
+            eprintln!("rollout live sync poll failed: {error}");
+        }
+    }
+}
+
+/// Whether live sync should skip this tick's poll because notifications are being produced
+/// faster than clients drain them: a busy tick while more than half of the connected
+/// clients are behind. Unread lines stay at each file's offset, so skipping only delays
+/// them, and at most `ROLLOUT_THROTTLE_MAX_SKIPPED_TICKS` polls are skipped in a row.
+fn should_throttle_rollout_poll(
+    events_since_last_tick: u64,
+    connected_clients: usize,
+    lagging_clients: usize,
+    throttled_ticks: u32,
+) -> bool {
+    throttled_ticks < ROLLOUT_THROTTLE_MAX_SKIPPED_TICKS
+        && connected_clients > 0
+        && lagging_clients * 2 > connected_clients
+        && events_since_last_tick >= ROLLOUT_THROTTLE_MIN_EVENTS_PER_TICK
+}
+
+fn resolve_codex_sessions_root() -> Option<PathBuf> {
+    if let Some(codex_home) = read_non_empty_env("CODEX_HOME") {
+        let root = PathBuf::from(codex_home).join("sessions");
+        if root.is_dir() {
+            return Some(root);
+        }
+    }
+
+    let home = read_non_empty_env("HOME")?;
+    let root = PathBuf::from(home).join(".codex").join("sessions");
+    if root.is_dir() {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+/// Discovery and polling only hold `sync_state` to snapshot or update tracked files, never
+/// across file I/O or broadcasts, so `bridge/rollout/status` and exports do not wait on disk.
+async fn rollout_live_sync_discover_files(
+    hub: &ClientHub,
+    sessions_root: &Path,
+    sync_state: &Mutex<RolloutLiveSyncState>,
+) -> Result<(), std::io::Error> {
+    let (max_tracked_files, tracked_paths) = {
+        let state = sync_state.lock().await;
+        (
+            state.max_tracked_files,
+            state.files.keys().cloned().collect::<HashSet<_>>(),
+        )
+    };
+    let (discovered_paths, over_cap_paths) =
+        discover_recent_rollout_files(sessions_root, max_tracked_files).await?;
+
+    let mut new_files = Vec::new();
+    for path in &discovered_paths {
+        if tracked_paths.contains(path) {
+            continue;
+        }
+
+        match RolloutTrackedFile::new(path.clone()).await {
+            Ok(tracked) => new_files.push(tracked),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    let discovered_set = discovered_paths.into_iter().collect::<HashSet<_>>();
+    let evicted_files = {
+        let mut state = sync_state.lock().await;
+        let evicted_files = over_cap_paths
+            .iter()
+            .filter_map(|path| state.untrack(path))
+            .collect::<Vec<_>>();
+        for tracked in new_files {
+            state.track(tracked);
+        }
+        let stale_paths = state
+            .files
+            .iter()
+            .filter(|(path, tracked)| {
+                !discovered_set.contains(*path)
+                    && tracked.last_seen.elapsed() >= ROLLOUT_LIVE_SYNC_MAX_FILE_AGE
+            })
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        for path in stale_paths {
+            state.untrack(&path);
+        }
+        let recent_paths = discovered_set
+            .iter()
+            .chain(&over_cap_paths)
+            .cloned()
+            .collect::<HashSet<_>>();
+        state.prune(&recent_paths, Instant::now());
+        evicted_files
+    };
+
+    for evicted in evicted_files {
+        hub.broadcast_notification(
+            "bridge/rollout/fileEvicted",
+            json!({
+                "path": evicted.path.to_string_lossy(),
+                "threadId": evicted.thread_id,
+                "reason": "max_tracked_files",
+                "maxTrackedFiles": max_tracked_files,
+            }),
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
+async fn rollout_live_sync_poll_files(
+    hub: &Arc<ClientHub>,
+    sync_state: &Mutex<RolloutLiveSyncState>,
+) -> Result<(), std::io::Error> {
+    let tracked_offsets = {
+        let state = sync_state.lock().await;
+        state
+            .files
+            .iter()
+            .map(|(path, tracked)| (path.clone(), tracked.offset))
+            .collect::<Vec<_>>()
+    };
+
+    for (path, offset) in tracked_offsets {
+        let read = match read_rollout_file_from(&path, offset).await {
+            Ok(read) => read,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                sync_state.lock().await.files.remove(&path);
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+
+        let notifications = {
+            let mut guard = sync_state.lock().await;
+            let state = &mut *guard;
+            let Some(tracked) = state.files.get_mut(&path) else {
+                continue;
+            };
+            // The file was reset or re-tracked while it was being read.
+            if tracked.offset != offset {
+                continue;
+            }
+            tracked.apply_read(read, &state.included_events, &state.extra_record_types)
+        };
+
+        for mut notification in notifications {
+            if let Some(thread_id) = notification.seq_thread_id.as_deref() {
+                // The live app-server already delivered this event during an active turn.
+                if hub.is_recent_live_event(&notification.method, &notification.params) {
+                    continue;
+                }
+                let seq = sync_state
+                    .lock()
+                    .await
+                    .next_thread_seq(thread_id, Instant::now());
+                if let Some(object) = notification.params.as_object_mut() {
+                    object.insert("rolloutSeq".to_string(), json!(seq));
+                }
+            }
+            hub.broadcast_notification(&notification.method, notification.params)
+                .await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the newest rollout files up to `max_files`, plus the recent files that
+/// were dropped because of that cap.
+async fn discover_recent_rollout_files(
+    root: &Path,
+    max_files: usize,
+) -> Result<(Vec<PathBuf>, Vec<PathBuf>), std::io::Error> {
+    let now = SystemTime::now();
+    let mut stack = vec![root.to_path_buf()];
+    let mut matches = Vec::<(PathBuf, SystemTime)>::new();
+
+    while let Some(dir) = stack.pop() {
+        let mut entries = match fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let metadata = entry.metadata().await?;
+
+            if metadata.is_dir() {
+                stack.push(path);
+                continue;
+            }
+
+            if !metadata.is_file() || !is_rollout_file_path(&path) {
+                continue;
+            }
+
+            let modified = metadata.modified().unwrap_or(now);
+            if now
+                .duration_since(modified)
+                .unwrap_or_else(|_| Duration::from_secs(0))
+                > ROLLOUT_LIVE_SYNC_MAX_FILE_AGE
+            {
+                continue;
+            }
+
+            matches.push((path, modified));
+        }
+    }
+
+    matches.sort_by_key(|entry| std::cmp::Reverse(entry.1));
+    let over_cap = matches
+        .split_off(max_files.min(matches.len()))
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+
+    Ok((
+        matches.into_iter().map(|(path, _)| path).collect(),
+        over_cap,
+    ))
+}
+
+/// Finds the rollout file of `thread_id` under `root`. Codex names rollout files
+/// `rollout-<timestamp>-<thread id>.jsonl`, so only file names are compared.
+async fn find_thread_rollout_file(
+    root: &Path,
+    thread_id: &str,
+) -> Result<Option<PathBuf>, std::io::Error> {
+    let suffix = format!("-{thread_id}.jsonl");
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let mut entries = match fs::read_dir(&dir).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(error) => return Err(error),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                stack.push(path);
+            } else if file_type.is_file()
+                && is_rollout_file_path(&path)
+                && entry.file_name().to_string_lossy().ends_with(&suffix)
+            {
+                return Ok(Some(path));
+            }
+        }
+    }
+    Ok(None)
+}
+
+fn is_rollout_file_path(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name.starts_with("rollout-") && name.ends_with(".jsonl"))
+        .unwrap_or(false)
+}
+
+async fn read_rollout_session_meta(
+    path: &Path,
+) -> Result<Option<(String, Option<String>)>, std::io::Error> {
+    let file = match fs::File::open(path).await {
+        Ok(file) => file,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error),
+    };
+
+    let mut lines = BufReader::new(file).lines();
+    let Some(first_line) = lines.next_line().await? else {
+        return Ok(None);
+    };
+
+    let parsed = match serde_json::from_str::<Value>(&first_line) {
+        Ok(parsed) => parsed,
+        Err(_) => return Ok(None),
+    };
+
+    let parsed_object = match parsed.as_object() {
+        Some(object) => object,
+        None => return Ok(None),
+    };
+
+    if read_string(parsed_object.get("type")).as_deref() != Some("session_meta") {
+        return Ok(None);
+    }
+
+    let payload = match parsed_object.get("payload").and_then(Value::as_object) {
+        Some(payload) => payload,
+        None => return Ok(None),
+    };
+
+    let thread_id = match extract_rollout_thread_id(payload, true) {
         Some(id) => id,
         None => return Ok(None),
     };
@@ -5615,7 +8488,7 @@ fn should_run_rollout_discovery_tick(tick: u64, interval_ticks: u64) -> bool {
         return true;
     }
 
-    tick == 1 || tick % interval_ticks == 0
+    tick == 1 || tick.is_multiple_of(interval_ticks)
 }
 
 fn rollout_originator_allowed(originator: Option<&str>) -> bool {
@@ -5655,14 +8528,38 @@ fn build_rollout_thread_status_notification(method: &str, params: &Value) -> Opt
     }))
 }
 
+/// Forwards a record type enabled by `BRIDGE_ROLLOUT_EXTRA_RECORD_TYPES` as-is under
+/// `codex/rollout/<type>`, adding only the thread id and timestamp.
+fn build_rollout_passthrough_notification(
+    record_type: &str,
+    payload: &serde_json::Map<String, Value>,
+    thread_id: &str,
+    timestamp: Option<&str>,
+) -> (String, Value) {
+    let thread_id = encode_engine_qualified_id(BridgeRuntimeEngine::Codex, thread_id);
+    (
+        format!("codex/rollout/{record_type}"),
+        json!({
+            "threadId": thread_id,
+            "thread_id": thread_id,
+            "recordType": record_type,
+            "timestamp": timestamp,
+            "payload": Value::Object(payload.clone()),
+        }),
+    )
+}
+
 fn build_rollout_event_msg_notification(
     payload: &serde_json::Map<String, Value>,
     thread_id: &str,
     timestamp: Option<&str>,
+    included_events: &HashSet<String>,
 ) -> Option<(String, Value)> {
     let thread_id = encode_engine_qualified_id(BridgeRuntimeEngine::Codex, thread_id);
     let raw_type = read_string(payload.get("type"))?;
-    if matches!(raw_type.as_str(), "user_message" | "context_compacted") {
+    if ROLLOUT_DROPPED_EVENT_TYPES.contains(&raw_type.as_str())
+        && !included_events.contains(&raw_type)
+    {
         return None;
     }
 
@@ -5795,6 +8692,69 @@ fn build_rollout_response_item_notification(
     None
 }
 
+/// Summarizes an `exec_command` output record as `exec_command_end` with the full output's
+/// byte count and a preview capped at `ROLLOUT_EXEC_OUTPUT_PREVIEW_MAX_BYTES`, so clients
+/// can decide whether to load the rest from the thread.
+fn build_rollout_exec_command_end_notification(
+    payload: &serde_json::Map<String, Value>,
+    thread_id: &str,
+    timestamp: Option<&str>,
+    command: Value,
+) -> Option<(String, Value)> {
+    let thread_id = encode_engine_qualified_id(BridgeRuntimeEngine::Codex, thread_id);
+    let (output, exit_code) = extract_rollout_exec_output(payload.get("output"));
+    let mut preview_len = output.len().min(ROLLOUT_EXEC_OUTPUT_PREVIEW_MAX_BYTES);
+    while !output.is_char_boundary(preview_len) {
+        preview_len -= 1;
+    }
+
+    let mut msg = serde_json::Map::new();
+    msg.insert("type".to_string(), json!("exec_command_end"));
+    msg.insert("thread_id".to_string(), json!(thread_id));
+    msg.insert("threadId".to_string(), json!(thread_id));
+    msg.insert("command".to_string(), command);
+    if let Some(call_id) = read_string(payload.get("call_id")) {
+        msg.insert("call_id".to_string(), json!(call_id));
+    }
+    if let Some(exit_code) = exit_code {
+        msg.insert("exit_code".to_string(), json!(exit_code));
+    }
+    msg.insert("outputBytes".to_string(), json!(output.len()));
+    msg.insert("preview".to_string(), json!(&output[..preview_len]));
+    msg.insert(
+        "previewTruncated".to_string(),
+        json!(preview_len < output.len()),
+    );
+    if let Some(timestamp) = timestamp {
+        msg.insert("timestamp".to_string(), json!(timestamp));
+    }
+    Some((
+        "codex/event/exec_command_end".to_string(),
+        json!({ "msg": Value::Object(msg) }),
+    ))
+}
+
+/// Reads the text of an exec output record. Plain strings are used as-is; the older
+/// `{ "output": ..., "metadata": { "exit_code": ... } }` shape also yields the exit code.
+fn extract_rollout_exec_output(raw_output: Option<&Value>) -> (String, Option<i64>) {
+    let structured = match raw_output {
+        Some(Value::String(text)) => match serde_json::from_str::<Value>(text) {
+            Ok(value) if value.get("output").is_some_and(Value::is_string) => value,
+            _ => return (text.clone(), None),
+        },
+        Some(value @ Value::Object(_)) => value.clone(),
+        _ => return (String::new(), None),
+    };
+    let text = read_string(structured.get("output"))
+        .or_else(|| read_string(structured.get("content")))
+        .unwrap_or_default();
+    let exit_code = structured
+        .get("metadata")
+        .and_then(|metadata| metadata.get("exit_code"))
+        .and_then(Value::as_i64);
+    (text, exit_code)
+}
+
 fn build_rollout_goal_ui_surface_notification(
     payload: &serde_json::Map<String, Value>,
     fallback_thread_id: &str,
@@ -6175,6 +9135,14 @@ impl BridgeError {
         }
     }
 
+    fn not_a_git_repository(cwd: &str) -> Self {
+        Self {
+            code: -32000,
+            message: format!("not a git repository: {cwd}"),
+            data: Some(json!({ "error": "not_a_git_repository", "cwd": cwd })),
+        }
+    }
+
     fn forbidden(error: &str, message: &str) -> Self {
         Self {
             code: -32003,
@@ -6190,6 +9158,9 @@ struct TerminalExecRequest {
     command: String,
     cwd: Option<String>,
     timeout_ms: Option<u64>,
+    capture_to_file: Option<bool>,
+    /// Hex HMAC-SHA256 of the trimmed command under `BRIDGE_COMMAND_SIGNING_KEY`.
+    command_token: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6200,35 +9171,146 @@ struct TerminalExecResponse {
     code: Option<i32>,
     stdout: String,
     stderr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stdout_path: Option<String>,
     timed_out: bool,
+    /// Set when stdout or stderr hit the capture cap and the command was stopped early.
+    truncated: bool,
     duration_ms: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct BridgeUpdateStartRequest {
-    version: Option<String>,
+struct TerminalHistoryEntry {
+    command: String,
+    cwd: String,
+    code: Option<i32>,
+    timed_out: bool,
+    duration_ms: u64,
+    executed_at: String,
 }
 
+/// Reads back part of a file written by `bridge/terminal/exec` with `captureToFile`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct GitStatusResponse {
-    branch: String,
-    clean: bool,
-    raw: String,
-    files: Vec<GitStatusEntry>,
-    cwd: String,
+#[serde(rename_all = "camelCase")]
+struct TerminalOutputReadRequest {
+    /// The `stdoutPath` returned by the exec.
+    path: String,
+    offset: Option<u64>,
+    limit: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct GitStatusEntry {
+struct TerminalOutputReadResponse {
     path: String,
-    original_path: Option<String>,
-    index_status: String,
-    worktree_status: String,
+    offset: u64,
+    /// Raw bytes of the chunk; a chunk boundary may split a multi-byte character.
+    data_base64: String,
+    size: u64,
+    eof: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TerminalHistoryRequest {
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TerminalHistoryResponse {
+    entries: Vec<TerminalHistoryEntry>,
+    capacity: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeUpdateStartRequest {
+    version: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitStatusResponse {
+    branch: String,
+    clean: bool,
+    raw: String,
+    files: Vec<GitStatusEntry>,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStatusMultiRequest {
+    cwds: Vec<String>,
+}
+
+/// `bridge/git/statusMulti` result, keyed by each requested `cwd` as given.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStatusMultiResponse {
+    results: BTreeMap<String, GitStatusMultiEntry>,
+}
+
+/// Exactly one of `status` or `error` is set.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStatusMultiEntry {
+    status: Option<GitStatusResponse>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitIsCleanResponse {
+    clean: bool,
+    changed_count: usize,
+    cwd: String,
+}
+
+/// Counts-only view of `git status` for badges.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStatusSummaryResponse {
+    branch: String,
+    upstream: Option<String>,
+    upstream_gone: bool,
+    ahead: u32,
+    behind: u32,
+    staged: usize,
+    unstaged: usize,
+    untracked: usize,
+    conflicts: usize,
+    clean: bool,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStatusEntry {
+    path: String,
+    original_path: Option<String>,
+    index_status: String,
+    worktree_status: String,
     staged: bool,
     unstaged: bool,
     untracked: bool,
+    ignored: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStagedPath {
+    path: String,
+    original_path: Option<String>,
+    status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStagedPathsResponse {
+    staged: Vec<GitStagedPath>,
+    cwd: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6255,6 +9337,70 @@ struct GitHistoryResponse {
     cwd: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitRevParseResponse {
+    #[serde(rename = "ref")]
+    git_ref: String,
+    hash: String,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitWorktreeEntry {
+    path: String,
+    head: Option<String>,
+    branch: Option<String>,
+    bare: bool,
+    detached: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitWorktreesResponse {
+    worktrees: Vec<GitWorktreeEntry>,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitReflogEntry {
+    hash: String,
+    selector: String,
+    subject: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitReflogResponse {
+    entries: Vec<GitReflogEntry>,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct GitFileLogResponse {
+    entries: Vec<GitHistoryCommit>,
+    path: String,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+struct GitBlameLine {
+    hash: String,
+    line_number: usize,
+    original_line_number: usize,
+    author: String,
+    /// Unix seconds.
+    author_time: Option<i64>,
+    summary: String,
+    content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitBlameRangeResponse {
+    lines: Vec<GitBlameLine>,
+    path: String,
+    cwd: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GitBranchSummary {
@@ -6303,6 +9449,26 @@ struct GitStageAllResponse {
     cwd: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitConfigResponse {
+    key: String,
+    value: Option<String>,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitMoveResponse {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    moved: bool,
+    from: String,
+    to: String,
+    cwd: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GitUnstageResponse {
@@ -6330,6 +9496,22 @@ struct GitCommitResponse {
     stdout: String,
     stderr: String,
     committed: bool,
+    /// The new `HEAD`, read before the repo lock is released; `None` unless committed.
+    hash: Option<String>,
+    cwd: String,
+}
+
+/// `code`/`stdout`/`stderr` come from `git commit`, or from `git add -A` when staging failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitCommitAllResponse {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    staged: bool,
+    committed: bool,
+    /// The new `HEAD`, read before the repo lock is released; `None` unless committed.
+    hash: Option<String>,
     cwd: String,
 }
 
@@ -6344,6 +9526,85 @@ struct GitSwitchResponse {
     cwd: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitInitResponse {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    initialized: bool,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitApplyPatchResponse {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    applied: bool,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitTagsResponse {
+    tags: Vec<String>,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStashEntry {
+    #[serde(rename = "ref")]
+    stash_ref: String,
+    message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStashListResponse {
+    stashes: Vec<GitStashEntry>,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStashActionResponse {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    success: bool,
+    #[serde(rename = "ref")]
+    stash_ref: String,
+    conflicts: Vec<String>,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitCherryPickResponse {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    success: bool,
+    aborted: bool,
+    hash: Option<String>,
+    conflicts: Vec<String>,
+    cwd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitTagCreateResponse {
+    code: Option<i32>,
+    stdout: String,
+    stderr: String,
+    created: bool,
+    tag: String,
+    cwd: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct GitPushResponse {
     code: Option<i32>,
@@ -6359,6 +9620,22 @@ struct GitQueryRequest {
     cwd: Option<String>,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitStatusRequest {
+    cwd: Option<String>,
+    #[serde(default)]
+    include_ignored: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitDiffRequest {
+    cwd: Option<String>,
+    base: Option<String>,
+    head: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GitHubAuthInstallRequest {
@@ -6392,6 +9669,30 @@ struct GitHistoryRequest {
     limit: Option<usize>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitFileLogRequest {
+    path: String,
+    cwd: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitBlameRangeRequest {
+    path: String,
+    start: usize,
+    end: usize,
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitInitRequest {
+    cwd: Option<String>,
+    initial_branch: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct GitCloneRequest {
@@ -6407,11 +9708,41 @@ struct GitFileRequest {
     cwd: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitConfigGetRequest {
+    key: String,
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitConfigSetRequest {
+    key: String,
+    value: String,
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitMoveRequest {
+    from: String,
+    to: String,
+    cwd: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct EventReplayRequest {
     after_event_id: Option<u64>,
     limit: Option<usize>,
+    methods: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventGetRequest {
+    event_id: u64,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -6438,31 +9769,105 @@ struct GitCommitRequest {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct GitSwitchRequest {
-    branch: String,
+struct GitApplyPatchRequest {
+    patch: String,
     cwd: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct AttachmentUploadRequest {
-    data_base64: String,
-    file_name: Option<String>,
-    mime_type: Option<String>,
-    thread_id: Option<String>,
-    kind: Option<String>,
+struct GitCherryPickRequest {
+    hash: Option<String>,
+    abort: Option<bool>,
+    cwd: Option<String>,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct WorkspaceListRequest {
-    limit: Option<usize>,
+struct GitRevParseRequest {
+    #[serde(rename = "ref")]
+    git_ref: String,
+    cwd: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct WorkspaceSummary {
-    path: String,
+struct GitStashRefRequest {
+    #[serde(rename = "ref")]
+    stash_ref: String,
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitTagCreateRequest {
+    name: String,
+    message: String,
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GitSwitchRequest {
+    branch: String,
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentUploadRequest {
+    data_base64: String,
+    file_name: Option<String>,
+    mime_type: Option<String>,
+    thread_id: Option<String>,
+    kind: Option<String>,
+    idempotency_key: Option<String>,
+    /// Hex SHA-256 of the decoded bytes; a mismatch rejects the upload before it is written.
+    expected_sha256: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentMoveRequest {
+    path: String,
+    thread_id: String,
+}
+
+/// One idempotency key's upload. The first request fills `upload`; retries that arrive
+/// while it is still writing wait on the cell instead of writing again.
+struct CachedAttachmentUpload {
+    /// The stored response and the SHA-256 of the stored bytes.
+    upload: OnceCell<(AttachmentUploadResponse, String)>,
+    stored_at: Instant,
+}
+
+/// Idempotent uploads keyed by (sanitized thread id, idempotency key).
+type AttachmentUploadCache = HashMap<(String, String), Arc<CachedAttachmentUpload>>;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceListRequest {
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceSetCwdRequest {
+    thread_id: String,
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceSetCwdResponse {
+    thread_id: String,
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceSummary {
+    path: String,
     chat_count: usize,
     updated_at: Option<u64>,
 }
@@ -6479,9 +9884,13 @@ struct WorkspaceListResponse {
 #[serde(rename_all = "camelCase")]
 struct FileSystemListRequest {
     path: Option<String>,
+    /// When set, `path` resolves against this directory and listing is confined to
+    /// `BRIDGE_WORKDIR` (file-browser mode) instead of browsing the whole host.
+    cwd: Option<String>,
     include_hidden: Option<bool>,
     directories_only: Option<bool>,
     include_git_repo: Option<bool>,
+    limit: Option<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6490,9 +9899,28 @@ struct FileSystemEntry {
     name: String,
     path: String,
     kind: String,
+    is_dir: bool,
     hidden: bool,
     selectable: bool,
     is_git_repo: bool,
+    size_bytes: Option<u64>,
+    modified_at: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileSystemWriteRequest {
+    path: String,
+    content_base64: String,
+    cwd: Option<String>,
+    create_dirs: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileSystemWriteResponse {
+    path: String,
+    size_bytes: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6502,6 +9930,7 @@ struct FileSystemListResponse {
     path: String,
     parent_path: Option<String>,
     entries: Vec<FileSystemEntry>,
+    truncated: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6514,6 +9943,13 @@ struct AttachmentUploadResponse {
     kind: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AttachmentMoveResponse {
+    path: String,
+    previous_path: String,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct VoiceTranscribeRequest {
@@ -6543,6 +9979,9 @@ struct PendingApproval {
     cwd: Option<String>,
     grant_root: Option<String>,
     proposed_execpolicy_amendment: Option<Vec<String>>,
+    /// File-change approvals only: the patch the app-server sent, capped at
+    /// `APPROVAL_DIFF_PREVIEW_MAX_BYTES` so the approval card can show it inline.
+    diff_preview: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -6552,6 +9991,14 @@ struct ResolveApprovalRequest {
     decision: Value,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolveAllApprovalsRequest {
+    decision: Value,
+    kind: Option<String>,
+    thread_id: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct UserInputAnswerPayload {
@@ -6716,6 +10163,76 @@ struct DismissBridgeUiSurfaceRequest {
     thread_id: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeTurnSteerRequest {
+    thread_id: String,
+    expected_turn_id: String,
+    input: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeThreadExportRequest {
+    thread_id: String,
+    inline: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadExportResponse {
+    thread_id: String,
+    rollout_path: String,
+    rollout_size_bytes: u64,
+    event_count: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rollout_base64: Option<String>,
+    attachments: Vec<ThreadExportAttachment>,
+    attachments_truncated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadExportAttachment {
+    file_name: String,
+    path: String,
+    size_bytes: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data_base64: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeThreadDeleteRequest {
+    thread_id: String,
+    confirm: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeSkillsOverviewRequest {
+    cwds: Option<Vec<String>>,
+}
+
+/// `skills/list` and `skills/remote/list` in one response. Each remote skill gains
+/// `installed`, true when a local skill has the same name (case-insensitive).
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeSkillsOverviewResponse {
+    local: Vec<Value>,
+    remote: Vec<Value>,
+    local_error: Option<String>,
+    remote_error: Option<String>,
+    at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BridgeThreadDeleteResponse {
+    thread_id: String,
+    deleted: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct BridgeThreadQueueReadRequest {
@@ -6824,6 +10341,13 @@ struct RpcQuery {
     token: Option<String>,
     client_type: Option<String>,
     client_name: Option<String>,
+    client_key: Option<String>,
+    queue_capacity: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientSubscriptionsSetRequest {
+    topics: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -6852,8 +10376,17 @@ async fn main() {
             "query-token auth is enabled (BRIDGE_ALLOW_QUERY_TOKEN_AUTH=true); prefer Authorization headers instead"
         );
     }
-    let hub = Arc::new(ClientHub::new());
-    let backend = match RuntimeBackend::start(&config, hub.clone()).await {
+    let hub = Arc::new(
+        ClientHub::new()
+            .with_rate_limit(config.client_rate_limit)
+            .with_replay_compression(config.replay_compress)
+            .with_max_clients(config.max_clients)
+            .with_strict_jsonrpc(config.strict_jsonrpc)
+            .with_rollout_dedup_window(config.rollout_dedup_window)
+            .with_turn_start_guard(config.guard_concurrent_turns),
+    );
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let backend = match RuntimeBackend::start(&config, hub.clone(), shutdown_rx.clone()).await {
         Ok(client) => client,
         Err(error) => {
             eprintln!("{error}");
@@ -6861,17 +10394,50 @@ async fn main() {
         }
     };
 
-    let terminal = Arc::new(TerminalService::new(
-        config.workdir.clone(),
-        config.terminal_allowed_commands.clone(),
-        config.disable_terminal_exec,
-        config.allow_outside_root_cwd,
-    ));
+    let terminal = Arc::new(
+        TerminalService::new(
+            config.workdir.clone(),
+            config.terminal_allowed_commands.clone(),
+            config.terminal_blocked_args.clone(),
+            config.disable_terminal_exec,
+            config.allow_outside_root_cwd,
+            config.terminal_history_size,
+        )
+        .with_attachments_root(config.attachments_dir.clone())
+        .with_readonly(config.terminal_readonly)
+        .with_command_signing_key(config.command_signing_key.as_deref())
+        .with_max_output_bytes(config.terminal_max_output_bytes),
+    );
     let git = Arc::new(GitService::new(
         terminal.clone(),
         config.workdir.clone(),
         config.allow_outside_root_cwd,
     ));
+    let workdir_git_root = git.workdir_repo_root().await;
+    if workdir_git_root.is_none() {
+        eprintln!(
+            "warning: BRIDGE_WORKDIR {} is not inside a git work tree; bridge/git/* calls without a repo cwd will fail with not_a_git_repository",
+            config.workdir.display()
+        );
+    }
+    let mut git_status_subscriptions =
+        GitStatusSubscriptions::new(git.clone(), hub.clone(), GIT_WATCH_DEBOUNCE);
+    if config.git_watch {
+        match workdir_git_root.as_ref() {
+            None => eprintln!(
+                "BRIDGE_GIT_WATCH ignored: {} is not inside a git work tree",
+                config.workdir.display()
+            ),
+            Some(repo_root) => match spawn_git_watch(git.clone(), hub.clone(), repo_root.clone()) {
+                Ok(()) => {
+                    git_status_subscriptions =
+                        git_status_subscriptions.with_global_watch_root(repo_root.clone());
+                }
+                Err(error) => eprintln!("{error}"),
+            },
+        }
+    }
+    let git_status_subscriptions = Arc::new(git_status_subscriptions);
     let updater = Arc::new(UpdateService::discover());
     let preview = Arc::new(BrowserPreviewService::new(
         config.port,
@@ -6879,6 +10445,10 @@ async fn main() {
         config.preview_connect_url.clone(),
     ));
     let queue = BridgeQueueService::new(backend.clone(), hub.clone());
+    if let Some(grace) = config.decline_on_no_clients_grace {
+        spawn_no_client_pending_decline(backend.clone(), hub.clone(), grace);
+    }
+    spawn_presence_broadcast(hub.clone(), PRESENCE_BROADCAST_DEBOUNCE);
 
     let state = Arc::new(AppState {
         config: config.clone(),
@@ -6887,16 +10457,27 @@ async fn main() {
         backend,
         queue,
         thread_list_streams: Arc::new(Mutex::new(HashMap::new())),
+        attachment_uploads: Arc::new(Mutex::new(HashMap::new())),
         terminal,
         git,
+        git_status_subscriptions,
+        rejected_methods: Arc::new(RejectedMethodCounter::default()),
+        workdir_git_root,
+        forwarded_methods: build_forwarded_methods(&config.extra_forwarded_methods),
         updater,
         preview,
     });
 
     let app = Router::new()
-        .route("/rpc", get(ws_handler))
+        .route(
+            "/rpc",
+            get(ws_handler)
+                .post(http_rpc_handler)
+                .options(http_rpc_preflight_handler),
+        )
         .route("/health", get(health_handler))
         .route("/status", get(status_handler))
+        .route("/metrics", get(metrics_handler))
         .route("/local-image", get(local_image_handler))
         .with_state(state.clone());
     let preview_app = Router::new()
@@ -6941,7 +10522,6 @@ async fn main() {
     }
     maybe_print_pairing_qr(&config);
 
-    let (shutdown_tx, shutdown_rx) = watch::channel(false);
     let preview_task = preview_listener.map(|listener| {
         let mut preview_shutdown_rx = shutdown_rx.clone();
         tokio::spawn(async move {
@@ -6956,11 +10536,15 @@ async fn main() {
         })
     });
     let shutdown_backend = state.backend.clone();
+    let shutdown_hub = state.hub.clone();
     let shutdown_signal_tx = shutdown_tx.clone();
     let serve_result = axum::serve(listener, app)
         .with_graceful_shutdown(async move {
             let signal = wait_for_shutdown_signal().await;
             eprintln!("shutdown signal received ({signal}), terminating managed backends");
+            shutdown_hub
+                .disconnect_all_clients("shutdown", close_code::AWAY)
+                .await;
             let _ = shutdown_signal_tx.send(true);
             shutdown_backend.shutdown().await;
         })
@@ -6983,6 +10567,8 @@ async fn health_handler(State(state): State<Arc<AppState>>) -> Json<Value> {
         "status": "ok",
         "at": now_iso(),
         "uptimeSec": state.started_at.elapsed().as_secs(),
+        "rolloutSync": state.backend.rollout_sync_started,
+        "gitRepo": state.workdir_git_root.is_some(),
     }))
 }
 
@@ -7005,6 +10591,130 @@ async fn status_handler(
     Json(state.bridge_status().await).into_response()
 }
 
+/// Prometheus text exposition of bridge counters that help diagnose UI lag.
+async fn metrics_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<RpcQuery>,
+) -> Response {
+    if !state.is_authorized(&headers, query.token.as_deref()).await {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({
+                "error": "unauthorized",
+                "message": "Missing or invalid bridge credentials"
+            })),
+        )
+            .into_response();
+    }
+
+    let connected_clients = *state.hub.subscribe_client_count().borrow();
+    let body = render_bridge_metrics(
+        connected_clients,
+        &state.backend.app_server_stdout_lag(),
+        &state.backend.app_server_method_traffic(),
+        &state.rejected_methods.snapshot(),
+    );
+    (
+        [(CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        body,
+    )
+        .into_response()
+}
+
+fn render_bridge_metrics(
+    connected_clients: usize,
+    stdout_lag: &[AppServerLagSnapshot],
+    method_traffic: &[AppServerMethodTrafficEntry],
+    rejected_methods: &[RejectedMethodEntry],
+) -> String {
+    let mut body = String::new();
+    body.push_str("# HELP clawdex_bridge_connected_clients Connected WebSocket clients.\n");
+    body.push_str("# TYPE clawdex_bridge_connected_clients gauge\n");
+    body.push_str(&format!(
+        "clawdex_bridge_connected_clients {connected_clients}\n"
+    ));
+
+    type LagValue = fn(&AppServerLagSnapshot) -> u64;
+    let series: [(&str, &str, &str, LagValue); 3] = [
+        (
+            "clawdex_bridge_app_server_messages_total",
+            "counter",
+            "App-server stdout messages handled.",
+            |entry| entry.handled_messages,
+        ),
+        (
+            "clawdex_bridge_app_server_slow_messages_total",
+            "counter",
+            "App-server stdout messages whose handling exceeded the lag threshold.",
+            |entry| entry.slow_messages,
+        ),
+        (
+            "clawdex_bridge_app_server_max_handle_ms",
+            "gauge",
+            "Longest time spent handling one app-server stdout message.",
+            |entry| entry.max_handle_ms,
+        ),
+    ];
+    for (name, kind, help, value) in series {
+        body.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {kind}\n"));
+        for entry in stdout_lag {
+            body.push_str(&format!(
+                "{name}{{engine=\"{}\"}} {}\n",
+                entry.engine.as_str(),
+                value(entry)
+            ));
+        }
+    }
+
+    type TrafficValue = fn(&AppServerMethodTrafficCounts) -> u64;
+    let traffic_series: [(&str, &str, TrafficValue); 2] = [
+        (
+            "clawdex_bridge_app_server_request_bytes_total",
+            "Bytes written to the app-server for forwarded requests, per method.",
+            |counts| counts.request_bytes,
+        ),
+        (
+            "clawdex_bridge_app_server_response_bytes_total",
+            "Bytes read from the app-server in responses to forwarded requests, per method.",
+            |counts| counts.response_bytes,
+        ),
+    ];
+    for (name, help, value) in traffic_series {
+        body.push_str(&format!("# HELP {name} {help}\n# TYPE {name} counter\n"));
+        for entry in method_traffic {
+            body.push_str(&format!(
+                "{name}{{engine=\"{}\",method=\"{}\"}} {}\n",
+                entry.engine.as_str(),
+                escape_metric_label_value(&entry.method),
+                value(&entry.counts)
+            ));
+        }
+    }
+
+    let name = "clawdex_bridge_rejected_method_calls_total";
+    body.push_str(&format!(
+        "# HELP {name} Client calls rejected because the method is not forwarded.\n# TYPE {name} counter\n"
+    ));
+    for entry in rejected_methods {
+        body.push_str(&format!(
+            "{name}{{method=\"{}\"}} {}\n",
+            escape_metric_label_value(&entry.method),
+            entry.count
+        ));
+    }
+    body
+}
+
+/// Escapes a Prometheus label value so a client-chosen method name cannot break out of
+/// its quotes or start a new sample line.
+fn escape_metric_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
 async fn local_image_handler(
     State(state): State<Arc<AppState>>,
     headers: HeaderMap,
@@ -7563,42 +11273,168 @@ async fn ws_handler(
             .into_response();
     }
 
-    let client_metadata = ClientConnectionMetadata::from_query(&query);
+    if let Some(max_clients) = state.config.max_clients {
+        let connected = *state.hub.subscribe_client_count().borrow();
+        if connected >= max_clients {
+            return (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(json!({
+                    "error": "too_many_clients",
+                    "message": format!(
+                        "Bridge already has {connected} connected clients (BRIDGE_MAX_CLIENTS={max_clients})"
+                    ),
+                    "maxClients": max_clients,
+                    "retryAfterMs": state.hub.reconnect_retry_after_ms(),
+                })),
+            )
+                .into_response();
+        }
+    }
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state, client_metadata))
+    let client_key = match normalize_client_key(query.client_key.as_deref()) {
+        Ok(client_key) => client_key,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "invalid_client_key",
+                    "message": message,
+                })),
+            )
+                .into_response();
+        }
+    };
+    let client_metadata = ClientConnectionMetadata::from_query(&query);
+    let queue_capacity =
+        resolve_client_queue_capacity(state.config.ws_client_queue_capacity, query.queue_capacity);
+    let max_frame_bytes = state.config.ws_max_frame_bytes;
+
+    ws.max_frame_size(max_frame_bytes)
+        .max_message_size(max_frame_bytes)
+        .on_upgrade(move |socket| {
+            handle_socket(socket, state, client_metadata, client_key, queue_capacity)
+        })
         .into_response()
 }
 
+/// Outbound queue depth for one client: `BRIDGE_WS_CLIENT_QUEUE_CAPACITY`, or a smaller
+/// `queueCapacity` from the upgrade query. Clients can shrink their queue, never grow it.
+fn resolve_client_queue_capacity(configured: usize, requested: Option<usize>) -> usize {
+    let configured = configured.clamp(MIN_WS_CLIENT_QUEUE_CAPACITY, MAX_WS_CLIENT_QUEUE_CAPACITY);
+    requested.map_or(configured, |requested| {
+        requested.clamp(MIN_WS_CLIENT_QUEUE_CAPACITY, configured)
+    })
+}
+
+fn normalize_client_key(raw: Option<&str>) -> Result<Option<String>, String> {
+    let Some(key) = raw.map(str::trim).filter(|key| !key.is_empty()) else {
+        return Ok(None);
+    };
+    if key.len() > CLIENT_KEY_MAX_LEN
+        || !key
+            .chars()
+            .all(|character| character.is_ascii_alphanumeric() || matches!(character, '-' | '_'))
+    {
+        return Err(format!(
+            "clientKey must be at most {CLIENT_KEY_MAX_LEN} characters of [A-Za-z0-9_-]"
+        ));
+    }
+
+    Ok(Some(key.to_string()))
+}
+
+fn normalize_client_subscription_topics(
+    topics: Vec<String>,
+) -> Result<BTreeSet<String>, BridgeError> {
+    let topics = topics
+        .iter()
+        .map(|topic| topic.trim())
+        .filter(|topic| !topic.is_empty())
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>();
+    if topics.len() > MAX_CLIENT_SUBSCRIPTIONS {
+        return Err(BridgeError::invalid_params(&format!(
+            "at most {MAX_CLIENT_SUBSCRIPTIONS} topics are allowed"
+        )));
+    }
+    if topics.iter().any(|topic| {
+        topic.len() > CLIENT_SUBSCRIPTION_TOPIC_MAX_LEN || topic.chars().any(char::is_control)
+    }) {
+        return Err(BridgeError::invalid_params(&format!(
+            "topics must be at most {CLIENT_SUBSCRIPTION_TOPIC_MAX_LEN} printable characters"
+        )));
+    }
+
+    Ok(topics)
+}
+
 async fn handle_socket(
     socket: WebSocket,
     state: Arc<AppState>,
     client_metadata: ClientConnectionMetadata,
+    client_key: Option<String>,
+    queue_capacity: usize,
 ) {
     let (mut socket_tx, mut socket_rx) = socket.split();
-    let (tx, mut rx) = mpsc::channel::<Message>(WS_CLIENT_QUEUE_CAPACITY);
-    let client_id = state
+    let (tx, mut rx) = mpsc::channel::<Message>(queue_capacity);
+    let Some(client_id) = state
         .hub
         .add_client_with_metadata(tx, client_metadata)
-        .await;
-
-    let mut writer_task = tokio::spawn(async move {
-        while let Some(message) = rx.recv().await {
-            if socket_tx.send(message).await.is_err() {
-                break;
-            }
-        }
-    });
-
-    state
-        .hub
+        .await
+    else {
+        // Another connection took the last slot after `ws_handler` checked the count.
+        let retry_after_ms = state.hub.reconnect_retry_after_ms();
+        let _ = socket_tx
+            .send(Message::Close(Some(CloseFrame {
+                code: close_code::AGAIN,
+                reason: format!("too_many_clients; retryAfterMs={retry_after_ms}").into(),
+            })))
+            .await;
+        return;
+    };
+    let session = match client_key {
+        Some(key) => {
+            let claim = state.hub.claim_client_key(client_id, key).await;
+            if let Some(replaced_client_id) = claim.replaced_client_id {
+                state.hub.close_replaced_client(replaced_client_id).await;
+                state
+                    .backend
+                    .drop_client_pending_requests(replaced_client_id)
+                    .await;
+            }
+            Some(json!({
+                "resumed": claim.resumed,
+                "subscriptions": claim.subscriptions,
+                "lastEventId": claim.last_event_id,
+                "missedFrom": claim.missed_from,
+            }))
+        }
+        None => None,
+    };
+    let mut connection_params = json!({
+        "status": "connected",
+        "at": now_iso(),
+        "queueCapacity": queue_capacity,
+    });
+    if let Some(session) = session {
+        connection_params["session"] = session;
+    }
+
+    let mut writer_task = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            if socket_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    state
+        .hub
         .send_json(
             client_id,
             json!({
                 "method": "bridge/connection/state",
-                "params": {
-                    "status": "connected",
-                    "at": now_iso(),
-                }
+                "params": connection_params,
             }),
         )
         .await;
@@ -7655,7 +11491,22 @@ async fn handle_socket(
                     }
                     Ok(Message::Pong(_)) => {}
                     Err(error) => {
-                        eprintln!("websocket error: {error}");
+                        if is_websocket_message_too_large_error(&error) {
+                            eprintln!("websocket message rejected: {error}");
+                            state
+                                .hub
+                                .send_json(
+                                    client_id,
+                                    oversized_websocket_message_error(
+                                        state.config.ws_max_frame_bytes,
+                                    ),
+                                )
+                                .await;
+                            state.hub.remove_client(client_id).await;
+                            let _ = timeout(Duration::from_secs(1), &mut writer_task).await;
+                        } else {
+                            eprintln!("websocket error: {error}");
+                        }
                         break;
                     }
                 }
@@ -7664,11 +11515,48 @@ async fn handle_socket(
     }
 
     state.hub.remove_client(client_id).await;
+    state
+        .git_status_subscriptions
+        .remove_client(client_id)
+        .await;
+    state.backend.drop_client_pending_requests(client_id).await;
     if !writer_task.is_finished() {
         writer_task.abort();
     }
 }
 
+/// axum wraps the tungstenite error of its (same-version) `tokio-tungstenite` dependency.
+fn is_websocket_message_too_large_error(error: &axum::Error) -> bool {
+    std::error::Error::source(error)
+        .and_then(|source| source.downcast_ref::<TungsteniteError>())
+        .is_some_and(|error| {
+            matches!(
+                error,
+                TungsteniteError::Capacity(CapacityError::MessageTooLong { .. })
+            )
+        })
+}
+
+fn oversized_websocket_message_error(max_frame_bytes: usize) -> Value {
+    json!({
+        "id": Value::Null,
+        "error": {
+            "code": -32600,
+            "message": format!(
+                "Websocket message exceeds the {max_frame_bytes} byte limit"
+            ),
+            "data": {
+                "tooLarge": true,
+                "maxBytes": max_frame_bytes,
+            }
+        }
+    })
+}
+
+fn is_rate_limit_exempt_method(method: &str) -> bool {
+    method == "bridge/health/read"
+}
+
 async fn handle_client_message(client_id: u64, text: String, state: &Arc<AppState>) {
     state.hub.mark_client_seen(client_id).await;
 
@@ -7688,74 +11576,369 @@ async fn handle_client_message(client_id: u64, text: String, state: &Arc<AppStat
         }
     };
 
-    let Some(object) = parsed.as_object() else {
+    let Value::Array(requests) = parsed else {
+        if let Some(response) = dispatch_client_request(client_id, &parsed, state).await {
+            state.hub.send_json(client_id, response).await;
+        }
+        return;
+    };
+
+    if requests.is_empty() || requests.len() > MAX_CLIENT_BATCH_SIZE {
         send_rpc_error(
             state,
             client_id,
             Value::Null,
             -32600,
-            "Invalid request payload",
+            &format!("Invalid batch: expected 1 to {MAX_CLIENT_BATCH_SIZE} requests"),
             None,
         )
         .await;
         return;
+    }
+
+    // Bridge methods answer inline and come back together as one array; forwarded
+    // requests answer individually when the app-server responds.
+    let mut responses = Vec::new();
+    for request in &requests {
+        if let Some(response) = dispatch_client_request(client_id, request, state).await {
+            responses.push(response);
+        }
+    }
+    if !responses.is_empty() {
+        state
+            .hub
+            .send_json(client_id, Value::Array(responses))
+            .await;
+    }
+}
+
+/// Answers one JSON-RPC request over plain HTTP for clients that can't hold a
+/// WebSocket. Forwarded methods wait for the app-server's response, and no
+/// notifications are delivered on this path.
+async fn http_rpc_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<RpcQuery>,
+    body: String,
+) -> Response {
+    if !is_cors_origin_allowed(&state.config, &headers) {
+        return forbidden_origin_response();
+    }
+    let mut response = if !state.is_authorized(&headers, query.token.as_deref()).await {
+        (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({
+                "error": "unauthorized",
+                "message": "Missing or invalid bridge credentials"
+            })),
+        )
+            .into_response()
+    } else if !is_json_content_type(&headers) {
+        (
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(json!({
+                "error": "unsupported_media_type",
+                "message": "POST /rpc requires Content-Type: application/json"
+            })),
+        )
+            .into_response()
+    } else {
+        let mut payload = dispatch_http_rpc_request(&body, &state).await;
+        if state.config.strict_jsonrpc {
+            add_jsonrpc_version(&mut payload);
+        }
+        Json(payload).into_response()
+    };
+    apply_cors_headers(&state.config, &headers, response.headers_mut());
+    response
+}
+
+async fn http_rpc_preflight_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Response {
+    if !is_cors_origin_allowed(&state.config, &headers) {
+        return forbidden_origin_response();
+    }
+    let mut response = StatusCode::NO_CONTENT.into_response();
+    apply_cors_headers(&state.config, &headers, response.headers_mut());
+    response
+}
+
+/// Requests without an `Origin` header (non-browser clients) are allowed; browser
+/// requests must come from an origin listed in `BRIDGE_CORS_ORIGINS` (or `*`).
+fn is_cors_origin_allowed(config: &BridgeConfig, request_headers: &HeaderMap) -> bool {
+    match request_headers.get(ORIGIN) {
+        None => true,
+        Some(origin) => origin.to_str().is_ok_and(|origin| {
+            config.cors_origins.contains("*") || config.cors_origins.contains(origin)
+        }),
+    }
+}
+
+fn forbidden_origin_response() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(json!({
+            "error": "forbidden",
+            "message": "Origin is not allowed by BRIDGE_CORS_ORIGINS"
+        })),
+    )
+        .into_response()
+}
+
+fn is_json_content_type(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+}
+
+/// Adds CORS headers when the request's `Origin` is allowed by `BRIDGE_CORS_ORIGINS`.
+fn apply_cors_headers(config: &BridgeConfig, request_headers: &HeaderMap, headers: &mut HeaderMap) {
+    let Some(origin) = request_headers
+        .get(ORIGIN)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return;
     };
+    if !is_cors_origin_allowed(config, request_headers) {
+        return;
+    }
+    let Ok(origin) = HeaderValue::from_str(origin) else {
+        return;
+    };
+    headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+    headers.insert(
+        ACCESS_CONTROL_ALLOW_METHODS,
+        HeaderValue::from_static("GET, POST, OPTIONS"),
+    );
+    headers.insert(
+        ACCESS_CONTROL_ALLOW_HEADERS,
+        HeaderValue::from_static("authorization, content-type"),
+    );
+    headers.insert(ACCESS_CONTROL_MAX_AGE, HeaderValue::from_static("600"));
+    headers.append(VARY, HeaderValue::from_static("Origin"));
+}
 
+/// Runs one HTTP JSON-RPC request as its own short-lived client and returns its
+/// response. HTTP callers share one rate-limit bucket.
+async fn dispatch_http_rpc_request(body: &str, state: &Arc<AppState>) -> Value {
+    let request = match serde_json::from_str::<Value>(body) {
+        Ok(value) => value,
+        Err(error) => {
+            return rpc_error_payload(Value::Null, -32700, &format!("Parse error: {error}"), None)
+        }
+    };
+    let Some(object) = request.as_object() else {
+        return rpc_error_payload(
+            Value::Null,
+            -32600,
+            "HTTP RPC accepts a single request object",
+            None,
+        );
+    };
+    let id = object.get("id").cloned().unwrap_or(Value::Null);
     let Some(method) = object.get("method").and_then(Value::as_str) else {
-        send_rpc_error(
-            state,
-            client_id,
+        return rpc_error_payload(id, -32600, "Missing method", None);
+    };
+    if state.config.strict_jsonrpc && !has_jsonrpc_version(object) {
+        return missing_jsonrpc_version_error(id);
+    }
+
+    if let Err(error) = check_request_rate_limit(state, HTTP_RPC_RATE_BUCKET_ID, method, &id).await
+    {
+        return error;
+    }
+
+    let params = object.get("params").cloned();
+
+    if HTTP_RPC_UNSUPPORTED_METHODS.contains(&method) {
+        return rpc_error_payload(
+            id,
+            -32601,
+            &format!("{method} is not available over HTTP; use the WebSocket"),
+            None,
+        );
+    }
+    if method.starts_with("bridge/") {
+        let client_id = state.hub.allocate_client_id();
+        let result = handle_bridge_method(method, params, state, client_id).await;
+        state.hub.remove_client(client_id).await;
+        return match result {
+            Ok(result) => json!({ "id": id, "result": result }),
+            Err(error) => rpc_error_payload(id, error.code, &error.message, error.data),
+        };
+    }
+
+    if !state.forwarded_methods.contains(method) {
+        return state.method_not_allowed_payload(id, method);
+    }
+
+    let archived_thread_id = archived_thread_id(method, params.as_ref());
+    let engine = state
+        .backend
+        .route_engine_for_method(method, params.as_ref());
+    let forwarded = forward_claiming_turn_start(state, method, params, &id, |params| {
+        state.backend.request_internal(method, params)
+    })
+    .await;
+    match forwarded {
+        Ok(result) => {
+            if let Some(thread_id) = archived_thread_id.as_deref() {
+                cleanup_removed_thread(
+                    &state.hub,
+                    state.config.attachment_cleanup_root().as_deref(),
+                    thread_id,
+                )
+                .await;
+            }
+            json!({ "id": id, "result": normalize_forwarded_result(method, result, engine) })
+        }
+        Err(error) => error,
+    }
+}
+
+/// Charges a request to the rate-limit bucket `bucket_id`, returning the error response
+/// to send when the bucket is empty.
+async fn check_request_rate_limit(
+    state: &AppState,
+    bucket_id: u64,
+    method: &str,
+    id: &Value,
+) -> Result<(), Value> {
+    if is_rate_limit_exempt_method(method) {
+        return Ok(());
+    }
+    let Err(retry_after) = state.hub.check_rate_limit(bucket_id, Instant::now()).await else {
+        return Ok(());
+    };
+    let retry_after_ms = u64::try_from(retry_after.as_millis())
+        .unwrap_or(u64::MAX)
+        .max(1);
+    Err(rpc_error_payload(
+        id.clone(),
+        -32000,
+        "Rate limit exceeded",
+        Some(json!({ "rateLimited": true, "retryAfterMs": retry_after_ms })),
+    ))
+}
+
+/// Forwards a request to the backend through `forward`, claiming the target thread of a
+/// `turn/start` first and releasing the claim if forwarding fails. Errors come back as
+/// the JSON-RPC error response to send.
+async fn forward_claiming_turn_start<T, F, Fut>(
+    state: &AppState,
+    method: &str,
+    params: Option<Value>,
+    id: &Value,
+    forward: F,
+) -> Result<T, Value>
+where
+    F: FnOnce(Option<Value>) -> Fut,
+    Fut: std::future::Future<Output = Result<T, String>>,
+{
+    let claimed_thread_id = claim_turn_start_thread(state, method, params.as_ref())
+        .map_err(|()| turn_in_flight_error(id.clone()))?;
+    forward(params).await.map_err(|error| {
+        if let Some(thread_id) = claimed_thread_id.as_deref() {
+            state.hub.release_turn_start(thread_id);
+        }
+        rpc_error_payload(id.clone(), -32000, &error, None)
+    })
+}
+
+/// Claims the target thread of a client `turn/start` under `BRIDGE_GUARD_CONCURRENT_TURNS`.
+/// Returns the claimed thread id to release if forwarding fails, or `Err` when a turn on
+/// that thread is already in flight.
+fn claim_turn_start_thread(
+    state: &AppState,
+    method: &str,
+    params: Option<&Value>,
+) -> Result<Option<String>, ()> {
+    if method != "turn/start" {
+        return Ok(None);
+    }
+    let Some(thread_id) = turn_start_thread_id(params) else {
+        return Ok(None);
+    };
+    if state.hub.try_claim_turn_start(&thread_id) {
+        Ok(Some(thread_id))
+    } else {
+        Err(())
+    }
+}
+
+fn turn_in_flight_error(id: Value) -> Value {
+    rpc_error_payload(
+        id,
+        -32000,
+        "A turn is already in flight on this thread",
+        Some(json!({ "turnInFlight": true })),
+    )
+}
+
+/// Handles one request object. Returns the response to send now, or `None` when the
+/// request was a notification or was forwarded and will be answered later.
+async fn dispatch_client_request(
+    client_id: u64,
+    request: &Value,
+    state: &Arc<AppState>,
+) -> Option<Value> {
+    let Some(object) = request.as_object() else {
+        return Some(rpc_error_payload(
+            Value::Null,
+            -32600,
+            "Invalid request payload",
+            None,
+        ));
+    };
+
+    let Some(method) = object.get("method").and_then(Value::as_str) else {
+        return Some(rpc_error_payload(
             object.get("id").cloned().unwrap_or(Value::Null),
             -32600,
             "Missing method",
             None,
-        )
-        .await;
-        return;
+        ));
     };
 
     let Some(id) = object.get("id").cloned() else {
         // Ignore client-side notifications for now.
-        return;
+        return None;
     };
 
+    if state.config.strict_jsonrpc && !has_jsonrpc_version(object) {
+        return Some(missing_jsonrpc_version_error(id));
+    }
+
+    if let Err(error) = check_request_rate_limit(state, client_id, method, &id).await {
+        return Some(error);
+    }
+
     let params = object.get("params").cloned();
 
     if method.starts_with("bridge/") {
-        match handle_bridge_method(method, params, state, client_id).await {
-            Ok(result) => {
-                state
-                    .hub
-                    .send_json(client_id, json!({ "id": id, "result": result }))
-                    .await;
-            }
-            Err(error) => {
-                send_rpc_error(state, client_id, id, error.code, &error.message, error.data).await;
-            }
-        }
-        return;
+        return Some(
+            match handle_bridge_method(method, params, state, client_id).await {
+                Ok(result) => json!({ "id": id, "result": result }),
+                Err(error) => rpc_error_payload(id, error.code, &error.message, error.data),
+            },
+        );
     }
 
-    if !is_forwarded_method(method) {
-        send_rpc_error(
-            state,
-            client_id,
-            id,
-            -32601,
-            &format!("Method not allowed: {method}"),
-            None,
-        )
-        .await;
-        return;
+    if !state.forwarded_methods.contains(method) {
+        return Some(state.method_not_allowed_payload(id, method));
     }
 
-    if let Err(error) = state
-        .backend
-        .forward_request(client_id, id.clone(), method, params)
-        .await
-    {
-        send_rpc_error(state, client_id, id, -32000, &error, None).await;
-    }
+    forward_claiming_turn_start(state, method, params, &id, |params| {
+        state
+            .backend
+            .forward_request(client_id, id.clone(), method, params)
+    })
+    .await
+    .err()
 }
 
 async fn handle_bridge_method(
@@ -7764,16 +11947,84 @@ async fn handle_bridge_method(
     state: &Arc<AppState>,
     client_id: u64,
 ) -> Result<Value, BridgeError> {
+    let params = apply_thread_cwd_default(method, params, state).await;
     match method {
         "bridge/health/read" => Ok(json!({
             "status": "ok",
             "at": now_iso(),
             "uptimeSec": state.started_at.elapsed().as_secs(),
         })),
+        "bridge/diagnostics/echo" => Ok(json!({
+            "params": params.unwrap_or(Value::Null),
+            "at": now_iso(),
+            "clientId": client_id,
+            "eventIdAtEcho": state.hub.latest_event_id(),
+        })),
+        "bridge/health/deep" => serde_json::to_value(
+            state
+                .backend
+                .probe_health(HEALTH_DEEP_SLOW_THRESHOLD, HEALTH_DEEP_TIMEOUT)
+                .await,
+        )
+        .map_err(|error| BridgeError::server(&error.to_string())),
         "bridge/status/read" => serde_json::to_value(state.bridge_status().await)
             .map_err(|error| BridgeError::server(&error.to_string())),
         "bridge/capabilities/read" => serde_json::to_value(state.bridge_capabilities())
             .map_err(|error| BridgeError::server(&error.to_string())),
+        "bridge/capabilities" => serde_json::to_value(state.bridge_capability_descriptor())
+            .map_err(|error| BridgeError::server(&error.to_string())),
+        "bridge/debug/trace" => {
+            if !state.config.enable_debug_trace {
+                return Err(BridgeError::forbidden(
+                    "debug_trace_disabled",
+                    "Debug tracing is disabled on this bridge.",
+                ));
+            }
+
+            let mut entries = Vec::new();
+            for bridge in [
+                state.backend.codex_backend(),
+                state.backend.cursor_backend(),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                entries.extend(bridge.debug_trace_entries().await);
+            }
+            serde_json::to_value(DebugTraceResponse {
+                entries,
+                capacity: DEBUG_TRACE_CAPACITY,
+            })
+            .map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/rollout/status" => {
+            let status = state.backend.rollout_sync.lock().await.status();
+            serde_json::to_value(status).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/appServer/info" => {
+            let mut servers = Vec::new();
+            for bridge in [
+                state.backend.codex_backend(),
+                state.backend.cursor_backend(),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if let Some(info) = bridge.server_info().await {
+                    servers.push(info);
+                }
+            }
+            serde_json::to_value(AppServerInfoResponse { servers })
+                .map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/appServer/stats" => serde_json::to_value(AppServerStatsResponse {
+            methods: state.backend.app_server_method_traffic(),
+        })
+        .map_err(|error| BridgeError::server(&error.to_string())),
+        "bridge/admin/rejectedMethods" => serde_json::to_value(RejectedMethodsResponse {
+            methods: state.rejected_methods.snapshot(),
+        })
+        .map_err(|error| BridgeError::server(&error.to_string())),
         "bridge/runtime/read" => serde_json::to_value(state.updater.runtime_info().await)
             .map_err(|error| BridgeError::server(&error.to_string())),
         "bridge/cursor/credentials/read" => {
@@ -7843,8 +12094,21 @@ async fn handle_bridge_method(
                 .map_err(|error| BridgeError::server(&error))?;
             serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
         }
-        "bridge/events/replay" => {
-            let request: EventReplayRequest =
+        "bridge/client/subscriptions/read" => Ok(json!({
+            "subscriptions": state.hub.client_subscriptions(client_id).await,
+        })),
+        "bridge/client/subscriptions/set" => {
+            let request: ClientSubscriptionsSetRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let topics = normalize_client_subscription_topics(request.topics)?;
+            state.hub.set_client_subscriptions(client_id, topics).await;
+            Ok(json!({
+                "subscriptions": state.hub.client_subscriptions(client_id).await,
+            }))
+        }
+        "bridge/events/replay" => {
+            let request: EventReplayRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
                     .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
 
@@ -7852,15 +12116,37 @@ async fn handle_bridge_method(
                 .limit
                 .unwrap_or(200)
                 .clamp(1, NOTIFICATION_REPLAY_MAX_LIMIT);
-            let (events, has_more) = state.hub.replay_since(request.after_event_id, limit).await;
+            let methods = normalize_replay_method_filters(request.methods.unwrap_or_default())?;
+            let (events, has_more, gap_detected) = state
+                .hub
+                .replay_matching_since(request.after_event_id, limit, &methods)
+                .await;
+            // A filtered page skips events, so it does not mean the client caught up.
+            if !has_more && methods.is_empty() {
+                state
+                    .hub
+                    .clear_client_missed_from(client_id, request.after_event_id.unwrap_or(0))
+                    .await;
+            }
 
             Ok(json!({
                 "events": events,
                 "hasMore": has_more,
+                "gapDetected": gap_detected,
                 "earliestEventId": state.hub.earliest_event_id().await,
                 "latestEventId": state.hub.latest_event_id(),
             }))
         }
+        "bridge/events/get" => {
+            let request: EventGetRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            Ok(state
+                .hub
+                .replay_event(request.event_id)
+                .await
+                .unwrap_or(Value::Null))
+        }
         "bridge/ui/present" | "bridge/ui/update" => {
             let surface: BridgeUiSurface =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -7992,6 +12278,103 @@ async fn handle_bridge_method(
                 .map_err(|error| BridgeError::server(&error))?;
             serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
         }
+        "bridge/turn/steer" => {
+            let request: BridgeTurnSteerRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let thread_id = request.thread_id.trim().to_string();
+            let turn_id = request.expected_turn_id.trim().to_string();
+            if thread_id.is_empty() || turn_id.is_empty() {
+                return Err(BridgeError::invalid_params(
+                    "threadId and expectedTurnId must not be empty",
+                ));
+            }
+
+            let result = state
+                .backend
+                .request_internal(
+                    "turn/steer",
+                    Some(json!({
+                        "threadId": thread_id,
+                        "expectedTurnId": turn_id,
+                        "input": request.input,
+                    })),
+                )
+                .await
+                .map_err(|error| BridgeError::server(&error))?;
+            // Other clients on the thread learn about the injected guidance right away instead
+            // of waiting for the next turn item.
+            state
+                .hub
+                .broadcast_notification(
+                    "bridge/turn.steered",
+                    json!({
+                        "threadId": thread_id,
+                        "turnId": turn_id,
+                        "clientId": client_id,
+                        "steeredAt": now_iso(),
+                    }),
+                )
+                .await;
+
+            Ok(result)
+        }
+        "bridge/skills/overview" => {
+            let request: BridgeSkillsOverviewRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let local_params = request.cwds.map(|cwds| json!({ "cwds": cwds }));
+            let (local, remote) = tokio::join!(
+                state.backend.request_internal("skills/list", local_params),
+                state.backend.request_internal("skills/remote/list", None),
+            );
+            serde_json::to_value(merge_skills_overview(local, remote))
+                .map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/thread/export" => {
+            let request: BridgeThreadExportRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let export = export_thread(state, request).await?;
+            serde_json::to_value(export).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/thread/delete" => {
+            let request: BridgeThreadDeleteRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let thread_id = request.thread_id.trim().to_string();
+            if thread_id.is_empty() {
+                return Err(BridgeError::invalid_params("threadId must not be empty"));
+            }
+            // thread/delete is not forwarded; callers must opt in explicitly per request.
+            if request.confirm != Some(true) {
+                return Err(BridgeError::invalid_params(
+                    "confirm must be true to delete a thread",
+                ));
+            }
+
+            state
+                .backend
+                .request_internal("thread/delete", Some(json!({ "threadId": thread_id })))
+                .await
+                .map_err(|error| BridgeError::server(&error))?;
+            cleanup_removed_thread(
+                &state.hub,
+                state.config.attachment_cleanup_root().as_deref(),
+                &thread_id,
+            )
+            .await;
+            state
+                .hub
+                .broadcast_notification("bridge/thread.deleted", json!({ "threadId": thread_id }))
+                .await;
+
+            serde_json::to_value(BridgeThreadDeleteResponse {
+                thread_id,
+                deleted: true,
+            })
+            .map_err(|error| BridgeError::server(&error.to_string()))
+        }
         "bridge/workspaces/list" => {
             let request: WorkspaceListRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -7999,6 +12382,13 @@ async fn handle_bridge_method(
             let result = list_workspace_roots(state, request).await?;
             serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
         }
+        "bridge/workspace/setCwd" => {
+            let request: WorkspaceSetCwdRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let result = set_thread_cwd(state, request).await?;
+            serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
+        }
         "bridge/fs/list" => {
             let request: FileSystemListRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -8006,6 +12396,13 @@ async fn handle_bridge_method(
             let result = list_filesystem_entries(state, request).await?;
             serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
         }
+        "bridge/fs/write" => {
+            let request: FileSystemWriteRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let result = write_filesystem_file(state, request).await?;
+            serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
+        }
         "bridge/terminal/exec" => {
             let request: TerminalExecRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -8022,6 +12419,20 @@ async fn handle_bridge_method(
 
             Ok(result_value)
         }
+        "bridge/terminal/history" => {
+            let request: TerminalHistoryRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let history = state.terminal.history(request.limit).await;
+            serde_json::to_value(history).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/terminal/output/read" => {
+            let request: TerminalOutputReadRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let result = state.terminal.read_captured_output(request).await?;
+            serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
+        }
         "bridge/github/auth/install" => {
             let request: GitHubAuthInstallRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -8036,20 +12447,91 @@ async fn handle_bridge_method(
             let uploaded = save_uploaded_attachment(request, state).await?;
             serde_json::to_value(uploaded).map_err(|error| BridgeError::server(&error.to_string()))
         }
+        "bridge/attachments/move" => {
+            let request: AttachmentMoveRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let moved = move_attachment_into_thread(&state.config.attachments_dir, request).await?;
+            serde_json::to_value(moved).map_err(|error| BridgeError::server(&error.to_string()))
+        }
         "bridge/git/status" => {
+            let request: GitStatusRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let status = state
+                .git
+                .get_status_with_ignored(request.cwd.as_deref(), request.include_ignored)
+                .await?;
+            serde_json::to_value(status).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/statusMulti" => {
+            let request: GitStatusMultiRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let result = git_status_multi(state, request).await?;
+            serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/statusSubscribe" => {
+            let request: GitQueryRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let root = state
+                .git_status_subscriptions
+                .subscribe(client_id, request.cwd.as_deref())
+                .await?;
+            Ok(json!({
+                "cwd": root.to_string_lossy(),
+                "subscribed": true,
+            }))
+        }
+        "bridge/git/statusUnsubscribe" => {
+            let request: GitQueryRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let (root, removed) = state
+                .git_status_subscriptions
+                .unsubscribe(client_id, request.cwd.as_deref())
+                .await?;
+            Ok(json!({
+                "cwd": root.to_string_lossy(),
+                "removed": removed,
+            }))
+        }
+        "bridge/git/isClean" => {
             let request: GitQueryRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
                     .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
-            let status = state.git.get_status(request.cwd.as_deref()).await?;
+            let status = state.git.is_clean(request.cwd.as_deref()).await?;
             serde_json::to_value(status).map_err(|error| BridgeError::server(&error.to_string()))
         }
-        "bridge/git/diff" => {
+        "bridge/git/statusSummary" => {
             let request: GitQueryRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
                     .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
-            let diff = state.git.get_diff(request.cwd.as_deref()).await?;
+            let summary = state.git.get_status_summary(request.cwd.as_deref()).await?;
+            serde_json::to_value(summary).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/diff" => {
+            let request: GitDiffRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let diff = state
+                .git
+                .get_diff(
+                    request.cwd.as_deref(),
+                    request.base.as_deref(),
+                    request.head.as_deref(),
+                )
+                .await?;
             serde_json::to_value(diff).map_err(|error| BridgeError::server(&error.to_string()))
         }
+        "bridge/git/stagedPaths" => {
+            let request: GitQueryRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let staged = state.git.get_staged_paths(request.cwd.as_deref()).await?;
+            serde_json::to_value(staged).map_err(|error| BridgeError::server(&error.to_string()))
+        }
         "bridge/git/history" => {
             let request: GitHistoryRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -8060,6 +12542,58 @@ async fn handle_bridge_method(
                 .await?;
             serde_json::to_value(history).map_err(|error| BridgeError::server(&error.to_string()))
         }
+        "bridge/git/revParse" => {
+            let request: GitRevParseRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let resolved = state
+                .git
+                .rev_parse(&request.git_ref, request.cwd.as_deref())
+                .await?;
+            serde_json::to_value(resolved).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/worktrees" => {
+            let request: GitQueryRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let worktrees = state.git.worktrees(request.cwd.as_deref()).await?;
+            serde_json::to_value(worktrees).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/reflog" => {
+            let request: GitHistoryRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let reflog = state
+                .git
+                .reflog(request.cwd.as_deref(), request.limit)
+                .await?;
+            serde_json::to_value(reflog).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/fileLog" => {
+            let request: GitFileLogRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let log = state
+                .git
+                .get_file_history(&request.path, request.cwd.as_deref(), request.limit)
+                .await?;
+            serde_json::to_value(log).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/blameRange" => {
+            let request: GitBlameRangeRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let blame = state
+                .git
+                .blame_range(
+                    &request.path,
+                    request.start,
+                    request.end,
+                    request.cwd.as_deref(),
+                )
+                .await?;
+            serde_json::to_value(blame).map_err(|error| BridgeError::server(&error.to_string()))
+        }
         "bridge/git/branches" => {
             let request: GitQueryRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -8140,6 +12674,29 @@ async fn handle_bridge_method(
 
             Ok(staged_value)
         }
+        "bridge/git/applyPatch" => {
+            let request: GitApplyPatchRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let GitApplyPatchRequest { patch, cwd } = request;
+
+            let applied = state.git.apply_cached_patch(&patch, cwd.as_deref()).await?;
+            let applied_value = serde_json::to_value(&applied)
+                .map_err(|error| BridgeError::server(&error.to_string()))?;
+
+            if applied.applied {
+                if let Ok(status) = state.git.get_status(cwd.as_deref()).await {
+                    let status_value = serde_json::to_value(status)
+                        .map_err(|error| BridgeError::server(&error.to_string()))?;
+                    state
+                        .hub
+                        .broadcast_notification("bridge/git/updated", status_value)
+                        .await;
+                }
+            }
+
+            Ok(applied_value)
+        }
         "bridge/git/unstage" => {
             let request: GitFileRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -8166,6 +12723,75 @@ async fn handle_bridge_method(
 
             Ok(unstaged_value)
         }
+        "bridge/git/configGet" => {
+            let request: GitConfigGetRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let result = state
+                .git
+                .config_get(&request.key, request.cwd.as_deref())
+                .await?;
+            serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/configSet" => {
+            let request: GitConfigSetRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let result = state
+                .git
+                .config_set(&request.key, &request.value, request.cwd.as_deref())
+                .await?;
+            serde_json::to_value(result).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/move" => {
+            let request: GitMoveRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let GitMoveRequest { from, to, cwd } = request;
+
+            let moved = state.git.move_path(&from, &to, cwd.as_deref()).await?;
+            let moved_value = serde_json::to_value(&moved)
+                .map_err(|error| BridgeError::server(&error.to_string()))?;
+
+            if moved.moved {
+                if let Ok(status) = state.git.get_status(cwd.as_deref()).await {
+                    let status_value = serde_json::to_value(status)
+                        .map_err(|error| BridgeError::server(&error.to_string()))?;
+                    state
+                        .hub
+                        .broadcast_notification("bridge/git/updated", status_value)
+                        .await;
+                }
+            }
+
+            Ok(moved_value)
+        }
+        "bridge/git/restoreStaged" => {
+            let request: GitFileRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let GitFileRequest { path, cwd } = request;
+            if path.trim().is_empty() {
+                return Err(BridgeError::invalid_params("path must not be empty"));
+            }
+
+            let restored = state.git.restore_staged(&path, cwd.as_deref()).await?;
+            let restored_value = serde_json::to_value(&restored)
+                .map_err(|error| BridgeError::server(&error.to_string()))?;
+
+            if restored.unstaged {
+                if let Ok(status) = state.git.get_status(cwd.as_deref()).await {
+                    let status_value = serde_json::to_value(status)
+                        .map_err(|error| BridgeError::server(&error.to_string()))?;
+                    state
+                        .hub
+                        .broadcast_notification("bridge/git/updated", status_value)
+                        .await;
+                }
+            }
+
+            Ok(restored_value)
+        }
         "bridge/git/unstageAll" => {
             let request: GitQueryRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -8193,17 +12819,47 @@ async fn handle_bridge_method(
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
                     .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
             let GitCommitRequest { message, cwd } = request;
-
-            if message.trim().is_empty() {
-                return Err(BridgeError::invalid_params("message must not be empty"));
-            }
+            validate_git_commit_message(&message)?;
 
             let commit = state.git.commit(message, cwd.as_deref()).await?;
             let commit_value = serde_json::to_value(&commit)
                 .map_err(|error| BridgeError::server(&error.to_string()))?;
 
             if commit.committed {
-                if let Ok(status) = state.git.get_status(cwd.as_deref()).await {
+                let status = state.git.get_status(cwd.as_deref()).await.ok();
+                if let Some(status) = &status {
+                    let status_value = serde_json::to_value(status)
+                        .map_err(|error| BridgeError::server(&error.to_string()))?;
+                    state
+                        .hub
+                        .broadcast_notification("bridge/git/updated", status_value)
+                        .await;
+                }
+                broadcast_git_committed(
+                    state,
+                    commit.hash.as_deref(),
+                    &commit.cwd,
+                    status.map(|status| status.branch),
+                )
+                .await;
+            }
+
+            Ok(commit_value)
+        }
+        "bridge/git/commitAll" => {
+            let request: GitCommitRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let GitCommitRequest { message, cwd } = request;
+            validate_git_commit_message(&message)?;
+
+            let commit = state.git.commit_all(message, cwd.as_deref()).await?;
+            let commit_value = serde_json::to_value(&commit)
+                .map_err(|error| BridgeError::server(&error.to_string()))?;
+
+            if commit.staged {
+                let status = state.git.get_status(cwd.as_deref()).await.ok();
+                if let Some(status) = &status {
                     let status_value = serde_json::to_value(status)
                         .map_err(|error| BridgeError::server(&error.to_string()))?;
                     state
@@ -8211,6 +12867,15 @@ async fn handle_bridge_method(
                         .broadcast_notification("bridge/git/updated", status_value)
                         .await;
                 }
+                if commit.committed {
+                    broadcast_git_committed(
+                        state,
+                        commit.hash.as_deref(),
+                        &commit.cwd,
+                        status.map(|status| status.branch),
+                    )
+                    .await;
+                }
             }
 
             Ok(commit_value)
@@ -8264,19 +12929,145 @@ async fn handle_bridge_method(
 
             Ok(push_value)
         }
-        "bridge/approvals/list" => {
-            let list = state.backend.list_pending_approvals().await;
-            serde_json::to_value(list).map_err(|error| BridgeError::server(&error.to_string()))
-        }
-        "bridge/approvals/resolve" => {
-            let request: ResolveApprovalRequest =
+        "bridge/git/init" => {
+            let request: GitInitRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
                     .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let GitInitRequest {
+                cwd,
+                initial_branch,
+            } = request;
 
-            if !is_valid_approval_decision(&request.decision) {
-                return Err(BridgeError::invalid_params(
-                    "decision must be one of: accept/approved, acceptForSession/approved_for_session, decline/denied, cancel/abort, or an execpolicy amendment object",
-                ));
+            let initialized = state
+                .git
+                .init(cwd.as_deref(), initial_branch.as_deref())
+                .await?;
+            let initialized_value = serde_json::to_value(&initialized)
+                .map_err(|error| BridgeError::server(&error.to_string()))?;
+
+            if initialized.initialized {
+                if let Ok(status) = state.git.get_status(cwd.as_deref()).await {
+                    let status_value = serde_json::to_value(status)
+                        .map_err(|error| BridgeError::server(&error.to_string()))?;
+                    state
+                        .hub
+                        .broadcast_notification("bridge/git/updated", status_value)
+                        .await;
+                }
+            }
+
+            Ok(initialized_value)
+        }
+        "bridge/git/cherryPick" => {
+            let request: GitCherryPickRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let GitCherryPickRequest { hash, abort, cwd } = request;
+
+            let picked = state
+                .git
+                .cherry_pick(hash.as_deref(), abort.unwrap_or(false), cwd.as_deref())
+                .await?;
+            let picked_value = serde_json::to_value(&picked)
+                .map_err(|error| BridgeError::server(&error.to_string()))?;
+
+            // Conflicted picks still change the index, so clients need fresh status either way.
+            if let Ok(status) = state.git.get_status(cwd.as_deref()).await {
+                let status_value = serde_json::to_value(status)
+                    .map_err(|error| BridgeError::server(&error.to_string()))?;
+                state
+                    .hub
+                    .broadcast_notification("bridge/git/updated", status_value)
+                    .await;
+            }
+
+            Ok(picked_value)
+        }
+        "bridge/git/stashList" => {
+            let request: GitQueryRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let stashes = state.git.stash_list(request.cwd.as_deref()).await?;
+            serde_json::to_value(stashes).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/stashApply" | "bridge/git/stashDrop" => {
+            let request: GitStashRefRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let GitStashRefRequest { stash_ref, cwd } = request;
+            let action = if method == "bridge/git/stashApply" {
+                GitStashAction::Apply
+            } else {
+                GitStashAction::Drop
+            };
+
+            let result = state
+                .git
+                .stash_action(action, &stash_ref, cwd.as_deref())
+                .await?;
+            let result_value = serde_json::to_value(&result)
+                .map_err(|error| BridgeError::server(&error.to_string()))?;
+
+            // Applying changes the working tree even when it stops on conflicts.
+            if action == GitStashAction::Apply {
+                if let Ok(status) = state.git.get_status(cwd.as_deref()).await {
+                    let status_value = serde_json::to_value(status)
+                        .map_err(|error| BridgeError::server(&error.to_string()))?;
+                    state
+                        .hub
+                        .broadcast_notification("bridge/git/updated", status_value)
+                        .await;
+                }
+            }
+
+            Ok(result_value)
+        }
+        "bridge/git/tags" => {
+            let request: GitQueryRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let tags = state.git.list_tags(request.cwd.as_deref()).await?;
+            serde_json::to_value(tags).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/git/tagCreate" => {
+            let request: GitTagCreateRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+            let GitTagCreateRequest { name, message, cwd } = request;
+
+            let created = state
+                .git
+                .create_tag(&name, &message, cwd.as_deref())
+                .await?;
+            let created_value = serde_json::to_value(&created)
+                .map_err(|error| BridgeError::server(&error.to_string()))?;
+
+            if created.created {
+                if let Ok(status) = state.git.get_status(cwd.as_deref()).await {
+                    let status_value = serde_json::to_value(status)
+                        .map_err(|error| BridgeError::server(&error.to_string()))?;
+                    state
+                        .hub
+                        .broadcast_notification("bridge/git/updated", status_value)
+                        .await;
+                }
+            }
+
+            Ok(created_value)
+        }
+        "bridge/approvals/list" => {
+            let list = state.backend.list_pending_approvals().await;
+            serde_json::to_value(list).map_err(|error| BridgeError::server(&error.to_string()))
+        }
+        "bridge/approvals/resolve" => {
+            let request: ResolveApprovalRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+
+            if !is_valid_approval_decision(&request.decision) {
+                return Err(BridgeError::invalid_params(
+                    "decision must be one of: accept/approved, acceptForSession/approved_for_session, decline/denied, cancel/abort, or an execpolicy amendment object",
+                ));
             }
 
             let resolved = state
@@ -8299,6 +13090,65 @@ async fn handle_bridge_method(
                 "decision": request.decision,
             }))
         }
+        "bridge/approvals/resolveAll" => {
+            let request: ResolveAllApprovalsRequest =
+                serde_json::from_value(params.unwrap_or_else(|| json!({})))
+                    .map_err(|error| BridgeError::invalid_params(&error.to_string()))?;
+
+            if !is_valid_approval_decision(&request.decision) {
+                return Err(BridgeError::invalid_params(
+                    "decision must be one of: accept/approved, acceptForSession/approved_for_session, decline/denied, cancel/abort, or an execpolicy amendment object",
+                ));
+            }
+
+            let kind = request
+                .kind
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty());
+            let thread_id = request
+                .thread_id
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty());
+
+            let matching = state
+                .backend
+                .list_pending_approvals()
+                .await
+                .into_iter()
+                .filter(|approval| kind.is_none_or(|kind| approval.kind == kind))
+                .filter(|approval| {
+                    thread_id.is_none_or(|thread_id| approval.thread_id == thread_id)
+                });
+
+            let mut resolved_ids = Vec::new();
+            let mut failures = Vec::new();
+            for approval in matching {
+                match state
+                    .backend
+                    .resolve_approval(&approval.id, &request.decision)
+                    .await
+                {
+                    Ok(Some(_)) => resolved_ids.push(approval.id),
+                    Ok(None) => failures.push(json!({
+                        "id": approval.id,
+                        "error": "approval_not_found",
+                    })),
+                    Err(error) => failures.push(json!({
+                        "id": approval.id,
+                        "error": error,
+                    })),
+                }
+            }
+
+            Ok(json!({
+                "ok": failures.is_empty(),
+                "resolvedIds": resolved_ids,
+                "failures": failures,
+                "decision": request.decision,
+            }))
+        }
         "bridge/userInput/resolve" => {
             let request: ResolveUserInputRequest =
                 serde_json::from_value(params.unwrap_or_else(|| json!({})))
@@ -8341,10 +13191,47 @@ async fn handle_bridge_method(
                     .map_err(|e| BridgeError::invalid_params(&e.to_string()))?;
             transcribe_voice(request).await
         }
-        _ => Err(BridgeError::method_not_found(&format!(
-            "Unknown bridge method: {method}"
-        ))),
+        _ => Err(BridgeError {
+            data: state.unknown_method_data(method),
+            ..BridgeError::method_not_found(&format!("Unknown bridge method: {method}"))
+        }),
+    }
+}
+
+/// Closest known method by edit distance, if it is near enough to be a likely typo.
+fn suggest_method_name<'a>(
+    method: &str,
+    candidates: impl Iterator<Item = &'a str>,
+) -> Option<&'a str> {
+    if method.len() > SUGGEST_METHOD_NAME_MAX_LEN {
+        return None;
+    }
+    let method_len = method.chars().count();
+    let max_distance = (method_len / 4).max(2);
+    candidates
+        // The distance is at least the length difference, so skip hopeless candidates.
+        .filter(|candidate| candidate.chars().count().abs_diff(method_len) <= max_distance)
+        .map(|candidate| (levenshtein_distance(method, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by(|left, right| left.0.cmp(&right.0).then_with(|| left.1.cmp(right.1)))
+        .map(|(_, candidate)| candidate)
+}
+
+fn levenshtein_distance(left: &str, right: &str) -> usize {
+    let right = right.chars().collect::<Vec<_>>();
+    let mut previous = (0..=right.len()).collect::<Vec<_>>();
+    let mut current = vec![0; right.len() + 1];
+    for (row, left_char) in left.chars().enumerate() {
+        current[0] = row + 1;
+        for (column, right_char) in right.iter().enumerate() {
+            let substitution = previous[column] + usize::from(left_char != *right_char);
+            current[column + 1] = substitution
+                .min(previous[column + 1] + 1)
+                .min(current[column] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
     }
+    previous[right.len()]
 }
 
 async fn forward_codex_auth_callback(
@@ -8466,6 +13353,7 @@ async fn cancel_thread_list_stream(
     }))
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn run_thread_list_stream(
     state: Arc<AppState>,
     client_id: u64,
@@ -8636,6 +13524,186 @@ fn thread_list_stream_key(client_id: u64, stream_id: &str) -> String {
     format!("{client_id}:{}", stream_id.trim())
 }
 
+/// Runs `git status` for each requested repo concurrently. A repo that fails to resolve or
+/// read reports its own `error` instead of failing the whole call.
+async fn git_status_multi(
+    state: &AppState,
+    request: GitStatusMultiRequest,
+) -> Result<GitStatusMultiResponse, BridgeError> {
+    let mut cwds = request
+        .cwds
+        .into_iter()
+        .map(|cwd| cwd.trim().to_string())
+        .filter(|cwd| !cwd.is_empty())
+        .collect::<Vec<_>>();
+    cwds.sort();
+    cwds.dedup();
+    if cwds.is_empty() {
+        return Err(BridgeError::invalid_params("cwds must not be empty"));
+    }
+    if cwds.len() > MAX_GIT_STATUS_MULTI_CWDS {
+        return Err(BridgeError::invalid_params(&format!(
+            "cwds accepts at most {MAX_GIT_STATUS_MULTI_CWDS} entries"
+        )));
+    }
+
+    let statuses = join_all(cwds.iter().map(|cwd| state.git.get_status(Some(cwd)))).await;
+    let results = cwds
+        .into_iter()
+        .zip(statuses)
+        .map(|(cwd, status)| {
+            let entry = match status {
+                Ok(status) => GitStatusMultiEntry {
+                    status: Some(status),
+                    error: None,
+                },
+                Err(error) => GitStatusMultiEntry {
+                    status: None,
+                    error: Some(error.message),
+                },
+            };
+            (cwd, entry)
+        })
+        .collect();
+
+    Ok(GitStatusMultiResponse { results })
+}
+
+/// Emits `bridge/git/committed` with the new `HEAD` hash and branch after a successful
+/// commit, so clients can show it without re-reading the full status. `hash` is the one
+/// the commit call read under the repo lock; without it nothing is sent.
+async fn broadcast_git_committed(
+    state: &AppState,
+    hash: Option<&str>,
+    cwd: &str,
+    branch: Option<String>,
+) {
+    let Some(hash) = hash else {
+        return;
+    };
+    state
+        .hub
+        .broadcast_notification(
+            "bridge/git/committed",
+            json!({
+                "hash": hash,
+                "branch": branch,
+                "cwd": cwd,
+            }),
+        )
+        .await;
+}
+
+/// Shared by `bridge/git/commit` and `bridge/git/commitAll`.
+fn validate_git_commit_message(message: &str) -> Result<(), BridgeError> {
+    if message.trim().is_empty() {
+        return Err(BridgeError::invalid_params("message must not be empty"));
+    }
+
+    Ok(())
+}
+
+/// Stores (or, with an empty `cwd`, clears) the default working directory for a thread.
+/// The directory must exist and stay inside the same sandbox as git and terminal requests.
+async fn set_thread_cwd(
+    state: &AppState,
+    request: WorkspaceSetCwdRequest,
+) -> Result<WorkspaceSetCwdResponse, BridgeError> {
+    let thread_id = request.thread_id.trim().to_string();
+    if thread_id.is_empty() {
+        return Err(BridgeError::invalid_params("threadId must not be empty"));
+    }
+
+    let raw_cwd = request.cwd.as_deref().map(str::trim).unwrap_or("");
+    if raw_cwd.is_empty() {
+        state.hub.forget_thread_cwd(&thread_id).await;
+        return Ok(WorkspaceSetCwdResponse {
+            thread_id,
+            cwd: None,
+        });
+    }
+
+    let requested = PathBuf::from(raw_cwd);
+    let requested = normalize_path(&if requested.is_absolute() {
+        requested
+    } else {
+        state.config.workdir.join(requested)
+    });
+    let not_a_directory = || {
+        BridgeError::invalid_params(&format!(
+            "cwd is not a directory: {}",
+            requested.to_string_lossy()
+        ))
+    };
+    // Resolved so a symlink inside the root cannot point the stored cwd outside it. The
+    // lexical path is what gets stored, since the git and terminal resolvers compare it
+    // against the configured (possibly symlinked) root.
+    let resolved = fs::canonicalize(&requested)
+        .await
+        .map_err(|_| not_a_directory())?;
+    if !state.config.allow_outside_root_cwd {
+        let root = fs::canonicalize(&state.config.workdir)
+            .await
+            .unwrap_or_else(|_| normalize_path(&state.config.workdir));
+        if !resolved.starts_with(&root)
+            || !requested.starts_with(normalize_path(&state.config.workdir))
+        {
+            return Err(BridgeError::invalid_params(
+                "cwd must stay within BRIDGE_WORKDIR",
+            ));
+        }
+    }
+    if !fs::metadata(&resolved)
+        .await
+        .is_ok_and(|metadata| metadata.is_dir())
+    {
+        return Err(not_a_directory());
+    }
+
+    state
+        .hub
+        .store_thread_cwd(&thread_id, requested.clone())
+        .await;
+    Ok(WorkspaceSetCwdResponse {
+        thread_id,
+        cwd: Some(requested.to_string_lossy().to_string()),
+    })
+}
+
+/// Fills in `cwd` for git and terminal requests that pass a `threadId` with a stored
+/// `bridge/workspace/setCwd` directory and no explicit `cwd` of their own.
+async fn apply_thread_cwd_default(
+    method: &str,
+    params: Option<Value>,
+    state: &AppState,
+) -> Option<Value> {
+    if !method.starts_with("bridge/git/") && method != "bridge/terminal/exec" {
+        return params;
+    }
+    let mut object = match params {
+        Some(Value::Object(object)) => object,
+        other => return other,
+    };
+    let has_cwd = object
+        .get("cwd")
+        .and_then(Value::as_str)
+        .is_some_and(|cwd| !cwd.trim().is_empty());
+    let thread_id = object
+        .get("threadId")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|thread_id| !thread_id.is_empty());
+    if let (false, Some(thread_id)) = (has_cwd, thread_id) {
+        if let Some(cwd) = state.hub.thread_cwd(thread_id).await {
+            object.insert(
+                "cwd".to_string(),
+                Value::String(cwd.to_string_lossy().to_string()),
+            );
+        }
+    }
+    Some(Value::Object(object))
+}
+
 async fn list_workspace_roots(
     state: &Arc<AppState>,
     request: WorkspaceListRequest,
@@ -8727,8 +13795,34 @@ async fn list_filesystem_entries(
     let include_hidden = request.include_hidden.unwrap_or(false);
     let directories_only = request.directories_only.unwrap_or(true);
     let include_git_repo = request.include_git_repo.unwrap_or(false);
-    let current_path =
-        resolve_browsable_directory(&state.config.workdir, request.path.as_deref()).await?;
+    let limit = request
+        .limit
+        .unwrap_or(MAX_FS_LIST_ENTRIES)
+        .clamp(1, MAX_FS_LIST_ENTRIES);
+    let sandbox_root = match request.cwd.as_deref().map(str::trim) {
+        Some(cwd) if !cwd.is_empty() => Some(
+            fs::canonicalize(&state.config.workdir)
+                .await
+                .map(|root| (normalize_path(&root), cwd.to_string()))
+                .map_err(|error| {
+                    BridgeError::server(&format!("failed to resolve BRIDGE_WORKDIR: {error}"))
+                })?,
+        ),
+        _ => None,
+    };
+    let current_path = match &sandbox_root {
+        Some((root, cwd)) => {
+            let base = normalize_path(&state.config.workdir.join(cwd));
+            let current_path = resolve_browsable_directory(&base, request.path.as_deref()).await?;
+            if !current_path.starts_with(root) {
+                return Err(BridgeError::invalid_params(
+                    "path must stay within BRIDGE_WORKDIR",
+                ));
+            }
+            current_path
+        }
+        None => resolve_browsable_directory(&state.config.workdir, request.path.as_deref()).await?,
+    };
 
     let mut read_dir = fs::read_dir(&current_path)
         .await
@@ -8756,16 +13850,26 @@ async fn list_filesystem_entries(
             Err(_) => continue,
         };
 
-        let is_directory = if file_type.is_dir() {
-            true
-        } else if file_type.is_symlink() {
-            fs::metadata(&entry_path)
-                .await
-                .map(|metadata| metadata.is_dir())
-                .unwrap_or(false)
+        if file_type.is_symlink() {
+            if let Some((root, _)) = &sandbox_root {
+                let escapes = fs::canonicalize(&entry_path)
+                    .await
+                    .map(|target| !target.starts_with(root))
+                    .unwrap_or(true);
+                if escapes {
+                    continue;
+                }
+            }
+        }
+        let metadata = if file_type.is_symlink() {
+            fs::metadata(&entry_path).await.ok()
         } else {
-            false
+            entry.metadata().await.ok()
         };
+        let is_directory = metadata
+            .as_ref()
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or(false);
         if directories_only && !is_directory {
             continue;
         }
@@ -8781,9 +13885,17 @@ async fn list_filesystem_entries(
             name,
             path: path_to_string(&entry_path),
             kind,
+            is_dir: is_directory,
             hidden,
             selectable: is_directory,
             is_git_repo,
+            size_bytes: metadata
+                .as_ref()
+                .filter(|metadata| !metadata.is_dir())
+                .map(|metadata| metadata.len()),
+            modified_at: metadata
+                .and_then(|metadata| metadata.modified().ok())
+                .map(|modified| DateTime::<Utc>::from(modified).to_rfc3339()),
         });
     }
 
@@ -8796,44 +13908,224 @@ async fn list_filesystem_entries(
         })
     });
 
-    let parent_path = current_path.parent().map(path_to_string);
+    let truncated = entries.len() > limit;
+    entries.truncate(limit);
+
+    let parent_path = current_path
+        .parent()
+        .filter(|parent| {
+            sandbox_root
+                .as_ref()
+                .map(|(root, _)| parent.starts_with(root))
+                .unwrap_or(true)
+        })
+        .map(path_to_string);
 
     Ok(FileSystemListResponse {
         bridge_root: path_to_string(&state.config.workdir),
         path: path_to_string(&current_path),
         parent_path,
         entries,
+        truncated,
     })
 }
 
-async fn transcribe_voice(request: VoiceTranscribeRequest) -> Result<Value, BridgeError> {
-    let max_voice_transcription_bytes = resolve_max_voice_transcription_bytes();
-    let estimated_size = estimate_base64_decoded_size(&request.data_base64)?;
-    if estimated_size > max_voice_transcription_bytes {
+async fn write_filesystem_file(
+    state: &Arc<AppState>,
+    request: FileSystemWriteRequest,
+) -> Result<FileSystemWriteResponse, BridgeError> {
+    let encoded = request.content_base64.trim();
+    let bytes = if encoded.is_empty() {
+        Vec::new()
+    } else {
+        if base64_exceeds_decoded_limit(encoded, MAX_FS_WRITE_BYTES)? {
+            return Err(BridgeError::invalid_params(&format!(
+                "file content exceeds max size of {MAX_FS_WRITE_BYTES} bytes"
+            )));
+        }
+        decode_base64_payload(encoded)?
+    };
+    if bytes.len() > MAX_FS_WRITE_BYTES {
         return Err(BridgeError::invalid_params(&format!(
-            "audio payload exceeds max size of {max_voice_transcription_bytes} bytes",
+            "file content exceeds max size of {MAX_FS_WRITE_BYTES} bytes"
         )));
     }
 
-    let audio_bytes = decode_base64_payload(&request.data_base64)?;
+    let workdir = &state.config.workdir;
+    let target = resolve_fs_write_target(workdir, request.cwd.as_deref(), &request.path)?;
+    let parent = target
+        .parent()
+        .ok_or_else(|| BridgeError::invalid_params("path must point to a file"))?;
 
-    // Minimum ~16KB — roughly 0.5s at 16kHz 16-bit mono.
-    if audio_bytes.len() < 16_000 {
-        return Err(BridgeError::invalid_params(
-            "audio payload too short (minimum ~0.5 seconds required)",
-        ));
+    // The lexical check above can't see symlinked directories, so check the real path of
+    // the deepest existing ancestor before creating anything under it.
+    let canonical_root = fs::canonicalize(workdir)
+        .await
+        .unwrap_or_else(|_| workdir.clone());
+    let mut existing = parent;
+    let canonical_existing = loop {
+        match fs::canonicalize(existing).await {
+            Ok(canonical) => break canonical,
+            Err(_) => {
+                existing = existing
+                    .parent()
+                    .ok_or_else(|| BridgeError::invalid_params("path must point to a file"))?;
+            }
+        }
+    };
+    ensure_fs_write_contained(&canonical_root, &canonical_existing)?;
+
+    if existing != parent {
+        if !request.create_dirs.unwrap_or(false) {
+            return Err(BridgeError::invalid_params(&format!(
+                "parent directory does not exist ({})",
+                parent.to_string_lossy()
+            )));
+        }
+        fs::create_dir_all(parent).await.map_err(|error| {
+            BridgeError::server(&format!("failed to create parent directories: {error}"))
+        })?;
+        let canonical_parent = fs::canonicalize(parent).await.map_err(|error| {
+            BridgeError::server(&format!("failed to resolve parent directory: {error}"))
+        })?;
+        ensure_fs_write_contained(&canonical_root, &canonical_parent)?;
     }
-    if audio_bytes.len() > max_voice_transcription_bytes {
-        return Err(BridgeError::invalid_params(&format!(
-            "audio payload exceeds max size of {max_voice_transcription_bytes} bytes",
-        )));
+    if fs::metadata(&target)
+        .await
+        .map(|metadata| metadata.is_dir())
+        .unwrap_or(false)
+    {
+        return Err(BridgeError::invalid_params("path points to a directory"));
     }
 
-    // Resolve auth: env vars first, then ~/.codex/auth.json.
-    let (endpoint, bearer_token, include_model) = resolve_transcription_auth()?;
-    let normalized_mime_type = normalize_transcription_mime_type(request.mime_type.as_deref());
-    let normalized_file_name =
-        normalize_transcription_file_name(request.file_name.as_deref(), &normalized_mime_type);
+    write_file_atomically(&target, &bytes).await?;
+
+    Ok(FileSystemWriteResponse {
+        path: path_to_string(&target),
+        size_bytes: bytes.len(),
+    })
+}
+
+fn resolve_fs_write_target(
+    workdir: &Path,
+    raw_cwd: Option<&str>,
+    raw_path: &str,
+) -> Result<PathBuf, BridgeError> {
+    let trimmed = raw_path.trim();
+    if trimmed.is_empty() {
+        return Err(BridgeError::invalid_params("path must not be empty"));
+    }
+
+    let base = match raw_cwd.map(str::trim).filter(|cwd| !cwd.is_empty()) {
+        Some(cwd) => normalize_path(&workdir.join(cwd)),
+        None => workdir.to_path_buf(),
+    };
+    let target = normalize_path(&base.join(trimmed));
+    if !target.starts_with(workdir) || target == workdir {
+        return Err(BridgeError::invalid_params(
+            "path must stay within BRIDGE_WORKDIR",
+        ));
+    }
+    if target
+        .strip_prefix(workdir)
+        .map(has_git_dir_component)
+        .unwrap_or(true)
+    {
+        return Err(BridgeError::invalid_params(
+            "writing inside .git directories is not allowed",
+        ));
+    }
+
+    Ok(target)
+}
+
+/// Case-insensitive so `.GIT` is refused too on case-insensitive filesystems.
+fn has_git_dir_component(relative: &Path) -> bool {
+    relative
+        .components()
+        .any(|part| part.as_os_str().eq_ignore_ascii_case(".git"))
+}
+
+/// Checks a canonicalized directory against the canonicalized workdir, including `.git`
+/// directories reached through symlinks.
+fn ensure_fs_write_contained(
+    canonical_root: &Path,
+    canonical_dir: &Path,
+) -> Result<(), BridgeError> {
+    let relative = canonical_dir
+        .strip_prefix(canonical_root)
+        .map_err(|_| BridgeError::invalid_params("path must stay within BRIDGE_WORKDIR"))?;
+    if has_git_dir_component(relative) {
+        return Err(BridgeError::invalid_params(
+            "writing inside .git directories is not allowed",
+        ));
+    }
+    Ok(())
+}
+
+/// Writes to a sibling temp file and renames it over `target`, so readers never see a
+/// partially written file. Existing file permissions are carried over.
+async fn write_file_atomically(target: &Path, bytes: &[u8]) -> Result<(), BridgeError> {
+    let parent = target
+        .parent()
+        .ok_or_else(|| BridgeError::invalid_params("path must point to a file"))?;
+    let file_name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| BridgeError::invalid_params("path must point to a file"))?;
+    let nonce = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    let temp_path = parent.join(format!(
+        ".{file_name}.clawdex-tmp-{}-{nonce}",
+        std::process::id()
+    ));
+
+    let result = async {
+        fs::write(&temp_path, bytes).await?;
+        if let Ok(metadata) = fs::metadata(target).await {
+            fs::set_permissions(&temp_path, metadata.permissions()).await?;
+        }
+        fs::rename(&temp_path, target).await
+    }
+    .await;
+    if let Err(error) = result {
+        let _ = fs::remove_file(&temp_path).await;
+        return Err(BridgeError::server(&format!(
+            "failed to write file: {error}"
+        )));
+    }
+
+    Ok(())
+}
+
+async fn transcribe_voice(request: VoiceTranscribeRequest) -> Result<Value, BridgeError> {
+    let max_voice_transcription_bytes = resolve_max_voice_transcription_bytes();
+    if base64_exceeds_decoded_limit(&request.data_base64, max_voice_transcription_bytes)? {
+        return Err(voice_payload_too_large(max_voice_transcription_bytes));
+    }
+
+    let audio_bytes = decode_base64_payload(&request.data_base64)?;
+
+    // Minimum ~16KB — roughly 0.5s at 16kHz 16-bit mono.
+    if audio_bytes.len() < 16_000 {
+        return Err(BridgeError::invalid_params(
+            "audio payload too short (minimum ~0.5 seconds required)",
+        ));
+    }
+    // The estimate above is an upper bound, so this only fires if decoding ever disagrees.
+    if audio_bytes.len() > max_voice_transcription_bytes {
+        return Err(voice_payload_too_large(max_voice_transcription_bytes));
+    }
+    let near_size_cap =
+        should_recommend_transcription_chunking(audio_bytes.len(), max_voice_transcription_bytes);
+
+    // Resolve auth: env vars first, then ~/.codex/auth.json.
+    let (endpoint, bearer_token, include_model) = resolve_transcription_auth()?;
+    let normalized_mime_type = normalize_transcription_mime_type(request.mime_type.as_deref());
+    let normalized_file_name =
+        normalize_transcription_file_name(request.file_name.as_deref(), &normalized_mime_type);
 
     let file_part = reqwest::multipart::Part::bytes(audio_bytes)
         .file_name(normalized_file_name)
@@ -8843,7 +14135,7 @@ async fn transcribe_voice(request: VoiceTranscribeRequest) -> Result<Value, Brid
     let mut form = reqwest::multipart::Form::new().part("file", file_part);
 
     if include_model {
-        form = form.text("model", "gpt-4o-transcribe");
+        form = form.text("model", resolve_transcription_model());
     }
 
     if let Some(prompt) = request.prompt {
@@ -8853,13 +14145,62 @@ async fn transcribe_voice(request: VoiceTranscribeRequest) -> Result<Value, Brid
         }
     }
 
-    let response = transcription_http_client()
-        .post(&endpoint)
-        .bearer_auth(&bearer_token)
+    let body = send_transcription_request(
+        transcription_http_client(),
+        &endpoint,
+        &bearer_token,
+        form,
+        near_size_cap,
+    )
+    .await?;
+
+    let text = body["text"].as_str().unwrap_or("").to_string();
+
+    serde_json::to_value(VoiceTranscribeResponse { text })
+        .map_err(|e| BridgeError::server(&e.to_string()))
+}
+
+fn voice_payload_too_large(max_bytes: usize) -> BridgeError {
+    BridgeError {
+        code: -32602,
+        message: format!("audio payload exceeds max size of {max_bytes} bytes"),
+        data: Some(json!({ "maxBytes": max_bytes, "recommendChunking": true })),
+    }
+}
+
+/// Clips in the top quarter of the size cap are the ones most likely to time out or to
+/// outgrow the cap on the next recording, so errors for them suggest splitting.
+fn should_recommend_transcription_chunking(size_bytes: usize, max_bytes: usize) -> bool {
+    size_bytes >= max_bytes / 4 * 3
+}
+
+async fn send_transcription_request(
+    client: &HttpClient,
+    endpoint: &str,
+    bearer_token: &str,
+    form: reqwest::multipart::Form,
+    near_size_cap: bool,
+) -> Result<Value, BridgeError> {
+    let response = client
+        .post(endpoint)
+        .bearer_auth(bearer_token)
         .multipart(form)
         .send()
         .await
-        .map_err(|e| BridgeError::server(&e.to_string()))?;
+        .map_err(|e| {
+            if e.is_timeout() {
+                BridgeError {
+                    code: -32000,
+                    message: "transcription request timed out".to_string(),
+                    data: Some(json!({
+                        "timedOut": true,
+                        "recommendChunking": near_size_cap,
+                    })),
+                }
+            } else {
+                BridgeError::server(&e.to_string())
+            }
+        })?;
 
     if !response.status().is_success() {
         let status = response.status().as_u16();
@@ -8874,20 +14215,20 @@ async fn transcribe_voice(request: VoiceTranscribeRequest) -> Result<Value, Brid
         });
     }
 
-    let body: Value = response
+    response
         .json()
         .await
-        .map_err(|e| BridgeError::server(&e.to_string()))?;
-
-    let text = body["text"].as_str().unwrap_or("").to_string();
-
-    Ok(serde_json::to_value(VoiceTranscribeResponse { text })
-        .map_err(|e| BridgeError::server(&e.to_string()))?)
+        .map_err(|e| BridgeError::server(&e.to_string()))
 }
 
 fn transcription_http_client() -> &'static HttpClient {
     static CLIENT: OnceLock<HttpClient> = OnceLock::new();
-    CLIENT.get_or_init(HttpClient::new)
+    CLIENT.get_or_init(|| {
+        HttpClient::builder()
+            .timeout(resolve_transcription_timeout())
+            .build()
+            .expect("build transcription client")
+    })
 }
 
 fn bridge_chatgpt_auth_cache() -> &'static StdRwLock<Option<BridgeChatGptAuthBundle>> {
@@ -8992,6 +14333,196 @@ fn resolve_bridge_chatgpt_auth_bundle_for_refresh() -> Option<BridgeChatGptAuthB
     read_cached_bridge_chatgpt_auth()
 }
 
+/// Tokens produced by `BRIDGE_CHATGPT_REFRESH_COMMAND`, reused until shortly before they expire.
+#[derive(Debug, Clone)]
+struct CachedChatGptRefresh {
+    auth: BridgeChatGptAuthBundle,
+    expires_at: DateTime<Utc>,
+}
+
+fn bridge_chatgpt_refresh_cache() -> &'static StdRwLock<Option<CachedChatGptRefresh>> {
+    static CACHE: OnceLock<StdRwLock<Option<CachedChatGptRefresh>>> = OnceLock::new();
+    CACHE.get_or_init(|| StdRwLock::new(None))
+}
+
+/// Serializes refresh command runs and remembers the last success, so requests that
+/// arrive while a refresh is running share its result instead of starting their own.
+fn bridge_chatgpt_refresh_flight() -> &'static Mutex<Option<(Instant, BridgeChatGptAuthBundle)>> {
+    static FLIGHT: OnceLock<Mutex<Option<(Instant, BridgeChatGptAuthBundle)>>> = OnceLock::new();
+    FLIGHT.get_or_init(|| Mutex::new(None))
+}
+
+#[cfg(test)]
+fn bridge_chatgpt_refresh_command_override() -> &'static StdRwLock<Option<String>> {
+    static OVERRIDE: OnceLock<StdRwLock<Option<String>>> = OnceLock::new();
+    OVERRIDE.get_or_init(|| StdRwLock::new(None))
+}
+
+#[cfg(test)]
+fn set_bridge_chatgpt_refresh_command_override(command: Option<String>) {
+    if let Ok(mut guard) = bridge_chatgpt_refresh_command_override().write() {
+        *guard = command;
+    }
+    if let Ok(mut guard) = bridge_chatgpt_refresh_cache().write() {
+        *guard = None;
+    }
+}
+
+fn resolve_bridge_chatgpt_refresh_command() -> Option<String> {
+    #[cfg(test)]
+    if let Ok(guard) = bridge_chatgpt_refresh_command_override().read() {
+        if let Some(command) = guard.clone() {
+            return Some(command);
+        }
+    }
+
+    read_non_empty_env("BRIDGE_CHATGPT_REFRESH_COMMAND")
+}
+
+/// Resolves the tokens for an app-server refresh request. A configured refresh command
+/// takes precedence; otherwise this falls back to env-provided or login-cached tokens.
+async fn resolve_bridge_chatgpt_auth_for_refresh_request(
+) -> Result<Option<BridgeChatGptAuthBundle>, String> {
+    let Some(command) = resolve_bridge_chatgpt_refresh_command() else {
+        return Ok(resolve_bridge_chatgpt_auth_bundle_for_refresh());
+    };
+
+    let requested_at = Instant::now();
+    let mut last_refresh = bridge_chatgpt_refresh_flight().lock().await;
+    if let Some((finished_at, auth)) = last_refresh.as_ref() {
+        if *finished_at >= requested_at {
+            return Ok(Some(auth.clone()));
+        }
+    }
+
+    let now = Utc::now();
+    if let Ok(guard) = bridge_chatgpt_refresh_cache().read() {
+        if let Some(cached) = guard.as_ref() {
+            let skew = chrono::Duration::from_std(CHATGPT_REFRESH_EXPIRY_SKEW)
+                .unwrap_or_else(|_| chrono::Duration::zero());
+            if cached.expires_at - skew > now {
+                return Ok(Some(cached.auth.clone()));
+            }
+        }
+    }
+
+    let stdout = run_bridge_chatgpt_refresh_command(&command).await?;
+    let (auth, expires_at) = parse_chatgpt_refresh_command_output(&stdout, now)?;
+    if let Ok(mut guard) = bridge_chatgpt_refresh_cache().write() {
+        *guard = expires_at.map(|expires_at| CachedChatGptRefresh {
+            auth: auth.clone(),
+            expires_at,
+        });
+    }
+    *last_refresh = Some((Instant::now(), auth.clone()));
+    Ok(Some(auth))
+}
+
+async fn run_bridge_chatgpt_refresh_command(command: &str) -> Result<String, String> {
+    let parts = shlex::split(command)
+        .filter(|parts| !parts.is_empty())
+        .ok_or_else(|| "BRIDGE_CHATGPT_REFRESH_COMMAND has invalid quoting".to_string())?;
+
+    let mut process = Command::new(&parts[0]);
+    process
+        .args(&parts[1..])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let output = timeout(CHATGPT_REFRESH_COMMAND_TIMEOUT, process.output())
+        .await
+        .map_err(|_| {
+            format!(
+                "refresh command timed out after {}s",
+                CHATGPT_REFRESH_COMMAND_TIMEOUT.as_secs()
+            )
+        })?
+        .map_err(|error| format!("failed to run refresh command: {error}"))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.trim();
+        return Err(if stderr.is_empty() {
+            format!("refresh command exited with {}", output.status)
+        } else {
+            format!("refresh command exited with {}: {stderr}", output.status)
+        });
+    }
+
+    String::from_utf8(output.stdout)
+        .map_err(|_| "refresh command output is not valid UTF-8".to_string())
+}
+
+/// Parses refresh command stdout: `{ accessToken, accountId, planType?, expiresAt?, expiresIn? }`.
+/// `expiresAt` is RFC 3339 or unix seconds, `expiresIn` is seconds from `now`. Output without
+/// an expiry is used once and not cached.
+fn parse_chatgpt_refresh_command_output(
+    stdout: &str,
+    now: DateTime<Utc>,
+) -> Result<(BridgeChatGptAuthBundle, Option<DateTime<Utc>>), String> {
+    let payload: Value = serde_json::from_str(stdout.trim())
+        .map_err(|error| format!("refresh command output is not valid JSON: {error}"))?;
+    let object = payload
+        .as_object()
+        .ok_or_else(|| "refresh command output must be a JSON object".to_string())?;
+
+    let read_string = |keys: &[&str]| {
+        keys.iter()
+            .filter_map(|key| object.get(*key).and_then(Value::as_str))
+            .map(str::trim)
+            .find(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+
+    let access_token = read_string(&["accessToken"])
+        .ok_or_else(|| "refresh command output is missing accessToken".to_string())?;
+    let account_id = read_string(&["accountId", "chatgptAccountId"])
+        .ok_or_else(|| "refresh command output is missing accountId".to_string())?;
+    let plan_type = read_string(&["planType", "chatgptPlanType"]);
+
+    let expires_at = match (object.get("expiresAt"), object.get("expiresIn")) {
+        (Some(Value::String(raw)), _) => Some(
+            DateTime::parse_from_rfc3339(raw.trim())
+                .map_err(|_| "refresh command expiresAt must be an RFC 3339 timestamp".to_string())?
+                .with_timezone(&Utc),
+        ),
+        (Some(Value::Number(raw)), _) => Some(
+            raw.as_i64()
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+                .ok_or_else(|| "refresh command expiresAt is out of range".to_string())?,
+        ),
+        (Some(Value::Null) | None, Some(Value::Number(raw))) => Some(
+            raw.as_i64()
+                .filter(|seconds| *seconds >= 0)
+                .map(|seconds| now + chrono::Duration::seconds(seconds))
+                .ok_or_else(|| {
+                    "refresh command expiresIn must be a non-negative integer".to_string()
+                })?,
+        ),
+        (Some(Value::Null) | None, None | Some(Value::Null)) => None,
+        _ => return Err("refresh command expiry fields have an invalid type".to_string()),
+    };
+
+    Ok((
+        BridgeChatGptAuthBundle {
+            access_token,
+            account_id,
+            plan_type,
+        },
+        expires_at,
+    ))
+}
+
+fn chatgpt_tokens_refresh_result(auth: BridgeChatGptAuthBundle) -> Value {
+    json!({
+        "accessToken": auth.access_token,
+        "chatgptAccountId": auth.account_id,
+        "chatgptPlanType": auth.plan_type,
+    })
+}
+
 fn resolve_bridge_chatgpt_access_token_for_transcription() -> Option<String> {
     read_non_empty_env("BRIDGE_CHATGPT_ACCESS_TOKEN")
         .or_else(|| read_cached_bridge_chatgpt_auth().map(|auth| auth.access_token))
@@ -9027,6 +14558,14 @@ fn extract_chatgpt_auth_tokens_from_account_login_start(
 }
 
 fn resolve_transcription_auth() -> Result<(String, String, bool), BridgeError> {
+    // Path 0: BRIDGE_TRANSCRIPTION_ENDPOINT → self-hosted/compatible endpoint.
+    if let Some(resolved) = resolve_transcription_endpoint_override(
+        read_non_empty_env("BRIDGE_TRANSCRIPTION_ENDPOINT"),
+        read_non_empty_env("OPENAI_API_KEY"),
+    )? {
+        return Ok(resolved);
+    }
+
     // Path 1: OPENAI_API_KEY env var → OpenAI direct API.
     if let Some(api_key) = read_non_empty_env("OPENAI_API_KEY") {
         return Ok((
@@ -9099,6 +14638,43 @@ fn resolve_transcription_auth() -> Result<(String, String, bool), BridgeError> {
     })
 }
 
+fn resolve_transcription_endpoint_override(
+    endpoint: Option<String>,
+    api_key: Option<String>,
+) -> Result<Option<(String, String, bool)>, BridgeError> {
+    let Some(endpoint) = endpoint else {
+        return Ok(None);
+    };
+    if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+        return Err(BridgeError::server(
+            "BRIDGE_TRANSCRIPTION_ENDPOINT must be an http:// or https:// URL",
+        ));
+    }
+    let Some(api_key) = api_key else {
+        return Err(BridgeError {
+            code: -32002,
+            message: "BRIDGE_TRANSCRIPTION_ENDPOINT is set but OPENAI_API_KEY is missing"
+                .to_string(),
+            data: None,
+        });
+    };
+
+    Ok(Some((endpoint, api_key, true)))
+}
+
+fn resolve_transcription_timeout() -> Duration {
+    let millis = read_non_empty_env("BRIDGE_TRANSCRIPTION_TIMEOUT_MS")
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_TRANSCRIPTION_TIMEOUT_MS);
+    Duration::from_millis(millis)
+}
+
+fn resolve_transcription_model() -> String {
+    read_non_empty_env("BRIDGE_TRANSCRIPTION_MODEL")
+        .unwrap_or_else(|| DEFAULT_TRANSCRIPTION_MODEL.to_string())
+}
+
 fn resolve_codex_auth_json_path() -> Option<PathBuf> {
     if let Some(codex_home) = read_non_empty_env("CODEX_HOME") {
         let path = PathBuf::from(codex_home).join("auth.json");
@@ -9123,6 +14699,67 @@ async fn send_rpc_error(
     message: &str,
     data: Option<Value>,
 ) {
+    state
+        .hub
+        .send_json(client_id, rpc_error_payload(id, code, message, data))
+        .await;
+}
+
+/// Validates `bridge/events/replay` method filters: exact names, or a prefix followed by
+/// a single trailing `*`.
+fn normalize_replay_method_filters(raw: Vec<String>) -> Result<Vec<String>, BridgeError> {
+    if raw.len() > NOTIFICATION_REPLAY_MAX_METHOD_FILTERS {
+        return Err(BridgeError::invalid_params(&format!(
+            "methods accepts at most {NOTIFICATION_REPLAY_MAX_METHOD_FILTERS} entries"
+        )));
+    }
+    raw.into_iter()
+        .map(|pattern| {
+            let pattern = pattern.trim();
+            let literal = pattern.strip_suffix('*').unwrap_or(pattern);
+            if pattern.is_empty() || literal.contains('*') {
+                return Err(BridgeError::invalid_params(
+                    "methods entries must be a method name or a prefix ending in a single '*'",
+                ));
+            }
+            Ok(pattern.to_string())
+        })
+        .collect()
+}
+
+fn notification_method_matches(method: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| topic_matches(method, pattern))
+}
+
+fn topic_matches(method: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => method.starts_with(prefix),
+        None => method == pattern,
+    }
+}
+
+/// Stamps `"jsonrpc": "2.0"` on a message, or on each message of a batch.
+fn add_jsonrpc_version(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.insert("jsonrpc".to_string(), json!("2.0"));
+        }
+        Value::Array(items) => items.iter_mut().for_each(add_jsonrpc_version),
+        _ => {}
+    }
+}
+
+fn has_jsonrpc_version(object: &serde_json::Map<String, Value>) -> bool {
+    object.get("jsonrpc").and_then(Value::as_str) == Some("2.0")
+}
+
+fn missing_jsonrpc_version_error(id: Value) -> Value {
+    rpc_error_payload(id, -32600, "Invalid request: jsonrpc must be \"2.0\"", None)
+}
+
+fn rpc_error_payload(id: Value, code: i64, message: &str, data: Option<Value>) -> Value {
     let mut payload = json!({
         "id": id,
         "error": {
@@ -9135,7 +14772,7 @@ async fn send_rpc_error(
         payload["error"]["data"] = data;
     }
 
-    state.hub.send_json(client_id, payload).await;
+    payload
 }
 
 fn resolve_bridge_workdir(raw_workdir: PathBuf) -> Result<PathBuf, String> {
@@ -10465,14 +16102,13 @@ fn should_rewrite_preview_html_response(headers: &HeaderMap) -> bool {
         return false;
     }
 
-    match headers
-        .get(CONTENT_ENCODING)
-        .and_then(|value| value.to_str().ok())
-        .map(|value| value.trim().to_ascii_lowercase())
-    {
-        Some(value) if !value.is_empty() && value != "identity" => false,
-        _ => true,
-    }
+    !matches!(
+        headers
+            .get(CONTENT_ENCODING)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_ascii_lowercase()),
+        Some(value) if !value.is_empty() && value != "identity"
+    )
 }
 
 fn rewrite_preview_html_document(
@@ -10700,7 +16336,7 @@ fn normalize_browser_preview_target_url(raw: &str) -> Result<Url, BridgeError> {
             "targetUrl must use http:// or https://",
         ));
     }
-    if parsed.username().trim().len() > 0 || parsed.password().is_some() {
+    if !parsed.username().trim().is_empty() || parsed.password().is_some() {
         return Err(BridgeError::invalid_params(
             "targetUrl must not include username or password",
         ));
@@ -11381,6 +17017,102 @@ fn parse_connect_url_env(name: &str) -> Result<Option<String>, String> {
         .map(Some)
 }
 
+/// `BRIDGE_ROLLOUT_DEDUP_WINDOW_MS`; `0` turns live/rollout deduplication off.
+fn resolve_rollout_dedup_window() -> Duration {
+    read_non_empty_env("BRIDGE_ROLLOUT_DEDUP_WINDOW_MS")
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_ROLLOUT_DEDUP_WINDOW)
+}
+
+fn resolve_decline_on_no_clients_grace() -> Option<Duration> {
+    if !parse_bool_env("BRIDGE_DECLINE_ON_NO_CLIENTS") {
+        return None;
+    }
+
+    let grace_ms = read_non_empty_env("BRIDGE_DECLINE_ON_NO_CLIENTS_GRACE_MS")
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_DECLINE_ON_NO_CLIENTS_GRACE_MS);
+    Some(Duration::from_millis(grace_ms))
+}
+
+/// Rate limiting is opt-in: it stays off unless `BRIDGE_CLIENT_RATE_LIMIT_PER_SEC` is set
+/// to a positive rate.
+fn resolve_client_rate_limit() -> Option<ClientRateLimit> {
+    let per_second = read_non_empty_env("BRIDGE_CLIENT_RATE_LIMIT_PER_SEC")
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|value| *value > 0)?;
+
+    let burst = read_non_empty_env("BRIDGE_CLIENT_RATE_LIMIT_BURST")
+        .and_then(|value| value.parse::<u32>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_CLIENT_RATE_LIMIT_BURST);
+    Some(ClientRateLimit { per_second, burst })
+}
+
+fn resolve_rollout_max_tracked_files() -> usize {
+    read_non_empty_env("BRIDGE_ROLLOUT_MAX_TRACKED_FILES")
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES)
+}
+
+/// Resolves `BRIDGE_ATTACHMENTS_DIR`: absolute paths are used as-is, relative ones are joined
+/// to the workdir and must stay inside it. Defaults to `<workdir>/.clawdex-mobile-attachments`.
+fn resolve_attachments_dir(workdir: &Path, raw: Option<String>) -> Result<PathBuf, String> {
+    let Some(raw) = raw else {
+        return Ok(workdir.join(MOBILE_ATTACHMENTS_DIR));
+    };
+
+    let configured = PathBuf::from(raw.trim());
+    if configured.is_absolute() {
+        return Ok(normalize_path(&configured));
+    }
+
+    let resolved = normalize_path(&workdir.join(configured));
+    if resolved == workdir || !resolved.starts_with(workdir) {
+        return Err(
+            "BRIDGE_ATTACHMENTS_DIR must be absolute or a subdirectory of BRIDGE_WORKDIR"
+                .to_string(),
+        );
+    }
+    Ok(resolved)
+}
+
+fn resolve_ws_client_queue_capacity() -> usize {
+    read_non_empty_env("BRIDGE_WS_CLIENT_QUEUE_CAPACITY")
+        .and_then(|value| value.parse::<usize>().ok())
+        .map(|value| value.clamp(MIN_WS_CLIENT_QUEUE_CAPACITY, MAX_WS_CLIENT_QUEUE_CAPACITY))
+        .unwrap_or(DEFAULT_WS_CLIENT_QUEUE_CAPACITY)
+}
+
+fn resolve_terminal_history_size() -> usize {
+    read_non_empty_env("BRIDGE_TERMINAL_HISTORY_SIZE")
+        .and_then(|value| value.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_TERMINAL_HISTORY_SIZE)
+}
+
+fn resolve_terminal_max_output_bytes() -> usize {
+    read_non_empty_env("BRIDGE_TERMINAL_MAX_OUTPUT_BYTES")
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_TERMINAL_MAX_OUTPUT_BYTES)
+}
+
+fn resolve_ws_max_frame_bytes() -> usize {
+    read_non_empty_env("BRIDGE_WS_MAX_FRAME_BYTES")
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_WS_MAX_FRAME_BYTES)
+}
+
+fn resolve_app_server_max_line_bytes() -> usize {
+    read_non_empty_env("BRIDGE_APP_SERVER_MAX_LINE_BYTES")
+        .and_then(|value| value.parse::<usize>().ok())
+        .filter(|value| *value > 0)
+        .unwrap_or(DEFAULT_APP_SERVER_MAX_LINE_BYTES)
+}
+
 fn resolve_max_voice_transcription_bytes() -> usize {
     read_non_empty_env("BRIDGE_MAX_VOICE_TRANSCRIPTION_BYTES")
         .and_then(|value| value.parse::<usize>().ok())
@@ -11415,6 +17147,34 @@ fn parse_csv_env(name: &str, fallback: &[&str]) -> HashSet<String> {
     }
 }
 
+/// Collects `BRIDGE_TERMINAL_<BINARY>_BLOCKED_ARGS=push,reset` entries into a
+/// per-binary set of arguments that `bridge/terminal/exec` refuses to pass.
+fn parse_terminal_blocked_args(
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> HashMap<String, HashSet<String>> {
+    let mut blocked = HashMap::new();
+    for (name, raw) in vars {
+        let Some(binary) = name
+            .strip_prefix("BRIDGE_TERMINAL_")
+            .and_then(|rest| rest.strip_suffix("_BLOCKED_ARGS"))
+            .filter(|binary| !binary.is_empty())
+        else {
+            continue;
+        };
+
+        let args = raw
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(str::to_string)
+            .collect::<HashSet<_>>();
+        if !args.is_empty() {
+            blocked.insert(binary.to_ascii_lowercase(), args);
+        }
+    }
+    blocked
+}
+
 fn parse_enabled_bridge_engines_csv(raw: &str) -> Result<Vec<BridgeRuntimeEngine>, String> {
     let mut parsed = Vec::new();
     let mut seen = HashSet::new();
@@ -11692,7 +17452,7 @@ fn route_engine_from_params(params: Option<&Value>) -> Option<BridgeRuntimeEngin
             .or_else(|| params.get("parent_thread_id")),
     );
     if let Some(thread_id) = thread_id.as_deref() {
-        if let Some((engine, _)) = parse_engine_qualified_id(&thread_id) {
+        if let Some((engine, _)) = parse_engine_qualified_id(thread_id) {
             return Some(engine);
         }
     }
@@ -12958,8 +18718,7 @@ fn opencode_messages_to_turns(
         };
 
         if role == "user" {
-            let turn_id =
-                read_string(info.get("id")).unwrap_or_else(|| generate_opencode_local_id());
+            let turn_id = read_string(info.get("id")).unwrap_or_else(generate_opencode_local_id);
             let user_content = opencode_user_content_items(message_object);
             let mut turn = json!({
                 "id": turn_id.clone(),
@@ -13010,7 +18769,7 @@ fn opencode_messages_to_turns(
                 .filter(|text| !text.trim().is_empty())
             {
                 let item_id =
-                    read_string(info.get("id")).unwrap_or_else(|| generate_opencode_local_id());
+                    read_string(info.get("id")).unwrap_or_else(generate_opencode_local_id);
                 if let Some(items) = turns[index].get_mut("items").and_then(Value::as_array_mut) {
                     items.push(json!({
                         "type": "agentMessage",
@@ -13316,54 +19075,600 @@ fn thread_has_running_turn(thread: &Value) -> bool {
     )
 }
 
+const FORWARDED_METHODS: &[&str] = &[
+    "account/login/cancel",
+    "account/login/start",
+    "account/logout",
+    "account/rateLimits/read",
+    "account/read",
+    "app/list",
+    "collaborationMode/list",
+    "command/exec",
+    "config/batchWrite",
+    "config/mcpServer/reload",
+    "config/read",
+    "config/value/write",
+    "configRequirements/read",
+    "experimentalFeature/list",
+    "feedback/upload",
+    "fuzzyFileSearch/sessionStart",
+    "fuzzyFileSearch/sessionStop",
+    "fuzzyFileSearch/sessionUpdate",
+    "mcpServer/oauth/login",
+    "mcpServerStatus/list",
+    "mock/experimentalMethod",
+    "model/list",
+    "review/start",
+    "skills/config/write",
+    "skills/list",
+    "skills/remote/export",
+    "skills/remote/list",
+    "thread/archive",
+    "thread/backgroundTerminals/clean",
+    "thread/compact/start",
+    "thread/fork",
+    "thread/list",
+    "thread/loaded/list",
+    "thread/name/set",
+    "thread/read",
+    "thread/resume",
+    "thread/rollback",
+    "thread/start",
+    "thread/unarchive",
+    "turn/interrupt",
+    "turn/start",
+    "turn/steer",
+];
+
+#[cfg(test)]
 fn is_forwarded_method(method: &str) -> bool {
-    matches!(
-        method,
-        "account/login/cancel"
-            | "account/login/start"
-            | "account/logout"
-            | "account/rateLimits/read"
-            | "account/read"
-            | "app/list"
-            | "collaborationMode/list"
-            | "command/exec"
-            | "config/batchWrite"
-            | "config/mcpServer/reload"
-            | "config/read"
-            | "config/value/write"
-            | "configRequirements/read"
-            | "experimentalFeature/list"
-            | "feedback/upload"
-            | "fuzzyFileSearch/sessionStart"
-            | "fuzzyFileSearch/sessionStop"
-            | "fuzzyFileSearch/sessionUpdate"
-            | "mcpServer/oauth/login"
-            | "mcpServerStatus/list"
-            | "mock/experimentalMethod"
-            | "model/list"
-            | "review/start"
-            | "skills/config/write"
-            | "skills/list"
-            | "skills/remote/export"
-            | "skills/remote/list"
-            | "thread/archive"
-            | "thread/backgroundTerminals/clean"
-            | "thread/compact/start"
-            | "thread/fork"
-            | "thread/list"
-            | "thread/loaded/list"
-            | "thread/name/set"
-            | "thread/read"
-            | "thread/resume"
-            | "thread/rollback"
-            | "thread/start"
-            | "thread/unarchive"
-            | "turn/interrupt"
-            | "turn/start"
-            | "turn/steer"
-    )
+    FORWARDED_METHODS.contains(&method)
 }
 
+/// Merges `BRIDGE_EXTRA_FORWARDED_METHODS` into the built-in allowlist. `bridge/` methods are
+/// handled locally and never forwarded, so they are skipped.
+fn build_forwarded_methods(extra: &HashSet<String>) -> HashSet<String> {
+    FORWARDED_METHODS
+        .iter()
+        .map(|method| method.to_string())
+        .chain(
+            extra
+                .iter()
+                .filter(|method| !method.starts_with("bridge/"))
+                .cloned(),
+        )
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+struct BridgeParamSchema {
+    name: &'static str,
+    #[serde(rename = "type")]
+    kind: &'static str,
+}
+
+impl BridgeParamSchema {
+    const fn new(name: &'static str, kind: &'static str) -> Self {
+        Self { name, kind }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+struct BridgeMethodSchema {
+    method: &'static str,
+    params: &'static [BridgeParamSchema],
+}
+
+impl BridgeMethodSchema {
+    const fn new(method: &'static str, params: &'static [BridgeParamSchema]) -> Self {
+        Self { method, params }
+    }
+}
+
+/// Param shapes for every `bridge/*` method. Types use a `?` suffix for optional fields and
+/// `[]` for arrays. Keep this in sync with `handle_bridge_method`.
+const BRIDGE_METHOD_SCHEMAS: &[BridgeMethodSchema] = &[
+    BridgeMethodSchema::new("bridge/health/read", &[]),
+    BridgeMethodSchema::new("bridge/health/deep", &[]),
+    BridgeMethodSchema::new("bridge/diagnostics/echo", &[]),
+    BridgeMethodSchema::new("bridge/status/read", &[]),
+    BridgeMethodSchema::new("bridge/capabilities", &[]),
+    BridgeMethodSchema::new("bridge/capabilities/read", &[]),
+    BridgeMethodSchema::new("bridge/runtime/read", &[]),
+    BridgeMethodSchema::new("bridge/appServer/info", &[]),
+    BridgeMethodSchema::new("bridge/appServer/stats", &[]),
+    BridgeMethodSchema::new("bridge/admin/rejectedMethods", &[]),
+    BridgeMethodSchema::new("bridge/rollout/status", &[]),
+    BridgeMethodSchema::new("bridge/debug/trace", &[]),
+    BridgeMethodSchema::new("bridge/cursor/credentials/read", &[]),
+    BridgeMethodSchema::new(
+        "bridge/browser/session/create",
+        &[BridgeParamSchema::new("targetUrl", "string")],
+    ),
+    BridgeMethodSchema::new("bridge/browser/sessions/list", &[]),
+    BridgeMethodSchema::new(
+        "bridge/browser/session/close",
+        &[BridgeParamSchema::new("sessionId", "string")],
+    ),
+    BridgeMethodSchema::new("bridge/browser/targets/discover", &[]),
+    BridgeMethodSchema::new(
+        "bridge/codex/auth/callback/forward",
+        &[BridgeParamSchema::new("callbackUrl", "string")],
+    ),
+    BridgeMethodSchema::new("bridge/codex/app-server/restart", &[]),
+    BridgeMethodSchema::new(
+        "bridge/update/start",
+        &[BridgeParamSchema::new("version", "string?")],
+    ),
+    BridgeMethodSchema::new("bridge/restart/start", &[]),
+    BridgeMethodSchema::new("bridge/client/subscriptions/read", &[]),
+    BridgeMethodSchema::new(
+        "bridge/client/subscriptions/set",
+        &[BridgeParamSchema::new("topics", "string[]")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/events/replay",
+        &[
+            BridgeParamSchema::new("afterEventId", "number?"),
+            BridgeParamSchema::new("limit", "number?"),
+            BridgeParamSchema::new("methods", "string[]?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/events/get",
+        &[BridgeParamSchema::new("eventId", "number")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/ui/present",
+        &[
+            BridgeParamSchema::new("id", "string"),
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("turnId", "string?"),
+            BridgeParamSchema::new("kind", "string?"),
+            BridgeParamSchema::new("presentation", "string"),
+            BridgeParamSchema::new("tone", "string?"),
+            BridgeParamSchema::new("title", "string"),
+            BridgeParamSchema::new("subtitle", "string?"),
+            BridgeParamSchema::new("bodyMarkdown", "string?"),
+            BridgeParamSchema::new("blocks", "object[]?"),
+            BridgeParamSchema::new("actions", "object[]?"),
+            BridgeParamSchema::new("dismissible", "boolean?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/ui/update",
+        &[
+            BridgeParamSchema::new("id", "string"),
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("turnId", "string?"),
+            BridgeParamSchema::new("kind", "string?"),
+            BridgeParamSchema::new("presentation", "string"),
+            BridgeParamSchema::new("tone", "string?"),
+            BridgeParamSchema::new("title", "string"),
+            BridgeParamSchema::new("subtitle", "string?"),
+            BridgeParamSchema::new("bodyMarkdown", "string?"),
+            BridgeParamSchema::new("blocks", "object[]?"),
+            BridgeParamSchema::new("actions", "object[]?"),
+            BridgeParamSchema::new("dismissible", "boolean?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/ui/dismiss",
+        &[
+            BridgeParamSchema::new("id", "string"),
+            BridgeParamSchema::new("threadId", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/ui/resolve",
+        &[
+            BridgeParamSchema::new("id", "string"),
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("turnId", "string?"),
+            BridgeParamSchema::new("actionId", "string"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/thread/list/stream/start",
+        &[
+            BridgeParamSchema::new("streamId", "string?"),
+            BridgeParamSchema::new("includeSubAgents", "boolean?"),
+            BridgeParamSchema::new("limits", "number[]?"),
+            BridgeParamSchema::new("delayMs", "number?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/thread/list/stream/cancel",
+        &[BridgeParamSchema::new("streamId", "string")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/thread/queue/read",
+        &[BridgeParamSchema::new("threadId", "string")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/thread/queue/send",
+        &[
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("content", "string"),
+            BridgeParamSchema::new("turnStart", "object"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/thread/queue/steer",
+        &[
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("itemId", "string"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/thread/queue/cancel",
+        &[
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("itemId", "string"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/turn/steer",
+        &[
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("expectedTurnId", "string"),
+            BridgeParamSchema::new("input", "object[]"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/skills/overview",
+        &[BridgeParamSchema::new("cwds", "string[]?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/thread/export",
+        &[
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("inline", "boolean?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/thread/delete",
+        &[
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("confirm", "boolean"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/workspace/setCwd",
+        &[
+            BridgeParamSchema::new("threadId", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/workspaces/list",
+        &[BridgeParamSchema::new("limit", "number?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/fs/write",
+        &[
+            BridgeParamSchema::new("path", "string"),
+            BridgeParamSchema::new("contentBase64", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("createDirs", "boolean?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/fs/list",
+        &[
+            BridgeParamSchema::new("path", "string?"),
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("includeHidden", "boolean?"),
+            BridgeParamSchema::new("directoriesOnly", "boolean?"),
+            BridgeParamSchema::new("includeGitRepo", "boolean?"),
+            BridgeParamSchema::new("limit", "number?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/terminal/exec",
+        &[
+            BridgeParamSchema::new("command", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("timeoutMs", "number?"),
+            BridgeParamSchema::new("captureToFile", "boolean?"),
+            BridgeParamSchema::new("commandToken", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/terminal/history",
+        &[BridgeParamSchema::new("limit", "number?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/terminal/output/read",
+        &[
+            BridgeParamSchema::new("path", "string"),
+            BridgeParamSchema::new("offset", "number?"),
+            BridgeParamSchema::new("limit", "number?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/github/auth/install",
+        &[
+            BridgeParamSchema::new("accessToken", "string?"),
+            BridgeParamSchema::new("repositories", "string[]?"),
+            BridgeParamSchema::new("grants", "object[]?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/attachments/upload",
+        &[
+            BridgeParamSchema::new("dataBase64", "string"),
+            BridgeParamSchema::new("fileName", "string?"),
+            BridgeParamSchema::new("mimeType", "string?"),
+            BridgeParamSchema::new("threadId", "string?"),
+            BridgeParamSchema::new("kind", "string?"),
+            BridgeParamSchema::new("idempotencyKey", "string?"),
+            BridgeParamSchema::new("expectedSha256", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/attachments/move",
+        &[
+            BridgeParamSchema::new("path", "string"),
+            BridgeParamSchema::new("threadId", "string"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/status",
+        &[
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("includeIgnored", "boolean?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/statusMulti",
+        &[BridgeParamSchema::new("cwds", "string[]")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/statusSummary",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/isClean",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/statusSubscribe",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/statusUnsubscribe",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/diff",
+        &[
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("base", "string?"),
+            BridgeParamSchema::new("head", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/stagedPaths",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/history",
+        &[
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("limit", "number?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/revParse",
+        &[
+            BridgeParamSchema::new("ref", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/worktrees",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/reflog",
+        &[
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("limit", "number?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/fileLog",
+        &[
+            BridgeParamSchema::new("path", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("limit", "number?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/blameRange",
+        &[
+            BridgeParamSchema::new("path", "string"),
+            BridgeParamSchema::new("start", "number"),
+            BridgeParamSchema::new("end", "number"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/branches",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/clone",
+        &[
+            BridgeParamSchema::new("url", "string"),
+            BridgeParamSchema::new("parentPath", "string?"),
+            BridgeParamSchema::new("directoryName", "string"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/stage",
+        &[
+            BridgeParamSchema::new("path", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/stageAll",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/init",
+        &[
+            BridgeParamSchema::new("cwd", "string?"),
+            BridgeParamSchema::new("initialBranch", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/configGet",
+        &[
+            BridgeParamSchema::new("key", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/configSet",
+        &[
+            BridgeParamSchema::new("key", "string"),
+            BridgeParamSchema::new("value", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/move",
+        &[
+            BridgeParamSchema::new("from", "string"),
+            BridgeParamSchema::new("to", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/restoreStaged",
+        &[
+            BridgeParamSchema::new("path", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/cherryPick",
+        &[
+            BridgeParamSchema::new("hash", "string?"),
+            BridgeParamSchema::new("abort", "boolean?"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/applyPatch",
+        &[
+            BridgeParamSchema::new("patch", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/unstage",
+        &[
+            BridgeParamSchema::new("path", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/unstageAll",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/commit",
+        &[
+            BridgeParamSchema::new("message", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/commitAll",
+        &[
+            BridgeParamSchema::new("message", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/switch",
+        &[
+            BridgeParamSchema::new("branch", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/push",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/stashList",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/stashApply",
+        &[
+            BridgeParamSchema::new("ref", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/stashDrop",
+        &[
+            BridgeParamSchema::new("ref", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/tags",
+        &[BridgeParamSchema::new("cwd", "string?")],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/git/tagCreate",
+        &[
+            BridgeParamSchema::new("name", "string"),
+            BridgeParamSchema::new("message", "string"),
+            BridgeParamSchema::new("cwd", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new("bridge/approvals/list", &[]),
+    BridgeMethodSchema::new(
+        "bridge/approvals/resolve",
+        &[
+            BridgeParamSchema::new("id", "string"),
+            BridgeParamSchema::new("decision", "any"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/approvals/resolveAll",
+        &[
+            BridgeParamSchema::new("decision", "any"),
+            BridgeParamSchema::new("kind", "string?"),
+            BridgeParamSchema::new("threadId", "string?"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/userInput/resolve",
+        &[
+            BridgeParamSchema::new("id", "string"),
+            BridgeParamSchema::new("answers", "object"),
+        ],
+    ),
+    BridgeMethodSchema::new(
+        "bridge/voice/transcribe",
+        &[
+            BridgeParamSchema::new("dataBase64", "string"),
+            BridgeParamSchema::new("prompt", "string?"),
+            BridgeParamSchema::new("fileName", "string?"),
+            BridgeParamSchema::new("mimeType", "string?"),
+        ],
+    ),
+];
+
 #[derive(Clone)]
 enum ApprovalDecisionCanonical {
     Accept,
@@ -13448,6 +19753,86 @@ fn approval_decision_to_response_value(
     }
 }
 
+fn legacy_approval_rejection(id: Value, method: &str) -> Value {
+    json!({
+        "id": id,
+        "error": {
+            "code": -32601,
+            "message": format!(
+                "Legacy approval method {method} is rejected by this bridge (BRIDGE_REJECT_LEGACY_APPROVALS=true)"
+            )
+        }
+    })
+}
+
+fn merge_skills_overview(
+    local: Result<Value, String>,
+    remote: Result<Value, String>,
+) -> BridgeSkillsOverviewResponse {
+    let (local, local_error) = match local {
+        Ok(result) => (collect_local_skills(&result), None),
+        Err(error) => (Vec::new(), Some(error)),
+    };
+    let installed = local
+        .iter()
+        .filter_map(skill_name_key)
+        .collect::<HashSet<_>>();
+    let (remote, remote_error) = match remote {
+        Ok(result) => (
+            skill_entries(&result)
+                .into_iter()
+                .map(|mut skill| {
+                    let is_installed =
+                        skill_name_key(&skill).is_some_and(|name| installed.contains(&name));
+                    if let Some(object) = skill.as_object_mut() {
+                        object.insert("installed".to_string(), json!(is_installed));
+                    }
+                    skill
+                })
+                .collect(),
+            None,
+        ),
+        Err(error) => (Vec::new(), Some(error)),
+    };
+
+    BridgeSkillsOverviewResponse {
+        local,
+        remote,
+        local_error,
+        remote_error,
+        at: now_iso(),
+    }
+}
+
+/// `skills/list` groups skills per cwd (`data: [{ cwd, skills }]`); flatten those groups and
+/// accept a flat list too.
+fn collect_local_skills(result: &Value) -> Vec<Value> {
+    skill_entries(result)
+        .into_iter()
+        .flat_map(
+            |entry| match entry.get("skills").and_then(Value::as_array) {
+                Some(skills) => skills.clone(),
+                None => vec![entry],
+            },
+        )
+        .collect()
+}
+
+fn skill_entries(result: &Value) -> Vec<Value> {
+    result
+        .get("data")
+        .or_else(|| result.get("skills"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+}
+
+fn skill_name_key(skill: &Value) -> Option<String> {
+    read_string(skill.get("name"))
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+}
+
 fn parse_internal_id(value: Option<&Value>) -> Option<u64> {
     let value = value?;
 
@@ -13461,8 +19846,10 @@ fn parse_internal_id(value: Option<&Value>) -> Option<u64> {
         }
     }
 
+    // The bridge always sends numeric ids, but an app-server that echoes them back as
+    // strings must still correlate.
     if let Some(raw) = value.as_str() {
-        return raw.parse::<u64>().ok();
+        return raw.trim().parse::<u64>().ok();
     }
 
     None
@@ -13472,6 +19859,84 @@ fn read_string(value: Option<&Value>) -> Option<String> {
     value.and_then(Value::as_str).map(str::to_string)
 }
 
+fn is_sensitive_trace_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    [
+        "token",
+        "secret",
+        "password",
+        "apikey",
+        "api_key",
+        "authorization",
+    ]
+    .iter()
+    .any(|marker| key.contains(marker))
+}
+
+fn redact_debug_trace_value(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| {
+                    if is_sensitive_trace_key(&key) {
+                        (key, json!(DEBUG_TRACE_REDACTED))
+                    } else {
+                        (key, redact_debug_trace_value(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(entries) => {
+            Value::Array(entries.into_iter().map(redact_debug_trace_value).collect())
+        }
+        other => other,
+    }
+}
+
+/// Params for `bridge/userInput.resolved`. Deliberately built from the request
+/// alone so answers (including `isSecret` ones) can never reach a broadcast.
+fn user_input_resolved_params(request: &PendingUserInputRequest) -> Value {
+    json!({
+        "id": request.id,
+        "threadId": request.thread_id,
+        "turnId": request.turn_id,
+        "resolvedAt": now_iso(),
+    })
+}
+
+fn redact_secret_user_input_answers(
+    questions: &[PendingUserInputQuestion],
+    answers: &HashMap<String, UserInputAnswerPayload>,
+) -> Value {
+    let secret_ids = questions
+        .iter()
+        .filter(|question| question.is_secret)
+        .map(|question| question.id.as_str())
+        .collect::<HashSet<_>>();
+    Value::Object(
+        answers
+            .iter()
+            .map(|(id, answer)| {
+                let value = if secret_ids.contains(id.as_str()) {
+                    json!(DEBUG_TRACE_REDACTED)
+                } else {
+                    json!(answer)
+                };
+                (id.clone(), value)
+            })
+            .collect(),
+    )
+}
+
+/// Extracts `0.46.0` from a `codex_cli_rs/0.46.0 (...)` user agent.
+fn parse_user_agent_version(user_agent: &str) -> Option<String> {
+    let product = user_agent.split_whitespace().next()?;
+    let (_, version) = product.split_once('/')?;
+    let version = version.trim();
+    (!version.is_empty()).then(|| version.to_string())
+}
+
 fn parse_string_array_strict(value: Option<&Value>) -> Option<Vec<String>> {
     let entries = value.and_then(Value::as_array)?;
     if entries.is_empty() {
@@ -13515,6 +19980,56 @@ fn parse_execpolicy_amendment(value: Option<&Value>) -> Option<Vec<String>> {
     None
 }
 
+/// Reads a patch from file-change approval params: a `diff`/`patch` string, or the legacy
+/// `fileChanges` map (`update` entries carry `unified_diff`, `add` entries the new content).
+fn parse_approval_diff_preview(params: &serde_json::Map<String, Value>) -> Option<String> {
+    let diff = ["diff", "patch", "unifiedDiff"]
+        .iter()
+        .find_map(|key| read_string(params.get(*key)))
+        .or_else(|| {
+            let changes = params.get("fileChanges").and_then(Value::as_object)?;
+            let mut sections = Vec::new();
+            for (path, change) in changes {
+                let change = change.as_object();
+                let section = match read_string(change.and_then(|c| c.get("type"))).as_deref() {
+                    Some("update") => {
+                        let Some(diff) = read_string(change.and_then(|c| c.get("unified_diff")))
+                        else {
+                            continue;
+                        };
+                        format!("--- {path}\n+++ {path}\n{diff}")
+                    }
+                    Some("add") => {
+                        let content =
+                            read_string(change.and_then(|c| c.get("content"))).unwrap_or_default();
+                        let added = content
+                            .lines()
+                            .map(|line| format!("+{line}"))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        format!("--- /dev/null\n+++ {path}\n{added}")
+                    }
+                    Some("delete") => format!("--- {path}\n+++ /dev/null"),
+                    _ => continue,
+                };
+                sections.push(section);
+            }
+            (!sections.is_empty()).then(|| sections.join("\n"))
+        })?;
+    if diff.trim().is_empty() {
+        return None;
+    }
+    if diff.len() <= APPROVAL_DIFF_PREVIEW_MAX_BYTES {
+        return Some(diff);
+    }
+
+    let mut preview_len = APPROVAL_DIFF_PREVIEW_MAX_BYTES;
+    while !diff.is_char_boundary(preview_len) {
+        preview_len -= 1;
+    }
+    Some(format!("{}\n[diff truncated]", &diff[..preview_len]))
+}
+
 fn parse_user_input_questions(value: Option<&Value>) -> Vec<PendingUserInputQuestion> {
     let Some(array) = value.and_then(Value::as_array) else {
         return Vec::new();
@@ -13669,13 +20184,107 @@ async fn save_uploaded_attachment(
     request: AttachmentUploadRequest,
     state: &Arc<AppState>,
 ) -> Result<AttachmentUploadResponse, BridgeError> {
+    let Some(key) = normalize_attachment_idempotency_key(request.idempotency_key.as_deref())?
+    else {
+        return write_uploaded_attachment(request, state)
+            .await
+            .map(|(uploaded, _)| uploaded);
+    };
+    let scope = request
+        .thread_id
+        .as_deref()
+        .map(|thread_id| sanitize_path_segment(&decode_engine_qualified_id(thread_id)))
+        .unwrap_or_default();
+    let cache_key = (scope, key);
+
+    // The map lock only reserves the key; the write happens outside it.
+    let cached = {
+        let mut uploads = state.attachment_uploads.lock().await;
+        uploads.retain(|_, cached| cached.stored_at.elapsed() < ATTACHMENT_IDEMPOTENCY_TTL);
+        if !uploads.contains_key(&cache_key) && uploads.len() >= ATTACHMENT_IDEMPOTENCY_MAX_KEYS {
+            if let Some(oldest) = uploads
+                .iter()
+                .min_by_key(|(_, cached)| cached.stored_at)
+                .map(|(key, _)| key.clone())
+            {
+                uploads.remove(&oldest);
+            }
+        }
+        uploads
+            .entry(cache_key)
+            .or_insert_with(|| {
+                Arc::new(CachedAttachmentUpload {
+                    upload: OnceCell::new(),
+                    stored_at: Instant::now(),
+                })
+            })
+            .clone()
+    };
+
+    // A failed write leaves the cell empty, so the next retry writes again.
+    let expected_sha256 = request.expected_sha256.clone();
+    let (uploaded, sha256) = cached
+        .upload
+        .get_or_try_init(|| write_uploaded_attachment(request, state))
+        .await?;
+    // A retry must still match its own checksum, not just reuse the first upload.
+    if let Some(expected) = expected_sha256.as_deref() {
+        check_attachment_sha256(sha256, expected)?;
+    }
+    Ok(uploaded.clone())
+}
+
+fn attachment_sha256(bytes: &[u8]) -> String {
+    ring::digest::digest(&ring::digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+fn check_attachment_sha256(actual: &str, expected: &str) -> Result<(), BridgeError> {
+    let expected = expected.trim().to_ascii_lowercase();
+    if expected.len() != 64 || !expected.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(BridgeError::invalid_params(
+            "expectedSha256 must be 64 hex characters",
+        ));
+    }
+
+    if actual != expected {
+        return Err(BridgeError {
+            code: -32602,
+            message: format!("attachment checksum mismatch: expected {expected}, got {actual}"),
+            data: Some(json!({ "checksumMismatch": true })),
+        });
+    }
+
+    Ok(())
+}
+
+fn normalize_attachment_idempotency_key(raw: Option<&str>) -> Result<Option<String>, BridgeError> {
+    let Some(key) = raw.map(str::trim).filter(|key| !key.is_empty()) else {
+        return Ok(None);
+    };
+    if key.len() > ATTACHMENT_IDEMPOTENCY_KEY_MAX_LEN || key.chars().any(char::is_control) {
+        return Err(BridgeError::invalid_params(&format!(
+            "idempotencyKey must be at most {ATTACHMENT_IDEMPOTENCY_KEY_MAX_LEN} printable characters"
+        )));
+    }
+
+    Ok(Some(key.to_string()))
+}
+
+/// Stores the upload and returns its response with the SHA-256 of the stored bytes.
+async fn write_uploaded_attachment(
+    request: AttachmentUploadRequest,
+    state: &Arc<AppState>,
+) -> Result<(AttachmentUploadResponse, String), BridgeError> {
     let encoded = request.data_base64.trim();
     if encoded.is_empty() {
         return Err(BridgeError::invalid_params("dataBase64 must not be empty"));
     }
 
-    let estimated_size = estimate_base64_decoded_size(encoded)?;
-    if estimated_size > MAX_ATTACHMENT_BYTES {
+    if base64_exceeds_decoded_limit(encoded, MAX_ATTACHMENT_BYTES)? {
         return Err(BridgeError::invalid_params(&format!(
             "attachment exceeds max size of {MAX_ATTACHMENT_BYTES} bytes"
         )));
@@ -13691,6 +20300,10 @@ async fn save_uploaded_attachment(
             "attachment exceeds max size of {MAX_ATTACHMENT_BYTES} bytes"
         )));
     }
+    let sha256 = attachment_sha256(&bytes);
+    if let Some(expected) = request.expected_sha256.as_deref() {
+        check_attachment_sha256(&sha256, expected)?;
+    }
 
     let normalized_kind =
         normalize_attachment_kind(request.kind.as_deref(), request.mime_type.as_deref());
@@ -13700,7 +20313,8 @@ async fn save_uploaded_attachment(
         normalized_kind,
     );
 
-    let mut attachment_dir = state.config.workdir.join(MOBILE_ATTACHMENTS_DIR);
+    let attachments_root = &state.config.attachments_dir;
+    let mut attachment_dir = attachments_root.clone();
     if let Some(thread_id) = request.thread_id.as_deref() {
         let normalized_thread = sanitize_path_segment(&decode_engine_qualified_id(thread_id));
         if !normalized_thread.is_empty() {
@@ -13716,9 +20330,9 @@ async fn save_uploaded_attachment(
     let unique_name = format!("{timestamp}-{}-{file_name}", std::process::id());
     let target_path = attachment_dir.join(unique_name);
     let normalized_target = normalize_path(&target_path);
-    if !normalized_target.starts_with(&state.config.workdir) {
+    if !normalized_target.starts_with(attachments_root) {
         return Err(BridgeError::invalid_params(
-            "attachment path must stay within BRIDGE_WORKDIR",
+            "attachment path must stay within the attachments directory",
         ));
     }
 
@@ -13726,18 +20340,21 @@ async fn save_uploaded_attachment(
         .await
         .map_err(|error| BridgeError::server(&format!("failed to persist attachment: {error}")))?;
 
-    Ok(AttachmentUploadResponse {
-        path: normalized_target.to_string_lossy().to_string(),
-        file_name,
-        mime_type: request
-            .mime_type
-            .as_deref()
-            .map(str::trim)
-            .filter(|value| !value.is_empty())
-            .map(str::to_string),
-        size_bytes: bytes.len(),
-        kind: normalized_kind.to_string(),
-    })
+    Ok((
+        AttachmentUploadResponse {
+            path: normalized_target.to_string_lossy().to_string(),
+            file_name,
+            mime_type: request
+                .mime_type
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string),
+            size_bytes: bytes.len(),
+            kind: normalized_kind.to_string(),
+        },
+        sha256,
+    ))
 }
 
 fn extract_base64_payload(raw: &str) -> Result<&str, BridgeError> {
@@ -13755,21 +20372,42 @@ fn extract_base64_payload(raw: &str) -> Result<&str, BridgeError> {
     Ok(payload)
 }
 
+/// Upper bound on the decoded size: partial blocks round up, and trailing `=` only counts
+/// as padding when the payload is whole 4-character blocks, so the estimate never falls
+/// below what `decode_base64_payload` returns.
 fn estimate_base64_decoded_size(raw: &str) -> Result<usize, BridgeError> {
     let payload = extract_base64_payload(raw)?;
     let encoded_len = payload.len();
-    let padding = payload
-        .as_bytes()
-        .iter()
-        .rev()
-        .take_while(|byte| **byte == b'=')
-        .count()
-        .min(2);
+    let padding = if encoded_len % 4 == 0 {
+        payload
+            .as_bytes()
+            .iter()
+            .rev()
+            .take_while(|byte| **byte == b'=')
+            .count()
+            .min(2)
+    } else {
+        0
+    };
 
-    let block_count = (encoded_len + 3) / 4;
+    let block_count = encoded_len.div_ceil(4);
     Ok(block_count.saturating_mul(3).saturating_sub(padding))
 }
 
+/// Checks an encoded payload against a decoded-size cap. Inputs whose raw length alone is
+/// far past the cap are rejected without scanning them for a data URI prefix or padding.
+fn base64_exceeds_decoded_limit(raw: &str, max_decoded_bytes: usize) -> Result<bool, BridgeError> {
+    let max_encoded_len = max_decoded_bytes
+        .div_ceil(3)
+        .saturating_mul(4)
+        .saturating_add(BASE64_LENGTH_CHECK_SLACK);
+    if raw.len() > max_encoded_len {
+        return Ok(true);
+    }
+
+    Ok(estimate_base64_decoded_size(raw)? > max_decoded_bytes)
+}
+
 fn decode_base64_payload(raw: &str) -> Result<Vec<u8>, BridgeError> {
     let payload = extract_base64_payload(raw)?;
 
@@ -13889,8 +20527,7 @@ fn build_attachment_file_name(
 fn sanitize_filename(value: &str) -> String {
     let basename = value
         .split(['/', '\\'])
-        .filter(|segment| !segment.trim().is_empty())
-        .next_back()
+        .rfind(|segment| !segment.trim().is_empty())
         .unwrap_or("attachment");
 
     let mut cleaned = basename
@@ -13916,72 +20553,411 @@ fn sanitize_filename(value: &str) -> String {
     cleaned
 }
 
-fn sanitize_path_segment(value: &str) -> String {
-    let mut cleaned = value
-        .trim()
-        .chars()
-        .map(|char| {
-            if char.is_ascii_alphanumeric() || matches!(char, '-' | '_') {
-                char
-            } else {
-                '_'
-            }
-        })
-        .collect::<String>();
-
-    cleaned = cleaned.trim_matches('_').to_string();
-    if cleaned.len() > 64 {
-        cleaned.truncate(64);
+/// Removes the per-thread attachment folder created by `write_uploaded_attachment`.
+/// Returns whether a folder was removed.
+async fn remove_thread_attachments(root: &Path, thread_id: &str) -> std::io::Result<bool> {
+    let segment = sanitize_path_segment(&decode_engine_qualified_id(thread_id));
+    if segment.is_empty() {
+        return Ok(false);
     }
 
-    cleaned
+    match fs::remove_dir_all(root.join(segment)).await {
+        Ok(()) => Ok(true),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error),
+    }
 }
 
-fn infer_extension_from_mime(raw_mime_type: Option<&str>) -> Option<&'static str> {
-    let mime = raw_mime_type?.trim().to_ascii_lowercase();
-    match mime.as_str() {
-        "image/jpeg" | "image/jpg" => Some("jpg"),
-        "image/png" => Some("png"),
-        "image/webp" => Some("webp"),
-        "image/gif" => Some("gif"),
-        "image/heic" => Some("heic"),
-        "image/heif" => Some("heif"),
-        "text/plain" => Some("txt"),
-        "application/json" => Some("json"),
-        "application/pdf" => Some("pdf"),
-        _ => None,
+/// Drops the state the bridge keeps for a thread once it has been archived or deleted:
+/// the stored `bridge/workspace/setCwd` default and, when cleanup is enabled, the
+/// thread's attachment folder.
+async fn cleanup_removed_thread(
+    hub: &ClientHub,
+    attachment_cleanup_root: Option<&Path>,
+    thread_id: &str,
+) {
+    hub.forget_thread_cwd(thread_id).await;
+    if let Some(root) = attachment_cleanup_root {
+        if let Err(error) = remove_thread_attachments(root, thread_id).await {
+            eprintln!("failed to remove attachments for thread {thread_id}: {error}");
+        }
     }
 }
 
-fn contains_disallowed_control_chars(value: &str) -> bool {
-    value
-        .chars()
-        .any(|char| matches!(char, ';' | '|' | '&' | '<' | '>' | '`'))
-}
+/// Moves an uploaded attachment into the thread folder `write_uploaded_attachment`
+/// would have used, for files uploaded before the thread existed. The source must be
+/// a regular file inside `root`.
+async fn move_attachment_into_thread(
+    root: &Path,
+    request: AttachmentMoveRequest,
+) -> Result<AttachmentMoveResponse, BridgeError> {
+    let segment = sanitize_path_segment(&decode_engine_qualified_id(&request.thread_id));
+    if segment.is_empty() {
+        return Err(BridgeError::invalid_params(
+            "threadId must contain letters, digits, '-' or '_'",
+        ));
+    }
 
-fn now_iso() -> String {
-    Utc::now().to_rfc3339()
-}
+    let raw_path = request.path.trim();
+    if raw_path.is_empty() {
+        return Err(BridgeError::invalid_params("path must not be empty"));
+    }
+    let source = normalize_path(&root.join(raw_path));
+    if source == root || !source.starts_with(root) {
+        return Err(BridgeError::invalid_params(
+            "path must be inside the attachments directory",
+        ));
+    }
+    let metadata = fs::symlink_metadata(&source)
+        .await
+        .map_err(|error| BridgeError::invalid_params(&format!("attachment not found: {error}")))?;
+    if metadata.file_type().is_symlink() {
+        return Err(BridgeError::invalid_params("path must not be a symlink"));
+    }
+    if !metadata.is_file() {
+        return Err(BridgeError::invalid_params(
+            "path must be an attachment file",
+        ));
+    }
+    let (Some(file_name), Some(source_dir)) = (source.file_name(), source.parent()) else {
+        return Err(BridgeError::invalid_params(
+            "path must be an attachment file",
+        ));
+    };
+    // The lexical check above does not see symlinked directories inside the root.
+    let canonical_root = fs::canonicalize(root).await.map_err(|error| {
+        BridgeError::server(&format!("failed to resolve attachments directory: {error}"))
+    })?;
+    let escapes_root = |dir: &Path| !dir.starts_with(&canonical_root);
+    let canonical_source_dir = fs::canonicalize(source_dir)
+        .await
+        .map_err(|error| BridgeError::invalid_params(&format!("attachment not found: {error}")))?;
+    if escapes_root(&canonical_source_dir) {
+        return Err(BridgeError::invalid_params(
+            "path must be inside the attachments directory",
+        ));
+    }
 
-fn resolve_local_image_path(raw_path: &str) -> Result<PathBuf, &'static str> {
-    let trimmed = raw_path.trim();
-    if trimmed.is_empty() {
-        return Err("Image path is required");
+    let thread_dir = root.join(&segment);
+    let target = thread_dir.join(file_name);
+    let previous_path = source.to_string_lossy().to_string();
+    if target == source {
+        return Ok(AttachmentMoveResponse {
+            path: previous_path.clone(),
+            previous_path,
+        });
     }
 
-    let path = PathBuf::from(trimmed);
-    if !path.is_absolute() {
-        return Err("Image path must be absolute");
+    fs::create_dir_all(&thread_dir).await.map_err(|error| {
+        BridgeError::server(&format!("failed to create attachment directory: {error}"))
+    })?;
+    let canonical_thread_dir = fs::canonicalize(&thread_dir).await.map_err(|error| {
+        BridgeError::server(&format!("failed to resolve attachment directory: {error}"))
+    })?;
+    if escapes_root(&canonical_thread_dir) {
+        return Err(BridgeError::invalid_params(
+            "thread attachment directory must be inside the attachments directory",
+        ));
+    }
+    // Linking fails if the name is taken, so the collision check and the move are one step.
+    let canonical_source = canonical_source_dir.join(file_name);
+    let canonical_target = canonical_thread_dir.join(file_name);
+    fs::hard_link(&canonical_source, &canonical_target)
+        .await
+        .map_err(|error| {
+            if error.kind() == std::io::ErrorKind::AlreadyExists {
+                BridgeError::invalid_params(&format!(
+                    "thread {segment} already has an attachment named {}",
+                    file_name.to_string_lossy()
+                ))
+            } else {
+                BridgeError::server(&format!("failed to move attachment: {error}"))
+            }
+        })?;
+    if let Err(error) = fs::remove_file(&canonical_source).await {
+        let _ = fs::remove_file(&canonical_target).await;
+        return Err(BridgeError::server(&format!(
+            "failed to move attachment: {error}"
+        )));
     }
 
-    Ok(normalize_path(&path))
+    Ok(AttachmentMoveResponse {
+        path: target.to_string_lossy().to_string(),
+        previous_path,
+    })
 }
 
-fn infer_image_content_type_from_path(path: &Path) -> Option<&'static str> {
-    let extension = path.extension()?.to_str()?.trim().to_ascii_lowercase();
-    match extension.as_str() {
-        "png" => Some("image/png"),
-        "jpg" | "jpeg" => Some("image/jpeg"),
+/// Builds a shareable manifest of a thread from its rollout file and the folder
+/// `write_uploaded_attachment` keeps for it. With `inline`, files up to
+/// `THREAD_EXPORT_INLINE_FILE_MAX_BYTES` are embedded as base64 until
+/// `THREAD_EXPORT_INLINE_TOTAL_MAX_BYTES` is used up.
+async fn export_thread(
+    state: &AppState,
+    request: BridgeThreadExportRequest,
+) -> Result<ThreadExportResponse, BridgeError> {
+    let raw_thread_id = decode_engine_qualified_id(&request.thread_id);
+    if raw_thread_id.is_empty() {
+        return Err(BridgeError::invalid_params("threadId must not be empty"));
+    }
+    let inline = request.inline.unwrap_or(false);
+
+    let (tracked_path, sessions_root) = {
+        let sync = state.backend.rollout_sync.lock().await;
+        let tracked_path = sync
+            .files
+            .values()
+            .find(|file| file.thread_id.as_deref() == Some(raw_thread_id.as_str()))
+            .map(|file| file.path.clone());
+        (tracked_path, sync.sessions_root.clone())
+    };
+    // Live sync only tracks recent files and is off under `BRIDGE_DISABLE_ROLLOUT_SYNC`, so
+    // older or untracked threads are looked up in the sessions directory by name.
+    let rollout_path = match tracked_path {
+        Some(path) => Some(path),
+        None => match sessions_root.or_else(resolve_codex_sessions_root) {
+            Some(root) => find_thread_rollout_file(&root, &raw_thread_id)
+                .await
+                .map_err(|error| {
+                    BridgeError::server(&format!("failed to search rollout files: {error}"))
+                })?,
+            None => None,
+        },
+    }
+    .ok_or_else(|| {
+        BridgeError::invalid_params(&format!("no rollout file found for thread {raw_thread_id}"))
+    })?;
+    let read_error = |error: std::io::Error| {
+        BridgeError::server(&format!("failed to read rollout file: {error}"))
+    };
+    let rollout_size_bytes = fs::metadata(&rollout_path).await.map_err(read_error)?.len();
+    let event_count = count_rollout_file_events(&rollout_path)
+        .await
+        .map_err(read_error)?;
+
+    let mut inline_budget = THREAD_EXPORT_INLINE_TOTAL_MAX_BYTES;
+    let mut take_inline = |size: u64| {
+        if !inline || size > THREAD_EXPORT_INLINE_FILE_MAX_BYTES || size > inline_budget {
+            return false;
+        }
+        inline_budget -= size;
+        true
+    };
+    let rollout_base64 = if take_inline(rollout_size_bytes) {
+        let bytes = fs::read(&rollout_path).await.map_err(read_error)?;
+        Some(general_purpose::STANDARD.encode(bytes))
+    } else {
+        None
+    };
+
+    let mut attachment_paths = Vec::new();
+    let segment = sanitize_path_segment(&raw_thread_id);
+    if !segment.is_empty() {
+        let attachment_dir = state.config.attachments_dir.join(segment);
+        match fs::read_dir(&attachment_dir).await {
+            Ok(mut entries) => {
+                while let Some(entry) = entries.next_entry().await.map_err(|error| {
+                    BridgeError::server(&format!("failed to list attachments: {error}"))
+                })? {
+                    let metadata = entry.metadata().await.map_err(|error| {
+                        BridgeError::server(&format!("failed to read attachment: {error}"))
+                    })?;
+                    if metadata.is_file() {
+                        attachment_paths.push((entry.path(), metadata.len()));
+                    }
+                }
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(BridgeError::server(&format!(
+                    "failed to list attachments: {error}"
+                )));
+            }
+        }
+    }
+    attachment_paths.sort();
+    let attachments_truncated = attachment_paths.len() > THREAD_EXPORT_MAX_ATTACHMENTS;
+    attachment_paths.truncate(THREAD_EXPORT_MAX_ATTACHMENTS);
+
+    let mut attachments = Vec::with_capacity(attachment_paths.len());
+    for (path, size_bytes) in attachment_paths {
+        let data_base64 = if take_inline(size_bytes) {
+            let bytes = fs::read(&path).await.map_err(|error| {
+                BridgeError::server(&format!("failed to read attachment: {error}"))
+            })?;
+            Some(general_purpose::STANDARD.encode(bytes))
+        } else {
+            None
+        };
+        attachments.push(ThreadExportAttachment {
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            size_bytes,
+            data_base64,
+        });
+    }
+
+    Ok(ThreadExportResponse {
+        thread_id: encode_engine_qualified_id(BridgeRuntimeEngine::Codex, &raw_thread_id),
+        rollout_path: rollout_path.to_string_lossy().to_string(),
+        rollout_size_bytes,
+        event_count,
+        rollout_base64,
+        attachments,
+        attachments_truncated,
+    })
+}
+
+/// Counts the non-blank lines of a rollout file a chunk at a time, so exporting a large
+/// rollout does not load it into memory.
+async fn count_rollout_file_events(path: &Path) -> Result<usize, std::io::Error> {
+    let mut file = fs::File::open(path).await?;
+    let mut buffer = vec![0_u8; 64 * 1024];
+    let mut count = 0;
+    let mut line_has_content = false;
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        for byte in &buffer[..read] {
+            if *byte == b'\n' {
+                count += usize::from(line_has_content);
+                line_has_content = false;
+            } else if !byte.is_ascii_whitespace() {
+                line_has_content = true;
+            }
+        }
+    }
+    Ok(count + usize::from(line_has_content))
+}
+
+fn sanitize_path_segment(value: &str) -> String {
+    let mut cleaned = value
+        .trim()
+        .chars()
+        .map(|char| {
+            if char.is_ascii_alphanumeric() || matches!(char, '-' | '_') {
+                char
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+
+    cleaned = cleaned.trim_matches('_').to_string();
+    if cleaned.len() > 64 {
+        cleaned.truncate(64);
+    }
+
+    cleaned
+}
+
+fn infer_extension_from_mime(raw_mime_type: Option<&str>) -> Option<&'static str> {
+    let mime = raw_mime_type?.trim().to_ascii_lowercase();
+    match mime.as_str() {
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "image/webp" => Some("webp"),
+        "image/gif" => Some("gif"),
+        "image/heic" => Some("heic"),
+        "image/heif" => Some("heif"),
+        "text/plain" => Some("txt"),
+        "application/json" => Some("json"),
+        "application/pdf" => Some("pdf"),
+        _ => None,
+    }
+}
+
+fn build_codex_app_server_command(
+    cli_bin: &str,
+    extra_args: &[String],
+    extra_env: &[(String, String)],
+) -> Command {
+    let mut command = Command::new(cli_bin);
+    command
+        .arg("app-server")
+        .arg("--listen")
+        .arg("stdio://")
+        .args(extra_args)
+        .envs(extra_env.iter().map(|(key, value)| (key, value)))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    command
+}
+
+/// Parses `BRIDGE_APP_SERVER_ARGS`, shell-split and appended after `app-server --listen stdio://`.
+/// The child is spawned without a shell, so metacharacters such as `;` are plain argument text.
+fn parse_app_server_args(raw: &str) -> Result<Vec<String>, String> {
+    if raw.chars().any(char::is_control) {
+        return Err("BRIDGE_APP_SERVER_ARGS contains control characters".to_string());
+    }
+    shlex::split(raw).ok_or_else(|| "BRIDGE_APP_SERVER_ARGS has invalid quoting".to_string())
+}
+
+/// Parses `BRIDGE_APP_SERVER_ENV` as shell-split `KEY=value` pairs for the app-server child.
+fn parse_app_server_env(raw: &str) -> Result<Vec<(String, String)>, String> {
+    if raw.chars().any(char::is_control) {
+        return Err("BRIDGE_APP_SERVER_ENV contains control characters".to_string());
+    }
+    let tokens =
+        shlex::split(raw).ok_or_else(|| "BRIDGE_APP_SERVER_ENV has invalid quoting".to_string())?;
+
+    tokens
+        .into_iter()
+        .map(|token| {
+            let (key, value) = token.split_once('=').ok_or_else(|| {
+                format!("BRIDGE_APP_SERVER_ENV entry \"{token}\" must be KEY=value")
+            })?;
+            let valid_key = key
+                .chars()
+                .next()
+                .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+                && key
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+            if !valid_key {
+                return Err(format!(
+                    "BRIDGE_APP_SERVER_ENV has invalid variable name \"{key}\""
+                ));
+            }
+            Ok((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+fn contains_disallowed_control_chars(value: &str) -> bool {
+    value
+        .chars()
+        .any(|char| matches!(char, ';' | '|' | '&' | '<' | '>' | '`'))
+}
+
+fn now_iso() -> String {
+    Utc::now().to_rfc3339()
+}
+
+fn resolve_local_image_path(raw_path: &str) -> Result<PathBuf, &'static str> {
+    let trimmed = raw_path.trim();
+    if trimmed.is_empty() {
+        return Err("Image path is required");
+    }
+
+    let path = PathBuf::from(trimmed);
+    if !path.is_absolute() {
+        return Err("Image path must be absolute");
+    }
+
+    Ok(normalize_path(&path))
+}
+
+fn infer_image_content_type_from_path(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.trim().to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
         "gif" => Some("image/gif"),
         "webp" => Some("image/webp"),
         "heic" => Some("image/heic"),
@@ -14053,11 +21029,52 @@ mod tests {
         fn drop(&mut self) {
             clear_cached_bridge_chatgpt_auth();
             set_bridge_chatgpt_auth_cache_path_override(None);
+            set_bridge_chatgpt_refresh_command_override(None);
             let _ = std::fs::remove_dir_all(&self.temp_dir);
         }
     }
 
     async fn build_test_bridge(hub: Arc<ClientHub>) -> Arc<AppServerBridge> {
+        build_test_bridge_with_attachment_cleanup(hub, None).await
+    }
+
+    async fn build_test_bridge_with_attachment_cleanup(
+        hub: Arc<ClientHub>,
+        attachment_cleanup_root: Option<PathBuf>,
+    ) -> Arc<AppServerBridge> {
+        let mut child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn cat process");
+        let writer = child.stdin.take().expect("child stdin available");
+
+        Arc::new(AppServerBridge {
+            engine: BridgeRuntimeEngine::Codex,
+            child: Mutex::new(child),
+            child_pid: 0,
+            writer: Mutex::new(writer),
+            pending_requests: Mutex::new(HashMap::new()),
+            pending_requests_by_client: Mutex::new(HashMap::new()),
+            internal_waiters: Mutex::new(HashMap::new()),
+            pending_approvals: Mutex::new(HashMap::new()),
+            pending_user_inputs: Mutex::new(HashMap::new()),
+            next_request_id: AtomicU64::new(1),
+            approval_counter: AtomicU64::new(1),
+            user_input_counter: AtomicU64::new(1),
+            server_info: Mutex::new(None),
+            ready: AtomicBool::new(false),
+            debug_trace: None,
+            attachment_cleanup_root,
+            reject_legacy_approvals: false,
+            stdout_lag: AppServerStdoutLag::default(),
+            method_traffic: AppServerMethodTraffic::default(),
+            hub,
+        })
+    }
+
+    async fn build_traced_test_bridge(hub: Arc<ClientHub>) -> Arc<AppServerBridge> {
         let mut child = Command::new("cat")
             .stdin(Stdio::piped())
             .stdout(Stdio::null())
@@ -14072,12 +21089,20 @@ mod tests {
             child_pid: 0,
             writer: Mutex::new(writer),
             pending_requests: Mutex::new(HashMap::new()),
+            pending_requests_by_client: Mutex::new(HashMap::new()),
             internal_waiters: Mutex::new(HashMap::new()),
             pending_approvals: Mutex::new(HashMap::new()),
             pending_user_inputs: Mutex::new(HashMap::new()),
             next_request_id: AtomicU64::new(1),
             approval_counter: AtomicU64::new(1),
             user_input_counter: AtomicU64::new(1),
+            server_info: Mutex::new(None),
+            ready: AtomicBool::new(false),
+            debug_trace: Some(Mutex::new(VecDeque::new())),
+            attachment_cleanup_root: None,
+            reject_legacy_approvals: false,
+            stdout_lag: AppServerStdoutLag::default(),
+            method_traffic: AppServerMethodTraffic::default(),
             hub,
         })
     }
@@ -14156,12 +21181,22 @@ mod tests {
             codex,
             opencode,
             cursor: Arc::new(StdRwLock::new(None)),
+            rollout_sync: Arc::new(Mutex::new(RolloutLiveSyncState::new(
+                DEFAULT_ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES,
+            ))),
+            rollout_sync_started: false,
         })
     }
 
     async fn build_test_state() -> Arc<AppState> {
+        build_test_state_with_config(|_| {}).await
+    }
+
+    async fn build_test_state_with_config(
+        configure: impl FnOnce(&mut BridgeConfig),
+    ) -> Arc<AppState> {
         let workdir = normalize_path(&env::temp_dir());
-        let config = Arc::new(BridgeConfig {
+        let mut config = BridgeConfig {
             host: "127.0.0.1".to_string(),
             port: 8787,
             preview_port: 8788,
@@ -14183,24 +21218,69 @@ mod tests {
             allow_query_token_auth: false,
             allow_outside_root_cwd: false,
             disable_terminal_exec: true,
+            terminal_readonly: false,
             terminal_allowed_commands: HashSet::new(),
             show_pairing_qr: false,
-        });
-
-        let hub = Arc::new(ClientHub::new());
+            ws_max_frame_bytes: DEFAULT_WS_MAX_FRAME_BYTES,
+            terminal_history_size: DEFAULT_TERMINAL_HISTORY_SIZE,
+            decline_on_no_clients_grace: None,
+            terminal_blocked_args: HashMap::new(),
+            enable_debug_trace: false,
+            app_server_args: Vec::new(),
+            app_server_env: Vec::new(),
+            rollout_max_tracked_files: DEFAULT_ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES,
+            git_watch: false,
+            client_rate_limit: None,
+            max_clients: None,
+            cleanup_attachments_on_archive: false,
+            ws_client_queue_capacity: DEFAULT_WS_CLIENT_QUEUE_CAPACITY,
+            attachments_dir: workdir.join(MOBILE_ATTACHMENTS_DIR),
+            rollout_include_events: HashSet::new(),
+            rollout_extra_record_types: HashSet::new(),
+            extra_forwarded_methods: HashSet::new(),
+            cors_origins: HashSet::new(),
+            disable_rollout_sync: false,
+            replay_compress: false,
+            command_signing_key: None,
+            app_server_max_line_bytes: DEFAULT_APP_SERVER_MAX_LINE_BYTES,
+            reject_legacy_approvals: false,
+            rollout_dedup_window: DEFAULT_ROLLOUT_DEDUP_WINDOW,
+            terminal_max_output_bytes: DEFAULT_TERMINAL_MAX_OUTPUT_BYTES,
+            strict_jsonrpc: false,
+            guard_concurrent_turns: false,
+        };
+        configure(&mut config);
+        let config = Arc::new(config);
+
+        let hub = Arc::new(
+            ClientHub::new()
+                .with_rate_limit(config.client_rate_limit)
+                .with_replay_compression(config.replay_compress)
+                .with_max_clients(config.max_clients)
+                .with_strict_jsonrpc(config.strict_jsonrpc)
+                .with_rollout_dedup_window(config.rollout_dedup_window)
+                .with_turn_start_guard(config.guard_concurrent_turns),
+        );
         let backend =
             build_test_runtime_backend(hub.clone(), BridgeRuntimeEngine::Codex, true).await;
         let terminal = Arc::new(TerminalService::new(
             config.workdir.clone(),
             config.terminal_allowed_commands.clone(),
+            config.terminal_blocked_args.clone(),
             config.disable_terminal_exec,
             config.allow_outside_root_cwd,
+            config.terminal_history_size,
         ));
         let git = Arc::new(GitService::new(
             terminal.clone(),
             config.workdir.clone(),
             config.allow_outside_root_cwd,
         ));
+        let git_status_subscriptions = Arc::new(GitStatusSubscriptions::new(
+            git.clone(),
+            hub.clone(),
+            Duration::from_millis(50),
+        ));
         let updater = Arc::new(UpdateService::discover());
         let preview = Arc::new(BrowserPreviewService::new(
             config.port,
@@ -14208,6 +21288,8 @@ mod tests {
             config.preview_connect_url.clone(),
         ));
         let queue = BridgeQueueService::new(backend.clone(), hub.clone());
+        let forwarded_methods = build_forwarded_methods(&config.extra_forwarded_methods);
+        let workdir_git_root = git.workdir_repo_root().await;
 
         Arc::new(AppState {
             config,
@@ -14216,8 +21298,13 @@ mod tests {
             backend,
             queue,
             thread_list_streams: Arc::new(Mutex::new(HashMap::new())),
+            attachment_uploads: Arc::new(Mutex::new(HashMap::new())),
             terminal,
             git,
+            git_status_subscriptions,
+            rejected_methods: Arc::new(RejectedMethodCounter::default()),
+            workdir_git_root,
+            forwarded_methods,
             updater,
             preview,
         })
@@ -15216,6 +22303,44 @@ mod tests {
         shutdown_test_backend(&state.backend).await;
     }
 
+    #[tokio::test]
+    async fn bridge_capability_descriptor_lists_methods_and_limits() {
+        let state = build_test_state().await;
+
+        let descriptor = serde_json::to_value(state.bridge_capability_descriptor())
+            .expect("serialize capability descriptor");
+        let commit = descriptor["methods"]
+            .as_array()
+            .expect("methods array")
+            .iter()
+            .find(|entry| entry["method"] == "bridge/git/commit")
+            .expect("bridge/git/commit schema");
+        assert_eq!(commit["params"][0]["name"], "message");
+        assert_eq!(commit["params"][0]["type"], "string");
+        assert!(descriptor["forwardedMethods"]
+            .as_array()
+            .expect("forwarded methods array")
+            .contains(&json!("thread/start")));
+        assert_eq!(
+            descriptor["limits"]["maxAttachmentBytes"],
+            MAX_ATTACHMENT_BYTES
+        );
+        assert_eq!(descriptor["features"]["terminalExec"], false);
+        assert_eq!(descriptor["features"]["queryTokenAuth"], false);
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[test]
+    fn bridge_method_schemas_are_unique_bridge_methods() {
+        let mut seen = HashSet::new();
+        for schema in BRIDGE_METHOD_SCHEMAS {
+            assert!(schema.method.starts_with("bridge/"), "{}", schema.method);
+            assert!(seen.insert(schema.method), "duplicate {}", schema.method);
+            assert!(!is_forwarded_method(schema.method));
+        }
+    }
+
     #[test]
     fn parse_enabled_bridge_engines_csv_preserves_order_and_removes_duplicates() {
         let parsed =
@@ -15253,6 +22378,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn codex_app_server_command_appends_extra_args_in_order() {
+        let args = parse_app_server_args("--config \"/etc/codex/config.toml\" -c model=o3")
+            .expect("app-server args");
+        let env = parse_app_server_env("CODEX_HOME=/srv/codex PROFILE='fast lane'")
+            .expect("app-server env");
+        let command = build_codex_app_server_command("codex", &args, &env);
+        let command = command.as_std();
+
+        assert_eq!(command.get_program(), "codex");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            vec![
+                "app-server",
+                "--listen",
+                "stdio://",
+                "--config",
+                "/etc/codex/config.toml",
+                "-c",
+                "model=o3",
+            ]
+        );
+        let envs = command
+            .get_envs()
+            .filter_map(|(key, value)| Some((key.to_str()?, value?.to_str()?)))
+            .collect::<HashMap<_, _>>();
+        assert_eq!(envs.get("CODEX_HOME"), Some(&"/srv/codex"));
+        assert_eq!(envs.get("PROFILE"), Some(&"fast lane"));
+    }
+
+    #[test]
+    fn app_server_args_keep_shell_metacharacters_as_argument_text() {
+        assert_eq!(
+            parse_app_server_args("-c 'notify=a;b|c&d' -c \"prompt=$HOME\"")
+                .expect("app-server args"),
+            vec!["-c", "notify=a;b|c&d", "-c", "prompt=$HOME"]
+        );
+    }
+
+    #[test]
+    fn app_server_spawn_overrides_reject_invalid_input() {
+        assert_eq!(
+            parse_app_server_args("").expect("empty args"),
+            Vec::<String>::new()
+        );
+        assert!(parse_app_server_args("--flag \"unterminated").is_err());
+        assert!(parse_app_server_args("--a\nb").is_err());
+        assert!(parse_app_server_args("--a\0b").is_err());
+        assert!(parse_app_server_env("NOVALUE").is_err());
+        assert!(parse_app_server_env("1BAD=x").is_err());
+    }
+
+    #[test]
+    fn parse_terminal_blocked_args_groups_entries_by_binary() {
+        let parsed = parse_terminal_blocked_args([
+            (
+                "BRIDGE_TERMINAL_GIT_BLOCKED_ARGS".to_string(),
+                "push, reset,,".to_string(),
+            ),
+            (
+                "BRIDGE_TERMINAL_LS_BLOCKED_ARGS".to_string(),
+                " ".to_string(),
+            ),
+            (
+                "BRIDGE_TERMINAL_ALLOWED_COMMANDS".to_string(),
+                "git".to_string(),
+            ),
+        ]);
+
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(
+            parsed.get("git"),
+            Some(&HashSet::from(["push".to_string(), "reset".to_string()]))
+        );
+    }
+
     #[tokio::test]
     async fn bridge_capabilities_reflect_single_engine_state() {
         let hub = Arc::new(ClientHub::new());
@@ -15321,43 +22522,493 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn replay_since_returns_notifications_after_cursor() {
-        let hub = ClientHub::with_replay_capacity(16);
-        hub.broadcast_notification("turn/started", json!({ "threadId": "thr_1" }))
-            .await;
-        hub.broadcast_notification("turn/completed", json!({ "threadId": "thr_1" }))
+    async fn pending_approvals_are_declined_after_no_client_grace() {
+        let hub = Arc::new(ClientHub::new());
+        let backend =
+            build_test_runtime_backend(hub.clone(), BridgeRuntimeEngine::Codex, false).await;
+        let codex = test_codex_backend(&backend);
+        let (client_id, _rx) = add_test_client(&hub).await;
+
+        spawn_no_client_pending_decline(backend.clone(), hub.clone(), Duration::from_millis(200));
+        codex
+            .handle_server_request(
+                APPROVAL_COMMAND_METHOD,
+                json!("approval-1"),
+                Some(json!({
+                    "threadId": "thr_1",
+                    "turnId": "turn_1",
+                    "itemId": "item_1",
+                    "command": "rm -rf build"
+                })),
+            )
             .await;
+        sleep(Duration::from_millis(120)).await;
+        assert_eq!(backend.list_pending_approvals().await.len(), 1);
 
-        let (events, has_more) = hub.replay_since(Some(1), 10).await;
-        assert_eq!(events.len(), 1);
-        assert!(!has_more);
-        assert_eq!(events[0]["method"], "turn/completed");
-        assert_eq!(events[0]["eventId"], 2);
-        assert_eq!(hub.latest_event_id(), 2);
-    }
+        hub.remove_client(client_id).await;
+        sleep(Duration::from_millis(300)).await;
 
-    #[tokio::test]
-    async fn replay_since_respects_limit() {
-        let hub = ClientHub::with_replay_capacity(16);
-        hub.broadcast_notification("event/1", json!({})).await;
-        hub.broadcast_notification("event/2", json!({})).await;
-        hub.broadcast_notification("event/3", json!({})).await;
+        assert!(backend.list_pending_approvals().await.is_empty());
+        let (events, _, _) = hub.replay_since(None, 100).await;
+        let declined = events
+            .iter()
+            .find(|event| event["method"] == "bridge/pending.autoDeclined")
+            .expect("auto-decline notification");
+        assert_eq!(declined["params"]["reason"], "no_clients");
+        assert_eq!(
+            declined["params"]["approvalIds"].as_array().map(Vec::len),
+            Some(1)
+        );
+        assert!(events
+            .iter()
+            .any(|event| event["method"] == "bridge/approval.resolved"
+                && event["params"]["decision"] == "decline"));
 
-        let (events, has_more) = hub.replay_since(Some(0), 2).await;
-        assert_eq!(events.len(), 2);
-        assert!(has_more);
-        assert_eq!(events[0]["eventId"], 1);
-        assert_eq!(events[1]["eventId"], 2);
+        // The grace period already ran out for this window, so a new request doesn't
+        // get a fresh one.
+        codex
+            .handle_server_request(
+                APPROVAL_COMMAND_METHOD,
+                json!("approval-2"),
+                Some(json!({
+                    "threadId": "thr_1",
+                    "turnId": "turn_2",
+                    "itemId": "item_2",
+                    "command": "rm -rf dist"
+                })),
+            )
+            .await;
+        sleep(Duration::from_millis(50)).await;
+        assert!(backend.list_pending_approvals().await.is_empty());
+
+        shutdown_test_backend(&backend).await;
     }
 
     #[tokio::test]
-    async fn replay_buffer_evicts_oldest_entries() {
-        let hub = ClientHub::with_replay_capacity(2);
-        hub.broadcast_notification("event/1", json!({})).await;
-        hub.broadcast_notification("event/2", json!({})).await;
-        hub.broadcast_notification("event/3", json!({})).await;
+    async fn legacy_approvals_are_rejected_only_when_configured() {
+        let legacy_params = json!({
+            "conversationId": "thr_1",
+            "callId": "call_1",
+            "command": ["git", "push"]
+        });
 
-        let (events, has_more) = hub.replay_since(Some(0), 10).await;
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub.clone()).await;
+        bridge
+            .handle_server_request(
+                LEGACY_APPROVAL_COMMAND_METHOD,
+                json!(11),
+                Some(legacy_params.clone()),
+            )
+            .await;
+        assert_eq!(bridge.pending_approvals.lock().await.len(), 1);
+        shutdown_test_bridge(&bridge).await;
+
+        let hub = Arc::new(ClientHub::new());
+        let mut rejecting = Arc::into_inner(build_test_bridge(hub.clone()).await)
+            .expect("test bridge has a single owner");
+        rejecting.reject_legacy_approvals = true;
+        let rejecting = Arc::new(rejecting);
+        for method in [LEGACY_APPROVAL_COMMAND_METHOD, LEGACY_APPROVAL_PATCH_METHOD] {
+            rejecting
+                .handle_server_request(method, json!(12), Some(legacy_params.clone()))
+                .await;
+        }
+        assert!(rejecting.pending_approvals.lock().await.is_empty());
+        let (events, _, _) = hub.replay_since(None, 100).await;
+        assert!(events
+            .iter()
+            .all(|event| event["method"] != "bridge/approval.requested"));
+
+        rejecting
+            .handle_server_request(
+                APPROVAL_COMMAND_METHOD,
+                json!(13),
+                Some(json!({
+                    "threadId": "thr_1",
+                    "turnId": "turn_1",
+                    "itemId": "item_1",
+                    "command": "git push"
+                })),
+            )
+            .await;
+        assert_eq!(rejecting.pending_approvals.lock().await.len(), 1);
+
+        let rejection = legacy_approval_rejection(json!(12), LEGACY_APPROVAL_PATCH_METHOD);
+        assert_eq!(rejection["id"], 12);
+        assert_eq!(rejection["error"]["code"], -32601);
+        assert!(rejection["error"]["message"]
+            .as_str()
+            .is_some_and(|message| message.contains("applyPatchApproval")));
+
+        shutdown_test_bridge(&rejecting).await;
+    }
+
+    #[tokio::test]
+    async fn file_change_approval_carries_bounded_diff_preview() {
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub.clone()).await;
+        let long_patch = format!(
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1 @@\n{}",
+            "+let value = \"é\";\n".repeat(APPROVAL_DIFF_PREVIEW_MAX_BYTES / 8)
+        );
+        bridge
+            .handle_server_request(
+                APPROVAL_FILE_METHOD,
+                json!(21),
+                Some(json!({
+                    "threadId": "thr_1",
+                    "turnId": "turn_1",
+                    "itemId": "item_1",
+                    "reason": "edit lib",
+                    "patch": long_patch,
+                })),
+            )
+            .await;
+        bridge
+            .handle_server_request(
+                LEGACY_APPROVAL_PATCH_METHOD,
+                json!(22),
+                Some(json!({
+                    "conversationId": "thr_1",
+                    "callId": "call_1",
+                    "fileChanges": {
+                        "notes.md": { "type": "add", "content": "hello\nworld\n" },
+                    },
+                })),
+            )
+            .await;
+        bridge
+            .handle_server_request(
+                APPROVAL_COMMAND_METHOD,
+                json!(23),
+                Some(json!({ "threadId": "thr_1", "command": "ls", "patch": "ignored" })),
+            )
+            .await;
+
+        let approvals = bridge.pending_approvals.lock().await;
+        let preview_for = |id: i64| {
+            approvals
+                .values()
+                .find(|entry| entry.app_server_request_id == json!(id))
+                .expect("pending approval")
+                .approval
+                .diff_preview
+                .clone()
+        };
+        let preview = preview_for(21).expect("file change preview");
+        assert!(preview.starts_with("--- a/src/lib.rs"));
+        assert!(preview.ends_with("\n[diff truncated]"));
+        assert!(preview.len() <= APPROVAL_DIFF_PREVIEW_MAX_BYTES + "\n[diff truncated]".len());
+        assert_eq!(
+            preview_for(22).as_deref(),
+            Some("--- /dev/null\n+++ notes.md\n+hello\n+world")
+        );
+        assert!(preview_for(23).is_none());
+        drop(approvals);
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn approvals_resolve_all_answers_every_matching_approval() {
+        let state = build_test_state().await;
+        let codex = test_codex_backend(&state.backend);
+        for index in 1..=3 {
+            codex
+                .handle_server_request(
+                    APPROVAL_COMMAND_METHOD,
+                    json!(format!("approval-{index}")),
+                    Some(json!({
+                        "threadId": "thr_1",
+                        "turnId": "turn_1",
+                        "itemId": format!("item_{index}"),
+                        "command": "cargo test"
+                    })),
+                )
+                .await;
+        }
+        assert_eq!(state.backend.list_pending_approvals().await.len(), 3);
+
+        let result = handle_bridge_method(
+            "bridge/approvals/resolveAll",
+            Some(json!({
+                "decision": "accept",
+                "kind": "commandExecution",
+                "threadId": "codex:thr_1",
+            })),
+            &state,
+            1,
+        )
+        .await
+        .expect("resolveAll should succeed");
+
+        assert_eq!(result["ok"], true);
+        assert_eq!(result["resolvedIds"].as_array().map(Vec::len), Some(3));
+        assert_eq!(result["failures"], json!([]));
+        assert!(state.backend.list_pending_approvals().await.is_empty());
+
+        let (events, _, _) = state.hub.replay_since(None, 100).await;
+        let resolved = events
+            .iter()
+            .filter(|event| {
+                event["method"] == "bridge/approval.resolved"
+                    && event["params"]["decision"] == "accept"
+            })
+            .count();
+        assert_eq!(resolved, 3);
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn bounded_line_reader_skips_oversized_lines() {
+        let oversized = format!("{{\"method\":\"{}\"}}", "x".repeat(64));
+        let input = format!("{{\"id\":1}}\n{oversized}\n{{\"id\":2}}");
+        // A tiny buffer makes the oversized line span many `fill_buf` chunks.
+        let mut reader = BufReader::with_capacity(4, input.as_bytes());
+
+        assert_eq!(
+            read_bounded_line(&mut reader, 32).await.expect("read"),
+            Some(BoundedLine::Line("{\"id\":1}".to_string()))
+        );
+        let skipped = read_bounded_line(&mut reader, 32).await.expect("read");
+        assert_eq!(skipped, Some(BoundedLine::TooLong(oversized.len())));
+        assert_eq!(
+            read_bounded_line(&mut reader, 32).await.expect("read"),
+            Some(BoundedLine::Line("{\"id\":2}".to_string()))
+        );
+        assert_eq!(
+            read_bounded_line(&mut reader, 32).await.expect("read"),
+            None
+        );
+
+        let warning =
+            oversized_app_server_line_warning(BridgeRuntimeEngine::Codex, oversized.len(), 32);
+        assert!(warning.contains(&format!("codex sent {} bytes", oversized.len())));
+        assert!(warning.contains("BRIDGE_APP_SERVER_MAX_LINE_BYTES=32"));
+    }
+
+    #[tokio::test]
+    async fn stdout_lag_counts_slow_handlers() {
+        let lag = AppServerStdoutLag::default();
+        let threshold = Duration::from_millis(40);
+
+        assert_eq!(lag.measure(threshold, async {}).await, None);
+        let slow = lag
+            .measure(threshold, sleep(Duration::from_millis(60)))
+            .await
+            .expect("slow handler is reported");
+        assert!(slow >= threshold);
+
+        let snapshot = lag.snapshot(BridgeRuntimeEngine::Codex);
+        assert_eq!(snapshot.handled_messages, 2);
+        assert_eq!(snapshot.slow_messages, 1);
+        assert!(snapshot.max_handle_ms >= 60);
+
+        let metrics = render_bridge_metrics(3, &[snapshot], &[], &[]);
+        assert!(metrics.contains("clawdex_bridge_connected_clients 3\n"));
+        assert!(
+            metrics.contains("clawdex_bridge_app_server_slow_messages_total{engine=\"codex\"} 1\n")
+        );
+        assert!(metrics.contains("clawdex_bridge_app_server_messages_total{engine=\"codex\"} 2\n"));
+    }
+
+    #[tokio::test]
+    async fn presence_changes_are_debounced_and_report_client_count() {
+        let hub = Arc::new(ClientHub::with_replay_capacity(16));
+        spawn_presence_broadcast(hub.clone(), Duration::from_millis(50));
+        let presence_counts = |events: Vec<Value>| {
+            events
+                .iter()
+                .filter(|event| event["method"] == "bridge/presence/changed")
+                .map(|event| event["params"]["connectedClients"].clone())
+                .collect::<Vec<_>>()
+        };
+
+        let (first_id, mut first_rx) = add_test_client(&hub).await;
+        let presence = recv_client_json(&mut first_rx).await;
+        assert_eq!(presence["method"], "bridge/presence/changed");
+        assert_eq!(presence["params"]["connectedClients"], 1);
+        assert!(presence["params"].get("clientIds").is_none());
+
+        // A flaky reconnect inside the debounce window nets out to no change.
+        let (flaky_id, _flaky_rx) = add_test_client(&hub).await;
+        hub.remove_client(flaky_id).await;
+        sleep(Duration::from_millis(120)).await;
+        assert_eq!(
+            presence_counts(hub.replay_since(None, 100).await.0),
+            vec![json!(1)]
+        );
+
+        let (second_id, _second_rx) = add_test_client(&hub).await;
+        sleep(Duration::from_millis(120)).await;
+        hub.remove_client(second_id).await;
+        sleep(Duration::from_millis(120)).await;
+        hub.remove_client(first_id).await;
+        sleep(Duration::from_millis(120)).await;
+
+        assert_eq!(
+            presence_counts(hub.replay_since(None, 100).await.0),
+            vec![json!(1), json!(2), json!(1), json!(0)]
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_since_returns_notifications_after_cursor() {
+        let hub = ClientHub::with_replay_capacity(16);
+        hub.broadcast_notification("turn/started", json!({ "threadId": "thr_1" }))
+            .await;
+        hub.broadcast_notification("turn/completed", json!({ "threadId": "thr_1" }))
+            .await;
+
+        let (events, has_more, _) = hub.replay_since(Some(1), 10).await;
+        assert_eq!(events.len(), 1);
+        assert!(!has_more);
+        assert_eq!(events[0]["method"], "turn/completed");
+        assert_eq!(events[0]["eventId"], 2);
+        assert_eq!(hub.latest_event_id(), 2);
+    }
+
+    #[tokio::test]
+    async fn replay_since_respects_limit() {
+        let hub = ClientHub::with_replay_capacity(16);
+        hub.broadcast_notification("event/1", json!({})).await;
+        hub.broadcast_notification("event/2", json!({})).await;
+        hub.broadcast_notification("event/3", json!({})).await;
+
+        let (events, has_more, _) = hub.replay_since(Some(0), 2).await;
+        assert_eq!(events.len(), 2);
+        assert!(has_more);
+        assert_eq!(events[0]["eventId"], 1);
+        assert_eq!(events[1]["eventId"], 2);
+    }
+
+    #[tokio::test]
+    async fn replay_method_filter_matches_exact_names_and_prefixes() {
+        let hub = ClientHub::with_replay_capacity(16);
+        for method in [
+            "turn/started",
+            "codex/event/exec_command_begin",
+            "codex/event/agent_message_delta",
+            "turn/completed",
+            "codex/event/exec_command_begin",
+        ] {
+            hub.broadcast_notification(method, json!({})).await;
+        }
+        let ids = |events: &[Value]| {
+            events
+                .iter()
+                .map(|event| event["eventId"].as_u64().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+
+        let exact = vec!["codex/event/exec_command_begin".to_string()];
+        let (events, has_more, _) = hub.replay_matching_since(None, 10, &exact).await;
+        assert_eq!(ids(&events), vec![2, 5]);
+        assert!(!has_more);
+
+        let prefix = vec!["codex/event/*".to_string(), "turn/completed".to_string()];
+        let (events, has_more, _) = hub.replay_matching_since(None, 10, &prefix).await;
+        assert_eq!(ids(&events), vec![2, 3, 4, 5]);
+        assert!(!has_more);
+
+        assert!(normalize_replay_method_filters(vec!["codex/*/begin".to_string()]).is_err());
+        assert!(normalize_replay_method_filters(vec![" ".to_string()]).is_err());
+    }
+
+    #[tokio::test]
+    async fn filtered_replay_pages_from_the_last_returned_event() {
+        let hub = ClientHub::with_replay_capacity(16);
+        for method in ["a/1", "b/1", "a/2", "b/2", "b/3", "a/3"] {
+            hub.broadcast_notification(method, json!({})).await;
+        }
+        let filter = vec!["a/*".to_string()];
+
+        let (first, has_more, _) = hub.replay_matching_since(Some(0), 2, &filter).await;
+        assert_eq!(first[0]["method"], "a/1");
+        assert_eq!(first[1]["method"], "a/2");
+        assert!(has_more, "a/3 is still pending after the page");
+
+        let cursor = first[1]["eventId"].as_u64();
+        assert_eq!(cursor, Some(3));
+        let (second, has_more, _) = hub.replay_matching_since(cursor, 2, &filter).await;
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0]["method"], "a/3");
+        assert_eq!(second[0]["eventId"], 6);
+        assert!(!has_more);
+
+        let (none_left, has_more, _) = hub.replay_matching_since(Some(6), 2, &filter).await;
+        assert!(none_left.is_empty() && !has_more);
+    }
+
+    #[tokio::test]
+    async fn compressed_replay_returns_identical_payloads() {
+        let hub = ClientHub::with_replay_capacity(16).with_replay_compression(true);
+        let diff = (0..2_000)
+            .map(|line| format!("+line {line} with unicode é and \"quotes\"\n"))
+            .collect::<String>();
+        let params = json!({
+            "threadId": "thr_1",
+            "diff": diff,
+            "stats": { "added": 2_000, "ratio": 0.125, "negative": -3, "flags": [true, null] },
+        });
+        hub.broadcast_notification("turn/diff/updated", params.clone())
+            .await;
+        hub.broadcast_notification("turn/completed", json!({ "threadId": "thr_1" }))
+            .await;
+
+        {
+            let replay = hub.notification_replay.read().await;
+            let ReplayPayload::Gzip(bytes) = &replay[0].payload else {
+                panic!("payload stored compressed");
+            };
+            assert!(bytes.len() < diff.len() / 4);
+            assert_eq!(replay[0].method, "turn/diff/updated");
+        }
+
+        let (events, has_more, _) = hub.replay_since(None, 10).await;
+        assert!(!has_more);
+        assert_eq!(
+            events[0],
+            json!({ "method": "turn/diff/updated", "eventId": 1, "params": params })
+        );
+        assert_eq!(events[1]["method"], "turn/completed");
+
+        let filter = vec!["turn/completed".to_string()];
+        let (filtered, _, _) = hub.replay_matching_since(None, 10, &filter).await;
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0]["eventId"], 2);
+    }
+
+    #[tokio::test]
+    async fn compressed_replay_keeps_small_payloads_and_reports_undecodable_ones_as_gaps() {
+        let hub = ClientHub::with_replay_capacity(16).with_replay_compression(true);
+        hub.broadcast_notification("turn/started", json!({ "threadId": "thr_1" }))
+            .await;
+        hub.broadcast_notification("turn/completed", json!({ "threadId": "thr_1" }))
+            .await;
+        {
+            let mut replay = hub.notification_replay.write().await;
+            assert!(matches!(replay[0].payload, ReplayPayload::Json(_)));
+            replay[1].payload = ReplayPayload::Gzip(b"not gzip".to_vec());
+        }
+
+        let (events, has_more, gap_detected) = hub.replay_since(Some(0), 10).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["eventId"], 1);
+        assert!(!has_more);
+        assert!(gap_detected);
+    }
+
+    #[tokio::test]
+    async fn replay_buffer_evicts_oldest_entries() {
+        let hub = ClientHub::with_replay_capacity(2);
+        hub.broadcast_notification("event/1", json!({})).await;
+        hub.broadcast_notification("event/2", json!({})).await;
+        hub.broadcast_notification("event/3", json!({})).await;
+
+        let (events, has_more, _) = hub.replay_since(Some(0), 10).await;
         assert_eq!(events.len(), 2);
         assert!(!has_more);
         assert_eq!(hub.earliest_event_id().await, Some(2));
@@ -15365,6 +23016,104 @@ mod tests {
         assert_eq!(events[1]["eventId"], 3);
     }
 
+    #[tokio::test]
+    async fn events_get_returns_one_retained_notification_by_id() {
+        let state = build_test_state().await;
+        for index in 1..=5 {
+            state
+                .hub
+                .broadcast_notification(&format!("event/{index}"), json!({ "index": index }))
+                .await;
+        }
+        let (events, _, _) = state.hub.replay_since(None, 100).await;
+        let target = events
+            .iter()
+            .find(|event| event["method"] == "event/3")
+            .expect("event/3 retained")
+            .clone();
+
+        let fetched = handle_bridge_method(
+            "bridge/events/get",
+            Some(json!({ "eventId": target["eventId"] })),
+            &state,
+            0,
+        )
+        .await
+        .expect("get event");
+        assert_eq!(fetched, target);
+        assert_eq!(fetched["params"]["index"], 3);
+
+        let missing = handle_bridge_method(
+            "bridge/events/get",
+            Some(json!({ "eventId": state.hub.latest_event_id() + 1 })),
+            &state,
+            0,
+        )
+        .await
+        .expect("get missing event");
+        assert!(missing.is_null());
+
+        let hub = ClientHub::with_replay_capacity(2);
+        for index in 1..=3 {
+            hub.broadcast_notification(&format!("event/{index}"), json!({}))
+                .await;
+        }
+        assert!(hub.replay_event(1).await.is_none());
+        assert_eq!(
+            hub.replay_event(3).await.expect("retained")["method"],
+            "event/3"
+        );
+
+        let hub = ClientHub::new();
+        for event_id in [2, 1, 3] {
+            hub.push_replay(
+                event_id,
+                "event/raced",
+                json!({ "method": "event/raced", "eventId": event_id }),
+            )
+            .await;
+        }
+        for event_id in 1..=3 {
+            assert_eq!(
+                hub.replay_event(event_id).await.expect("retained")["eventId"],
+                event_id
+            );
+        }
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn replay_flags_gap_when_cursor_predates_retained_events() {
+        let hub = ClientHub::with_replay_capacity(2);
+        for index in 1..=4 {
+            hub.broadcast_notification(&format!("event/{index}"), json!({}))
+                .await;
+        }
+
+        let (events, _, gap_detected) = hub.replay_since(Some(1), 10).await;
+        assert!(gap_detected);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["eventId"], 3);
+
+        let (_, _, gap_detected) = hub.replay_since(Some(2), 10).await;
+        assert!(!gap_detected);
+        let (_, _, gap_detected) = hub.replay_since(None, 10).await;
+        assert!(!gap_detected);
+    }
+
+    #[tokio::test]
+    async fn replay_gap_uses_the_oldest_retained_id_when_pushed_out_of_order() {
+        let hub = ClientHub::with_replay_capacity(4);
+        hub.push_replay(2, "event/2", json!({ "eventId": 2 })).await;
+        hub.push_replay(1, "event/1", json!({ "eventId": 1 })).await;
+
+        let (events, _, gap_detected) = hub.replay_since(Some(0), 10).await;
+        assert!(!gap_detected);
+        assert_eq!(events.len(), 2);
+        assert_eq!(hub.earliest_event_id().await, Some(1));
+    }
+
     #[tokio::test]
     async fn send_json_evicts_closed_clients() {
         let hub = ClientHub::with_replay_capacity(4);
@@ -15389,7 +23138,8 @@ mod tests {
                     client_name: "Mohit's iPhone".to_string(),
                 },
             )
-            .await;
+            .await
+            .expect("client slot available");
 
         let clients = hub.client_connections().await;
         assert_eq!(clients.len(), 1);
@@ -15412,8 +23162,8 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn broadcast_json_keeps_clients_when_queue_is_temporarily_full() {
-        let hub = ClientHub::with_replay_capacity(4);
+    async fn send_json_retains_clients_whose_queue_drains_within_backoff() {
+        let hub = Arc::new(ClientHub::with_replay_capacity(4));
         let (tx, mut rx) = mpsc::channel(1);
         let tx_clone = tx.clone();
         let client_id = hub.add_client(tx).await;
@@ -15422,18 +23172,197 @@ mod tests {
             .try_send(Message::Text("queued".to_string().into()))
             .expect("seed full queue");
 
-        hub.broadcast_json(json!({ "method": "event/x" })).await;
+        let drain_after = |rx: mpsc::Receiver<Message>, delay_ms: u64| {
+            tokio::spawn(async move {
+                let mut rx = rx;
+                sleep(Duration::from_millis(delay_ms)).await;
+                let message = rx.recv().await;
+                (rx, message)
+            })
+        };
 
+        let drain = drain_after(rx, 100);
+        hub.send_json(client_id, json!({ "seq": 1 })).await;
+        let (next_rx, drained) = drain.await.expect("drain task");
+        rx = next_rx;
+        assert!(drained.is_some());
         assert!(hub.clients.read().await.contains_key(&client_id));
-        let message = rx.recv().await.expect("first queued message");
-        let Message::Text(text) = message else {
-            panic!("expected text frame");
-        };
-        assert_eq!(text, "queued");
-    }
 
-    #[test]
-    fn forwarded_method_allowlist_matches_expected() {
+        // The second consecutive full queue waits longer than the 250ms base timeout.
+        let drain = drain_after(rx, 350);
+        hub.send_json(client_id, json!({ "seq": 2 })).await;
+        let (next_rx, drained) = drain.await.expect("drain task");
+        rx = next_rx;
+        assert!(drained.is_some());
+        assert!(hub.clients.read().await.contains_key(&client_id));
+
+        assert_eq!(recv_client_json(&mut rx).await["seq"], 2);
+        hub.send_json(client_id, json!({ "seq": 3 })).await;
+        assert!(!hub
+            .client_full_queue_streaks
+            .lock()
+            .await
+            .contains_key(&client_id));
+        assert_eq!(recv_client_json(&mut rx).await["seq"], 3);
+    }
+
+    #[tokio::test]
+    async fn client_queue_capacity_sets_backpressure_depth() {
+        assert_eq!(resolve_client_queue_capacity(256, None), 256);
+        assert_eq!(resolve_client_queue_capacity(256, Some(32)), 32);
+        assert_eq!(
+            resolve_client_queue_capacity(256, Some(1)),
+            MIN_WS_CLIENT_QUEUE_CAPACITY
+        );
+        assert_eq!(resolve_client_queue_capacity(256, Some(1_000_000)), 256);
+        assert_eq!(
+            resolve_client_queue_capacity(1_000_000, Some(1_000_000)),
+            MAX_WS_CLIENT_QUEUE_CAPACITY
+        );
+
+        let hub = ClientHub::with_replay_capacity(4);
+        let capacity = resolve_client_queue_capacity(256, Some(32));
+        let (tx, mut rx) = mpsc::channel(capacity);
+        let client_id = hub.add_client(tx).await;
+
+        for seq in 0..capacity {
+            hub.send_json(client_id, json!({ "seq": seq })).await;
+        }
+        assert!(hub.clients.read().await.contains_key(&client_id));
+        assert!(!hub
+            .client_full_queue_streaks
+            .lock()
+            .await
+            .contains_key(&client_id));
+
+        hub.send_json(client_id, json!({ "seq": capacity })).await;
+        assert!(!hub.clients.read().await.contains_key(&client_id));
+        assert_eq!(recv_client_json(&mut rx).await["seq"], 0);
+    }
+
+    #[tokio::test]
+    async fn broadcast_skips_client_with_pending_direct_send() {
+        let hub = Arc::new(ClientHub::with_replay_capacity(4));
+        let (tx, mut rx) = mpsc::channel(2);
+        let tx_clone = tx.clone();
+        let client_id = hub.add_client(tx).await;
+
+        for seq in [0, 1] {
+            tx_clone
+                .try_send(Message::Text(json!({ "seq": seq }).to_string().into()))
+                .expect("seed full queue");
+        }
+
+        let direct = tokio::spawn({
+            let hub = hub.clone();
+            async move { hub.send_json(client_id, json!({ "seq": 2 })).await }
+        });
+        // Let the direct send start waiting on the full queue, then broadcast behind it.
+        sleep(Duration::from_millis(20)).await;
+        // The broadcast returns at once instead of waiting out the direct send's backoff.
+        let missed_event_id = hub.latest_event_id() + 1;
+        timeout(
+            Duration::from_millis(100),
+            hub.broadcast_notification("codex/event/seq", json!({ "seq": 3 })),
+        )
+        .await
+        .expect("broadcast does not wait on a busy client");
+        assert_eq!(
+            hub.client_missed_from.lock().await.get(&client_id),
+            Some(&missed_event_id)
+        );
+
+        // Drain the seeds without yielding so the queued direct send finds room.
+        let seeds = [rx.try_recv(), rx.try_recv()];
+        assert!(seeds.iter().all(Result::is_ok));
+        direct.await.expect("direct send task");
+
+        // The skipped notification was not delivered out of order behind the direct send.
+        assert_eq!(recv_client_json(&mut rx).await["seq"], 2);
+        assert!(rx.try_recv().is_err());
+        hub.clear_client_missed_from(client_id, missed_event_id - 1)
+            .await;
+
+        hub.broadcast_json(json!({ "seq": 4 })).await;
+        assert_eq!(recv_client_json(&mut rx).await["seq"], 4);
+        hub.send_json(client_id, json!({ "seq": 5 })).await;
+        assert_eq!(recv_client_json(&mut rx).await["seq"], 5);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn slow_client_send_timeout_grows_until_cap() {
+        assert_eq!(slow_client_send_timeout(1), Duration::from_millis(250));
+        assert_eq!(slow_client_send_timeout(2), Duration::from_millis(500));
+        assert_eq!(slow_client_send_timeout(4), Duration::from_millis(2_000));
+        assert_eq!(slow_client_send_timeout(40), Duration::from_millis(4_000));
+    }
+
+    #[tokio::test]
+    async fn broadcast_json_keeps_clients_when_queue_is_temporarily_full() {
+        let hub = ClientHub::with_replay_capacity(4);
+        let (tx, mut rx) = mpsc::channel(1);
+        let tx_clone = tx.clone();
+        let client_id = hub.add_client(tx).await;
+
+        tx_clone
+            .try_send(Message::Text("queued".to_string().into()))
+            .expect("seed full queue");
+
+        hub.broadcast_json(json!({ "method": "event/x" })).await;
+
+        assert!(hub.clients.read().await.contains_key(&client_id));
+        let message = rx.recv().await.expect("first queued message");
+        let Message::Text(text) = message else {
+            panic!("expected text frame");
+        };
+        assert_eq!(text, "queued");
+    }
+
+    #[tokio::test]
+    async fn dropped_broadcasts_track_per_client_gap() {
+        let hub = ClientHub::with_replay_capacity(16);
+        let (tx, mut rx) = mpsc::channel(1);
+        let client_id = hub.add_client(tx).await;
+        hub.claim_client_key(client_id, "phone-1".to_string()).await;
+
+        hub.broadcast_notification("turn/started", json!({})).await;
+        hub.broadcast_notification("turn/completed", json!({}))
+            .await;
+        hub.broadcast_notification("turn/completed", json!({}))
+            .await;
+        assert_eq!(recv_client_json(&mut rx).await["eventId"], 1);
+        assert_eq!(
+            hub.client_missed_from.lock().await.get(&client_id),
+            Some(&2)
+        );
+
+        hub.remove_client(client_id).await;
+        let (tx, _rx) = mpsc::channel(8);
+        let resumed_id = hub.add_client(tx).await;
+        let claim = hub
+            .claim_client_key(resumed_id, "phone-1".to_string())
+            .await;
+        assert!(claim.resumed);
+        assert_eq!(claim.missed_from, Some(2));
+
+        hub.client_missed_from.lock().await.insert(resumed_id, 2);
+        hub.clear_client_missed_from(resumed_id, 2).await;
+        assert!(hub
+            .client_missed_from
+            .lock()
+            .await
+            .contains_key(&resumed_id));
+        hub.clear_client_missed_from(resumed_id, 1).await;
+        assert!(!hub
+            .client_missed_from
+            .lock()
+            .await
+            .contains_key(&resumed_id));
+    }
+
+    #[test]
+    fn forwarded_method_allowlist_matches_expected() {
         assert!(is_forwarded_method("thread/start"));
         assert!(is_forwarded_method("turn/start"));
         assert!(is_forwarded_method("account/read"));
@@ -15444,6 +23373,21 @@ mod tests {
         assert!(!is_forwarded_method("thread/delete"));
     }
 
+    #[test]
+    fn built_forwarded_methods_add_extras_but_never_bridge_methods() {
+        let built_in = build_forwarded_methods(&HashSet::new());
+        assert_eq!(built_in.len(), FORWARDED_METHODS.len());
+        assert!(FORWARDED_METHODS
+            .iter()
+            .all(|method| built_in.contains(*method)));
+
+        let extra = HashSet::from(["thread/newer".to_string(), "bridge/health/read".to_string()]);
+        let forwarded = build_forwarded_methods(&extra);
+        assert!(forwarded.contains("thread/newer"));
+        assert!(!forwarded.contains("bridge/health/read"));
+        assert_eq!(forwarded.len(), FORWARDED_METHODS.len() + 1);
+    }
+
     #[test]
     fn approval_decision_validation_accepts_expected_forms() {
         assert!(is_valid_approval_decision(&json!("accept")));
@@ -15535,6 +23479,7 @@ mod tests {
     fn parse_internal_id_supports_numeric_and_string_ids() {
         assert_eq!(parse_internal_id(Some(&json!(42))), Some(42));
         assert_eq!(parse_internal_id(Some(&json!("17"))), Some(17));
+        assert_eq!(parse_internal_id(Some(&json!(" 17 "))), Some(17));
         assert_eq!(parse_internal_id(Some(&json!(-1))), None);
         assert_eq!(parse_internal_id(Some(&json!("invalid"))), None);
         assert_eq!(parse_internal_id(None), None);
@@ -15592,6 +23537,7 @@ mod tests {
             .expect("event payload object"),
             "thread-1",
             Some("2026-02-25T00:00:00Z"),
+            &HashSet::new(),
         )
         .expect("reasoning notification");
 
@@ -15609,6 +23555,7 @@ mod tests {
             .expect("event payload object"),
             "thread-1",
             Some("2026-02-25T00:00:01Z"),
+            &HashSet::new(),
         )
         .expect("agent message notification");
 
@@ -15630,6 +23577,7 @@ mod tests {
             .expect("event payload object"),
             "thread-1",
             None,
+            &HashSet::new(),
         )
         .expect("token count notification");
 
@@ -15650,10 +23598,32 @@ mod tests {
             .expect("event payload object"),
             "thread-1",
             None,
+            &HashSet::new(),
         )
         .is_none());
     }
 
+    #[test]
+    fn rollout_event_msg_mapping_forwards_dropped_events_when_included() {
+        let payload = json!({
+            "type": "user_message",
+            "message": "hello"
+        });
+        let payload = payload.as_object().expect("event payload object");
+        assert!(
+            build_rollout_event_msg_notification(payload, "thread-1", None, &HashSet::new())
+                .is_none()
+        );
+
+        let included = HashSet::from(["user_message".to_string()]);
+        let user_message =
+            build_rollout_event_msg_notification(payload, "thread-1", None, &included)
+                .expect("included user message notification");
+        assert_eq!(user_message.0, "codex/event/user_message");
+        assert_eq!(user_message.1["msg"]["message"], "hello");
+        assert_eq!(user_message.1["msg"]["threadId"], "codex:thread-1");
+    }
+
     #[test]
     fn extract_rollout_thread_id_prefers_parent_thread_id_from_source() {
         let payload = json!({
@@ -15912,759 +23882,4746 @@ mod tests {
         assert_eq!(extract_rollout_search_query(&json!({})), None);
     }
 
-    #[test]
-    fn rollout_discovery_tick_scheduler_handles_one_tick_interval() {
-        assert!(should_run_rollout_discovery_tick(1, 1));
-        assert!(should_run_rollout_discovery_tick(10, 1));
-        assert!(should_run_rollout_discovery_tick(5, 0));
-    }
+    #[tokio::test]
+    async fn rollout_status_reports_tracked_files_and_originator_filtering() {
+        let state = build_test_state().await;
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("clawdex-rollout-status-{nonce}"));
+        std::fs::create_dir_all(&dir).expect("create rollout dir");
+
+        let mut tracked = Vec::new();
+        for (name, originator) in [("a", "codex_cli_rs"), ("b", "vscode_extension")] {
+            let path = dir.join(format!("rollout-{name}.jsonl"));
+            let meta = json!({
+                "type": "session_meta",
+                "payload": { "id": format!("thr_{name}"), "originator": originator }
+            });
+            std::fs::write(&path, format!("{meta}\n")).expect("write rollout file");
+            tracked.push(
+                RolloutTrackedFile::new(path)
+                    .await
+                    .expect("track rollout file"),
+            );
+        }
+        {
+            let mut sync = state.backend.rollout_sync.lock().await;
+            for file in tracked {
+                sync.files.insert(file.path.clone(), file);
+            }
+        }
 
-    #[test]
-    fn rollout_discovery_tick_scheduler_handles_multi_tick_intervals() {
-        assert!(should_run_rollout_discovery_tick(1, 3));
-        assert!(!should_run_rollout_discovery_tick(2, 3));
-        assert!(should_run_rollout_discovery_tick(3, 3));
-        assert!(should_run_rollout_discovery_tick(6, 3));
+        let status = handle_bridge_method("bridge/rollout/status", None, &state, 1)
+            .await
+            .expect("rollout status");
+        let files = status["files"].as_array().expect("files array");
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0]["threadId"], "thr_a");
+        assert_eq!(files[0]["includeForLiveSync"], true);
+        assert_eq!(files[1]["threadId"], "thr_b");
+        assert_eq!(files[1]["originator"], "vscode_extension");
+        assert_eq!(files[1]["includeForLiveSync"], false);
+        assert_eq!(files[1]["linesProcessed"], 0);
+
+        std::fs::remove_dir_all(&dir).ok();
+        shutdown_test_backend(&state.backend).await;
     }
 
-    #[test]
-    fn parse_user_input_questions_filters_invalid_entries_and_maps_options() {
-        let questions = parse_user_input_questions(Some(&json!([
-            {
-                "id": "q1",
-                "header": "Repo",
-                "question": "Pick one",
-                "isOther": true,
-                "isSecret": false,
-                "options": [
-                    { "label": "main", "description": "default branch" },
-                    { "label": "develop" },
-                    { "description": "missing label" }
-                ]
-            },
-            {
-                "id": "q2",
-                "question": "Missing header"
-            },
-            "not-an-object"
-        ])));
+    #[tokio::test]
+    async fn rollout_event_count_skips_blank_lines_across_chunks() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let path = env::temp_dir().join(format!("clawdex-rollout-count-{nonce}.jsonl"));
+        let long_line = "x".repeat(200 * 1024);
+        std::fs::write(&path, format!("{long_line}\n\n  \n{{}}\n{long_line}"))
+            .expect("write rollout file");
 
-        assert_eq!(questions.len(), 1);
-        assert_eq!(questions[0].id, "q1");
-        assert_eq!(questions[0].header, "Repo");
-        assert_eq!(questions[0].question, "Pick one");
-        assert!(questions[0].is_other);
-        assert!(!questions[0].is_secret);
-        let options = questions[0].options.as_ref().expect("options to exist");
-        assert_eq!(options.len(), 2);
-        assert_eq!(options[0].label, "main");
-        assert_eq!(options[0].description, "default branch");
-        assert_eq!(options[1].label, "develop");
-        assert_eq!(options[1].description, "");
+        let count = count_rollout_file_events(&path)
+            .await
+            .expect("count rollout events");
+        assert_eq!(count, 3);
+
+        std::fs::remove_file(&path).ok();
     }
 
-    #[test]
-    fn user_input_answer_validation_enforces_non_empty_ids_and_answers() {
-        let mut valid = HashMap::new();
-        valid.insert(
-            "q1".to_string(),
-            UserInputAnswerPayload {
-                answers: vec!["yes".to_string()],
-            },
-        );
-        assert!(is_valid_user_input_answers(&valid));
+    #[tokio::test]
+    async fn thread_export_lists_rollout_and_attachments() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let dir = env::temp_dir().join(format!("clawdex-thread-export-{nonce}"));
+        let attachments_dir = dir.join("attachments");
+        std::fs::create_dir_all(attachments_dir.join("thr_export")).expect("create dirs");
+        std::fs::write(attachments_dir.join("thr_export/a.png"), b"png").expect("write a");
+        std::fs::write(attachments_dir.join("thr_export/b.txt"), b"notes").expect("write b");
+        std::fs::create_dir_all(attachments_dir.join("thr_other")).expect("create other");
+        std::fs::write(attachments_dir.join("thr_other/c.txt"), b"x").expect("write c");
+
+        let rollout_path = dir.join("rollout-export.jsonl");
+        let lines = [
+            json!({ "type": "session_meta", "payload": { "id": "thr_export" } }),
+            json!({ "type": "event_msg", "payload": { "type": "agent_message", "message": "hi" } }),
+            json!({ "type": "event_msg", "payload": { "type": "task_complete" } }),
+        ];
+        let content = lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        std::fs::write(&rollout_path, &content).expect("write rollout file");
 
-        let mut invalid_question_id = HashMap::new();
-        invalid_question_id.insert(
-            "  ".to_string(),
-            UserInputAnswerPayload {
-                answers: vec!["yes".to_string()],
-            },
-        );
-        assert!(!is_valid_user_input_answers(&invalid_question_id));
+        let state = build_test_state_with_config(|config| {
+            config.attachments_dir = attachments_dir.clone();
+        })
+        .await;
+        let tracked = RolloutTrackedFile::new(rollout_path.clone())
+            .await
+            .expect("track rollout file");
+        state
+            .backend
+            .rollout_sync
+            .lock()
+            .await
+            .files
+            .insert(rollout_path.clone(), tracked);
 
-        let mut invalid_empty_answers = HashMap::new();
-        invalid_empty_answers.insert(
-            "q1".to_string(),
-            UserInputAnswerPayload {
-                answers: Vec::new(),
-            },
+        let manifest = handle_bridge_method(
+            "bridge/thread/export",
+            Some(json!({ "threadId": "codex:thr_export" })),
+            &state,
+            1,
+        )
+        .await
+        .expect("export thread");
+        assert_eq!(manifest["threadId"], "codex:thr_export");
+        assert_eq!(
+            manifest["rolloutPath"],
+            rollout_path.to_string_lossy().as_ref()
         );
-        assert!(!is_valid_user_input_answers(&invalid_empty_answers));
+        assert_eq!(manifest["eventCount"], 3);
+        assert!(manifest.get("rolloutBase64").is_none());
+        let attachments = manifest["attachments"].as_array().expect("attachments");
+        let names = attachments
+            .iter()
+            .map(|entry| entry["fileName"].as_str().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a.png", "b.txt"]);
+        assert_eq!(attachments[1]["sizeBytes"], 5);
 
-        let mut invalid_blank_answer = HashMap::new();
-        invalid_blank_answer.insert(
-            "q1".to_string(),
-            UserInputAnswerPayload {
-                answers: vec!["   ".to_string()],
-            },
+        let inlined = handle_bridge_method(
+            "bridge/thread/export",
+            Some(json!({ "threadId": "thr_export", "inline": true })),
+            &state,
+            1,
+        )
+        .await
+        .expect("export thread inline");
+        assert_eq!(
+            inlined["rolloutBase64"],
+            general_purpose::STANDARD.encode(&content)
         );
-        assert!(!is_valid_user_input_answers(&invalid_blank_answer));
+        assert_eq!(inlined["attachments"][1]["dataBase64"], "bm90ZXM=");
+
+        let missing = handle_bridge_method(
+            "bridge/thread/export",
+            Some(json!({ "threadId": "thr_missing" })),
+            &state,
+            1,
+        )
+        .await
+        .expect_err("untracked thread");
+        assert!(missing.message.contains("thr_missing"));
+
+        std::fs::remove_dir_all(&dir).ok();
+        shutdown_test_backend(&state.backend).await;
     }
 
-    #[test]
-    fn decode_base64_payload_supports_standard_urlsafe_and_data_uri_inputs() {
-        assert_eq!(
-            decode_base64_payload("aGVsbG8=").expect("decode standard base64"),
-            b"hello".to_vec()
-        );
-        assert_eq!(
-            decode_base64_payload("data:text/plain;base64,aGVsbG8=")
-                .expect("decode data-uri base64"),
-            b"hello".to_vec()
-        );
+    #[tokio::test]
+    async fn thread_export_finds_untracked_rollouts_with_sync_disabled() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let sessions_root = env::temp_dir().join(format!("clawdex-thread-export-sessions-{nonce}"));
+        let day_dir = sessions_root.join("2026").join("10").join("17");
+        std::fs::create_dir_all(&day_dir).expect("create sessions dir");
+        let rollout_path = day_dir.join("rollout-2026-10-17T09-30-00-thr_untracked.jsonl");
+        std::fs::write(
+            &rollout_path,
+            format!(
+                "{}\n{}\n",
+                json!({ "type": "session_meta", "payload": { "id": "thr_untracked" } }),
+                json!({ "type": "event_msg", "payload": { "type": "task_complete" } }),
+            ),
+        )
+        .expect("write rollout file");
+        std::fs::write(
+            day_dir.join("rollout-2026-10-17T09-00-00-thr_other.jsonl"),
+            "{}\n",
+        )
+        .expect("write other rollout file");
+
+        let state = build_test_state_with_config(|config| {
+            config.disable_rollout_sync = true;
+        })
+        .await;
+        // Stands in for the `CODEX_HOME` lookup; nothing is tracked.
+        state.backend.rollout_sync.lock().await.sessions_root = Some(sessions_root.clone());
+
+        let manifest = handle_bridge_method(
+            "bridge/thread/export",
+            Some(json!({ "threadId": "codex:thr_untracked" })),
+            &state,
+            1,
+        )
+        .await
+        .expect("export untracked thread");
         assert_eq!(
-            decode_base64_payload("_w==").expect("decode url-safe base64"),
-            vec![255]
+            manifest["rolloutPath"],
+            rollout_path.to_string_lossy().as_ref()
         );
+        assert_eq!(manifest["eventCount"], 2);
+
+        let missing = handle_bridge_method(
+            "bridge/thread/export",
+            Some(json!({ "threadId": "thr_missing" })),
+            &state,
+            1,
+        )
+        .await
+        .expect_err("thread without a rollout file");
+        assert!(missing.message.contains("thr_missing"));
+
+        std::fs::remove_dir_all(&sessions_root).ok();
+        shutdown_test_backend(&state.backend).await;
     }
 
-    #[test]
-    fn decode_base64_payload_rejects_invalid_payloads() {
-        assert!(decode_base64_payload("not@@base64").is_err());
-        assert!(decode_base64_payload("data:text/plain;base64,").is_err());
+    #[tokio::test]
+    async fn rollout_discovery_evicts_oldest_file_past_cap() {
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-rollout-cap-{nonce}"));
+        std::fs::create_dir_all(&root).expect("create sessions root");
+
+        let mut sync = RolloutLiveSyncState::new(2);
+        for (index, name) in ["old", "mid", "new"].into_iter().enumerate() {
+            let path = root.join(format!("rollout-{name}.jsonl"));
+            let meta =
+                json!({ "type": "session_meta", "payload": { "id": format!("thr_{name}") } });
+            std::fs::write(&path, format!("{meta}\n")).expect("write rollout file");
+            let modified = SystemTime::now() - Duration::from_secs(60 * (3 - index as u64));
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .and_then(|file| file.set_modified(modified))
+                .expect("set rollout mtime");
+            let tracked = RolloutTrackedFile::new(path.clone())
+                .await
+                .expect("track rollout file");
+            sync.files.insert(path, tracked);
+        }
+
+        let sync = Mutex::new(sync);
+        rollout_live_sync_discover_files(&hub, &root, &sync)
+            .await
+            .expect("discover rollout files");
+
+        let mut remaining = sync
+            .lock()
+            .await
+            .files
+            .values()
+            .filter_map(|file| file.thread_id.clone())
+            .collect::<Vec<_>>();
+        remaining.sort();
+        assert_eq!(remaining, vec!["thr_mid", "thr_new"]);
+
+        let evicted = recv_client_json(&mut rx).await;
+        assert_eq!(evicted["method"], "bridge/rollout/fileEvicted");
+        assert_eq!(evicted["params"]["threadId"], "thr_old");
+        assert_eq!(evicted["params"]["maxTrackedFiles"], 2);
+
+        std::fs::remove_dir_all(&root).ok();
     }
 
-    #[test]
-    fn estimate_base64_decoded_size_matches_expected_values() {
-        assert_eq!(
-            estimate_base64_decoded_size("aGVsbG8=").unwrap_or_default(),
-            5
-        );
-        assert_eq!(
-            estimate_base64_decoded_size("data:text/plain;base64,aGVsbG8=").unwrap_or_default(),
-            5
-        );
-        assert_eq!(estimate_base64_decoded_size("YQ==").unwrap_or_default(), 1);
+    #[tokio::test]
+    async fn rollout_extra_record_types_are_forwarded_when_enabled() {
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-rollout-extra-{nonce}"));
+        std::fs::create_dir_all(&root).expect("create sessions root");
+        let path = root.join("rollout-extra.jsonl");
+        let lines = [
+            json!({ "type": "session_meta", "payload": { "id": "thr_extra" } }),
+            json!({
+                "type": "turn_context",
+                "timestamp": "2026-01-01T00:00:00Z",
+                "payload": { "cwd": "/repo", "model": "gpt-5" }
+            }),
+            json!({ "type": "compacted", "payload": { "message": "summary" } }),
+            json!({ "type": "event_msg", "payload": { "type": "token_count", "info": null } }),
+        ];
+        let contents = lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        std::fs::write(&path, contents).expect("write rollout file");
+
+        let mut sync = RolloutLiveSyncState::new(4)
+            .with_extra_record_types(HashSet::from(["turn_context".to_string()]));
+        let tracked = RolloutTrackedFile::new(path.clone())
+            .await
+            .expect("track rollout file");
+        sync.files.insert(path.clone(), tracked);
+        let sync = Mutex::new(sync);
+        rollout_live_sync_poll_files(&hub, &sync)
+            .await
+            .expect("poll rollout files");
+
+        let turn_context = recv_client_json(&mut rx).await;
+        assert_eq!(turn_context["method"], "codex/rollout/turn_context");
+        assert_eq!(turn_context["params"]["threadId"], "codex:thr_extra");
+        assert_eq!(turn_context["params"]["recordType"], "turn_context");
+        assert_eq!(turn_context["params"]["timestamp"], "2026-01-01T00:00:00Z");
+        assert_eq!(turn_context["params"]["payload"]["model"], "gpt-5");
+        // `compacted` is not enabled, so the next notification is the event after it.
+        let next = recv_client_json(&mut rx).await;
+        assert_eq!(next["method"], "codex/event/token_count");
+
+        std::fs::remove_dir_all(&root).ok();
     }
 
-    #[test]
-    fn resolve_bridge_workdir_requires_absolute_existing_paths() {
-        let temp_dir = env::temp_dir();
-        let resolved = resolve_bridge_workdir(temp_dir.clone()).expect("resolve temp dir");
-        assert!(resolved.is_absolute());
+    #[tokio::test]
+    async fn rollout_exec_output_becomes_capped_exec_command_end() {
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-rollout-exec-{nonce}"));
+        std::fs::create_dir_all(&root).expect("create sessions root");
+        let path = root.join("rollout-exec.jsonl");
+        let output = format!("x{}", "€".repeat(20_000));
+        let lines = [
+            json!({ "type": "session_meta", "payload": { "id": "thr_exec" } }),
+            json!({
+                "type": "response_item",
+                "payload": {
+                    "type": "function_call",
+                    "name": "exec_command",
+                    "call_id": "call_ls",
+                    "arguments": "{\"cmd\":\"ls -la\"}"
+                }
+            }),
+            json!({
+                "type": "response_item",
+                "payload": { "type": "function_call_output", "call_id": "call_ls", "output": output }
+            }),
+        ];
+        let contents = lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        std::fs::write(&path, contents).expect("write rollout file");
 
-        assert!(resolve_bridge_workdir(PathBuf::from("relative/path")).is_err());
+        let mut sync = RolloutLiveSyncState::new(4);
+        let tracked = RolloutTrackedFile::new(path.clone())
+            .await
+            .expect("track rollout file");
+        sync.files.insert(path.clone(), tracked);
+        let sync = Mutex::new(sync);
+        rollout_live_sync_poll_files(&hub, &sync)
+            .await
+            .expect("poll rollout files");
+
+        let begin = recv_client_json(&mut rx).await;
+        assert_eq!(begin["method"], "codex/event/exec_command_begin");
+        let end = recv_client_json(&mut rx).await;
+        assert_eq!(end["method"], "codex/event/exec_command_end");
+        let msg = &end["params"]["msg"];
+        assert_eq!(msg["call_id"], "call_ls");
+        assert_eq!(msg["command"], json!(["ls", "-la"]));
+        assert_eq!(msg["outputBytes"], output.len());
+        assert_eq!(msg["previewTruncated"], true);
+        let preview = msg["preview"].as_str().expect("preview text");
+        assert!(preview.len() <= ROLLOUT_EXEC_OUTPUT_PREVIEW_MAX_BYTES);
+        assert!(preview.len() > ROLLOUT_EXEC_OUTPUT_PREVIEW_MAX_BYTES - 4);
+        assert!(output.starts_with(preview));
+        assert!(sync.lock().await.files[&path].pending_exec_calls.is_empty());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
 
-        let nonce = std::time::SystemTime::now()
+    #[tokio::test]
+    async fn rollout_event_matching_recent_live_event_is_suppressed() {
+        let hub = Arc::new(ClientHub::new().with_rollout_dedup_window(Duration::from_secs(5)));
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let bridge = build_test_bridge(hub.clone()).await;
+        let nonce = SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .expect("system clock after unix epoch")
             .as_nanos();
-        let missing = env::temp_dir().join(format!("clawdex-missing-{nonce}"));
-        assert!(resolve_bridge_workdir(missing).is_err());
+        let root = env::temp_dir().join(format!("clawdex-rollout-dedup-{nonce}"));
+        std::fs::create_dir_all(&root).expect("create sessions root");
+        let path = root.join("rollout-dedup.jsonl");
+
+        bridge
+            .handle_notification(
+                "codex/event/exec_command_begin",
+                Some(json!({
+                    "id": "turn_1",
+                    "conversationId": "thr_dup",
+                    "msg": { "type": "exec_command_begin", "call_id": "call_1", "command": ["ls"] }
+                })),
+            )
+            .await;
+        let live = recv_client_json(&mut rx).await;
+        assert_eq!(live["method"], "codex/event/exec_command_begin");
+
+        let lines = [
+            json!({ "type": "session_meta", "payload": { "id": "thr_dup" } }),
+            json!({
+                "type": "event_msg",
+                "timestamp": "2026-01-01T00:00:00Z",
+                "payload": { "type": "exec_command_begin", "call_id": "call_1", "command": ["ls"] }
+            }),
+            json!({
+                "type": "event_msg",
+                "payload": { "type": "exec_command_begin", "call_id": "call_2", "command": ["pwd"] }
+            }),
+        ];
+        let contents = lines
+            .iter()
+            .map(|line| format!("{line}\n"))
+            .collect::<String>();
+        std::fs::write(&path, contents).expect("write rollout file");
+
+        let mut sync = RolloutLiveSyncState::new(4);
+        let tracked = RolloutTrackedFile::new(path.clone())
+            .await
+            .expect("track rollout file");
+        sync.files.insert(path, tracked);
+        let sync = Mutex::new(sync);
+        rollout_live_sync_poll_files(&hub, &sync)
+            .await
+            .expect("poll rollout files");
+
+        let forwarded = recv_client_json(&mut rx).await;
+        assert_eq!(forwarded["method"], "codex/event/exec_command_begin");
+        assert_eq!(forwarded["params"]["msg"]["call_id"], "call_2");
+        assert_eq!(forwarded["params"]["rolloutSeq"], 1);
+        assert!(timeout(Duration::from_millis(50), rx.recv()).await.is_err());
+
+        let unmatched = json!({
+            "conversationId": "thr_dup",
+            "msg": { "type": "exec_command_begin", "call_id": "call_1", "command": ["ls"] }
+        });
+        let disabled = ClientHub::new();
+        disabled.record_live_event("codex/event/exec_command_begin", &unmatched);
+        assert!(!disabled.is_recent_live_event("codex/event/exec_command_begin", &unmatched));
+        assert!(hub.is_recent_live_event("codex/event/exec_command_begin", &unmatched));
+        assert!(!hub.is_recent_live_event("codex/event/exec_command_end", &unmatched));
+
+        shutdown_test_bridge(&bridge).await;
+        std::fs::remove_dir_all(&root).ok();
     }
 
     #[test]
-    fn attachment_kind_normalization_uses_kind_then_mime_fallback() {
-        assert_eq!(normalize_attachment_kind(Some("image"), None), "image");
-        assert_eq!(normalize_attachment_kind(Some(" FILE "), None), "file");
-        assert_eq!(
-            normalize_attachment_kind(Some("unknown"), Some("image/png")),
-            "image"
-        );
-        assert_eq!(
-            normalize_attachment_kind(None, Some("application/pdf")),
-            "file"
-        );
+    fn live_event_dedup_forgets_events_outside_the_window() {
+        let dedup = LiveEventDedup::new(Duration::from_millis(100));
+        let start = Instant::now();
+        dedup.record(7, start);
+        assert!(dedup.contains(7, start + Duration::from_millis(100)));
+        assert!(!dedup.contains(7, start + Duration::from_millis(101)));
+        assert!(!dedup.contains(8, start));
     }
 
     #[test]
-    fn attachment_file_name_building_sanitizes_and_infers_extension() {
-        assert_eq!(
-            build_attachment_file_name(None, Some("image/png"), "image"),
-            "image.png"
-        );
+    fn rollout_sync_prunes_idle_thread_seqs_and_unrecent_resume_offsets() {
+        let mut sync = RolloutLiveSyncState::new(4);
+        let start = Instant::now();
+        assert_eq!(sync.next_thread_seq("thr_idle", start), 1);
+        let later = start + ROLLOUT_LIVE_SYNC_MAX_FILE_AGE;
+        assert_eq!(sync.next_thread_seq("thr_active", later), 1);
+        sync.resume_offsets
+            .insert(PathBuf::from("/sessions/rollout-recent.jsonl"), 10);
+        sync.resume_offsets
+            .insert(PathBuf::from("/sessions/rollout-old.jsonl"), 20);
+
+        let recent_paths = HashSet::from([PathBuf::from("/sessions/rollout-recent.jsonl")]);
+        sync.prune(&recent_paths, later);
+
         assert_eq!(
-            build_attachment_file_name(Some("../weird name?.txt"), None, "file"),
-            "weird_name_.txt"
+            sync.thread_seqs.keys().collect::<Vec<_>>(),
+            vec!["thr_active"]
         );
         assert_eq!(
-            build_attachment_file_name(Some("notes"), Some("application/json"), "file"),
-            "notes.json"
+            sync.resume_offsets.keys().collect::<Vec<_>>(),
+            vec![&PathBuf::from("/sessions/rollout-recent.jsonl")]
         );
+        assert_eq!(sync.next_thread_seq("thr_active", later), 2);
     }
 
-    #[test]
-    fn sanitize_filename_drops_path_segments_and_limits_length() {
-        assert_eq!(
-            sanitize_filename("../unsafe/..\\evil?.txt"),
-            "evil_.txt".to_string()
-        );
-        assert_eq!(sanitize_filename("..."), "attachment".to_string());
-        assert_eq!(sanitize_filename(&"a".repeat(120)).len(), 96);
-    }
+    #[tokio::test]
+    async fn rollout_notifications_carry_per_thread_sequence_numbers() {
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-rollout-seq-{nonce}"));
+        std::fs::create_dir_all(&root).expect("create sessions root");
+        let path = root.join("rollout-seq.jsonl");
+        let message = |text: &str| {
+            json!({
+                "type": "event_msg",
+                "payload": { "type": "agent_message", "message": text }
+            })
+        };
+        let meta = json!({ "type": "session_meta", "payload": { "id": "thr_seq" } });
+        std::fs::write(
+            &path,
+            format!("{meta}\n{}\n{}\n", message("first"), message("second")),
+        )
+        .expect("write rollout file");
 
-    #[test]
-    fn sanitize_path_segment_keeps_safe_characters_only() {
-        assert_eq!(
-            sanitize_path_segment(" ../Thread 01/.. "),
-            "Thread_01".to_string()
-        );
-        assert_eq!(sanitize_path_segment(&"a".repeat(80)).len(), 64);
-    }
+        let mut sync = RolloutLiveSyncState::new(4);
+        let tracked = RolloutTrackedFile::new(path.clone())
+            .await
+            .expect("track rollout file");
+        sync.files.insert(path.clone(), tracked);
+        let sync = Mutex::new(sync);
+        rollout_live_sync_poll_files(&hub, &sync)
+            .await
+            .expect("poll rollout files");
+
+        let first = recv_client_json(&mut rx).await;
+        let second = recv_client_json(&mut rx).await;
+        assert_eq!(first["params"]["msg"]["delta"], "first");
+        assert_eq!(first["params"]["rolloutSeq"], 1);
+        assert_eq!(second["params"]["msg"]["delta"], "second");
+        assert_eq!(second["params"]["rolloutSeq"], 2);
+
+        // Re-tracking the same file after a re-scan resumes where it stopped instead of
+        // replaying the tail, and keeps counting for the thread.
+        sync.lock().await.untrack(&path);
+        let tracked = RolloutTrackedFile::new(path.clone())
+            .await
+            .expect("re-track rollout file");
+        sync.lock().await.track(tracked);
+        rollout_live_sync_poll_files(&hub, &sync)
+            .await
+            .expect("poll re-tracked file");
+        assert!(timeout(Duration::from_millis(50), rx.recv()).await.is_err());
+
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| writeln!(file, "{}", message("third")))
+            .expect("append rollout line");
+        rollout_live_sync_poll_files(&hub, &sync)
+            .await
+            .expect("poll appended line");
+        let third = recv_client_json(&mut rx).await;
+        assert_eq!(third["params"]["msg"]["delta"], "third");
+        assert_eq!(third["params"]["rolloutSeq"], 3);
 
-    #[test]
-    fn infer_extension_from_mime_handles_supported_and_unknown_values() {
-        assert_eq!(infer_extension_from_mime(Some("image/JPEG")), Some("jpg"));
-        assert_eq!(infer_extension_from_mime(Some("text/plain")), Some("txt"));
-        assert_eq!(infer_extension_from_mime(Some("application/zip")), None);
+        std::fs::remove_dir_all(&root).ok();
     }
 
-    #[test]
-    fn transcription_mime_normalization_accepts_known_values_and_falls_back() {
-        assert_eq!(
-            normalize_transcription_mime_type(Some(" audio/MP4 ")),
-            "audio/mp4".to_string()
-        );
-        assert_eq!(
-            normalize_transcription_mime_type(Some("audio/webm;codecs=opus")),
-            "audio/webm".to_string()
-        );
-        assert_eq!(
-            normalize_transcription_mime_type(Some("audio/mpga")),
-            "audio/mpeg".to_string()
-        );
-        assert_eq!(
-            normalize_transcription_mime_type(Some("application/octet-stream")),
-            "audio/wav".to_string()
-        );
-        assert_eq!(
-            normalize_transcription_mime_type(None),
-            "audio/wav".to_string()
-        );
+    #[tokio::test]
+    async fn rollout_live_sync_stops_when_shutdown_is_signaled() {
+        let hub = Arc::new(ClientHub::new());
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-rollout-shutdown-{nonce}"));
+        std::fs::create_dir_all(&root).expect("create sessions root");
+
+        let sync = Arc::new(Mutex::new(RolloutLiveSyncState::new(4)));
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let task = tokio::spawn(run_rollout_live_sync(
+            hub,
+            sync.clone(),
+            root.clone(),
+            Duration::from_millis(10),
+            shutdown_rx,
+        ));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!task.is_finished());
+        assert!(sync.lock().await.tick > 0);
+
+        shutdown_tx.send(true).expect("signal shutdown");
+        timeout(Duration::from_secs(1), task)
+            .await
+            .expect("sync loop exits after shutdown")
+            .expect("sync loop does not panic");
+
+        std::fs::remove_dir_all(&root).ok();
     }
 
-    #[test]
-    fn voice_transcribe_request_deserializes_legacy_and_extended_shapes() {
-        let legacy: VoiceTranscribeRequest = serde_json::from_value(json!({
-            "dataBase64": "YQ==",
-            "prompt": "hello"
-        }))
-        .expect("deserialize legacy request shape");
-        assert_eq!(legacy.data_base64, "YQ==");
-        assert_eq!(legacy.prompt.as_deref(), Some("hello"));
-        assert!(legacy.file_name.is_none());
-        assert!(legacy.mime_type.is_none());
+    #[tokio::test]
+    async fn rollout_live_sync_start_is_gated_by_disable_flag() {
+        let state = build_test_state_with_config(|config| config.disable_rollout_sync = true).await;
+        let sync = Arc::new(Mutex::new(RolloutLiveSyncState::new(4)));
+        let (shutdown_tx, shutdown_rx) = watch::channel(true);
+
+        assert!(!start_rollout_live_sync(
+            &state.config,
+            state.hub.clone(),
+            sync.clone(),
+            shutdown_rx.clone(),
+        ));
+        let enabled = BridgeConfig {
+            disable_rollout_sync: false,
+            ..(*state.config).clone()
+        };
+        assert!(start_rollout_live_sync(
+            &enabled,
+            state.hub.clone(),
+            sync,
+            shutdown_rx,
+        ));
+        drop(shutdown_tx);
 
-        let extended: VoiceTranscribeRequest = serde_json::from_value(json!({
-            "dataBase64": "YQ==",
-            "prompt": "hello",
-            "fileName": "audio.m4a",
-            "mimeType": "audio/mp4"
-        }))
-        .expect("deserialize extended request shape");
-        assert_eq!(extended.data_base64, "YQ==");
-        assert_eq!(extended.prompt.as_deref(), Some("hello"));
-        assert_eq!(extended.file_name.as_deref(), Some("audio.m4a"));
-        assert_eq!(extended.mime_type.as_deref(), Some("audio/mp4"));
+        let Json(health) = health_handler(State(state.clone())).await;
+        assert_eq!(health["rolloutSync"], false);
+        assert_eq!(health["gitRepo"], false);
+
+        shutdown_test_backend(&state.backend).await;
     }
 
     #[test]
-    fn transcription_file_name_normalization_sanitizes_and_sets_extension() {
-        assert_eq!(
-            normalize_transcription_file_name(Some("../voice note"), "audio/mp4"),
-            "voice_note.m4a".to_string()
-        );
-        assert_eq!(
-            normalize_transcription_file_name(None, "audio/wav"),
-            "audio.wav".to_string()
-        );
-        assert_eq!(
-            normalize_transcription_file_name(Some("meeting"), "audio/webm"),
-            "meeting.webm".to_string()
-        );
+    fn rollout_poll_throttles_only_busy_ticks_with_mostly_lagging_clients() {
+        let busy = ROLLOUT_THROTTLE_MIN_EVENTS_PER_TICK;
+        assert!(should_throttle_rollout_poll(busy, 3, 2, 0));
+        assert!(should_throttle_rollout_poll(busy * 10, 1, 1, 0));
+        // Quiet ticks, a lagging minority, or no clients never throttle.
+        assert!(!should_throttle_rollout_poll(busy - 1, 3, 3, 0));
+        assert!(!should_throttle_rollout_poll(busy, 4, 2, 0));
+        assert!(!should_throttle_rollout_poll(busy, 0, 0, 0));
+        // Skipped polls are bounded so files keep advancing.
+        assert!(should_throttle_rollout_poll(
+            busy,
+            2,
+            2,
+            ROLLOUT_THROTTLE_MAX_SKIPPED_TICKS - 1
+        ));
+        assert!(!should_throttle_rollout_poll(
+            busy,
+            2,
+            2,
+            ROLLOUT_THROTTLE_MAX_SKIPPED_TICKS
+        ));
     }
 
     #[test]
-    fn disallowed_control_character_detection_flags_shell_metacharacters() {
-        assert!(!contains_disallowed_control_chars("git status"));
-        assert!(contains_disallowed_control_chars("echo hi; ls"));
-        assert!(contains_disallowed_control_chars("echo `whoami`"));
+    fn rollout_discovery_tick_scheduler_handles_one_tick_interval() {
+        assert!(should_run_rollout_discovery_tick(1, 1));
+        assert!(should_run_rollout_discovery_tick(10, 1));
+        assert!(should_run_rollout_discovery_tick(5, 0));
     }
 
     #[test]
-    fn normalize_path_collapses_current_and_parent_components() {
-        assert_eq!(
-            normalize_path(Path::new("/tmp/./bridge/../repo/./main.rs")),
-            PathBuf::from("/tmp/repo/main.rs")
-        );
-        assert_eq!(
-            normalize_path(Path::new("a/b/../c/./d")),
-            PathBuf::from("a/c/d")
-        );
+    fn rollout_discovery_tick_scheduler_handles_multi_tick_intervals() {
+        assert!(should_run_rollout_discovery_tick(1, 3));
+        assert!(!should_run_rollout_discovery_tick(2, 3));
+        assert!(should_run_rollout_discovery_tick(3, 3));
+        assert!(should_run_rollout_discovery_tick(6, 3));
     }
 
     #[test]
-    fn resolve_local_image_path_requires_absolute_paths() {
-        assert_eq!(
-            resolve_local_image_path("/tmp/../tmp/example.png").unwrap(),
-            PathBuf::from("/tmp/example.png")
+    fn parse_user_input_questions_filters_invalid_entries_and_maps_options() {
+        let questions = parse_user_input_questions(Some(&json!([
+            {
+                "id": "q1",
+                "header": "Repo",
+                "question": "Pick one",
+                "isOther": true,
+                "isSecret": false,
+                "options": [
+                    { "label": "main", "description": "default branch" },
+                    { "label": "develop" },
+                    { "description": "missing label" }
+                ]
+            },
+            {
+                "id": "q2",
+                "question": "Missing header"
+            },
+            "not-an-object"
+        ])));
+
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].id, "q1");
+        assert_eq!(questions[0].header, "Repo");
+        assert_eq!(questions[0].question, "Pick one");
+        assert!(questions[0].is_other);
+        assert!(!questions[0].is_secret);
+        let options = questions[0].options.as_ref().expect("options to exist");
+        assert_eq!(options.len(), 2);
+        assert_eq!(options[0].label, "main");
+        assert_eq!(options[0].description, "default branch");
+        assert_eq!(options[1].label, "develop");
+        assert_eq!(options[1].description, "");
+    }
+
+    #[test]
+    fn user_input_answer_validation_enforces_non_empty_ids_and_answers() {
+        let mut valid = HashMap::new();
+        valid.insert(
+            "q1".to_string(),
+            UserInputAnswerPayload {
+                answers: vec!["yes".to_string()],
+            },
+        );
+        assert!(is_valid_user_input_answers(&valid));
+
+        let mut invalid_question_id = HashMap::new();
+        invalid_question_id.insert(
+            "  ".to_string(),
+            UserInputAnswerPayload {
+                answers: vec!["yes".to_string()],
+            },
+        );
+        assert!(!is_valid_user_input_answers(&invalid_question_id));
+
+        let mut invalid_empty_answers = HashMap::new();
+        invalid_empty_answers.insert(
+            "q1".to_string(),
+            UserInputAnswerPayload {
+                answers: Vec::new(),
+            },
+        );
+        assert!(!is_valid_user_input_answers(&invalid_empty_answers));
+
+        let mut invalid_blank_answer = HashMap::new();
+        invalid_blank_answer.insert(
+            "q1".to_string(),
+            UserInputAnswerPayload {
+                answers: vec!["   ".to_string()],
+            },
         );
+        assert!(!is_valid_user_input_answers(&invalid_blank_answer));
+    }
+
+    #[test]
+    fn decode_base64_payload_supports_standard_urlsafe_and_data_uri_inputs() {
         assert_eq!(
-            resolve_local_image_path("relative/example.png").unwrap_err(),
-            "Image path must be absolute"
+            decode_base64_payload("aGVsbG8=").expect("decode standard base64"),
+            b"hello".to_vec()
+        );
+        assert_eq!(
+            decode_base64_payload("data:text/plain;base64,aGVsbG8=")
+                .expect("decode data-uri base64"),
+            b"hello".to_vec()
+        );
+        assert_eq!(
+            decode_base64_payload("_w==").expect("decode url-safe base64"),
+            vec![255]
         );
     }
 
     #[test]
-    fn infer_image_content_type_from_path_supports_common_extensions() {
+    fn decode_base64_payload_rejects_invalid_payloads() {
+        assert!(decode_base64_payload("not@@base64").is_err());
+        assert!(decode_base64_payload("data:text/plain;base64,").is_err());
+    }
+
+    #[test]
+    fn estimate_base64_decoded_size_matches_expected_values() {
         assert_eq!(
-            infer_image_content_type_from_path(Path::new("/tmp/example.png")),
-            Some("image/png")
+            estimate_base64_decoded_size("aGVsbG8=").unwrap_or_default(),
+            5
         );
         assert_eq!(
-            infer_image_content_type_from_path(Path::new("/tmp/example.JPG")),
-            Some("image/jpeg")
+            estimate_base64_decoded_size("data:text/plain;base64,aGVsbG8=").unwrap_or_default(),
+            5
         );
+        assert_eq!(estimate_base64_decoded_size("YQ==").unwrap_or_default(), 1);
+        // Stray `=` on a partial block is not padding and must not shrink the estimate.
         assert_eq!(
-            infer_image_content_type_from_path(Path::new("/tmp/example.txt")),
-            None
+            estimate_base64_decoded_size("YWJjZ==").unwrap_or_default(),
+            6
         );
     }
 
     #[test]
-    fn constant_time_eq_handles_equal_and_different_strings() {
-        assert!(constant_time_eq("secret-token", "secret-token"));
-        assert!(!constant_time_eq("secret-token", "secret-tok3n"));
-        assert!(!constant_time_eq("secret-token", "secret-token-extra"));
+    fn base64_limit_estimate_never_undercounts_decoded_size() {
+        for len in 1..=48usize {
+            let bytes = vec![0xAB; len];
+            for encoded in [
+                general_purpose::STANDARD.encode(&bytes),
+                general_purpose::URL_SAFE.encode(&bytes),
+            ] {
+                let decoded = decode_base64_payload(&encoded).expect("decode").len();
+                let estimate = estimate_base64_decoded_size(&encoded).expect("estimate");
+                assert!(estimate >= decoded, "len {len}: {estimate} < {decoded}");
+                assert!(
+                    estimate - decoded <= 2,
+                    "len {len}: {estimate} vs {decoded}"
+                );
+
+                // A payload one byte past the cap is caught before decoding, and one at
+                // the cap is not rejected by rounding.
+                assert!(base64_exceeds_decoded_limit(&encoded, decoded - 1).expect("check"));
+                assert!(!base64_exceeds_decoded_limit(&encoded, decoded).expect("check"));
+            }
+        }
     }
 
     #[test]
-    fn build_pairing_payload_includes_url_and_token_for_connectable_host() {
-        let config = BridgeConfig {
-            host: "127.0.0.1".to_string(),
-            port: 8787,
-            preview_port: 8788,
-            connect_url: None,
-            preview_connect_url: None,
-            workdir: PathBuf::from("/tmp/workdir"),
-            cli_bin: "codex".to_string(),
-            opencode_cli_bin: "opencode".to_string(),
-            cursor_app_server_bin: "cursor-app-server".to_string(),
-            active_engine: BridgeRuntimeEngine::Codex,
-            enabled_engines: vec![BridgeRuntimeEngine::Codex],
-            opencode_host: "127.0.0.1".to_string(),
-            opencode_port: 4090,
-            opencode_server_username: "opencode".to_string(),
-            opencode_server_password: Some("secret-token".to_string()),
-            auth_token: Some("secret-token".to_string()),
-            auth_enabled: true,
-            allow_insecure_no_auth: false,
-            allow_query_token_auth: false,
-            allow_outside_root_cwd: false,
-            disable_terminal_exec: false,
-            terminal_allowed_commands: HashSet::new(),
-            show_pairing_qr: true,
-        };
+    fn base64_limit_rejects_oversized_input_before_parsing() {
+        assert!(!base64_exceeds_decoded_limit("data:text/plain;base64,aGVsbG8=", 5).expect("fits"));
+        assert!(base64_exceeds_decoded_limit("aGVsbG8=", 4).expect("estimated past cap"));
 
-        let payload = build_pairing_payload(&config).expect("pairing payload");
-        let parsed: Value = serde_json::from_str(&payload).expect("valid json");
+        // Whitespace-only input fails payload extraction, so `Ok(true)` proves the raw
+        // length check ran first.
+        let oversized = " ".repeat(4 * 1024 + BASE64_LENGTH_CHECK_SLACK + 1);
+        assert!(estimate_base64_decoded_size(&oversized).is_err());
+        assert!(base64_exceeds_decoded_limit(&oversized, 3 * 1024).expect("cheap pre-check"));
+    }
 
-        assert_eq!(parsed["type"], "clawdex-bridge-pair");
-        assert_eq!(parsed["bridgeUrl"], "http://127.0.0.1:8787");
-        assert_eq!(parsed["bridgeToken"], "secret-token");
+    #[test]
+    fn resolve_bridge_workdir_requires_absolute_existing_paths() {
+        let temp_dir = env::temp_dir();
+        let resolved = resolve_bridge_workdir(temp_dir.clone()).expect("resolve temp dir");
+        assert!(resolved.is_absolute());
+
+        assert!(resolve_bridge_workdir(PathBuf::from("relative/path")).is_err());
+
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let missing = env::temp_dir().join(format!("clawdex-missing-{nonce}"));
+        assert!(resolve_bridge_workdir(missing).is_err());
     }
 
     #[test]
-    fn build_pairing_payload_uses_token_only_fallback_for_unspecified_bind_host() {
-        let config = BridgeConfig {
-            host: "0.0.0.0".to_string(),
-            port: 8787,
-            preview_port: 8788,
-            connect_url: None,
-            preview_connect_url: None,
-            workdir: PathBuf::from("/tmp/workdir"),
-            cli_bin: "codex".to_string(),
-            opencode_cli_bin: "opencode".to_string(),
-            cursor_app_server_bin: "cursor-app-server".to_string(),
-            active_engine: BridgeRuntimeEngine::Codex,
-            enabled_engines: vec![BridgeRuntimeEngine::Codex],
-            opencode_host: "127.0.0.1".to_string(),
-            opencode_port: 4090,
-            opencode_server_username: "opencode".to_string(),
-            opencode_server_password: Some("secret-token".to_string()),
-            auth_token: Some("secret-token".to_string()),
-            auth_enabled: true,
-            allow_insecure_no_auth: false,
-            allow_query_token_auth: false,
-            allow_outside_root_cwd: false,
-            disable_terminal_exec: false,
-            terminal_allowed_commands: HashSet::new(),
-            show_pairing_qr: true,
-        };
+    fn attachment_kind_normalization_uses_kind_then_mime_fallback() {
+        assert_eq!(normalize_attachment_kind(Some("image"), None), "image");
+        assert_eq!(normalize_attachment_kind(Some(" FILE "), None), "file");
+        assert_eq!(
+            normalize_attachment_kind(Some("unknown"), Some("image/png")),
+            "image"
+        );
+        assert_eq!(
+            normalize_attachment_kind(None, Some("application/pdf")),
+            "file"
+        );
+    }
+
+    #[test]
+    fn attachment_file_name_building_sanitizes_and_infers_extension() {
+        assert_eq!(
+            build_attachment_file_name(None, Some("image/png"), "image"),
+            "image.png"
+        );
+        assert_eq!(
+            build_attachment_file_name(Some("../weird name?.txt"), None, "file"),
+            "weird_name_.txt"
+        );
+        assert_eq!(
+            build_attachment_file_name(Some("notes"), Some("application/json"), "file"),
+            "notes.json"
+        );
+    }
+
+    #[test]
+    fn sanitize_filename_drops_path_segments_and_limits_length() {
+        assert_eq!(
+            sanitize_filename("../unsafe/..\\evil?.txt"),
+            "evil_.txt".to_string()
+        );
+        assert_eq!(sanitize_filename("..."), "attachment".to_string());
+        assert_eq!(sanitize_filename(&"a".repeat(120)).len(), 96);
+    }
+
+    #[test]
+    fn sanitize_path_segment_keeps_safe_characters_only() {
+        assert_eq!(
+            sanitize_path_segment(" ../Thread 01/.. "),
+            "Thread_01".to_string()
+        );
+        assert_eq!(sanitize_path_segment(&"a".repeat(80)).len(), 64);
+    }
+
+    #[test]
+    fn infer_extension_from_mime_handles_supported_and_unknown_values() {
+        assert_eq!(infer_extension_from_mime(Some("image/JPEG")), Some("jpg"));
+        assert_eq!(infer_extension_from_mime(Some("text/plain")), Some("txt"));
+        assert_eq!(infer_extension_from_mime(Some("application/zip")), None);
+    }
+
+    #[test]
+    fn transcription_mime_normalization_accepts_known_values_and_falls_back() {
+        assert_eq!(
+            normalize_transcription_mime_type(Some(" audio/MP4 ")),
+            "audio/mp4".to_string()
+        );
+        assert_eq!(
+            normalize_transcription_mime_type(Some("audio/webm;codecs=opus")),
+            "audio/webm".to_string()
+        );
+        assert_eq!(
+            normalize_transcription_mime_type(Some("audio/mpga")),
+            "audio/mpeg".to_string()
+        );
+        assert_eq!(
+            normalize_transcription_mime_type(Some("application/octet-stream")),
+            "audio/wav".to_string()
+        );
+        assert_eq!(
+            normalize_transcription_mime_type(None),
+            "audio/wav".to_string()
+        );
+    }
+
+    #[test]
+    fn fs_write_target_rejects_sandbox_escapes_and_git_internals() {
+        let root = Path::new("/bridge/root");
+        assert_eq!(
+            resolve_fs_write_target(root, Some("app"), "src/../notes.md").expect("target"),
+            PathBuf::from("/bridge/root/app/notes.md")
+        );
+        assert_eq!(
+            resolve_fs_write_target(root, None, "/bridge/root/out.txt").expect("absolute"),
+            PathBuf::from("/bridge/root/out.txt")
+        );
+
+        for (cwd, path) in [
+            (None, "../outside.txt"),
+            (None, "/etc/passwd"),
+            (Some("../elsewhere"), "file.txt"),
+            (Some("app"), "../../x.txt"),
+            (None, "."),
+            (None, " "),
+        ] {
+            let error = resolve_fs_write_target(root, cwd, path).expect_err(path);
+            assert_eq!(error.code, -32602, "{cwd:?} {path}");
+        }
+
+        let git_error =
+            resolve_fs_write_target(root, Some("repo"), ".git/config").expect_err("git internals");
+        assert!(git_error.message.contains(".git"));
+        assert!(resolve_fs_write_target(root, None, "repo/.git/hooks/pre-commit").is_err());
+        assert!(resolve_fs_write_target(root, None, "repo/.GIT/config").is_err());
+        assert!(resolve_fs_write_target(root, None, ".gitignore").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn fs_list_sorts_dirs_first_and_stays_in_sandbox() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = normalize_path(
+            &env::temp_dir().join(format!("clawdex-fs-list-{}-{nonce}", std::process::id())),
+        );
+        let outside = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-fs-list-outside-{}-{nonce}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(root.join("b-dir")).expect("create b-dir");
+        std::fs::create_dir_all(root.join("A-dir")).expect("create A-dir");
+        std::fs::create_dir_all(&outside).expect("create outside dir");
+        std::fs::write(root.join("z.txt"), "zz").expect("write z");
+        std::fs::write(root.join("a.txt"), "a").expect("write a");
+        std::os::unix::fs::symlink(&outside, root.join("escape")).expect("outside symlink");
+        std::os::unix::fs::symlink(root.join("a.txt"), root.join("link.txt"))
+            .expect("inside symlink");
+        let state = build_test_state_with_config(|config| {
+            config.workdir = root.clone();
+        })
+        .await;
+        let request = |path: Option<&str>, limit: Option<usize>| FileSystemListRequest {
+            path: path.map(str::to_string),
+            cwd: Some(".".to_string()),
+            directories_only: Some(false),
+            limit,
+            ..FileSystemListRequest::default()
+        };
+
+        let listed = list_filesystem_entries(&state, request(None, None))
+            .await
+            .expect("list root");
+        let names = listed
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["A-dir", "b-dir", "a.txt", "link.txt", "z.txt"]);
+        assert!(listed.entries[0].is_dir);
+        assert_eq!(listed.entries[0].size_bytes, None);
+        assert_eq!(listed.entries[4].size_bytes, Some(2));
+        assert!(listed.entries[4].modified_at.is_some());
+        assert!(!listed.truncated);
+        assert!(listed.parent_path.is_none());
+
+        let limited = list_filesystem_entries(&state, request(None, Some(2)))
+            .await
+            .expect("list with limit");
+        assert_eq!(limited.entries.len(), 2);
+        assert!(limited.truncated);
+
+        for path in ["..", "escape", outside.to_str().expect("utf8 path")] {
+            let error = list_filesystem_entries(&state, request(Some(path), None))
+                .await
+                .expect_err(path);
+            assert_eq!(error.code, -32602, "{path}");
+        }
+
+        shutdown_test_backend(&state.backend).await;
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_dir_all(&outside).ok();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn fs_write_replaces_file_atomically_and_rejects_symlink_escape() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = normalize_path(
+            &env::temp_dir().join(format!("clawdex-fs-write-{}-{nonce}", std::process::id())),
+        );
+        let outside = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-fs-write-outside-{}-{nonce}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::create_dir_all(&outside).expect("create outside dir");
+        std::os::unix::fs::symlink(&outside, root.join("escape")).expect("symlink");
+        let state = build_test_state_with_config(|config| {
+            config.workdir = root.clone();
+        })
+        .await;
+        let write = |path: &str, content: &str, create_dirs: bool| FileSystemWriteRequest {
+            path: path.to_string(),
+            content_base64: general_purpose::STANDARD.encode(content),
+            cwd: None,
+            create_dirs: Some(create_dirs),
+        };
+
+        let target = root.join("notes.txt");
+        std::fs::write(&target, "old").expect("seed file");
+        std::fs::set_permissions(&target, std::fs::Permissions::from_mode(0o640)).expect("chmod");
+        let written = write_filesystem_file(&state, write("notes.txt", "new contents", false))
+            .await
+            .expect("overwrite file");
+        assert_eq!(written.size_bytes, 12);
+        assert_eq!(
+            std::fs::read_to_string(&target).expect("read"),
+            "new contents"
+        );
+        assert_eq!(
+            std::fs::metadata(&target)
+                .expect("metadata")
+                .permissions()
+                .mode()
+                & 0o777,
+            0o640
+        );
+        let leftovers = std::fs::read_dir(&root)
+            .expect("read root")
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_name().to_string_lossy().contains("clawdex-tmp"))
+            .count();
+        assert_eq!(leftovers, 0);
+
+        assert!(
+            write_filesystem_file(&state, write("nested/dir/a.txt", "a", false))
+                .await
+                .is_err()
+        );
+        write_filesystem_file(&state, write("nested/dir/a.txt", "a", true))
+            .await
+            .expect("create dirs");
+        assert!(root.join("nested/dir/a.txt").is_file());
+
+        let escaped = write_filesystem_file(&state, write("escape/pwned.txt", "x", false))
+            .await
+            .expect_err("symlink escape");
+        assert_eq!(escaped.code, -32602);
+        assert!(!outside.join("pwned.txt").exists());
+
+        write_filesystem_file(&state, write("escape/new/dir/pwned.txt", "x", true))
+            .await
+            .expect_err("symlink escape with create_dirs");
+        assert!(!outside.join("new").exists());
+
+        shutdown_test_backend(&state.backend).await;
+        std::fs::remove_dir_all(&root).ok();
+        std::fs::remove_dir_all(&outside).ok();
+    }
+
+    #[tokio::test]
+    async fn attachment_upload_with_repeated_idempotency_key_writes_once() {
+        let state = build_test_state().await;
+        let thread_id = format!(
+            "thr_idempotent_{}",
+            SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system clock after unix epoch")
+                .as_nanos()
+        );
+        let upload = || AttachmentUploadRequest {
+            data_base64: "aGVsbG8=".to_string(),
+            file_name: Some("note.txt".to_string()),
+            mime_type: Some("text/plain".to_string()),
+            thread_id: Some(thread_id.clone()),
+            kind: None,
+            idempotency_key: Some("upload-1".to_string()),
+            expected_sha256: None,
+        };
+
+        let first = save_uploaded_attachment(upload(), &state)
+            .await
+            .expect("first upload");
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        let second = save_uploaded_attachment(upload(), &state)
+            .await
+            .expect("retried upload");
+        assert_eq!(first.path, second.path);
+
+        let matching = save_uploaded_attachment(
+            AttachmentUploadRequest {
+                expected_sha256: Some(
+                    "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string(),
+                ),
+                ..upload()
+            },
+            &state,
+        )
+        .await
+        .expect("retry with matching checksum");
+        assert_eq!(matching.path, first.path);
+        let mismatched = save_uploaded_attachment(
+            AttachmentUploadRequest {
+                expected_sha256: Some("0".repeat(64)),
+                ..upload()
+            },
+            &state,
+        )
+        .await
+        .expect_err("retry with a different checksum");
+        assert_eq!(mismatched.data, Some(json!({ "checksumMismatch": true })));
+
+        let dir = PathBuf::from(&first.path)
+            .parent()
+            .expect("attachment dir")
+            .to_path_buf();
+        assert_eq!(std::fs::read_dir(&dir).expect("read dir").count(), 1);
+
+        let other_key = save_uploaded_attachment(
+            AttachmentUploadRequest {
+                idempotency_key: Some("upload-2".to_string()),
+                ..upload()
+            },
+            &state,
+        )
+        .await
+        .expect("upload with new key");
+        assert_ne!(other_key.path, first.path);
+
+        std::fs::remove_dir_all(&dir).ok();
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn concurrent_attachment_uploads_with_one_key_write_once() {
+        let state = build_test_state().await;
+        let thread_id = format!(
+            "thr_idempotent_race_{}",
+            SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system clock after unix epoch")
+                .as_nanos()
+        );
+        let upload = || AttachmentUploadRequest {
+            data_base64: "aGVsbG8=".to_string(),
+            file_name: Some("note.txt".to_string()),
+            mime_type: Some("text/plain".to_string()),
+            thread_id: Some(thread_id.clone()),
+            kind: None,
+            idempotency_key: Some("upload-1".to_string()),
+            expected_sha256: None,
+        };
+
+        let (first, second) = tokio::join!(
+            save_uploaded_attachment(upload(), &state),
+            save_uploaded_attachment(upload(), &state)
+        );
+        let first = first.expect("first upload");
+        assert_eq!(first.path, second.expect("concurrent retry").path);
+        let dir = PathBuf::from(&first.path)
+            .parent()
+            .expect("attachment dir")
+            .to_path_buf();
+        assert_eq!(std::fs::read_dir(&dir).expect("read dir").count(), 1);
+
+        std::fs::remove_dir_all(&dir).ok();
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn attachment_upload_verifies_expected_sha256() {
+        let state = build_test_state().await;
+        let thread_id = format!(
+            "thr_checksum_{}",
+            SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .expect("system clock after unix epoch")
+                .as_nanos()
+        );
+        let upload = |expected_sha256: &str| AttachmentUploadRequest {
+            data_base64: "aGVsbG8=".to_string(),
+            file_name: Some("note.txt".to_string()),
+            mime_type: Some("text/plain".to_string()),
+            thread_id: Some(thread_id.clone()),
+            kind: None,
+            idempotency_key: None,
+            expected_sha256: Some(expected_sha256.to_string()),
+        };
+        // sha256("hello")
+        let hello_sha256 = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+        let stored = save_uploaded_attachment(upload(&hello_sha256.to_ascii_uppercase()), &state)
+            .await
+            .expect("matching checksum");
+        assert_eq!(std::fs::read(&stored.path).expect("read upload"), b"hello");
+        let dir = PathBuf::from(&stored.path)
+            .parent()
+            .expect("attachment dir")
+            .to_path_buf();
+
+        let mismatch = save_uploaded_attachment(upload(&"0".repeat(64)), &state)
+            .await
+            .expect_err("mismatched checksum");
+        assert_eq!(mismatch.code, -32602);
+        assert_eq!(mismatch.data, Some(json!({ "checksumMismatch": true })));
+        assert_eq!(std::fs::read_dir(&dir).expect("read dir").count(), 1);
+
+        let malformed = save_uploaded_attachment(upload("abc"), &state)
+            .await
+            .expect_err("malformed checksum");
+        assert_eq!(malformed.code, -32602);
+        assert!(malformed.data.is_none());
+
+        std::fs::remove_dir_all(&dir).ok();
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[test]
+    fn attachments_dir_resolves_relative_to_workdir_or_absolute() {
+        let workdir = PathBuf::from("/srv/project");
+        assert_eq!(
+            resolve_attachments_dir(&workdir, None).expect("default"),
+            PathBuf::from("/srv/project/.clawdex-mobile-attachments")
+        );
+        assert_eq!(
+            resolve_attachments_dir(&workdir, Some("uploads/mobile".to_string()))
+                .expect("relative"),
+            PathBuf::from("/srv/project/uploads/mobile")
+        );
+        assert_eq!(
+            resolve_attachments_dir(&workdir, Some("/mnt/volume/../attachments".to_string()))
+                .expect("absolute"),
+            PathBuf::from("/mnt/attachments")
+        );
+        assert!(resolve_attachments_dir(&workdir, Some("../elsewhere".to_string())).is_err());
+        assert!(resolve_attachments_dir(&workdir, Some(".".to_string())).is_err());
+    }
+
+    #[tokio::test]
+    async fn attachment_upload_uses_absolute_attachments_dir_override() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let attachments_dir = env::temp_dir().join(format!("clawdex-attachments-volume-{nonce}"));
+        let state = build_test_state_with_config(|config| {
+            config.attachments_dir = attachments_dir.clone();
+        })
+        .await;
+
+        let uploaded = save_uploaded_attachment(
+            AttachmentUploadRequest {
+                data_base64: "aGVsbG8=".to_string(),
+                file_name: Some("note.txt".to_string()),
+                mime_type: Some("text/plain".to_string()),
+                thread_id: Some("codex:thr_volume".to_string()),
+                kind: None,
+                idempotency_key: None,
+                expected_sha256: None,
+            },
+            &state,
+        )
+        .await
+        .expect("upload into attachments dir");
+        let path = PathBuf::from(&uploaded.path);
+        assert!(path.starts_with(attachments_dir.join("thr_volume")));
+        assert_eq!(std::fs::read(&path).expect("read attachment"), b"hello");
+
+        std::fs::remove_dir_all(&attachments_dir).ok();
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[test]
+    fn voice_transcribe_request_deserializes_legacy_and_extended_shapes() {
+        let legacy: VoiceTranscribeRequest = serde_json::from_value(json!({
+            "dataBase64": "YQ==",
+            "prompt": "hello"
+        }))
+        .expect("deserialize legacy request shape");
+        assert_eq!(legacy.data_base64, "YQ==");
+        assert_eq!(legacy.prompt.as_deref(), Some("hello"));
+        assert!(legacy.file_name.is_none());
+        assert!(legacy.mime_type.is_none());
+
+        let extended: VoiceTranscribeRequest = serde_json::from_value(json!({
+            "dataBase64": "YQ==",
+            "prompt": "hello",
+            "fileName": "audio.m4a",
+            "mimeType": "audio/mp4"
+        }))
+        .expect("deserialize extended request shape");
+        assert_eq!(extended.data_base64, "YQ==");
+        assert_eq!(extended.prompt.as_deref(), Some("hello"));
+        assert_eq!(extended.file_name.as_deref(), Some("audio.m4a"));
+        assert_eq!(extended.mime_type.as_deref(), Some("audio/mp4"));
+    }
+
+    #[test]
+    fn transcription_file_name_normalization_sanitizes_and_sets_extension() {
+        assert_eq!(
+            normalize_transcription_file_name(Some("../voice note"), "audio/mp4"),
+            "voice_note.m4a".to_string()
+        );
+        assert_eq!(
+            normalize_transcription_file_name(None, "audio/wav"),
+            "audio.wav".to_string()
+        );
+        assert_eq!(
+            normalize_transcription_file_name(Some("meeting"), "audio/webm"),
+            "meeting.webm".to_string()
+        );
+    }
+
+    #[test]
+    fn disallowed_control_character_detection_flags_shell_metacharacters() {
+        assert!(!contains_disallowed_control_chars("git status"));
+        assert!(contains_disallowed_control_chars("echo hi; ls"));
+        assert!(contains_disallowed_control_chars("echo `whoami`"));
+    }
+
+    #[test]
+    fn normalize_path_collapses_current_and_parent_components() {
+        assert_eq!(
+            normalize_path(Path::new("/tmp/./bridge/../repo/./main.rs")),
+            PathBuf::from("/tmp/repo/main.rs")
+        );
+        assert_eq!(
+            normalize_path(Path::new("a/b/../c/./d")),
+            PathBuf::from("a/c/d")
+        );
+    }
+
+    #[test]
+    fn resolve_local_image_path_requires_absolute_paths() {
+        assert_eq!(
+            resolve_local_image_path("/tmp/../tmp/example.png").unwrap(),
+            PathBuf::from("/tmp/example.png")
+        );
+        assert_eq!(
+            resolve_local_image_path("relative/example.png").unwrap_err(),
+            "Image path must be absolute"
+        );
+    }
+
+    #[test]
+    fn infer_image_content_type_from_path_supports_common_extensions() {
+        assert_eq!(
+            infer_image_content_type_from_path(Path::new("/tmp/example.png")),
+            Some("image/png")
+        );
+        assert_eq!(
+            infer_image_content_type_from_path(Path::new("/tmp/example.JPG")),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            infer_image_content_type_from_path(Path::new("/tmp/example.txt")),
+            None
+        );
+    }
+
+    #[test]
+    fn constant_time_eq_handles_equal_and_different_strings() {
+        assert!(constant_time_eq("secret-token", "secret-token"));
+        assert!(!constant_time_eq("secret-token", "secret-tok3n"));
+        assert!(!constant_time_eq("secret-token", "secret-token-extra"));
+    }
+
+    #[test]
+    fn build_pairing_payload_includes_url_and_token_for_connectable_host() {
+        let config = BridgeConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8787,
+            preview_port: 8788,
+            connect_url: None,
+            preview_connect_url: None,
+            workdir: PathBuf::from("/tmp/workdir"),
+            cli_bin: "codex".to_string(),
+            opencode_cli_bin: "opencode".to_string(),
+            cursor_app_server_bin: "cursor-app-server".to_string(),
+            active_engine: BridgeRuntimeEngine::Codex,
+            enabled_engines: vec![BridgeRuntimeEngine::Codex],
+            opencode_host: "127.0.0.1".to_string(),
+            opencode_port: 4090,
+            opencode_server_username: "opencode".to_string(),
+            opencode_server_password: Some("secret-token".to_string()),
+            auth_token: Some("secret-token".to_string()),
+            auth_enabled: true,
+            allow_insecure_no_auth: false,
+            allow_query_token_auth: false,
+            allow_outside_root_cwd: false,
+            disable_terminal_exec: false,
+            terminal_readonly: false,
+            terminal_allowed_commands: HashSet::new(),
+            show_pairing_qr: true,
+            ws_max_frame_bytes: DEFAULT_WS_MAX_FRAME_BYTES,
+            terminal_history_size: DEFAULT_TERMINAL_HISTORY_SIZE,
+            decline_on_no_clients_grace: None,
+            terminal_blocked_args: HashMap::new(),
+            enable_debug_trace: false,
+            app_server_args: Vec::new(),
+            app_server_env: Vec::new(),
+            rollout_max_tracked_files: DEFAULT_ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES,
+            git_watch: false,
+            client_rate_limit: None,
+            max_clients: None,
+            cleanup_attachments_on_archive: false,
+            ws_client_queue_capacity: DEFAULT_WS_CLIENT_QUEUE_CAPACITY,
+            attachments_dir: env::temp_dir().join(MOBILE_ATTACHMENTS_DIR),
+            rollout_include_events: HashSet::new(),
+            rollout_extra_record_types: HashSet::new(),
+            extra_forwarded_methods: HashSet::new(),
+            cors_origins: HashSet::new(),
+            disable_rollout_sync: false,
+            replay_compress: false,
+            command_signing_key: None,
+            app_server_max_line_bytes: DEFAULT_APP_SERVER_MAX_LINE_BYTES,
+            reject_legacy_approvals: false,
+            rollout_dedup_window: DEFAULT_ROLLOUT_DEDUP_WINDOW,
+            terminal_max_output_bytes: DEFAULT_TERMINAL_MAX_OUTPUT_BYTES,
+            strict_jsonrpc: false,
+            guard_concurrent_turns: false,
+        };
+
+        let payload = build_pairing_payload(&config).expect("pairing payload");
+        let parsed: Value = serde_json::from_str(&payload).expect("valid json");
+
+        assert_eq!(parsed["type"], "clawdex-bridge-pair");
+        assert_eq!(parsed["bridgeUrl"], "http://127.0.0.1:8787");
+        assert_eq!(parsed["bridgeToken"], "secret-token");
+    }
+
+    #[test]
+    fn build_pairing_payload_uses_token_only_fallback_for_unspecified_bind_host() {
+        let config = BridgeConfig {
+            host: "0.0.0.0".to_string(),
+            port: 8787,
+            preview_port: 8788,
+            connect_url: None,
+            preview_connect_url: None,
+            workdir: PathBuf::from("/tmp/workdir"),
+            cli_bin: "codex".to_string(),
+            opencode_cli_bin: "opencode".to_string(),
+            cursor_app_server_bin: "cursor-app-server".to_string(),
+            active_engine: BridgeRuntimeEngine::Codex,
+            enabled_engines: vec![BridgeRuntimeEngine::Codex],
+            opencode_host: "127.0.0.1".to_string(),
+            opencode_port: 4090,
+            opencode_server_username: "opencode".to_string(),
+            opencode_server_password: Some("secret-token".to_string()),
+            auth_token: Some("secret-token".to_string()),
+            auth_enabled: true,
+            allow_insecure_no_auth: false,
+            allow_query_token_auth: false,
+            allow_outside_root_cwd: false,
+            disable_terminal_exec: false,
+            terminal_readonly: false,
+            terminal_allowed_commands: HashSet::new(),
+            show_pairing_qr: true,
+            ws_max_frame_bytes: DEFAULT_WS_MAX_FRAME_BYTES,
+            terminal_history_size: DEFAULT_TERMINAL_HISTORY_SIZE,
+            decline_on_no_clients_grace: None,
+            terminal_blocked_args: HashMap::new(),
+            enable_debug_trace: false,
+            app_server_args: Vec::new(),
+            app_server_env: Vec::new(),
+            rollout_max_tracked_files: DEFAULT_ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES,
+            git_watch: false,
+            client_rate_limit: None,
+            max_clients: None,
+            cleanup_attachments_on_archive: false,
+            ws_client_queue_capacity: DEFAULT_WS_CLIENT_QUEUE_CAPACITY,
+            attachments_dir: env::temp_dir().join(MOBILE_ATTACHMENTS_DIR),
+            rollout_include_events: HashSet::new(),
+            rollout_extra_record_types: HashSet::new(),
+            extra_forwarded_methods: HashSet::new(),
+            cors_origins: HashSet::new(),
+            disable_rollout_sync: false,
+            replay_compress: false,
+            command_signing_key: None,
+            app_server_max_line_bytes: DEFAULT_APP_SERVER_MAX_LINE_BYTES,
+            reject_legacy_approvals: false,
+            rollout_dedup_window: DEFAULT_ROLLOUT_DEDUP_WINDOW,
+            terminal_max_output_bytes: DEFAULT_TERMINAL_MAX_OUTPUT_BYTES,
+            strict_jsonrpc: false,
+            guard_concurrent_turns: false,
+        };
+
+        assert!(build_pairing_payload(&config).is_none());
+
+        let fallback = build_token_only_pairing_payload(&config).expect("token-only payload");
+        let parsed: Value = serde_json::from_str(&fallback).expect("valid json");
+
+        assert_eq!(parsed["type"], "clawdex-bridge-token");
+        assert_eq!(parsed["bridgeToken"], "secret-token");
+    }
+
+    #[test]
+    fn build_pairing_payload_prefers_connect_url_when_configured() {
+        let config = BridgeConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8787,
+            preview_port: 8788,
+            connect_url: Some("https://octocat-8787.app.github.dev".to_string()),
+            preview_connect_url: Some("https://octocat-8788.app.github.dev".to_string()),
+            workdir: PathBuf::from("/tmp/workdir"),
+            cli_bin: "codex".to_string(),
+            opencode_cli_bin: "opencode".to_string(),
+            cursor_app_server_bin: "cursor-app-server".to_string(),
+            active_engine: BridgeRuntimeEngine::Codex,
+            enabled_engines: vec![BridgeRuntimeEngine::Codex],
+            opencode_host: "127.0.0.1".to_string(),
+            opencode_port: 4090,
+            opencode_server_username: "opencode".to_string(),
+            opencode_server_password: Some("secret-token".to_string()),
+            auth_token: Some("secret-token".to_string()),
+            auth_enabled: true,
+            allow_insecure_no_auth: false,
+            allow_query_token_auth: false,
+            allow_outside_root_cwd: false,
+            disable_terminal_exec: false,
+            terminal_readonly: false,
+            terminal_allowed_commands: HashSet::new(),
+            show_pairing_qr: true,
+            ws_max_frame_bytes: DEFAULT_WS_MAX_FRAME_BYTES,
+            terminal_history_size: DEFAULT_TERMINAL_HISTORY_SIZE,
+            decline_on_no_clients_grace: None,
+            terminal_blocked_args: HashMap::new(),
+            enable_debug_trace: false,
+            app_server_args: Vec::new(),
+            app_server_env: Vec::new(),
+            rollout_max_tracked_files: DEFAULT_ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES,
+            git_watch: false,
+            client_rate_limit: None,
+            max_clients: None,
+            cleanup_attachments_on_archive: false,
+            ws_client_queue_capacity: DEFAULT_WS_CLIENT_QUEUE_CAPACITY,
+            attachments_dir: env::temp_dir().join(MOBILE_ATTACHMENTS_DIR),
+            rollout_include_events: HashSet::new(),
+            rollout_extra_record_types: HashSet::new(),
+            extra_forwarded_methods: HashSet::new(),
+            cors_origins: HashSet::new(),
+            disable_rollout_sync: false,
+            replay_compress: false,
+            command_signing_key: None,
+            app_server_max_line_bytes: DEFAULT_APP_SERVER_MAX_LINE_BYTES,
+            reject_legacy_approvals: false,
+            rollout_dedup_window: DEFAULT_ROLLOUT_DEDUP_WINDOW,
+            terminal_max_output_bytes: DEFAULT_TERMINAL_MAX_OUTPUT_BYTES,
+            strict_jsonrpc: false,
+            guard_concurrent_turns: false,
+        };
+
+        let payload = build_pairing_payload(&config).expect("pairing payload");
+        let parsed: Value = serde_json::from_str(&payload).expect("valid json");
+
+        assert_eq!(parsed["type"], "clawdex-bridge-pair");
+        assert_eq!(parsed["bridgeUrl"], "https://octocat-8787.app.github.dev");
+        assert_eq!(parsed["bridgeToken"], "secret-token");
+    }
+
+    #[test]
+    fn bridge_config_authorization_validates_header_and_query_token_paths() {
+        let base = BridgeConfig {
+            host: "127.0.0.1".to_string(),
+            port: 8787,
+            preview_port: 8788,
+            connect_url: None,
+            preview_connect_url: None,
+            workdir: PathBuf::from("/tmp/workdir"),
+            cli_bin: "codex".to_string(),
+            opencode_cli_bin: "opencode".to_string(),
+            cursor_app_server_bin: "cursor-app-server".to_string(),
+            active_engine: BridgeRuntimeEngine::Codex,
+            enabled_engines: vec![BridgeRuntimeEngine::Codex, BridgeRuntimeEngine::Opencode],
+            opencode_host: "127.0.0.1".to_string(),
+            opencode_port: 4090,
+            opencode_server_username: "opencode".to_string(),
+            opencode_server_password: Some("secret-token".to_string()),
+            auth_token: Some("secret-token".to_string()),
+            auth_enabled: true,
+            allow_insecure_no_auth: false,
+            allow_query_token_auth: false,
+            allow_outside_root_cwd: false,
+            disable_terminal_exec: false,
+            terminal_readonly: false,
+            terminal_allowed_commands: HashSet::new(),
+            show_pairing_qr: false,
+            ws_max_frame_bytes: DEFAULT_WS_MAX_FRAME_BYTES,
+            terminal_history_size: DEFAULT_TERMINAL_HISTORY_SIZE,
+            decline_on_no_clients_grace: None,
+            terminal_blocked_args: HashMap::new(),
+            enable_debug_trace: false,
+            app_server_args: Vec::new(),
+            app_server_env: Vec::new(),
+            rollout_max_tracked_files: DEFAULT_ROLLOUT_LIVE_SYNC_MAX_TRACKED_FILES,
+            git_watch: false,
+            client_rate_limit: None,
+            max_clients: None,
+            cleanup_attachments_on_archive: false,
+            ws_client_queue_capacity: DEFAULT_WS_CLIENT_QUEUE_CAPACITY,
+            attachments_dir: env::temp_dir().join(MOBILE_ATTACHMENTS_DIR),
+            rollout_include_events: HashSet::new(),
+            rollout_extra_record_types: HashSet::new(),
+            extra_forwarded_methods: HashSet::new(),
+            cors_origins: HashSet::new(),
+            disable_rollout_sync: false,
+            replay_compress: false,
+            command_signing_key: None,
+            app_server_max_line_bytes: DEFAULT_APP_SERVER_MAX_LINE_BYTES,
+            reject_legacy_approvals: false,
+            rollout_dedup_window: DEFAULT_ROLLOUT_DEDUP_WINDOW,
+            terminal_max_output_bytes: DEFAULT_TERMINAL_MAX_OUTPUT_BYTES,
+            strict_jsonrpc: false,
+            guard_concurrent_turns: false,
+        };
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "authorization",
+            "bearer secret-token".parse().expect("header value"),
+        );
+        assert!(base.is_authorized_with_bridge_token(&headers, None));
+        assert!(!base.is_authorized_with_bridge_token(&HeaderMap::new(), Some("secret-token")));
+        assert!(!base.is_authorized_with_bridge_token(&HeaderMap::new(), Some("secret-tok3n")));
+
+        let mut query_allowed = base.clone();
+        query_allowed.allow_query_token_auth = true;
+        assert!(
+            query_allowed.is_authorized_with_bridge_token(&HeaderMap::new(), Some("secret-token"))
+        );
+        assert!(query_allowed
+            .is_authorized_with_bridge_token(&HeaderMap::new(), Some("  secret-token  ")));
+
+        let mut auth_disabled = base;
+        auth_disabled.auth_enabled = false;
+        auth_disabled.auth_token = None;
+        assert!(!auth_disabled.is_authorized_with_bridge_token(&HeaderMap::new(), None));
+    }
+
+    #[test]
+    fn git_watch_debounce_waits_for_quiet_window_and_caps_delay() {
+        let window = Duration::from_millis(100);
+        let mut debounce = GitWatchDebounce::new(window, Duration::from_millis(250));
+        let start = Instant::now();
+        assert!(debounce.deadline().is_none());
+
+        debounce.record("src/lib.rs".to_string(), start);
+        debounce.record("src/lib.rs".to_string(), start + Duration::from_millis(60));
+        assert!(debounce
+            .take_due(start + Duration::from_millis(120))
+            .is_none());
+        assert_eq!(
+            debounce.take_due(start + Duration::from_millis(160)),
+            Some(vec!["src/lib.rs".to_string()])
+        );
+        assert!(debounce.deadline().is_none());
+
+        let burst_start = start + Duration::from_secs(1);
+        for step in 0..5 {
+            debounce.record(
+                format!("file-{step}"),
+                burst_start + Duration::from_millis(step * 80),
+            );
+        }
+        assert_eq!(
+            debounce.deadline(),
+            Some(burst_start + Duration::from_millis(250))
+        );
+    }
+
+    #[test]
+    fn git_watch_relative_path_skips_git_internals() {
+        let root = Path::new("/repo");
+        assert_eq!(
+            git_watch_relative_path(root, Path::new("/repo/src/main.rs")).as_deref(),
+            Some("src/main.rs")
+        );
+        assert_eq!(
+            git_watch_relative_path(root, Path::new("/repo/.git/index")).as_deref(),
+            Some(".git/index")
+        );
+        assert!(git_watch_relative_path(root, Path::new("/repo/.git/objects/ab/cd")).is_none());
+        assert!(git_watch_relative_path(root, Path::new("/repo/.git/index.lock")).is_none());
+        assert!(git_watch_relative_path(root, Path::new("/elsewhere/file")).is_none());
+        assert!(git_watch_relative_path(root, Path::new("/repo")).is_none());
+    }
+
+    #[tokio::test]
+    async fn git_watch_broadcasts_one_status_per_debounced_change() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = normalize_path(
+            &env::temp_dir().join(format!("clawdex-git-watch-{}-{nonce}", std::process::id())),
+        );
+        std::fs::create_dir_all(&root).expect("create watch root");
+        let terminal = Arc::new(TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            16,
+        ));
+        let git = Arc::new(GitService::new(terminal, root.clone(), false));
+        git.init(None, None).await.expect("git init");
+        std::fs::write(root.join(".gitignore"), "build/\n").expect("write gitignore");
+
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let watch_task = tokio::spawn(run_git_watch(
+            events_rx,
+            git,
+            hub.clone(),
+            root.clone(),
+            GitWatchDebounce::new(Duration::from_millis(50), GIT_WATCH_MAX_DELAY),
+            GitWatchAudience::Everyone,
+        ));
+
+        std::fs::write(root.join("notes.txt"), "hello").expect("write tracked file");
+        for path in ["notes.txt", ".git/objects/ab/cdef", "notes.txt"] {
+            events_tx.send(root.join(path)).expect("send event");
+        }
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["method"], "bridge/git/updated");
+        assert!(payload["params"].to_string().contains("notes.txt"));
+        assert!(timeout(Duration::from_millis(200), rx.recv())
+            .await
+            .is_err());
+
+        events_tx
+            .send(root.join("build/output.bin"))
+            .expect("send ignored event");
+        assert!(timeout(Duration::from_millis(300), rx.recv())
+            .await
+            .is_err());
+
+        drop(events_tx);
+        watch_task.await.expect("watch task exits");
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn git_watch_is_not_retriggered_by_its_own_status_refresh() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-git-watch-self-{}-{nonce}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&root).expect("create watch root");
+        let terminal = Arc::new(TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            16,
+        ));
+        let git = Arc::new(GitService::new(terminal, root.clone(), false));
+        git.init(None, None).await.expect("git init");
+        std::fs::write(root.join("notes.txt"), "hello").expect("write tracked file");
+        for args in [
+            &["add", "notes.txt"][..],
+            &[
+                "-c",
+                "user.name=Bridge Test",
+                "-c",
+                "user.email=bridge@example.com",
+                "commit",
+                "-qm",
+                "initial",
+            ][..],
+        ] {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&root)
+                .args(args)
+                .status()
+                .expect("run git");
+            assert!(status.success(), "git {args:?} failed");
+        }
+
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let (watcher, events_rx) = start_git_watcher(&root).expect("start watcher");
+        let watch_task = tokio::spawn(run_git_watch(
+            events_rx,
+            git,
+            hub.clone(),
+            root.clone(),
+            GitWatchDebounce::new(Duration::from_millis(50), GIT_WATCH_MAX_DELAY),
+            GitWatchAudience::Everyone,
+        ));
+
+        // Rewriting the same content only changes the file's stat data, which a plain
+        // `git status` would refresh by rewriting `.git/index`.
+        sleep(Duration::from_millis(1100)).await;
+        std::fs::write(root.join("notes.txt"), "hello").expect("rewrite tracked file");
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["method"], "bridge/git/updated");
+        assert!(timeout(Duration::from_millis(500), rx.recv())
+            .await
+            .is_err());
+
+        watch_task.abort();
+        drop(watcher);
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn git_watch_flushes_a_steady_event_stream_after_max_delay() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-git-watch-steady-{}-{nonce}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&root).expect("create watch root");
+        let terminal = Arc::new(TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            16,
+        ));
+        let git = Arc::new(GitService::new(terminal, root.clone(), false));
+        git.init(None, None).await.expect("git init");
+        std::fs::write(root.join("notes.txt"), "hello").expect("write file");
+
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let (events_tx, events_rx) = mpsc::unbounded_channel();
+        let watch_task = tokio::spawn(run_git_watch(
+            events_rx,
+            git,
+            hub.clone(),
+            root.clone(),
+            GitWatchDebounce::new(Duration::from_millis(200), Duration::from_millis(300)),
+            GitWatchAudience::Everyone,
+        ));
+        // Events arrive well inside the quiet window, so only the max delay can flush them.
+        let producer_root = root.clone();
+        let producer = tokio::spawn(async move {
+            loop {
+                for _ in 0..50 {
+                    if events_tx.send(producer_root.join("notes.txt")).is_err() {
+                        return;
+                    }
+                }
+                sleep(Duration::from_millis(5)).await;
+            }
+        });
+
+        let started = Instant::now();
+        let payload = timeout(Duration::from_secs(3), rx.recv())
+            .await
+            .expect("flushed before the stream stops")
+            .expect("client channel open");
+        let Message::Text(text) = payload else {
+            panic!("expected text websocket frame");
+        };
+        assert!(text.contains("bridge/git/updated"));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        producer.abort();
+        let _ = producer.await;
+        watch_task.await.expect("watch task exits");
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn git_status_subscription_notifies_only_subscribed_clients() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-git-subscribe-{}-{nonce}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&root).expect("create repo root");
+        let state = build_test_state_with_config(|config| {
+            config.workdir = root.clone();
+        })
+        .await;
+        state.git.init(None, None).await.expect("git init");
+        let (subscriber_id, mut subscriber_rx) = add_test_client(&state.hub).await;
+        let (_other_id, mut other_rx) = add_test_client(&state.hub).await;
+
+        let subscribed = handle_bridge_method(
+            "bridge/git/statusSubscribe",
+            Some(json!({ "cwd": root.to_string_lossy() })),
+            &state,
+            subscriber_id,
+        )
+        .await
+        .expect("subscribe");
+        assert_eq!(subscribed["cwd"], root.to_string_lossy().as_ref());
+
+        std::fs::write(root.join("notes.txt"), "hello").expect("write file");
+        let payload = recv_client_json(&mut subscriber_rx).await;
+        assert_eq!(payload["method"], "bridge/git/updated");
+        assert!(payload["params"].to_string().contains("notes.txt"));
+        assert!(timeout(Duration::from_millis(200), other_rx.recv())
+            .await
+            .is_err());
+
+        let unsubscribed = handle_bridge_method(
+            "bridge/git/statusUnsubscribe",
+            Some(json!({ "cwd": root.to_string_lossy() })),
+            &state,
+            subscriber_id,
+        )
+        .await
+        .expect("unsubscribe");
+        assert_eq!(unsubscribed["removed"], true);
+        assert!(state
+            .git_status_subscriptions
+            .subscribers(&root)
+            .await
+            .is_empty());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn git_status_subscription_to_the_globally_watched_repo_is_not_notified_twice() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-git-subscribe-global-{}-{nonce}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&root).expect("create repo root");
+        let state = build_test_state_with_config(|config| {
+            config.workdir = root.clone();
+        })
+        .await;
+        state.git.init(None, None).await.expect("git init");
+        let (subscriber_id, mut subscriber_rx) = add_test_client(&state.hub).await;
+
+        let (watcher, events_rx) = start_git_watcher(&root).expect("start global watcher");
+        let global_watch = tokio::spawn(run_git_watch(
+            events_rx,
+            state.git.clone(),
+            state.hub.clone(),
+            root.clone(),
+            GitWatchDebounce::new(Duration::from_millis(50), GIT_WATCH_MAX_DELAY),
+            GitWatchAudience::Everyone,
+        ));
+        let subscriptions = Arc::new(
+            GitStatusSubscriptions::new(
+                state.git.clone(),
+                state.hub.clone(),
+                Duration::from_millis(50),
+            )
+            .with_global_watch_root(root.clone()),
+        );
+        subscriptions
+            .subscribe(subscriber_id, Some(&root.to_string_lossy()))
+            .await
+            .expect("subscribe");
+        assert!(subscriptions.watches.lock().await[&root].task.is_none());
+        assert_eq!(subscriptions.subscribers(&root).await, vec![subscriber_id]);
+
+        std::fs::write(root.join("notes.txt"), "hello").expect("write file");
+        let payload = recv_client_json(&mut subscriber_rx).await;
+        assert_eq!(payload["method"], "bridge/git/updated");
+        assert!(timeout(Duration::from_millis(300), subscriber_rx.recv())
+            .await
+            .is_err());
+
+        global_watch.abort();
+        drop(watcher);
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn git_status_subscription_racing_a_disconnect_leaves_no_watch() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let root = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-git-subscribe-disconnect-{}-{nonce}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&root).expect("create repo root");
+        let state = build_test_state_with_config(|config| {
+            config.workdir = root.clone();
+        })
+        .await;
+        state.git.init(None, None).await.expect("git init");
+        let (client_id, _rx) = add_test_client(&state.hub).await;
+        let subscriptions = Arc::new(GitStatusSubscriptions::new(
+            state.git.clone(),
+            state.hub.clone(),
+            Duration::from_millis(50),
+        ));
+
+        // Hold the watches lock so the subscribe parks after resolving the repo root.
+        let watches = subscriptions.watches.lock().await;
+        let subscribe = tokio::spawn({
+            let subscriptions = subscriptions.clone();
+            let cwd = root.to_string_lossy().to_string();
+            async move { subscriptions.subscribe(client_id, Some(&cwd)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        state.hub.remove_client(client_id).await;
+        drop(watches);
+        subscriptions.remove_client(client_id).await;
+
+        assert!(subscribe.await.expect("subscribe task").is_err());
+        assert!(subscriptions.watches.lock().await.is_empty());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn removing_client_drops_its_pending_forwarded_requests() {
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub.clone()).await;
+        let (gone_client, _gone_rx) = add_test_client(&hub).await;
+        let (kept_client, mut kept_rx) = add_test_client(&hub).await;
+
+        for (client_id, request_id) in [
+            (gone_client, "gone-1"),
+            (gone_client, "gone-2"),
+            (kept_client, "kept-1"),
+        ] {
+            bridge
+                .forward_request(client_id, json!(request_id), "thread/list", None)
+                .await
+                .expect("forward request");
+        }
+
+        hub.remove_client(gone_client).await;
+        assert_eq!(bridge.drop_client_pending_requests(gone_client).await, 2);
+        assert_eq!(bridge.drop_client_pending_requests(gone_client).await, 0);
+        assert_eq!(bridge.pending_requests.lock().await.len(), 1);
+        assert!(!bridge
+            .pending_requests_by_client
+            .lock()
+            .await
+            .contains_key(&gone_client));
+
+        bridge
+            .handle_response(json!({ "id": 3, "result": { "data": [] } }))
+            .await;
+        let payload = recv_client_json(&mut kept_rx).await;
+        assert_eq!(payload["id"], "kept-1");
+        assert!(bridge.pending_requests.lock().await.is_empty());
+        assert!(bridge.pending_requests_by_client.lock().await.is_empty());
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn app_server_batch_responses_route_each_element() {
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub.clone()).await;
+        let (client_id, mut rx) = add_test_client(&hub).await;
+
+        for request_id in ["batch-1", "batch-2"] {
+            bridge
+                .forward_request(client_id, json!(request_id), "thread/list", None)
+                .await
+                .expect("forward request");
+        }
+
+        bridge
+            .handle_incoming(json!([
+                { "id": 2, "result": { "second": true } },
+                { "id": 1, "result": { "first": true } },
+            ]))
+            .await;
+
+        let second = recv_client_json(&mut rx).await;
+        assert_eq!(second["id"], "batch-2");
+        assert_eq!(second["result"]["second"], true);
+        let first = recv_client_json(&mut rx).await;
+        assert_eq!(first["id"], "batch-1");
+        assert_eq!(first["result"]["first"], true);
+        assert!(bridge.pending_requests.lock().await.is_empty());
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn concurrent_turn_starts_on_one_thread_are_rejected_until_the_turn_ends() {
+        let state =
+            build_test_state_with_config(|config| config.guard_concurrent_turns = true).await;
+        let (client_id, _rx) = add_test_client(&state.hub).await;
+        let bridge = state.backend.codex_backend().expect("codex backend");
+        let turn_start = |id: u64, thread_id: &str| {
+            json!({
+                "id": id,
+                "method": "turn/start",
+                "params": { "threadId": thread_id, "input": [] },
+            })
+        };
+
+        assert!(
+            dispatch_client_request(client_id, &turn_start(1, "codex:thr_guard"), &state)
+                .await
+                .is_none()
+        );
+        let rejected = dispatch_client_request(client_id, &turn_start(2, "thr_guard"), &state)
+            .await
+            .expect("second start is rejected");
+        assert_eq!(rejected["id"], 2);
+        assert_eq!(rejected["error"]["code"], -32000);
+        assert_eq!(rejected["error"]["data"]["turnInFlight"], true);
+
+        // Other threads are independent, and a failed start frees its thread at once.
+        assert!(
+            dispatch_client_request(client_id, &turn_start(3, "thr_other"), &state)
+                .await
+                .is_none()
+        );
+        bridge
+            .handle_response(json!({ "id": 2, "error": { "code": -32000, "message": "boom" } }))
+            .await;
+        assert!(
+            dispatch_client_request(client_id, &turn_start(4, "thr_other"), &state)
+                .await
+                .is_none()
+        );
+
+        state
+            .hub
+            .broadcast_notification("turn/completed", json!({ "threadId": "codex:thr_guard" }))
+            .await;
+        assert!(
+            dispatch_client_request(client_id, &turn_start(5, "thr_guard"), &state)
+                .await
+                .is_none()
+        );
+        state
+            .hub
+            .broadcast_notification(
+                "codex/event/task_complete",
+                json!({ "msg": { "type": "task_complete", "thread_id": "thr_other" } }),
+            )
+            .await;
+        assert!(
+            dispatch_client_request(client_id, &turn_start(6, "thr_other"), &state)
+                .await
+                .is_none()
+        );
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn dropped_turn_start_requests_release_their_thread() {
+        let state =
+            build_test_state_with_config(|config| config.guard_concurrent_turns = true).await;
+        let (client_id, _rx) = add_test_client(&state.hub).await;
+        let (other_client_id, mut other_rx) = add_test_client(&state.hub).await;
+        let bridge = state.backend.codex_backend().expect("codex backend");
+        let turn_start = |id: u64, thread_id: &str| {
+            json!({
+                "id": id,
+                "method": "turn/start",
+                "params": { "threadId": thread_id, "input": [] },
+            })
+        };
+
+        // The client disconnects before its turn/start is answered.
+        assert!(
+            dispatch_client_request(client_id, &turn_start(1, "thr_gone"), &state)
+                .await
+                .is_none()
+        );
+        state.hub.remove_client(client_id).await;
+        state.backend.drop_client_pending_requests(client_id).await;
+        assert!(
+            dispatch_client_request(other_client_id, &turn_start(2, "thr_gone"), &state)
+                .await
+                .is_none()
+        );
+
+        // The app-server goes away with that start still pending.
+        bridge.fail_all_pending("app-server exited").await;
+        assert!(
+            dispatch_client_request(other_client_id, &turn_start(3, "thr_gone"), &state)
+                .await
+                .is_none()
+        );
+
+        // A second start reusing a pending id is rejected without holding its thread.
+        assert!(
+            dispatch_client_request(other_client_id, &turn_start(4, "thr_a"), &state)
+                .await
+                .is_none()
+        );
+        assert!(
+            dispatch_client_request(other_client_id, &turn_start(4, "thr_b"), &state)
+                .await
+                .is_none()
+        );
+        let duplicate = loop {
+            let payload = recv_client_json(&mut other_rx).await;
+            if payload["id"] == 4 {
+                break payload;
+            }
+        };
+        assert_eq!(duplicate["error"]["data"]["duplicateId"], true);
+        assert!(
+            dispatch_client_request(other_client_id, &turn_start(5, "thr_b"), &state)
+                .await
+                .is_none()
+        );
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn app_server_forwarded_response_routes_to_original_client_request_id() {
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub.clone()).await;
+        let (client_id, mut rx) = add_test_client(&hub).await;
+
+        bridge
+            .forward_request(
+                client_id,
+                json!("client-req-1"),
+                "thread/start",
+                Some(json!({ "foo": "bar" })),
+            )
+            .await
+            .expect("forward request");
+
+        bridge
+            .handle_response(json!({ "id": 1, "result": { "ok": true } }))
+            .await;
+
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["id"], "client-req-1");
+        assert_eq!(payload["result"]["ok"], true);
+        assert!(bridge.pending_requests.lock().await.is_empty());
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn chatgpt_token_refresh_uses_configured_refresh_command() {
+        let _auth_cache_scope = TestBridgeChatGptAuthCacheScope::new();
+        set_bridge_chatgpt_refresh_command_override(Some(
+            r#"echo '{"accessToken":"rotated-token","accountId":"account-456","planType":"pro","expiresIn":3600}'"#
+                .to_string(),
+        ));
+
+        let auth = resolve_bridge_chatgpt_auth_for_refresh_request()
+            .await
+            .expect("refresh command succeeds")
+            .expect("refreshed auth bundle");
+        let result = chatgpt_tokens_refresh_result(auth);
+        assert_eq!(result["accessToken"], "rotated-token");
+        assert_eq!(result["chatgptAccountId"], "account-456");
+        assert_eq!(result["chatgptPlanType"], "pro");
+
+        let cached = bridge_chatgpt_refresh_cache()
+            .read()
+            .expect("refresh cache lock")
+            .clone()
+            .expect("refresh result is cached with its expiry");
+        assert_eq!(cached.auth.access_token, "rotated-token");
+        assert!(cached.expires_at > Utc::now() + chrono::Duration::minutes(59));
+
+        set_bridge_chatgpt_refresh_command_override(Some(
+            r#"echo '{"accountId":"account-456"}'"#.to_string(),
+        ));
+        let error = resolve_bridge_chatgpt_auth_for_refresh_request()
+            .await
+            .expect_err("output without accessToken is rejected");
+        assert!(error.contains("missing accessToken"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn concurrent_chatgpt_token_refreshes_share_one_command_run() {
+        let auth_cache_scope = TestBridgeChatGptAuthCacheScope::new();
+        let runs = auth_cache_scope.temp_dir.join("runs");
+        set_bridge_chatgpt_refresh_command_override(Some(format!(
+            r#"sh -c 'echo run >> "{}"; sleep 0.3; echo "{{\"accessToken\":\"shared-token\",\"accountId\":\"account-1\"}}"'"#,
+            runs.display()
+        )));
+
+        let (first, second) = tokio::join!(
+            resolve_bridge_chatgpt_auth_for_refresh_request(),
+            resolve_bridge_chatgpt_auth_for_refresh_request()
+        );
+        for auth in [first, second] {
+            let auth = auth.expect("refresh succeeds").expect("auth bundle");
+            assert_eq!(auth.access_token, "shared-token");
+        }
+        let runs = std::fs::read_to_string(&runs).expect("read run log");
+        assert_eq!(runs.lines().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn chatgpt_token_refresh_does_not_block_app_server_messages() {
+        let _auth_cache_scope = TestBridgeChatGptAuthCacheScope::new();
+        set_bridge_chatgpt_refresh_command_override(Some(
+            r#"sh -c 'sleep 5; echo "{}"'"#.to_string(),
+        ));
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub).await;
+
+        timeout(
+            Duration::from_millis(500),
+            bridge.handle_server_request(
+                ACCOUNT_CHATGPT_TOKENS_REFRESH_METHOD,
+                json!(1),
+                Some(json!({ "reason": "unauthorized" })),
+            ),
+        )
+        .await
+        .expect("refresh runs off the stdout loop");
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[test]
+    fn chatgpt_refresh_command_output_parses_expiry_forms() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z")
+            .expect("valid timestamp")
+            .with_timezone(&Utc);
+
+        let (auth, expires_at) = parse_chatgpt_refresh_command_output(
+            r#"{"accessToken":"a","chatgptAccountId":"b","expiresAt":"2026-01-01T01:00:00Z"}"#,
+            now,
+        )
+        .expect("rfc3339 expiry");
+        assert_eq!(auth.account_id, "b");
+        assert_eq!(auth.plan_type, None);
+        assert_eq!(expires_at, Some(now + chrono::Duration::hours(1)));
+
+        let (_, expires_at) = parse_chatgpt_refresh_command_output(
+            r#"{"accessToken":"a","accountId":"b","expiresAt":1767229200}"#,
+            now,
+        )
+        .expect("unix expiry");
+        assert_eq!(expires_at, Some(now + chrono::Duration::hours(1)));
+
+        let (_, expires_at) =
+            parse_chatgpt_refresh_command_output(r#"{"accessToken":"a","accountId":"b"}"#, now)
+                .expect("no expiry");
+        assert_eq!(expires_at, None);
+
+        assert!(parse_chatgpt_refresh_command_output("not json", now).is_err());
+        assert!(parse_chatgpt_refresh_command_output(
+            r#"{"accessToken":"a","accountId":"b","expiresIn":"soon"}"#,
+            now
+        )
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn thread_archive_removes_thread_attachments_when_enabled() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-archive-attachments-{nonce}"));
+        let archived_dir = root.join("thr_archived");
+        let kept_dir = root.join("thr_kept");
+        std::fs::create_dir_all(&archived_dir).expect("create archived thread dir");
+        std::fs::create_dir_all(&kept_dir).expect("create kept thread dir");
+        std::fs::write(archived_dir.join("photo.png"), b"png").expect("write attachment");
+
+        let hub = Arc::new(ClientHub::new());
+        let bridge =
+            build_test_bridge_with_attachment_cleanup(hub.clone(), Some(root.clone())).await;
+        let (client_id, mut rx) = add_test_client(&hub).await;
+
+        bridge
+            .forward_request(
+                client_id,
+                json!("archive-1"),
+                "thread/archive",
+                Some(json!({ "threadId": "thr_archived" })),
+            )
+            .await
+            .expect("forward request");
+        bridge
+            .handle_response(json!({ "id": 1, "result": {} }))
+            .await;
+
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["id"], "archive-1");
+        assert!(!archived_dir.exists());
+        assert!(kept_dir.exists());
+
+        shutdown_test_bridge(&bridge).await;
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn http_thread_archive_removes_thread_attachments_when_enabled() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-http-archive-attachments-{nonce}"));
+        let archived_dir = root.join("thr_archived");
+        std::fs::create_dir_all(&archived_dir).expect("create archived thread dir");
+        std::fs::write(archived_dir.join("photo.png"), b"png").expect("write attachment");
+
+        let state = build_test_state_with_config({
+            let root = root.clone();
+            move |config| {
+                config.attachments_dir = root;
+                config.cleanup_attachments_on_archive = true;
+            }
+        })
+        .await;
+        state
+            .hub
+            .store_thread_cwd("thr_archived", env::temp_dir())
+            .await;
+        let codex = test_codex_backend(&state.backend);
+        let responder = tokio::spawn({
+            let codex = codex.clone();
+            async move {
+                let internal_id = loop {
+                    if let Some(id) = codex.internal_waiters.lock().await.keys().next() {
+                        break *id;
+                    }
+                    sleep(Duration::from_millis(2)).await;
+                };
+                codex
+                    .handle_response(json!({ "id": internal_id, "result": {} }))
+                    .await;
+            }
+        });
+
+        let payload = dispatch_http_rpc_request(
+            r#"{"id":"archive","method":"thread/archive","params":{"threadId":"thr_archived"}}"#,
+            &state,
+        )
+        .await;
+        responder.await.expect("responder");
+        assert_eq!(payload["id"], "archive");
+        assert!(payload.get("error").is_none(), "{payload}");
+        assert!(!archived_dir.exists());
+        assert!(state.hub.thread_cwd("thr_archived").await.is_none());
+
+        shutdown_test_backend(&state.backend).await;
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn attachments_move_puts_root_upload_into_thread_folder() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-move-attachments-{nonce}"));
+        std::fs::create_dir_all(&root).expect("create attachments root");
+        let source = root.join("20260101-000000-000-1-photo.png");
+        std::fs::write(&source, b"png").expect("write attachment");
+        let outside = env::temp_dir().join(format!("clawdex-move-outside-{nonce}.png"));
+        std::fs::write(&outside, b"png").expect("write outside file");
+
+        let state = build_test_state_with_config({
+            let root = root.clone();
+            move |config| config.attachments_dir = root
+        })
+        .await;
+
+        let moved = handle_bridge_method(
+            "bridge/attachments/move",
+            Some(json!({ "path": source.to_string_lossy(), "threadId": "codex:thr_new" })),
+            &state,
+            1,
+        )
+        .await
+        .expect("move attachment");
+        let target = root.join("thr_new").join("20260101-000000-000-1-photo.png");
+        assert_eq!(moved["path"], target.to_string_lossy().as_ref());
+        assert_eq!(moved["previousPath"], source.to_string_lossy().as_ref());
+        assert!(!source.exists());
+        assert_eq!(std::fs::read(&target).expect("moved file"), b"png");
+
+        for path in [
+            outside.to_string_lossy().to_string(),
+            format!("../clawdex-move-outside-{nonce}.png"),
+        ] {
+            let error = handle_bridge_method(
+                "bridge/attachments/move",
+                Some(json!({ "path": path, "threadId": "thr_new" })),
+                &state,
+                1,
+            )
+            .await
+            .expect_err("moves outside the attachments root are rejected");
+            assert_eq!(error.code, -32602);
+        }
+        assert!(outside.exists());
+
+        shutdown_test_backend(&state.backend).await;
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_file(&outside);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn attachments_move_rejects_symlinks_and_existing_targets() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let root = env::temp_dir().join(format!("clawdex-move-links-{nonce}"));
+        let outside_dir = env::temp_dir().join(format!("clawdex-move-links-outside-{nonce}"));
+        std::fs::create_dir_all(&root).expect("create attachments root");
+        std::fs::create_dir_all(&outside_dir).expect("create outside dir");
+        let outside = outside_dir.join("secret.png");
+        std::fs::write(&outside, b"secret").expect("write outside file");
+        std::os::unix::fs::symlink(&outside_dir, root.join("linked-dir")).expect("link dir");
+        std::os::unix::fs::symlink(&outside, root.join("linked.png")).expect("link file");
+        std::fs::create_dir_all(root.join("thr_taken")).expect("create thread dir");
+        std::fs::write(root.join("thr_taken").join("photo.png"), b"old").expect("write taken");
+        std::fs::write(root.join("photo.png"), b"new").expect("write upload");
+
+        let state = build_test_state_with_config({
+            let root = root.clone();
+            move |config| config.attachments_dir = root
+        })
+        .await;
+
+        for path in ["linked-dir/secret.png", "linked.png"] {
+            let error = handle_bridge_method(
+                "bridge/attachments/move",
+                Some(json!({ "path": path, "threadId": "thr_new" })),
+                &state,
+                1,
+            )
+            .await
+            .expect_err("symlinked sources are rejected");
+            assert_eq!(error.code, -32602);
+        }
+        assert!(outside.exists());
+
+        let taken = handle_bridge_method(
+            "bridge/attachments/move",
+            Some(json!({ "path": "photo.png", "threadId": "thr_taken" })),
+            &state,
+            1,
+        )
+        .await
+        .expect_err("existing target is kept");
+        assert_eq!(taken.code, -32602);
+        assert_eq!(
+            std::fs::read(root.join("thr_taken").join("photo.png")).expect("taken file"),
+            b"old"
+        );
+        assert!(root.join("photo.png").exists());
+
+        shutdown_test_backend(&state.backend).await;
+        let _ = std::fs::remove_dir_all(&root);
+        let _ = std::fs::remove_dir_all(&outside_dir);
+    }
+
+    #[tokio::test]
+    async fn successful_chatgpt_auth_token_login_populates_bridge_auth_cache() {
+        let _auth_cache_scope = TestBridgeChatGptAuthCacheScope::new();
+        clear_cached_bridge_chatgpt_auth();
+
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub.clone()).await;
+        let (client_id, mut rx) = add_test_client(&hub).await;
+
+        bridge
+            .forward_request(
+                client_id,
+                json!("client-req-chatgpt-login"),
+                "account/login/start",
+                Some(json!({
+                    "type": "chatgptAuthTokens",
+                    "accessToken": "bridge-cached-token",
+                    "chatgptAccountId": "account-123",
+                    "chatgptPlanType": "team",
+                })),
+            )
+            .await
+            .expect("forward request");
+
+        bridge
+            .handle_response(json!({ "id": 1, "result": { "type": "chatgptAuthTokens" } }))
+            .await;
+
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["id"], "client-req-chatgpt-login");
+        assert_eq!(payload["result"]["type"], "chatgptAuthTokens");
+
+        let refresh_auth =
+            resolve_bridge_chatgpt_auth_bundle_for_refresh().expect("cached auth bundle");
+        assert_eq!(refresh_auth.access_token, "bridge-cached-token");
+        assert_eq!(refresh_auth.account_id, "account-123");
+        assert_eq!(refresh_auth.plan_type.as_deref(), Some("team"));
+
+        let (url, token, uses_openai_api) =
+            resolve_transcription_auth().expect("transcription auth");
+        assert_eq!(url, "https://chatgpt.com/backend-api/transcribe");
+        assert_eq!(token, "bridge-cached-token");
+        assert!(!uses_openai_api);
+
+        clear_cached_bridge_chatgpt_auth();
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn transcription_request_reports_timeout_from_slow_upstream() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind slow upstream");
+        let address = listener.local_addr().expect("slow upstream address");
+        let server = tokio::spawn(async move {
+            // Accept and hold the connection without ever answering.
+            let (socket, _) = listener.accept().await.expect("accept request");
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            drop(socket);
+        });
+        let client = HttpClient::builder()
+            .timeout(Duration::from_millis(200))
+            .build()
+            .expect("build client");
+
+        let error = send_transcription_request(
+            &client,
+            &format!("http://{address}/v1/audio/transcriptions"),
+            "sk-test",
+            reqwest::multipart::Form::new().text("model", "test"),
+            true,
+        )
+        .await
+        .expect_err("slow upstream times out");
+        assert_eq!(error.code, -32000);
+        let data = error.data.expect("timeout data");
+        assert_eq!(data["timedOut"], true);
+        assert_eq!(data["recommendChunking"], true);
+
+        assert!(should_recommend_transcription_chunking(75, 100));
+        assert!(!should_recommend_transcription_chunking(74, 100));
+        server.abort();
+    }
+
+    #[test]
+    fn transcription_endpoint_override_takes_precedence_with_api_key() {
+        let resolved = resolve_transcription_endpoint_override(
+            Some("http://127.0.0.1:9000/v1/audio/transcriptions".to_string()),
+            Some("sk-local".to_string()),
+        )
+        .expect("override resolves")
+        .expect("override present");
+        assert_eq!(
+            resolved,
+            (
+                "http://127.0.0.1:9000/v1/audio/transcriptions".to_string(),
+                "sk-local".to_string(),
+                true,
+            )
+        );
+
+        assert!(
+            resolve_transcription_endpoint_override(None, Some("sk-local".to_string()))
+                .expect("no override")
+                .is_none()
+        );
+
+        let missing_key = resolve_transcription_endpoint_override(
+            Some("https://whisper.internal/v1/audio/transcriptions".to_string()),
+            None,
+        )
+        .expect_err("override requires api key");
+        assert_eq!(missing_key.code, -32002);
+
+        let bad_scheme = resolve_transcription_endpoint_override(
+            Some("whisper.internal".to_string()),
+            Some("sk-local".to_string()),
+        )
+        .expect_err("override requires http url");
+        assert!(bad_scheme.message.contains("BRIDGE_TRANSCRIPTION_ENDPOINT"));
+    }
+
+    #[tokio::test]
+    async fn successful_account_logout_clears_cached_bridge_chatgpt_auth() {
+        let _auth_cache_scope = TestBridgeChatGptAuthCacheScope::new();
+        clear_cached_bridge_chatgpt_auth();
+        cache_bridge_chatgpt_auth(BridgeChatGptAuthBundle {
+            access_token: "cached-before-logout".to_string(),
+            account_id: "account-logout".to_string(),
+            plan_type: Some("plus".to_string()),
+        });
+
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub.clone()).await;
+        let (client_id, mut rx) = add_test_client(&hub).await;
+
+        bridge
+            .forward_request(
+                client_id,
+                json!("client-req-logout"),
+                "account/logout",
+                None,
+            )
+            .await
+            .expect("forward request");
+
+        bridge
+            .handle_response(json!({ "id": 1, "result": {} }))
+            .await;
+
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["id"], "client-req-logout");
+        assert_eq!(payload["result"], json!({}));
+        assert!(read_cached_bridge_chatgpt_auth().is_none());
+        assert!(resolve_bridge_chatgpt_auth_bundle_for_refresh().is_none());
+
+        clear_cached_bridge_chatgpt_auth();
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn app_server_fail_all_pending_notifies_waiting_clients() {
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub.clone()).await;
+        let (client_a, mut rx_a) = add_test_client(&hub).await;
+        let (client_b, mut rx_b) = add_test_client(&hub).await;
+
+        bridge
+            .forward_request(client_a, json!("req-a"), "thread/start", None)
+            .await
+            .expect("forward request a");
+        bridge
+            .forward_request(client_b, json!("req-b"), "thread/start", None)
+            .await
+            .expect("forward request b");
+
+        bridge.fail_all_pending("app-server closed").await;
+
+        let payload_a = recv_client_json(&mut rx_a).await;
+        let payload_b = recv_client_json(&mut rx_b).await;
+
+        assert_eq!(payload_a["id"], "req-a");
+        assert_eq!(payload_a["error"]["code"], -32000);
+        assert_eq!(payload_b["id"], "req-b");
+        assert_eq!(payload_b["error"]["code"], -32000);
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn handle_server_request_item_tool_call_returns_structured_unsupported_result() {
+        let nonce = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let capture_path = env::temp_dir().join(format!("clawdex-tool-call-capture-{nonce}.jsonl"));
+        let shell_command = format!("cat > {}", capture_path.to_string_lossy());
+
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(shell_command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("spawn capture process");
+        let writer = child.stdin.take().expect("capture stdin available");
+
+        let hub = Arc::new(ClientHub::new());
+        let bridge = Arc::new(AppServerBridge {
+            engine: BridgeRuntimeEngine::Codex,
+            child: Mutex::new(child),
+            child_pid: 0,
+            writer: Mutex::new(writer),
+            pending_requests: Mutex::new(HashMap::new()),
+            pending_requests_by_client: Mutex::new(HashMap::new()),
+            internal_waiters: Mutex::new(HashMap::new()),
+            pending_approvals: Mutex::new(HashMap::new()),
+            pending_user_inputs: Mutex::new(HashMap::new()),
+            next_request_id: AtomicU64::new(1),
+            approval_counter: AtomicU64::new(1),
+            user_input_counter: AtomicU64::new(1),
+            server_info: Mutex::new(None),
+            ready: AtomicBool::new(false),
+            debug_trace: None,
+            attachment_cleanup_root: None,
+            reject_legacy_approvals: false,
+            stdout_lag: AppServerStdoutLag::default(),
+            method_traffic: AppServerMethodTraffic::default(),
+            hub: hub.clone(),
+        });
+
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+
+        bridge
+            .handle_server_request(
+                DYNAMIC_TOOL_CALL_METHOD,
+                json!("tool-call-1"),
+                Some(json!({
+                    "callId": "call_demo_1",
+                    "threadId": "thr_demo_1",
+                    "turnId": "turn_demo_1",
+                    "tool": "demo_tool",
+                    "arguments": { "hello": "world" }
+                })),
+            )
+            .await;
+
+        let notification = recv_client_json(&mut rx).await;
+        assert_eq!(notification["method"], "bridge/tool.call.unsupported");
+        assert_eq!(notification["params"]["request"]["tool"], "demo_tool");
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        shutdown_test_bridge(&bridge).await;
+
+        let captured = std::fs::read_to_string(&capture_path).expect("capture file exists");
+        std::fs::remove_file(&capture_path).ok();
+
+        println!("captured_app_server_response={captured}");
+
+        assert!(captured.contains("\"id\":\"tool-call-1\""));
+        assert!(captured.contains("\"success\":false"));
+        assert!(captured.contains("Dynamic tool calls are not supported by clawdex-mobile bridge"));
+    }
+
+    #[tokio::test]
+    async fn initialize_result_is_stored_and_announced() {
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let bridge = build_test_bridge(hub).await;
+
+        let initializing = tokio::spawn({
+            let bridge = bridge.clone();
+            async move { bridge.initialize().await }
+        });
+        timeout(Duration::from_secs(2), async {
+            while !bridge.internal_waiters.lock().await.contains_key(&1) {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("initialize registers its waiter");
+
+        bridge
+            .handle_response(json!({
+                "id": 1,
+                "result": {
+                    "userAgent": "codex_cli_rs/0.46.0 (Mac OS 15.0.0; arm64)",
+                    "capabilities": { "experimentalApi": true }
+                }
+            }))
+            .await;
+        initializing
+            .await
+            .expect("initialize task")
+            .expect("initialize succeeds");
+
+        let info = bridge.server_info().await.expect("stored server info");
+        assert_eq!(info.version.as_deref(), Some("0.46.0"));
+        assert_eq!(info.capabilities, Some(json!({ "experimentalApi": true })));
+
+        let ready = recv_client_json(&mut rx).await;
+        assert_eq!(ready["method"], "bridge/appServer/ready");
+        assert_eq!(ready["params"]["engine"], "codex");
+        assert_eq!(ready["params"]["version"], "0.46.0");
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn write_json_to_dead_child_marks_app_server_unavailable() {
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let bridge = build_test_bridge(hub.clone()).await;
+        bridge.ready.store(true, Ordering::Relaxed);
+
+        shutdown_test_bridge(&bridge).await;
+        let error = bridge
+            .write_json(json!({ "method": "initialized", "params": {} }))
+            .await
+            .expect_err("write to exited child fails");
+        assert!(!error.to_string().is_empty());
+        assert!(!bridge.ready.load(Ordering::Relaxed));
+
+        let unavailable = recv_client_json(&mut rx).await;
+        assert_eq!(unavailable["method"], "bridge/appServer/unavailable");
+        assert_eq!(unavailable["params"]["engine"], "codex");
+
+        // Only the transition out of ready is announced.
+        assert!(bridge.write_json(json!({})).await.is_err());
+        assert!(timeout(Duration::from_millis(100), rx.recv())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn debug_trace_records_forwarded_pairs_with_secrets_redacted() {
+        let hub = Arc::new(ClientHub::new());
+        let (client_id, _rx) = add_test_client(&hub).await;
+        let bridge = build_traced_test_bridge(hub).await;
+
+        bridge
+            .forward_request(
+                client_id,
+                json!("client-1"),
+                "account/login/start",
+                Some(json!({
+                    "type": "chatgptAuthTokens",
+                    "accessToken": "access-secret",
+                    "chatgptAccountId": "acct-1"
+                })),
+            )
+            .await
+            .expect("forward request");
+        bridge
+            .handle_response(json!({
+                "id": 1,
+                "result": { "type": "chatgptAuthTokens", "refresh_token": "refresh-secret" }
+            }))
+            .await;
+
+        bridge.pending_user_inputs.lock().await.insert(
+            "input-1".to_string(),
+            PendingUserInputEntry {
+                app_server_request_id: json!(42),
+                request: PendingUserInputRequest {
+                    id: "input-1".to_string(),
+                    thread_id: "thr_1".to_string(),
+                    turn_id: "turn_1".to_string(),
+                    item_id: "item_1".to_string(),
+                    requested_at: now_iso(),
+                    questions: ["api_key", "region"]
+                        .into_iter()
+                        .map(|id| PendingUserInputQuestion {
+                            id: id.to_string(),
+                            header: id.to_string(),
+                            question: id.to_string(),
+                            is_other: true,
+                            is_secret: id == "api_key",
+                            options: None,
+                        })
+                        .collect(),
+                },
+            },
+        );
+        let answers = HashMap::from([
+            (
+                "api_key".to_string(),
+                UserInputAnswerPayload {
+                    answers: vec!["sk-live-value".to_string()],
+                },
+            ),
+            (
+                "region".to_string(),
+                UserInputAnswerPayload {
+                    answers: vec!["eu-west".to_string()],
+                },
+            ),
+        ]);
+        bridge
+            .resolve_user_input("input-1", &answers)
+            .await
+            .expect("resolve user input");
+
+        let entries = bridge.debug_trace_entries().await;
+        let directions = entries
+            .iter()
+            .map(|entry| entry.direction)
+            .collect::<Vec<_>>();
+        assert_eq!(directions, vec!["request", "response", "userInputResponse"]);
+        assert_eq!(entries[0].method, "account/login/start");
+        assert_eq!(entries[1].method, "account/login/start");
+        assert_eq!(entries[0].request_id, json!(1));
+
+        let combined = entries
+            .iter()
+            .map(|entry| entry.payload.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(combined.contains("acct-1"));
+        assert!(combined.contains("eu-west"));
+        assert!(combined.contains(DEBUG_TRACE_REDACTED));
+        assert!(!combined.contains("access-secret"));
+        assert!(!combined.contains("refresh-secret"));
+        assert!(!combined.contains("sk-live-value"));
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn resolved_user_input_broadcast_never_contains_secret_answers() {
+        let hub = Arc::new(ClientHub::new());
+        let (_client_id, mut rx) = add_test_client(&hub).await;
+        let bridge = build_traced_test_bridge(hub).await;
+
+        bridge.pending_user_inputs.lock().await.insert(
+            "input-secret".to_string(),
+            PendingUserInputEntry {
+                app_server_request_id: json!(9),
+                request: PendingUserInputRequest {
+                    id: "input-secret".to_string(),
+                    thread_id: "thr_1".to_string(),
+                    turn_id: "turn_1".to_string(),
+                    item_id: "item_1".to_string(),
+                    requested_at: now_iso(),
+                    questions: vec![PendingUserInputQuestion {
+                        id: "password".to_string(),
+                        header: "Password".to_string(),
+                        question: "Database password?".to_string(),
+                        is_other: true,
+                        is_secret: true,
+                        options: None,
+                    }],
+                },
+            },
+        );
+        let answers = HashMap::from([(
+            "password".to_string(),
+            UserInputAnswerPayload {
+                answers: vec!["hunter2-secret".to_string()],
+            },
+        )]);
+        bridge
+            .resolve_user_input("input-secret", &answers)
+            .await
+            .expect("resolve user input");
+
+        let resolved = recv_client_json(&mut rx).await;
+        assert_eq!(resolved["method"], "bridge/userInput.resolved");
+        assert_eq!(resolved["params"]["id"], "input-secret");
+        assert!(!resolved.to_string().contains("hunter2-secret"));
+
+        let trace = bridge.debug_trace_entries().await;
+        assert_eq!(trace.len(), 1);
+        assert!(!trace[0].payload.contains("hunter2-secret"));
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[tokio::test]
+    async fn debug_trace_method_is_forbidden_unless_enabled() {
+        let state = build_test_state().await;
+
+        let error = handle_bridge_method("bridge/debug/trace", None, &state, 1)
+            .await
+            .expect_err("debug trace disabled by default");
+        assert_eq!(error.code, -32003);
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[test]
+    fn parse_user_agent_version_reads_product_token() {
+        assert_eq!(
+            parse_user_agent_version("codex_cli_rs/0.46.0 (Linux; x86_64)").as_deref(),
+            Some("0.46.0")
+        );
+        assert_eq!(parse_user_agent_version("codex"), None);
+    }
+
+    #[tokio::test]
+    async fn app_server_response_completes_internal_waiter() {
+        let hub = Arc::new(ClientHub::new());
+        let bridge = build_test_bridge(hub).await;
+        let (tx, rx) = oneshot::channel();
+        bridge.internal_waiters.lock().await.insert(7, tx);
+
+        bridge
+            .handle_response(json!({ "id": 7, "result": { "initialized": true } }))
+            .await;
+
+        let result = rx.await.expect("waiter result").expect("successful result");
+        assert_eq!(result["initialized"], true);
+
+        shutdown_test_bridge(&bridge).await;
+    }
+
+    #[test]
+    fn client_rate_bucket_refills_at_configured_rate() {
+        let limit = ClientRateLimit {
+            per_second: 10,
+            burst: 2,
+        };
+        let start = Instant::now();
+        let mut bucket = ClientRateBucket::full(limit, start);
+
+        assert!(bucket.try_take(limit, start).is_ok());
+        assert!(bucket.try_take(limit, start).is_ok());
+        let retry_after = bucket.try_take(limit, start).expect_err("bucket empty");
+        assert_eq!(retry_after.as_millis(), 100);
+
+        assert!(bucket
+            .try_take(limit, start + Duration::from_millis(50))
+            .is_err());
+        assert!(bucket
+            .try_take(limit, start + Duration::from_millis(150))
+            .is_ok());
+
+        assert!(bucket
+            .try_take(limit, start + Duration::from_secs(10))
+            .is_ok());
+        assert!(bucket
+            .try_take(limit, start + Duration::from_secs(10))
+            .is_ok());
+        assert!(bucket
+            .try_take(limit, start + Duration::from_secs(10))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn strict_jsonrpc_stamps_outbound_messages_and_rejects_unversioned_requests() {
+        let state = build_test_state_with_config(|config| {
+            config.strict_jsonrpc = true;
+        })
+        .await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
+
+        handle_client_message(
+            client_id,
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "bridge/capabilities" }).to_string(),
+            &state,
+        )
+        .await;
+        let response = recv_client_json(&mut rx).await;
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], 1);
+        assert!(response.get("result").is_some());
+
+        state
+            .hub
+            .broadcast_notification("turn/started", json!({ "threadId": "thr_1" }))
+            .await;
+        let notification = recv_client_json(&mut rx).await;
+        assert_eq!(notification["jsonrpc"], "2.0");
+        assert_eq!(notification["method"], "turn/started");
+
+        handle_client_message(
+            client_id,
+            json!({ "id": 2, "method": "bridge/capabilities" }).to_string(),
+            &state,
+        )
+        .await;
+        let rejected = recv_client_json(&mut rx).await;
+        assert_eq!(rejected["jsonrpc"], "2.0");
+        assert_eq!(rejected["id"], 2);
+        assert_eq!(rejected["error"]["code"], -32600);
+
+        let http = dispatch_http_rpc_request(
+            &json!({ "jsonrpc": "1.0", "id": "h1", "method": "bridge/health/read" }).to_string(),
+            &state,
+        )
+        .await;
+        assert_eq!(http["error"]["code"], -32600);
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn lenient_jsonrpc_accepts_unversioned_requests_and_omits_version() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
+
+        handle_client_message(
+            client_id,
+            json!({ "id": 1, "method": "bridge/capabilities" }).to_string(),
+            &state,
+        )
+        .await;
+        let response = recv_client_json(&mut rx).await;
+        assert!(response.get("jsonrpc").is_none());
+        assert!(response.get("result").is_some());
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn handle_client_message_rate_limits_after_burst_and_recovers() {
+        let state = build_test_state_with_config(|config| {
+            config.client_rate_limit = Some(ClientRateLimit {
+                per_second: 20,
+                burst: 2,
+            });
+        })
+        .await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
+        let request = |id: u64, method: &str| json!({ "id": id, "method": method }).to_string();
+
+        for id in 1..=2 {
+            handle_client_message(client_id, request(id, "bridge/capabilities"), &state).await;
+            let payload = recv_client_json(&mut rx).await;
+            assert_eq!(payload["id"], id);
+            assert!(payload.get("error").is_none());
+        }
+
+        handle_client_message(client_id, request(3, "bridge/capabilities"), &state).await;
+        let limited = recv_client_json(&mut rx).await;
+        assert_eq!(limited["id"], 3);
+        assert_eq!(limited["error"]["code"], -32000);
+        assert_eq!(limited["error"]["data"]["rateLimited"], true);
+        let retry_after_ms = limited["error"]["data"]["retryAfterMs"]
+            .as_u64()
+            .expect("retryAfterMs");
+        assert!((1..=50).contains(&retry_after_ms));
+
+        handle_client_message(client_id, request(4, "bridge/health/read"), &state).await;
+        let health = recv_client_json(&mut rx).await;
+        assert_eq!(health["id"], 4);
+        assert!(health.get("error").is_none());
+
+        sleep(Duration::from_millis(retry_after_ms + 10)).await;
+        handle_client_message(client_id, request(5, "bridge/capabilities"), &state).await;
+        let recovered = recv_client_json(&mut rx).await;
+        assert_eq!(recovered["id"], 5);
+        assert!(recovered.get("error").is_none());
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn reconnecting_with_same_client_key_restores_subscriptions() {
+        let state = build_test_state().await;
+        let (first_id, _first_rx) = add_test_client(&state.hub).await;
+        let claim = state
+            .hub
+            .claim_client_key(first_id, "phone-1".to_string())
+            .await;
+        assert!(!claim.resumed);
+
+        handle_bridge_method(
+            "bridge/client/subscriptions/set",
+            Some(json!({ "topics": ["git:/repo", "threads", "git:/repo"] })),
+            &state,
+            first_id,
+        )
+        .await
+        .expect("set subscriptions");
+        state.hub.remove_client(first_id).await;
+        assert!(state.hub.client_subscriptions(first_id).await.is_empty());
+
+        let (second_id, _second_rx) = add_test_client(&state.hub).await;
+        let claim = state
+            .hub
+            .claim_client_key(second_id, "phone-1".to_string())
+            .await;
+        assert!(claim.resumed);
+        assert_eq!(claim.subscriptions, vec!["git:/repo", "threads"]);
+        assert!(claim.replaced_client_id.is_none());
+        let read =
+            handle_bridge_method("bridge/client/subscriptions/read", None, &state, second_id)
+                .await
+                .expect("read subscriptions");
+        assert_eq!(read["subscriptions"], json!(["git:/repo", "threads"]));
+
+        let (third_id, _third_rx) = add_test_client(&state.hub).await;
+        let claim = state
+            .hub
+            .claim_client_key(third_id, "phone-1".to_string())
+            .await;
+        assert_eq!(claim.replaced_client_id, Some(second_id));
+        assert_eq!(claim.subscriptions, vec!["git:/repo", "threads"]);
+        state.hub.close_replaced_client(second_id).await;
+        assert!(state
+            .hub
+            .client_connections()
+            .await
+            .iter()
+            .all(|client| client.client_id != second_id));
+        assert_eq!(
+            state.hub.client_subscriptions(third_id).await,
+            vec!["git:/repo", "threads"]
+        );
+
+        let (other_id, _other_rx) = add_test_client(&state.hub).await;
+        assert!(
+            !state
+                .hub
+                .claim_client_key(other_id, "tablet-1".to_string())
+                .await
+                .resumed
+        );
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn broadcasts_skip_notifications_outside_client_subscriptions() {
+        let hub = Arc::new(ClientHub::new());
+        let (filtered_id, mut filtered_rx) = add_test_client(&hub).await;
+        let (_open_id, mut open_rx) = add_test_client(&hub).await;
+        hub.set_client_subscriptions(
+            filtered_id,
+            BTreeSet::from(["turn/*".to_string(), "bridge/git/updated".to_string()]),
+        )
+        .await;
+
+        hub.broadcast_notification("item/started", json!({})).await;
+        hub.broadcast_notification("turn/completed", json!({}))
+            .await;
+        hub.broadcast_notification("bridge/git/updated", json!({}))
+            .await;
+
+        assert_eq!(
+            recv_client_json(&mut filtered_rx).await["method"],
+            "turn/completed"
+        );
+        assert_eq!(
+            recv_client_json(&mut filtered_rx).await["method"],
+            "bridge/git/updated"
+        );
+        assert!(filtered_rx.try_recv().is_err());
+        for method in ["item/started", "turn/completed", "bridge/git/updated"] {
+            assert_eq!(recv_client_json(&mut open_rx).await["method"], method);
+        }
+        assert!(hub.client_missed_from.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reconnecting_with_same_client_key_keeps_the_rate_bucket() {
+        let limit = ClientRateLimit {
+            per_second: 1,
+            burst: 2,
+        };
+        let hub = Arc::new(ClientHub::new().with_rate_limit(Some(limit)));
+        let now = Instant::now();
+
+        let (first_id, _first_rx) = add_test_client(&hub).await;
+        hub.claim_client_key(first_id, "phone-1".to_string()).await;
+        assert!(hub.check_rate_limit(first_id, now).await.is_ok());
+        assert!(hub.check_rate_limit(first_id, now).await.is_ok());
+        hub.remove_client(first_id).await;
+
+        let (second_id, _second_rx) = add_test_client(&hub).await;
+        assert!(
+            hub.claim_client_key(second_id, "phone-1".to_string())
+                .await
+                .resumed
+        );
+        assert!(hub.check_rate_limit(second_id, now).await.is_err());
+
+        let (third_id, _third_rx) = add_test_client(&hub).await;
+        let claim = hub.claim_client_key(third_id, "phone-1".to_string()).await;
+        assert_eq!(claim.replaced_client_id, Some(second_id));
+        assert!(hub.check_rate_limit(third_id, now).await.is_err());
+
+        let (other_id, _other_rx) = add_test_client(&hub).await;
+        hub.claim_client_key(other_id, "tablet-1".to_string()).await;
+        assert!(hub.check_rate_limit(other_id, now).await.is_ok());
+    }
+
+    #[test]
+    fn client_keys_are_validated() {
+        assert_eq!(normalize_client_key(None), Ok(None));
+        assert_eq!(normalize_client_key(Some("  ")), Ok(None));
+        assert_eq!(
+            normalize_client_key(Some("device_01-a")),
+            Ok(Some("device_01-a".to_string()))
+        );
+        assert!(normalize_client_key(Some("bad key")).is_err());
+        assert!(normalize_client_key(Some(&"k".repeat(CLIENT_KEY_MAX_LEN + 1))).is_err());
+    }
+
+    #[tokio::test]
+    async fn handle_client_message_answers_bridge_batch_with_correlated_ids() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
+
+        let batch = json!([
+            { "id": "health", "method": "bridge/health/read" },
+            { "method": "bridge/health/read" },
+            { "id": 7, "method": "bridge/capabilities" },
+            { "id": 8, "method": "not/allowed" },
+        ]);
+        handle_client_message(client_id, batch.to_string(), &state).await;
+
+        let payload = recv_client_json(&mut rx).await;
+        let responses = payload.as_array().expect("batch response array");
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["id"], "health");
+        assert_eq!(responses[0]["result"]["status"], "ok");
+        assert_eq!(responses[1]["id"], 7);
+        assert!(responses[1]["result"]["methods"].is_array());
+        assert_eq!(responses[2]["id"], 8);
+        assert_eq!(responses[2]["error"]["code"], -32601);
+
+        handle_client_message(client_id, "[]".to_string(), &state).await;
+        let empty = recv_client_json(&mut rx).await;
+        assert_eq!(empty["id"], Value::Null);
+        assert_eq!(empty["error"]["code"], -32600);
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn http_rpc_requests_share_one_rate_limit_bucket() {
+        let state = build_test_state_with_config(|config| {
+            config.client_rate_limit = Some(ClientRateLimit {
+                per_second: 1,
+                burst: 2,
+            });
+        })
+        .await;
+        let request = json!({ "id": 1, "method": "bridge/capabilities" }).to_string();
+
+        for _ in 0..2 {
+            let payload = dispatch_http_rpc_request(&request, &state).await;
+            assert!(payload["result"].is_object());
+        }
+        let limited = dispatch_http_rpc_request(&request, &state).await;
+        assert_eq!(limited["error"]["code"], -32000);
+        assert_eq!(limited["error"]["data"]["rateLimited"], true);
+
+        let health = json!({ "id": 2, "method": "bridge/health/read" }).to_string();
+        let payload = dispatch_http_rpc_request(&health, &state).await;
+        assert_eq!(payload["result"]["status"], "ok");
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn http_rpc_post_answers_bridge_methods_with_cors_headers() {
+        let state = build_test_state_with_config(|config| {
+            config.cors_origins = HashSet::from(["http://localhost:5173".to_string()]);
+        })
+        .await;
+        let app = Router::new()
+            .route(
+                "/rpc",
+                get(ws_handler)
+                    .post(http_rpc_handler)
+                    .options(http_rpc_preflight_handler),
+            )
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind test listener");
+        let address = listener.local_addr().expect("test listener address");
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        let client = HttpClient::new();
+        let url = format!("http://{address}/rpc");
+
+        let response = client
+            .post(&url)
+            .bearer_auth("secret-token")
+            .header("origin", "http://localhost:5173")
+            .header("content-type", "application/json; charset=utf-8")
+            .body(json!({ "id": "h1", "method": "bridge/health/read" }).to_string())
+            .send()
+            .await
+            .expect("POST /rpc");
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get("access-control-allow-origin")
+                .and_then(|value| value.to_str().ok()),
+            Some("http://localhost:5173")
+        );
+        let payload: Value = response.json().await.expect("JSON body");
+        assert_eq!(payload["id"], "h1");
+        assert_eq!(payload["result"]["status"], "ok");
+
+        let other_origin = client
+            .post(&url)
+            .bearer_auth("secret-token")
+            .header("origin", "https://example.com")
+            .header("content-type", "application/json")
+            .body(json!({ "id": 2, "method": "bridge/health/read" }).to_string())
+            .send()
+            .await
+            .expect("POST /rpc");
+        assert_eq!(other_origin.status(), reqwest::StatusCode::FORBIDDEN);
+        assert!(other_origin
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none());
+        let preflight = client
+            .request(reqwest::Method::OPTIONS, &url)
+            .header("origin", "https://example.com")
+            .send()
+            .await
+            .expect("OPTIONS /rpc");
+        assert_eq!(preflight.status(), reqwest::StatusCode::FORBIDDEN);
+
+        let not_json = client
+            .post(&url)
+            .bearer_auth("secret-token")
+            .header("content-type", "text/plain")
+            .body(json!({ "id": 3, "method": "bridge/health/read" }).to_string())
+            .send()
+            .await
+            .expect("POST /rpc");
+        assert_eq!(
+            not_json.status(),
+            reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
+
+        let streaming = client
+            .post(&url)
+            .bearer_auth("secret-token")
+            .header("content-type", "application/json")
+            .body(
+                json!({
+                    "id": 4,
+                    "method": "bridge/client/subscriptions/set",
+                    "params": { "topics": ["turn/*"] }
+                })
+                .to_string(),
+            )
+            .send()
+            .await
+            .expect("POST /rpc");
+        let payload: Value = streaming.json().await.expect("JSON body");
+        assert_eq!(payload["error"]["code"], -32601);
+        assert!(state.hub.client_subscriptions.lock().await.is_empty());
+
+        let unauthorized = client
+            .post(&url)
+            .header("content-type", "application/json")
+            .body(json!({ "id": 5, "method": "bridge/health/read" }).to_string())
+            .send()
+            .await
+            .expect("POST /rpc");
+        assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        server.abort();
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn handle_client_message_returns_parse_error_for_invalid_json() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
+
+        handle_client_message(client_id, "{invalid-json".to_string(), &state).await;
+
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["id"], Value::Null);
+        assert_eq!(payload["error"]["code"], -32700);
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn websocket_rejects_oversized_frames_and_closes_connection() {
+        let state = build_test_state_with_config(|config| {
+            config.ws_max_frame_bytes = 1024;
+        })
+        .await;
+        let app = Router::new()
+            .route("/rpc", get(ws_handler))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind test listener");
+        let address = listener.local_addr().expect("test listener address");
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let mut request = format!("ws://{address}/rpc")
+            .into_client_request()
+            .expect("websocket request");
+        request.headers_mut().insert(
+            "authorization",
+            "Bearer secret-token".parse().expect("header value"),
+        );
+        let (mut socket, _) = connect_async(request).await.expect("connect websocket");
+
+        let connected = socket
+            .next()
+            .await
+            .expect("connection state")
+            .expect("frame");
+        let connected: Value =
+            serde_json::from_str(connected.to_text().expect("text frame")).expect("json");
+        assert_eq!(connected["method"], "bridge/connection/state");
+
+        socket
+            .send(UpstreamWsMessage::Text("x".repeat(4096).into()))
+            .await
+            .expect("send oversized frame");
+
+        let rejected = timeout(Duration::from_secs(2), socket.next())
+            .await
+            .expect("rejection before timeout")
+            .expect("rejection frame")
+            .expect("rejection message");
+        let rejected: Value =
+            serde_json::from_str(rejected.to_text().expect("text frame")).expect("json");
+        assert_eq!(rejected["id"], Value::Null);
+        assert_eq!(rejected["error"]["code"], -32600);
+        assert_eq!(rejected["error"]["data"]["tooLarge"], true);
+        assert_eq!(rejected["error"]["data"]["maxBytes"], 1024);
+
+        let closed = timeout(Duration::from_secs(2), socket.next())
+            .await
+            .expect("close before timeout");
+        assert!(matches!(
+            closed,
+            None | Some(Err(_)) | Some(Ok(UpstreamWsMessage::Close(_)))
+        ));
+        assert!(state.hub.client_connections().await.is_empty());
+
+        server.abort();
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn websocket_rejects_clients_beyond_max_and_frees_slot_on_disconnect() {
+        let state = build_test_state_with_config(|config| {
+            config.max_clients = Some(1);
+        })
+        .await;
+        let app = Router::new()
+            .route("/rpc", get(ws_handler))
+            .with_state(state.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind test listener");
+        let address = listener.local_addr().expect("test listener address");
+        let server = tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+        let connect = || async move {
+            let mut request = format!("ws://{address}/rpc")
+                .into_client_request()
+                .expect("websocket request");
+            request.headers_mut().insert(
+                "authorization",
+                "Bearer secret-token".parse().expect("header value"),
+            );
+            connect_async(request).await
+        };
+
+        let (mut first, _) = connect().await.expect("first client connects");
+        first
+            .next()
+            .await
+            .expect("connection state")
+            .expect("frame");
+
+        match connect().await {
+            Err(tokio_tungstenite::tungstenite::Error::Http(response)) => {
+                assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+                let body: Value = serde_json::from_slice(response.body().as_deref().expect("body"))
+                    .expect("json body");
+                assert_eq!(body["error"], "too_many_clients");
+                assert_eq!(body["maxClients"], 1);
+                assert_eq!(body["retryAfterMs"], RECONNECT_RETRY_AFTER_MAX_MS);
+            }
+            other => panic!("expected 503 rejection, got {other:?}"),
+        }
+
+        first.close(None).await.expect("close first client");
+        let mut client_count = state.hub.subscribe_client_count();
+        timeout(
+            Duration::from_secs(2),
+            client_count.wait_for(|count| *count == 0),
+        )
+        .await
+        .expect("slot freed before timeout")
+        .expect("client count channel open");
+
+        let (mut second, _) = connect().await.expect("client connects after slot frees");
+        second.close(None).await.ok();
+
+        server.abort();
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[test]
+    fn reconnect_retry_after_scales_with_client_load() {
+        assert_eq!(
+            reconnect_retry_after_ms(50, None),
+            RECONNECT_RETRY_AFTER_BASE_MS
+        );
+        assert_eq!(
+            reconnect_retry_after_ms(0, Some(4)),
+            RECONNECT_RETRY_AFTER_BASE_MS
+        );
+        assert_eq!(reconnect_retry_after_ms(2, Some(4)), 15_500);
+        assert_eq!(
+            reconnect_retry_after_ms(9, Some(4)),
+            RECONNECT_RETRY_AFTER_MAX_MS
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrent_clients_cannot_exceed_max_clients() {
+        let hub = Arc::new(ClientHub::new().with_max_clients(Some(1)));
+        let add = || {
+            let hub = hub.clone();
+            tokio::spawn(async move {
+                let (tx, _rx) = mpsc::channel(1);
+                hub.add_client_with_metadata(tx, ClientConnectionMetadata::default())
+                    .await
+            })
+        };
+
+        let (first, second) = tokio::join!(add(), add());
+        let added = [first, second]
+            .into_iter()
+            .filter_map(|result| result.expect("add task"))
+            .count();
+        assert_eq!(added, 1);
+        assert_eq!(hub.client_connections().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn disconnected_client_gets_degraded_state_with_retry_hint() {
+        let hub = Arc::new(ClientHub::new().with_max_clients(Some(4)));
+        let (_first_id, mut first_rx) = add_test_client(&hub).await;
+        let (_second_id, _second_rx) = add_test_client(&hub).await;
+        let retry_after_ms = hub.reconnect_retry_after_ms();
+        assert!(retry_after_ms > 0);
+        assert_eq!(retry_after_ms, reconnect_retry_after_ms(2, Some(4)));
+
+        hub.disconnect_all_clients("shutdown", close_code::AWAY)
+            .await;
+
+        let degraded = recv_client_json(&mut first_rx).await;
+        assert_eq!(degraded["method"], "bridge/connection/state");
+        assert_eq!(degraded["params"]["status"], "degraded");
+        assert_eq!(degraded["params"]["reason"], "shutdown");
+        assert_eq!(degraded["params"]["retryAfterMs"], retry_after_ms);
+
+        let Some(Message::Close(Some(frame))) = first_rx.recv().await else {
+            panic!("expected close frame");
+        };
+        assert_eq!(frame.code, close_code::AWAY);
+        assert_eq!(
+            frame.reason.as_str(),
+            format!("shutdown; retryAfterMs={retry_after_ms}")
+        );
+        assert!(hub.client_connections().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn replaced_client_gets_a_terminal_close_without_retry_hint() {
+        let hub = Arc::new(ClientHub::new().with_max_clients(Some(4)));
+        let (first_id, mut first_rx) = add_test_client(&hub).await;
+        let (_second_id, _second_rx) = add_test_client(&hub).await;
+
+        hub.close_replaced_client(first_id).await;
+
+        let closed = recv_client_json(&mut first_rx).await;
+        assert_eq!(closed["method"], "bridge/connection/state");
+        assert_eq!(closed["params"]["status"], "closed");
+        assert_eq!(closed["params"]["reason"], "replaced");
+        assert!(closed["params"].get("retryAfterMs").is_none());
+
+        let Some(Message::Close(Some(frame))) = first_rx.recv().await else {
+            panic!("expected close frame");
+        };
+        assert_eq!(frame.code, close_code::NORMAL);
+        assert_eq!(frame.reason.as_str(), "replaced");
+    }
+
+    #[test]
+    fn websocket_message_too_large_error_detection_matches_capacity_errors() {
+        assert!(is_websocket_message_too_large_error(&axum::Error::new(
+            TungsteniteError::Capacity(CapacityError::MessageTooLong {
+                size: 4096,
+                max_size: 1024,
+            })
+        )));
+        assert!(!is_websocket_message_too_large_error(&axum::Error::new(
+            TungsteniteError::ConnectionClosed
+        )));
+        assert!(!is_websocket_message_too_large_error(&axum::Error::new(
+            std::io::Error::other("Space limit exceeded: Message too long: 4096 > 1024")
+        )));
+    }
+
+    #[tokio::test]
+    async fn bridge_skills_overview_merges_local_and_remote_lists() {
+        let state = build_test_state().await;
+        let codex = test_codex_backend(&state.backend);
+
+        let overview = tokio::spawn({
+            let state = state.clone();
+            async move {
+                handle_bridge_method(
+                    "bridge/skills/overview",
+                    Some(json!({ "cwds": ["/repo"] })),
+                    &state,
+                    1,
+                )
+                .await
+            }
+        });
+
+        timeout(Duration::from_secs(1), async {
+            while codex.internal_waiters.lock().await.len() < 2 {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("both skills requests forwarded");
+        // `skills/list` is issued first, so it holds the first internal id.
+        codex
+            .handle_response(json!({
+                "id": 1,
+                "result": {
+                    "data": [
+                        { "cwd": "/repo", "skills": [{ "name": "pdf-tools" }, { "name": "Lint" }] },
+                        { "cwd": "/other", "skills": [{ "name": "deploy" }] }
+                    ]
+                }
+            }))
+            .await;
+        codex
+            .handle_response(json!({
+                "id": 2,
+                "result": {
+                    "data": [
+                        { "id": "sk_1", "name": "lint" },
+                        { "id": "sk_2", "name": "changelog" },
+                        { "id": "sk_3" }
+                    ]
+                }
+            }))
+            .await;
+
+        let result = overview
+            .await
+            .expect("overview task")
+            .expect("bridge/skills/overview succeeds");
+        let local_names = result["local"]
+            .as_array()
+            .expect("local skills")
+            .iter()
+            .map(|skill| skill["name"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(local_names, vec!["pdf-tools", "Lint", "deploy"]);
+        assert_eq!(result["remote"][0]["installed"], true);
+        assert_eq!(result["remote"][1]["installed"], false);
+        assert_eq!(result["remote"][2]["installed"], false);
+        assert_eq!(result["localError"], Value::Null);
+        assert!(result["at"].is_string());
+
+        let partial = merge_skills_overview(
+            Ok(json!({ "data": [{ "name": "lint" }] })),
+            Err("remote skills are disabled".to_string()),
+        );
+        assert_eq!(partial.local.len(), 1);
+        assert!(partial.remote.is_empty());
+        assert_eq!(
+            partial.remote_error.as_deref(),
+            Some("remote skills are disabled")
+        );
+
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn bridge_turn_steer_forwards_and_broadcasts_steered() {
+        let state = build_test_state().await;
+        let codex = test_codex_backend(&state.backend);
+
+        let steer = tokio::spawn({
+            let state = state.clone();
+            async move {
+                handle_bridge_method(
+                    "bridge/turn/steer",
+                    Some(json!({
+                        "threadId": "thr_1",
+                        "expectedTurnId": "turn_1",
+                        "input": [{ "type": "text", "text": "use the v2 API instead" }],
+                    })),
+                    &state,
+                    7,
+                )
+                .await
+            }
+        });
+
+        let internal_id = timeout(Duration::from_secs(1), async {
+            loop {
+                if let Some(id) = codex.internal_waiters.lock().await.keys().next().copied() {
+                    break id;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("turn/steer forwarded to app-server");
+        codex
+            .handle_response(json!({ "id": internal_id, "result": { "turnId": "turn_1" } }))
+            .await;
 
-        assert!(build_pairing_payload(&config).is_none());
+        let result = steer
+            .await
+            .expect("steer task")
+            .expect("bridge/turn/steer succeeds");
+        assert_eq!(result["turnId"], "turn_1");
 
-        let fallback = build_token_only_pairing_payload(&config).expect("token-only payload");
-        let parsed: Value = serde_json::from_str(&fallback).expect("valid json");
+        let (events, _, _) = state.hub.replay_since(None, 100).await;
+        let steered = events
+            .iter()
+            .find(|event| event["method"] == "bridge/turn.steered")
+            .expect("steered notification");
+        assert_eq!(steered["params"]["threadId"], "thr_1");
+        assert_eq!(steered["params"]["turnId"], "turn_1");
+        assert_eq!(steered["params"]["clientId"], 7);
 
-        assert_eq!(parsed["type"], "clawdex-bridge-token");
-        assert_eq!(parsed["bridgeToken"], "secret-token");
+        shutdown_test_backend(&state.backend).await;
     }
 
-    #[test]
-    fn build_pairing_payload_prefers_connect_url_when_configured() {
-        let config = BridgeConfig {
-            host: "127.0.0.1".to_string(),
-            port: 8787,
-            preview_port: 8788,
-            connect_url: Some("https://octocat-8787.app.github.dev".to_string()),
-            preview_connect_url: Some("https://octocat-8788.app.github.dev".to_string()),
-            workdir: PathBuf::from("/tmp/workdir"),
-            cli_bin: "codex".to_string(),
-            opencode_cli_bin: "opencode".to_string(),
-            cursor_app_server_bin: "cursor-app-server".to_string(),
-            active_engine: BridgeRuntimeEngine::Codex,
-            enabled_engines: vec![BridgeRuntimeEngine::Codex],
-            opencode_host: "127.0.0.1".to_string(),
-            opencode_port: 4090,
-            opencode_server_username: "opencode".to_string(),
-            opencode_server_password: Some("secret-token".to_string()),
-            auth_token: Some("secret-token".to_string()),
-            auth_enabled: true,
-            allow_insecure_no_auth: false,
-            allow_query_token_auth: false,
-            allow_outside_root_cwd: false,
-            disable_terminal_exec: false,
-            terminal_allowed_commands: HashSet::new(),
-            show_pairing_qr: true,
-        };
+    #[tokio::test]
+    async fn bridge_thread_delete_requires_explicit_confirmation() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
 
-        let payload = build_pairing_payload(&config).expect("pairing payload");
-        let parsed: Value = serde_json::from_str(&payload).expect("valid json");
+        for params in [
+            json!({ "threadId": "thr_1" }),
+            json!({ "threadId": "thr_1", "confirm": false }),
+        ] {
+            handle_client_message(
+                client_id,
+                json!({ "id": "delete-1", "method": "bridge/thread/delete", "params": params })
+                    .to_string(),
+                &state,
+            )
+            .await;
 
-        assert_eq!(parsed["type"], "clawdex-bridge-pair");
-        assert_eq!(parsed["bridgeUrl"], "https://octocat-8787.app.github.dev");
-        assert_eq!(parsed["bridgeToken"], "secret-token");
+            let payload = recv_client_json(&mut rx).await;
+            assert_eq!(payload["id"], "delete-1");
+            assert_eq!(payload["error"]["code"], -32602);
+        }
+
+        shutdown_test_backend(&state.backend).await;
     }
 
-    #[test]
-    fn bridge_config_authorization_validates_header_and_query_token_paths() {
-        let base = BridgeConfig {
-            host: "127.0.0.1".to_string(),
-            port: 8787,
-            preview_port: 8788,
-            connect_url: None,
-            preview_connect_url: None,
-            workdir: PathBuf::from("/tmp/workdir"),
-            cli_bin: "codex".to_string(),
-            opencode_cli_bin: "opencode".to_string(),
-            cursor_app_server_bin: "cursor-app-server".to_string(),
-            active_engine: BridgeRuntimeEngine::Codex,
-            enabled_engines: vec![BridgeRuntimeEngine::Codex, BridgeRuntimeEngine::Opencode],
-            opencode_host: "127.0.0.1".to_string(),
-            opencode_port: 4090,
-            opencode_server_username: "opencode".to_string(),
-            opencode_server_password: Some("secret-token".to_string()),
-            auth_token: Some("secret-token".to_string()),
-            auth_enabled: true,
-            allow_insecure_no_auth: false,
-            allow_query_token_auth: false,
-            allow_outside_root_cwd: false,
-            disable_terminal_exec: false,
-            terminal_allowed_commands: HashSet::new(),
-            show_pairing_qr: false,
-        };
+    #[tokio::test]
+    async fn handle_client_message_rejects_missing_method() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
 
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            "authorization",
-            "bearer secret-token".parse().expect("header value"),
-        );
-        assert!(base.is_authorized_with_bridge_token(&headers, None));
-        assert!(!base.is_authorized_with_bridge_token(&HeaderMap::new(), Some("secret-token")));
-        assert!(!base.is_authorized_with_bridge_token(&HeaderMap::new(), Some("secret-tok3n")));
+        handle_client_message(client_id, json!({ "id": "abc" }).to_string(), &state).await;
 
-        let mut query_allowed = base.clone();
-        query_allowed.allow_query_token_auth = true;
-        assert!(
-            query_allowed.is_authorized_with_bridge_token(&HeaderMap::new(), Some("secret-token"))
-        );
-        assert!(query_allowed
-            .is_authorized_with_bridge_token(&HeaderMap::new(), Some("  secret-token  ")));
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["id"], "abc");
+        assert_eq!(payload["error"]["code"], -32600);
+        assert_eq!(payload["error"]["message"], "Missing method");
 
-        let mut auth_disabled = base;
-        auth_disabled.auth_enabled = false;
-        auth_disabled.auth_token = None;
-        assert!(!auth_disabled.is_authorized_with_bridge_token(&HeaderMap::new(), None));
+        shutdown_test_backend(&state.backend).await;
     }
 
     #[tokio::test]
-    async fn app_server_forwarded_response_routes_to_original_client_request_id() {
-        let hub = Arc::new(ClientHub::new());
-        let bridge = build_test_bridge(hub.clone()).await;
-        let (client_id, mut rx) = add_test_client(&hub).await;
-
-        bridge
-            .forward_request(
-                client_id,
-                json!("client-req-1"),
-                "thread/start",
-                Some(json!({ "foo": "bar" })),
-            )
-            .await
-            .expect("forward request");
+    async fn handle_client_message_rejects_non_allowlisted_methods() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
 
-        bridge
-            .handle_response(json!({ "id": 1, "result": { "ok": true } }))
-            .await;
+        handle_client_message(
+            client_id,
+            json!({
+                "id": "abc",
+                "method": "thread/delete",
+            })
+            .to_string(),
+            &state,
+        )
+        .await;
 
         let payload = recv_client_json(&mut rx).await;
-        assert_eq!(payload["id"], "client-req-1");
-        assert_eq!(payload["result"]["ok"], true);
-        assert!(bridge.pending_requests.lock().await.is_empty());
+        assert_eq!(payload["id"], "abc");
+        assert_eq!(payload["error"]["code"], -32601);
 
-        shutdown_test_bridge(&bridge).await;
+        shutdown_test_backend(&state.backend).await;
     }
 
     #[tokio::test]
-    async fn successful_chatgpt_auth_token_login_populates_bridge_auth_cache() {
-        let _auth_cache_scope = TestBridgeChatGptAuthCacheScope::new();
-        clear_cached_bridge_chatgpt_auth();
-
-        let hub = Arc::new(ClientHub::new());
-        let bridge = build_test_bridge(hub.clone()).await;
-        let (client_id, mut rx) = add_test_client(&hub).await;
+    async fn rejected_methods_are_counted_per_method() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
 
-        bridge
-            .forward_request(
+        for method in ["thread/delete", "thread/delete", "thread/purge"] {
+            handle_client_message(
                 client_id,
-                json!("client-req-chatgpt-login"),
-                "account/login/start",
-                Some(json!({
-                    "type": "chatgptAuthTokens",
-                    "accessToken": "bridge-cached-token",
-                    "chatgptAccountId": "account-123",
-                    "chatgptPlanType": "team",
-                })),
+                json!({ "id": 1, "method": method }).to_string(),
+                &state,
             )
+            .await;
+            assert_eq!(recv_client_json(&mut rx).await["error"]["code"], -32601);
+        }
+
+        let rejected = handle_bridge_method("bridge/admin/rejectedMethods", None, &state, 0)
             .await
-            .expect("forward request");
+            .expect("rejected methods");
+        assert_eq!(
+            rejected["methods"],
+            json!([
+                { "method": "thread/delete", "count": 2 },
+                { "method": "thread/purge", "count": 1 },
+            ])
+        );
+        let metrics = render_bridge_metrics(0, &[], &[], &state.rejected_methods.snapshot());
+        assert!(metrics
+            .contains("clawdex_bridge_rejected_method_calls_total{method=\"thread/delete\"} 2\n"));
+        let injected = render_bridge_metrics(
+            0,
+            &[],
+            &[],
+            &[RejectedMethodEntry {
+                method: "a\"}\nfake_metric 1".to_string(),
+                count: 1,
+            }],
+        );
+        assert!(injected.contains(
+            "clawdex_bridge_rejected_method_calls_total{method=\"a\\\"}\\nfake_metric 1\"} 1\n"
+        ));
+        assert!(!injected.lines().any(|line| line.starts_with("fake_metric")));
 
-        bridge
-            .handle_response(json!({ "id": 1, "result": { "type": "chatgptAuthTokens" } }))
-            .await;
+        let counter = RejectedMethodCounter::default();
+        for index in 0..=REJECTED_METHODS_MAX_TRACKED {
+            counter.record(&format!("custom/method{index}"));
+        }
+        let snapshot = counter.snapshot();
+        assert_eq!(snapshot.len(), REJECTED_METHODS_MAX_TRACKED + 1);
+        assert!(snapshot
+            .iter()
+            .any(|entry| entry.method == REJECTED_METHODS_OVERFLOW_KEY && entry.count == 1));
 
-        let payload = recv_client_json(&mut rx).await;
-        assert_eq!(payload["id"], "client-req-chatgpt-login");
-        assert_eq!(payload["result"]["type"], "chatgptAuthTokens");
+        shutdown_test_backend(&state.backend).await;
+    }
 
-        let refresh_auth =
-            resolve_bridge_chatgpt_auth_bundle_for_refresh().expect("cached auth bundle");
-        assert_eq!(refresh_auth.access_token, "bridge-cached-token");
-        assert_eq!(refresh_auth.account_id, "account-123");
-        assert_eq!(refresh_auth.plan_type.as_deref(), Some("team"));
+    #[tokio::test]
+    async fn unknown_methods_suggest_the_closest_known_name() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
 
-        let (url, token, uses_openai_api) =
-            resolve_transcription_auth().expect("transcription auth");
-        assert_eq!(url, "https://chatgpt.com/backend-api/transcribe");
-        assert_eq!(token, "bridge-cached-token");
-        assert!(!uses_openai_api);
+        let error = handle_bridge_method("bridge/git/stauts", None, &state, client_id)
+            .await
+            .expect_err("typo is unknown");
+        assert_eq!(error.code, -32601);
+        assert_eq!(
+            error.data,
+            Some(json!({ "suggestion": "bridge/git/status" }))
+        );
 
-        clear_cached_bridge_chatgpt_auth();
-        shutdown_test_bridge(&bridge).await;
+        let unrelated = handle_bridge_method("bridge/zzz/qqq", None, &state, client_id)
+            .await
+            .expect_err("unrelated name is unknown");
+        assert_eq!(unrelated.data, None);
+
+        handle_client_message(
+            client_id,
+            json!({ "id": "typo", "method": "thread/strat" }).to_string(),
+            &state,
+        )
+        .await;
+        let payload = recv_client_json(&mut rx).await;
+        assert_eq!(payload["error"]["code"], -32601);
+        assert_eq!(payload["error"]["data"]["suggestion"], "thread/start");
+
+        let oversized = format!("thread/start{}", "x".repeat(SUGGEST_METHOD_NAME_MAX_LEN));
+        assert_eq!(
+            suggest_method_name(&oversized, ["thread/start"].into_iter()),
+            None
+        );
+
+        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+        shutdown_test_backend(&state.backend).await;
     }
 
     #[tokio::test]
-    async fn successful_account_logout_clears_cached_bridge_chatgpt_auth() {
-        let _auth_cache_scope = TestBridgeChatGptAuthCacheScope::new();
-        clear_cached_bridge_chatgpt_auth();
-        cache_bridge_chatgpt_auth(BridgeChatGptAuthBundle {
-            access_token: "cached-before-logout".to_string(),
-            account_id: "account-logout".to_string(),
-            plan_type: Some("plus".to_string()),
-        });
+    async fn handle_client_message_forwards_methods_added_by_env() {
+        let state = build_test_state_with_config(|config| {
+            config.extra_forwarded_methods = HashSet::from(["experimental/new".to_string()]);
+        })
+        .await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
 
-        let hub = Arc::new(ClientHub::new());
-        let bridge = build_test_bridge(hub.clone()).await;
-        let (client_id, mut rx) = add_test_client(&hub).await;
+        handle_client_message(
+            client_id,
+            json!({ "id": "extra", "method": "experimental/new" }).to_string(),
+            &state,
+        )
+        .await;
+        test_codex_backend(&state.backend)
+            .handle_response(json!({ "id": 1, "result": { "ok": true } }))
+            .await;
+        let forwarded = recv_client_json(&mut rx).await;
+        assert_eq!(forwarded["id"], "extra");
+        assert_eq!(forwarded["result"]["ok"], true);
 
-        bridge
-            .forward_request(
-                client_id,
-                json!("client-req-logout"),
-                "account/logout",
-                None,
-            )
-            .await
-            .expect("forward request");
+        handle_client_message(
+            client_id,
+            json!({ "id": "other", "method": "experimental/other" }).to_string(),
+            &state,
+        )
+        .await;
+        let rejected = recv_client_json(&mut rx).await;
+        assert_eq!(rejected["id"], "other");
+        assert_eq!(rejected["error"]["code"], -32601);
+
+        let descriptor = state.bridge_capability_descriptor();
+        assert_eq!(
+            descriptor.forwarded_methods.last().map(String::as_str),
+            Some("experimental/new")
+        );
 
-        bridge
-            .handle_response(json!({ "id": 1, "result": {} }))
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn forwarded_request_reusing_an_outstanding_id_is_rejected() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
+        let (other_client_id, _other_rx) = add_test_client(&state.hub).await;
+        let codex = test_codex_backend(&state.backend);
+        let request = json!({ "id": "dup", "method": "account/read" }).to_string();
+
+        handle_client_message(client_id, request.clone(), &state).await;
+        handle_client_message(client_id, request.clone(), &state).await;
+        let rejected = recv_client_json(&mut rx).await;
+        assert_eq!(rejected["id"], "dup");
+        assert_eq!(rejected["error"]["code"], -32600);
+        assert_eq!(rejected["error"]["data"]["duplicateId"], true);
+
+        handle_client_message(other_client_id, request.clone(), &state).await;
+        assert_eq!(codex.pending_requests.lock().await.len(), 2);
+
+        codex
+            .handle_response(json!({ "id": 1, "result": { "account": null } }))
             .await;
+        let answered = recv_client_json(&mut rx).await;
+        assert_eq!(answered["id"], "dup");
+        assert!(answered.get("error").is_none());
 
-        let payload = recv_client_json(&mut rx).await;
-        assert_eq!(payload["id"], "client-req-logout");
-        assert_eq!(payload["result"], json!({}));
-        assert!(read_cached_bridge_chatgpt_auth().is_none());
-        assert!(resolve_bridge_chatgpt_auth_bundle_for_refresh().is_none());
+        handle_client_message(client_id, request, &state).await;
+        assert_eq!(codex.pending_requests.lock().await.len(), 2);
 
-        clear_cached_bridge_chatgpt_auth();
-        shutdown_test_bridge(&bridge).await;
+        shutdown_test_backend(&state.backend).await;
     }
 
     #[tokio::test]
-    async fn app_server_fail_all_pending_notifies_waiting_clients() {
-        let hub = Arc::new(ClientHub::new());
-        let bridge = build_test_bridge(hub.clone()).await;
-        let (client_a, mut rx_a) = add_test_client(&hub).await;
-        let (client_b, mut rx_b) = add_test_client(&hub).await;
+    async fn app_server_response_with_stringified_id_routes_to_client() {
+        let state = build_test_state().await;
+        let (client_id, mut rx) = add_test_client(&state.hub).await;
+        let codex = test_codex_backend(&state.backend);
 
-        bridge
-            .forward_request(client_a, json!("req-a"), "thread/start", None)
+        handle_client_message(
+            client_id,
+            json!({ "id": 7, "method": "account/read" }).to_string(),
+            &state,
+        )
+        .await;
+        let internal_id = *codex
+            .pending_requests
+            .lock()
             .await
-            .expect("forward request a");
-        bridge
-            .forward_request(client_b, json!("req-b"), "thread/start", None)
+            .keys()
+            .next()
+            .expect("forwarded request is pending");
+
+        codex
+            .handle_incoming(json!({
+                "id": internal_id.to_string(),
+                "result": { "account": null },
+            }))
+            .await;
+        let answered = recv_client_json(&mut rx).await;
+        assert_eq!(answered["id"], 7);
+        assert_eq!(answered["result"], json!({ "account": null }));
+        assert!(codex.pending_requests.lock().await.is_empty());
+        assert!(codex
+            .pending_requests_by_client
+            .lock()
             .await
-            .expect("forward request b");
+            .get(&client_id)
+            .is_none_or(HashSet::is_empty));
 
-        bridge.fail_all_pending("app-server closed").await;
+        shutdown_test_backend(&state.backend).await;
+    }
 
-        let payload_a = recv_client_json(&mut rx_a).await;
-        let payload_b = recv_client_json(&mut rx_b).await;
+    #[tokio::test]
+    async fn successful_commit_broadcasts_hash_and_branch() {
+        let state = build_test_state().await;
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let repo = state
+            .config
+            .workdir
+            .join(format!("clawdex-git-committed-{nonce}"));
+        std::fs::create_dir_all(&repo).expect("create repo dir");
+        for args in [
+            &["init", "--quiet", "--initial-branch=main"][..],
+            &["config", "user.email", "bridge@example.com"],
+            &["config", "user.name", "Bridge Test"],
+        ] {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(args)
+                .status()
+                .expect("run git");
+            assert!(status.success());
+        }
+        std::fs::write(repo.join("note.txt"), "hello\n").expect("write file");
+
+        let result = handle_bridge_method(
+            "bridge/git/commitAll",
+            Some(json!({ "message": "Add note", "cwd": repo.to_string_lossy() })),
+            &state,
+            0,
+        )
+        .await
+        .expect("commit all");
+        assert_eq!(result["committed"], true);
 
-        assert_eq!(payload_a["id"], "req-a");
-        assert_eq!(payload_a["error"]["code"], -32000);
-        assert_eq!(payload_b["id"], "req-b");
-        assert_eq!(payload_b["error"]["code"], -32000);
+        let (events, _, _) = state.hub.replay_since(None, 100).await;
+        let committed = events
+            .iter()
+            .find(|event| event["method"] == "bridge/git/committed")
+            .expect("bridge/git/committed notification");
+        let head = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .expect("rev-parse");
+        assert_eq!(
+            committed["params"]["hash"],
+            String::from_utf8_lossy(&head.stdout).trim()
+        );
+        assert_eq!(committed["params"]["branch"], "main");
+        assert_eq!(result["hash"], committed["params"]["hash"]);
+
+        std::fs::write(repo.join("second.txt"), "again\n").expect("write file");
+        let staged = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["add", "second.txt"])
+            .status()
+            .expect("git add");
+        assert!(staged.success());
+        let after_event_id = state.hub.latest_event_id();
+        let result = handle_bridge_method(
+            "bridge/git/commit",
+            Some(json!({ "message": "Add second", "cwd": repo.to_string_lossy() })),
+            &state,
+            0,
+        )
+        .await
+        .expect("commit");
+        assert_eq!(result["committed"], true);
+        let head = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .expect("rev-parse");
+        let head = String::from_utf8_lossy(&head.stdout).trim().to_string();
+        assert_eq!(result["hash"], head);
+
+        let (events, _, _) = state.hub.replay_since(Some(after_event_id), 100).await;
+        let committed = events
+            .iter()
+            .find(|event| event["method"] == "bridge/git/committed")
+            .expect("bridge/git/committed notification");
+        assert_eq!(committed["params"]["hash"], head);
+        assert_eq!(committed["params"]["cwd"], result["cwd"]);
 
-        shutdown_test_bridge(&bridge).await;
+        std::fs::remove_dir_all(&repo).ok();
+        shutdown_test_backend(&state.backend).await;
     }
 
     #[tokio::test]
-    async fn handle_server_request_item_tool_call_returns_structured_unsupported_result() {
-        let nonce = std::time::SystemTime::now()
+    async fn git_root_is_reported_by_status_but_not_health() {
+        let nonce = SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .expect("system clock after unix epoch")
             .as_nanos();
-        let capture_path = env::temp_dir().join(format!("clawdex-tool-call-capture-{nonce}.jsonl"));
-        let shell_command = format!("cat > {}", capture_path.to_string_lossy());
+        let repo = normalize_path(&env::temp_dir().join(format!("clawdex-git-root-{nonce}")));
+        std::fs::create_dir_all(repo.join("nested")).expect("create repo dir");
+        let init = std::process::Command::new("git")
+            .arg("-C")
+            .arg(&repo)
+            .args(["init", "--quiet"])
+            .status()
+            .expect("run git init");
+        assert!(init.success());
+        let workdir = repo.join("nested");
+        let state = build_test_state_with_config(|config| config.workdir = workdir).await;
+
+        let Json(health) = health_handler(State(state.clone())).await;
+        assert_eq!(health["gitRepo"], true);
+        assert!(health.get("gitRoot").is_none());
+
+        let status = handle_bridge_method("bridge/status/read", None, &state, 0)
+            .await
+            .expect("status");
+        assert_eq!(status["gitRoot"], path_to_string(&repo));
 
-        let mut child = Command::new("sh")
-            .arg("-c")
-            .arg(shell_command)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .spawn()
-            .expect("spawn capture process");
-        let writer = child.stdin.take().expect("capture stdin available");
+        std::fs::remove_dir_all(&repo).ok();
+        shutdown_test_backend(&state.backend).await;
+    }
 
-        let hub = Arc::new(ClientHub::new());
-        let bridge = Arc::new(AppServerBridge {
-            engine: BridgeRuntimeEngine::Codex,
-            child: Mutex::new(child),
-            child_pid: 0,
-            writer: Mutex::new(writer),
-            pending_requests: Mutex::new(HashMap::new()),
-            internal_waiters: Mutex::new(HashMap::new()),
-            pending_approvals: Mutex::new(HashMap::new()),
-            pending_user_inputs: Mutex::new(HashMap::new()),
-            next_request_id: AtomicU64::new(1),
-            approval_counter: AtomicU64::new(1),
-            user_input_counter: AtomicU64::new(1),
-            hub: hub.clone(),
-        });
+    #[tokio::test]
+    async fn git_status_multi_returns_partial_results() {
+        let state = build_test_state().await;
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let base = state
+            .config
+            .workdir
+            .join(format!("clawdex-status-multi-{nonce}"));
+        let repos = ["repo-a", "repo-b"].map(|name| base.join(name));
+        for repo in &repos {
+            std::fs::create_dir_all(repo).expect("create repo dir");
+            let init = std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo)
+                .args(["init", "--quiet"])
+                .status()
+                .expect("run git init");
+            assert!(init.success());
+        }
+        std::fs::write(repos[1].join("new.txt"), "new\n").expect("write untracked");
+        let missing = base.join("missing");
+
+        let result = handle_bridge_method(
+            "bridge/git/statusMulti",
+            Some(json!({
+                "cwds": [
+                    repos[0].to_string_lossy(),
+                    repos[1].to_string_lossy(),
+                    missing.to_string_lossy(),
+                ],
+            })),
+            &state,
+            0,
+        )
+        .await
+        .expect("status multi");
+        let results = &result["results"];
+        let clean = &results[repos[0].to_string_lossy().as_ref()];
+        assert_eq!(clean["status"]["clean"], true);
+        assert!(clean["error"].is_null());
+        let dirty = &results[repos[1].to_string_lossy().as_ref()];
+        assert_eq!(dirty["status"]["clean"], false);
+        let failed = &results[missing.to_string_lossy().as_ref()];
+        assert!(failed["status"].is_null());
+        assert!(failed["error"].is_string());
+
+        let too_many = (0..=MAX_GIT_STATUS_MULTI_CWDS)
+            .map(|index| format!("repo-{index}"))
+            .collect::<Vec<_>>();
+        let error = handle_bridge_method(
+            "bridge/git/statusMulti",
+            Some(json!({ "cwds": too_many })),
+            &state,
+            0,
+        )
+        .await
+        .expect_err("too many cwds");
+        assert_eq!(error.code, -32602);
 
-        let (_client_id, mut rx) = add_test_client(&hub).await;
+        std::fs::remove_dir_all(&base).ok();
+        shutdown_test_backend(&state.backend).await;
+    }
 
-        bridge
-            .handle_server_request(
-                DYNAMIC_TOOL_CALL_METHOD,
-                json!("tool-call-1"),
-                Some(json!({
-                    "callId": "call_demo_1",
-                    "threadId": "thr_demo_1",
-                    "turnId": "turn_demo_1",
-                    "tool": "demo_tool",
-                    "arguments": { "hello": "world" }
-                })),
-            )
-            .await;
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn stored_thread_cwd_is_used_when_request_omits_cwd() {
+        let state = build_test_state().await;
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let dir_name = format!("clawdex-thread-cwd-{nonce}");
+        let thread_dir = state.config.workdir.join(&dir_name);
+        std::fs::create_dir_all(&thread_dir).expect("create thread dir");
 
-        let notification = recv_client_json(&mut rx).await;
-        assert_eq!(notification["method"], "bridge/tool.call.unsupported");
-        assert_eq!(notification["params"]["request"]["tool"], "demo_tool");
+        let stored = handle_bridge_method(
+            "bridge/workspace/setCwd",
+            Some(json!({ "threadId": "thr_1", "cwd": dir_name })),
+            &state,
+            0,
+        )
+        .await
+        .expect("set cwd");
+        assert_eq!(stored["cwd"], thread_dir.to_string_lossy().as_ref());
 
-        tokio::time::sleep(Duration::from_millis(60)).await;
-        shutdown_test_bridge(&bridge).await;
+        let defaulted = apply_thread_cwd_default(
+            "bridge/terminal/exec",
+            Some(json!({ "command": "pwd", "threadId": "thr_1" })),
+            &state,
+        )
+        .await
+        .expect("params");
+        assert_eq!(defaulted["cwd"], thread_dir.to_string_lossy().as_ref());
 
-        let captured = std::fs::read_to_string(&capture_path).expect("capture file exists");
-        std::fs::remove_file(&capture_path).ok();
+        let explicit = apply_thread_cwd_default(
+            "bridge/git/status",
+            Some(json!({ "threadId": "thr_1", "cwd": "." })),
+            &state,
+        )
+        .await
+        .expect("params");
+        assert_eq!(explicit["cwd"], ".");
 
-        println!("captured_app_server_response={captured}");
+        let other_thread = apply_thread_cwd_default(
+            "bridge/git/status",
+            Some(json!({ "threadId": "thr_2" })),
+            &state,
+        )
+        .await
+        .expect("params");
+        assert!(other_thread.get("cwd").is_none());
 
-        assert!(captured.contains("\"id\":\"tool-call-1\""));
-        assert!(captured.contains("\"success\":false"));
-        assert!(captured.contains("Dynamic tool calls are not supported by clawdex-mobile bridge"));
+        let outside = handle_bridge_method(
+            "bridge/workspace/setCwd",
+            Some(json!({ "threadId": "thr_1", "cwd": "/" })),
+            &state,
+            0,
+        )
+        .await
+        .expect_err("cwd outside the workdir");
+        assert_eq!(outside.code, -32602);
+
+        let escape_link = thread_dir.join("escape");
+        std::os::unix::fs::symlink("/", &escape_link).expect("create escaping symlink");
+        let escaped = handle_bridge_method(
+            "bridge/workspace/setCwd",
+            Some(json!({ "threadId": "thr_1", "cwd": format!("{dir_name}/escape") })),
+            &state,
+            0,
+        )
+        .await
+        .expect_err("cwd through a symlink out of the workdir");
+        assert_eq!(escaped.code, -32602);
+
+        std::fs::remove_dir_all(&thread_dir).ok();
+        shutdown_test_backend(&state.backend).await;
     }
 
+    #[cfg(unix)]
     #[tokio::test]
-    async fn app_server_response_completes_internal_waiter() {
+    async fn stored_thread_cwd_under_a_symlinked_workdir_passes_the_resolvers() {
+        let nonce = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let base = normalize_path(&env::temp_dir().join(format!("clawdex-linked-workdir-{nonce}")));
+        let real_root = base.join("real");
+        std::fs::create_dir_all(real_root.join("project")).expect("create project dir");
+        let linked_root = base.join("linked");
+        std::os::unix::fs::symlink(&real_root, &linked_root).expect("link workdir");
+        let state = build_test_state_with_config({
+            let linked_root = linked_root.clone();
+            move |config| config.workdir = linked_root
+        })
+        .await;
+
+        let stored = handle_bridge_method(
+            "bridge/workspace/setCwd",
+            Some(json!({ "threadId": "thr_1", "cwd": "project" })),
+            &state,
+            0,
+        )
+        .await
+        .expect("set cwd");
+        let project = linked_root.join("project");
+        assert_eq!(stored["cwd"], project.to_string_lossy().as_ref());
+
+        handle_bridge_method(
+            "bridge/git/init",
+            Some(json!({ "threadId": "thr_1" })),
+            &state,
+            0,
+        )
+        .await
+        .expect("git init in the stored cwd");
+        assert!(real_root.join("project").join(".git").exists());
+        handle_bridge_method(
+            "bridge/git/status",
+            Some(json!({ "threadId": "thr_1" })),
+            &state,
+            0,
+        )
+        .await
+        .expect("git status in the stored cwd");
+
+        std::fs::remove_dir_all(&base).ok();
+        shutdown_test_backend(&state.backend).await;
+    }
+
+    #[tokio::test]
+    async fn thread_archive_forgets_the_stored_cwd_only_after_it_succeeds() {
         let hub = Arc::new(ClientHub::new());
-        let bridge = build_test_bridge(hub).await;
-        let (tx, rx) = oneshot::channel();
-        bridge.internal_waiters.lock().await.insert(7, tx);
+        let bridge = build_test_bridge(hub.clone()).await;
+        let (client_id, mut rx) = add_test_client(&hub).await;
+        hub.store_thread_cwd("codex:thr_archived", env::temp_dir())
+            .await;
 
+        let archive = || {
+            bridge.forward_request(
+                client_id,
+                json!("archive"),
+                "thread/archive",
+                Some(json!({ "threadId": "thr_archived" })),
+            )
+        };
+        archive().await.expect("forward request");
         bridge
-            .handle_response(json!({ "id": 7, "result": { "initialized": true } }))
+            .handle_response(json!({ "id": 1, "error": { "code": -32000, "message": "busy" } }))
             .await;
+        recv_client_json(&mut rx).await;
+        assert!(hub.thread_cwd("thr_archived").await.is_some());
 
-        let result = rx.await.expect("waiter result").expect("successful result");
-        assert_eq!(result["initialized"], true);
+        archive().await.expect("forward request");
+        bridge
+            .handle_response(json!({ "id": 2, "result": {} }))
+            .await;
+        recv_client_json(&mut rx).await;
+        assert!(hub.thread_cwd("thr_archived").await.is_none());
 
         shutdown_test_bridge(&bridge).await;
     }
 
     #[tokio::test]
-    async fn handle_client_message_returns_parse_error_for_invalid_json() {
+    async fn diagnostics_echo_returns_params_unchanged_with_metadata() {
         let state = build_test_state().await;
-        let (client_id, mut rx) = add_test_client(&state.hub).await;
+        let (client_id, _rx) = add_test_client(&state.hub).await;
+        state
+            .hub
+            .broadcast_notification("turn/started", json!({ "threadId": "thr_1" }))
+            .await;
+        let params = json!({
+            "text": "héllo \u{1F600} \"quoted\"\n",
+            "nested": { "list": [1, 2.5, -3, null, true], "empty": {} },
+            "big": u64::MAX,
+        });
 
-        handle_client_message(client_id, "{invalid-json".to_string(), &state).await;
+        let echoed = handle_bridge_method(
+            "bridge/diagnostics/echo",
+            Some(params.clone()),
+            &state,
+            client_id,
+        )
+        .await
+        .expect("echo");
+        assert_eq!(echoed["params"], params);
+        assert_eq!(echoed["clientId"], client_id);
+        assert_eq!(echoed["eventIdAtEcho"], 1);
+        assert!(echoed["at"].as_str().is_some_and(|at| !at.is_empty()));
 
-        let payload = recv_client_json(&mut rx).await;
-        assert_eq!(payload["id"], Value::Null);
-        assert_eq!(payload["error"]["code"], -32700);
+        let empty = handle_bridge_method("bridge/diagnostics/echo", None, &state, client_id)
+            .await
+            .expect("echo without params");
+        assert_eq!(empty["params"], Value::Null);
 
         shutdown_test_backend(&state.backend).await;
     }
 
     #[tokio::test]
-    async fn handle_client_message_rejects_missing_method() {
+    async fn health_deep_probe_reports_ok_slow_and_down() {
         let state = build_test_state().await;
-        let (client_id, mut rx) = add_test_client(&state.hub).await;
+        let codex = test_codex_backend(&state.backend);
+        let answer_probe_after = |delay: Duration| {
+            let codex = codex.clone();
+            tokio::spawn(async move {
+                let internal_id = loop {
+                    if let Some(id) = codex.internal_waiters.lock().await.keys().next() {
+                        break *id;
+                    }
+                    sleep(Duration::from_millis(2)).await;
+                };
+                sleep(delay).await;
+                codex
+                    .handle_response(json!({ "id": internal_id, "result": { "data": [] } }))
+                    .await;
+            })
+        };
 
-        handle_client_message(client_id, json!({ "id": "abc" }).to_string(), &state).await;
+        let responder = answer_probe_after(Duration::ZERO);
+        let timely = state
+            .backend
+            .probe_health(Duration::from_secs(1), Duration::from_secs(2))
+            .await;
+        responder.await.expect("responder task");
+        assert_eq!(timely.app_server, "ok");
+        assert_eq!(timely.engine, BridgeRuntimeEngine::Codex);
+        assert!(timely.error.is_none());
 
-        let payload = recv_client_json(&mut rx).await;
-        assert_eq!(payload["id"], "abc");
-        assert_eq!(payload["error"]["code"], -32600);
-        assert_eq!(payload["error"]["message"], "Missing method");
+        let responder = answer_probe_after(Duration::from_millis(60));
+        let slow = state
+            .backend
+            .probe_health(Duration::from_millis(20), Duration::from_secs(2))
+            .await;
+        responder.await.expect("responder task");
+        assert_eq!(slow.app_server, "slow");
+        assert!(slow.latency_ms >= 20);
+
+        let down = state
+            .backend
+            .probe_health(Duration::from_millis(10), Duration::from_millis(50))
+            .await;
+        assert_eq!(down.app_server, "down");
+        assert!(down.error.is_some_and(|error| error.contains("50ms")));
+        assert!(codex.internal_waiters.lock().await.is_empty());
+
+        let payload = serde_json::to_value(AppServerProbe::from_outcome(
+            BridgeRuntimeEngine::Codex,
+            Duration::from_millis(12),
+            HEALTH_DEEP_SLOW_THRESHOLD,
+            Ok(()),
+        ))
+        .expect("serialize probe");
+        assert_eq!(payload["appServer"], "ok");
+        assert_eq!(payload["latencyMs"], 12);
+        assert_eq!(payload["engine"], "codex");
 
         shutdown_test_backend(&state.backend).await;
     }
 
     #[tokio::test]
-    async fn handle_client_message_rejects_non_allowlisted_methods() {
+    async fn app_server_stats_track_bytes_per_forwarded_method() {
         let state = build_test_state().await;
         let (client_id, mut rx) = add_test_client(&state.hub).await;
+        let codex = test_codex_backend(&state.backend);
 
+        handle_client_message(
+            client_id,
+            json!({ "id": "a", "method": "account/read" }).to_string(),
+            &state,
+        )
+        .await;
         handle_client_message(
             client_id,
             json!({
-                "id": "abc",
-                "method": "thread/delete",
+                "id": "b",
+                "method": "thread/read",
+                "params": { "threadId": "thr_with_a_longer_identifier" },
             })
             .to_string(),
             &state,
         )
         .await;
+        codex
+            .handle_response(json!({ "id": 1, "result": { "account": null } }))
+            .await;
+        codex
+            .handle_response(
+                json!({ "id": 2, "result": { "thread": { "id": "thr", "turns": [] } } }),
+            )
+            .await;
+        recv_client_json(&mut rx).await;
+        recv_client_json(&mut rx).await;
 
-        let payload = recv_client_json(&mut rx).await;
-        assert_eq!(payload["id"], "abc");
-        assert_eq!(payload["error"]["code"], -32601);
+        let stats = handle_bridge_method("bridge/appServer/stats", None, &state, client_id)
+            .await
+            .expect("stats");
+        let methods = stats["methods"].as_array().expect("methods");
+        assert_eq!(methods.len(), 2);
+        let account = &methods[0];
+        let thread = &methods[1];
+        assert_eq!(account["method"], "account/read");
+        assert_eq!(thread["method"], "thread/read");
+        for entry in [account, thread] {
+            assert_eq!(entry["engine"], "codex");
+            assert_eq!(entry["requests"], 1);
+            assert_eq!(entry["responses"], 1);
+        }
+        let bytes = |entry: &Value, key: &str| entry[key].as_u64().expect("byte count");
+        assert_eq!(
+            bytes(account, "requestBytes"),
+            json!({ "id": 1, "method": "account/read" })
+                .to_string()
+                .len() as u64
+                + 1
+        );
+        assert!(bytes(thread, "requestBytes") > bytes(account, "requestBytes"));
+        assert!(bytes(thread, "responseBytes") > bytes(account, "responseBytes"));
+
+        let metrics =
+            render_bridge_metrics(0, &[], &state.backend.app_server_method_traffic(), &[]);
+        assert!(metrics.contains(&format!(
+            "clawdex_bridge_app_server_response_bytes_total{{engine=\"codex\",method=\"thread/read\"}} {}\n",
+            bytes(thread, "responseBytes")
+        )));
 
         shutdown_test_backend(&state.backend).await;
     }
@@ -1,23 +1,56 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex as StdMutex},
+    time::Instant,
 };
 
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
 use crate::{
-    normalize_path, BridgeError, GitBranchSummary, GitBranchesResponse, GitCloneResponse,
-    GitCommitResponse, GitDiffResponse, GitHistoryCommit, GitHistoryResponse, GitPushResponse,
-    GitStageAllResponse, GitStageResponse, GitStatusEntry, GitStatusResponse, GitSwitchResponse,
-    GitUnstageAllResponse, GitUnstageResponse,
+    normalize_path, BridgeError, GitApplyPatchResponse, GitBlameLine, GitBlameRangeResponse,
+    GitBranchSummary, GitBranchesResponse, GitCherryPickResponse, GitCloneResponse,
+    GitCommitAllResponse, GitCommitResponse, GitConfigResponse, GitDiffResponse,
+    GitFileLogResponse, GitHistoryCommit, GitHistoryResponse, GitInitResponse, GitIsCleanResponse,
+    GitMoveResponse, GitPushResponse, GitReflogEntry, GitReflogResponse, GitRevParseResponse,
+    GitStageAllResponse, GitStageResponse, GitStagedPath, GitStagedPathsResponse,
+    GitStashActionResponse, GitStashEntry, GitStashListResponse, GitStatusEntry, GitStatusResponse,
+    GitStatusSummaryResponse, GitSwitchResponse, GitTagCreateResponse, GitTagsResponse,
+    GitUnstageAllResponse, GitUnstageResponse, GitWorktreeEntry, GitWorktreesResponse,
 };
 
 use super::TerminalService;
 
+const MAX_GIT_PATCH_BYTES: usize = 4 * 1024 * 1024;
+const GIT_HISTORY_PRETTY_FORMAT: &str = "--pretty=format:%H\x1f%h\x1f%an\x1f%aI\x1f%D\x1f%s\x1e";
+const DEFAULT_GIT_FILE_LOG_LIMIT: usize = 30;
+const MAX_GIT_FILE_LOG_LIMIT: usize = 100;
+const MAX_GIT_BLAME_RANGE_LINES: usize = 1_000;
+const DEFAULT_GIT_REFLOG_LIMIT: usize = 50;
+const MAX_GIT_REFLOG_LIMIT: usize = 500;
+/// Config keys the app may read or write; anything else could run commands or leak secrets.
+const GIT_CONFIG_SAFE_KEYS: &[&str] = &["user.name", "user.email", "commit.gpgsign"];
+const MAX_GIT_CONFIG_VALUE_LEN: usize = 256;
+const MAX_CACHED_REPO_ROOTS: usize = 256;
+
 #[derive(Clone)]
 pub(crate) struct GitService {
     terminal: Arc<TerminalService>,
     root: PathBuf,
     allow_outside_root: bool,
+    /// Git is not safe against concurrent index writes, so mutations on the same work
+    /// tree take turns, whichever subdirectory they run from; reads never lock.
+    repo_locks: Arc<StdMutex<HashMap<PathBuf, Arc<Mutex<()>>>>>,
+    /// Work-tree top level per resolved cwd, so git calls do not each spawn a
+    /// `git rev-parse` first. An entry is trusted while `<top level>/.git` exists and no
+    /// directory between the cwd and the top level has gained a `.git` of its own. Past
+    /// `MAX_CACHED_REPO_ROOTS` the least recently used entry makes room.
+    repo_roots: Arc<StdMutex<HashMap<PathBuf, CachedRepoRoot>>>,
+}
+
+struct CachedRepoRoot {
+    root: PathBuf,
+    last_used: Instant,
 }
 
 impl GitService {
@@ -30,21 +63,208 @@ impl GitService {
             terminal,
             root,
             allow_outside_root,
+            repo_locks: Arc::new(StdMutex::new(HashMap::new())),
+            repo_roots: Arc::new(StdMutex::new(HashMap::new())),
         }
     }
 
-    fn resolve_repo_path(&self, raw_cwd: Option<&str>) -> Result<PathBuf, BridgeError> {
+    async fn lock_repo(&self, repo_path: &Path) -> OwnedMutexGuard<()> {
+        let key = self
+            .lookup_repo_root(repo_path)
+            .await
+            .unwrap_or_else(|| repo_path.to_path_buf());
+        let lock = {
+            let mut locks = self
+                .repo_locks
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            // Drop locks nobody holds or waits on so the map does not grow without bound.
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks
+                .entry(key)
+                .or_insert_with(|| Arc::new(Mutex::new(())))
+                .clone()
+        };
+        lock.lock_owned().await
+    }
+
+    fn resolve_git_path(&self, raw_cwd: Option<&str>) -> Result<PathBuf, BridgeError> {
         resolve_git_cwd(raw_cwd, &self.root, self.allow_outside_root)
     }
 
+    async fn resolve_repo_path(&self, raw_cwd: Option<&str>) -> Result<PathBuf, BridgeError> {
+        let repo_path = self.resolve_git_path(raw_cwd)?;
+        if !self.is_repo(&repo_path).await {
+            return Err(BridgeError::not_a_git_repository(
+                &repo_path.to_string_lossy(),
+            ));
+        }
+
+        Ok(repo_path)
+    }
+
+    pub(crate) async fn is_repo(&self, path: &Path) -> bool {
+        self.lookup_repo_root(path).await.is_some()
+    }
+
+    /// Top-level directory of the work tree containing `path`, or `None` outside one. Only
+    /// asks git on a cache miss.
+    async fn lookup_repo_root(&self, path: &Path) -> Option<PathBuf> {
+        if let Some(root) = self.cached_repo_root(path).await {
+            return Some(root);
+        }
+        if !tokio::fs::metadata(path)
+            .await
+            .is_ok_and(|metadata| metadata.is_dir())
+        {
+            return None;
+        }
+
+        let args = vec![
+            "-C".to_string(),
+            path.to_string_lossy().to_string(),
+            "rev-parse".to_string(),
+            "--show-toplevel".to_string(),
+        ];
+        let result = self
+            .terminal
+            .execute_binary("git", &args, path.to_path_buf(), None)
+            .await
+            .ok()?;
+        let toplevel = result.stdout.trim();
+        if result.code != Some(0) || toplevel.is_empty() {
+            return None;
+        }
+
+        let root = normalize_path(Path::new(toplevel));
+        let mut roots = self
+            .repo_roots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if roots.len() >= MAX_CACHED_REPO_ROOTS && !roots.contains_key(path) {
+            let least_recently_used = roots
+                .iter()
+                .min_by_key(|(_, cached)| cached.last_used)
+                .map(|(cached_path, _)| cached_path.clone());
+            if let Some(least_recently_used) = least_recently_used {
+                roots.remove(&least_recently_used);
+            }
+        }
+        roots.insert(
+            path.to_path_buf(),
+            CachedRepoRoot {
+                root: root.clone(),
+                last_used: Instant::now(),
+            },
+        );
+        Some(root)
+    }
+
+    async fn cached_repo_root(&self, path: &Path) -> Option<PathBuf> {
+        let root = {
+            let mut roots = self
+                .repo_roots
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let cached = roots.get_mut(path)?;
+            cached.last_used = Instant::now();
+            cached.root.clone()
+        };
+        // The `.git` probes are blocking filesystem calls, so they run off the async
+        // workers and without the cache lock held.
+        let still_owned = tokio::task::spawn_blocking({
+            let root = root.clone();
+            let path = path.to_path_buf();
+            move || repo_root_still_owns(&root, &path)
+        })
+        .await
+        .unwrap_or(false);
+        if still_owned {
+            return Some(root);
+        }
+
+        let mut roots = self
+            .repo_roots
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Another lookup may have re-cached `path` while the probes ran.
+        if roots.get(path).is_some_and(|cached| cached.root == root) {
+            roots.remove(path);
+        }
+        None
+    }
+
+    /// Resolves `raw_cwd` to the top-level directory of its work tree.
+    pub(crate) async fn repo_root(&self, raw_cwd: Option<&str>) -> Result<PathBuf, BridgeError> {
+        let repo_path = self.resolve_git_path(raw_cwd)?;
+        self.lookup_repo_root(&repo_path)
+            .await
+            .ok_or_else(|| BridgeError::not_a_git_repository(&repo_path.to_string_lossy()))
+    }
+
+    /// Top-level directory of the repo containing the bridge root, or `None` when the
+    /// root is not inside a git work tree.
+    pub(crate) async fn workdir_repo_root(&self) -> Option<PathBuf> {
+        self.repo_root(None).await.ok()
+    }
+
+    pub(crate) async fn init(
+        &self,
+        raw_cwd: Option<&str>,
+        raw_initial_branch: Option<&str>,
+    ) -> Result<GitInitResponse, BridgeError> {
+        let repo_path = self.resolve_git_path(raw_cwd)?;
+        if !repo_path.is_dir() {
+            return Err(BridgeError::invalid_params(
+                "cwd must be an existing directory",
+            ));
+        }
+
+        let mut args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "init".to_string(),
+        ];
+        if let Some(raw_branch) = raw_initial_branch.filter(|value| !value.trim().is_empty()) {
+            let branch = normalize_git_branch_target(raw_branch)?;
+            args.push(format!("--initial-branch={branch}"));
+        }
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+
+        Ok(GitInitResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            initialized: result.code == Some(0),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
     pub(crate) async fn get_status(
         &self,
         raw_cwd: Option<&str>,
     ) -> Result<GitStatusResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+        self.get_status_with_ignored(raw_cwd, false).await
+    }
+
+    /// `get_status`, optionally listing ignored files as `!!` entries with `ignored: true`.
+    /// Ignored entries do not make the tree dirty and are left out of `raw`.
+    pub(crate) async fn get_status_with_ignored(
+        &self,
+        raw_cwd: Option<&str>,
+        include_ignored: bool,
+    ) -> Result<GitStatusResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
         let args = vec![
             "-C".to_string(),
             repo_path.to_string_lossy().to_string(),
+            // Status reads must not refresh `.git/index`: the rewrite would wake the git
+            // watcher that asked for the status and produce a second update.
+            "--no-optional-locks".to_string(),
             "status".to_string(),
             "--short".to_string(),
             "--branch".to_string(),
@@ -73,7 +293,9 @@ impl GitService {
             .filter(|line| !line.trim().is_empty())
             .collect::<Vec<_>>();
 
-        let porcelain_entries = self.get_porcelain_status_entries(&repo_path).await?;
+        let porcelain_entries = self
+            .get_porcelain_status_entries(&repo_path, include_ignored)
+            .await?;
 
         let branch = lines
             .iter()
@@ -87,7 +309,7 @@ impl GitService {
             .unwrap_or("unknown")
             .to_string();
 
-        let clean = porcelain_entries.is_empty();
+        let clean = porcelain_entries.iter().all(|entry| entry.ignored);
 
         Ok(GitStatusResponse {
             branch,
@@ -98,12 +320,65 @@ impl GitService {
         })
     }
 
+    pub(crate) async fn get_status_summary(
+        &self,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitStatusSummaryResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let raw = self.run_porcelain_status(&repo_path, false).await?;
+        let header = raw
+            .split('\0')
+            .find_map(|token| token.strip_prefix("## "))
+            .map(parse_porcelain_branch_header)
+            .unwrap_or_default();
+        let entries = parse_porcelain_status_entries(&raw)?;
+
+        let mut summary = GitStatusSummaryResponse {
+            branch: header.branch,
+            upstream: header.upstream,
+            upstream_gone: header.gone,
+            ahead: header.ahead,
+            behind: header.behind,
+            staged: 0,
+            unstaged: 0,
+            untracked: 0,
+            conflicts: 0,
+            clean: entries.is_empty(),
+            cwd: repo_path.to_string_lossy().to_string(),
+        };
+        for entry in &entries {
+            if entry.untracked {
+                summary.untracked += 1;
+            } else if is_conflicted_status(&entry.index_status, &entry.worktree_status) {
+                summary.conflicts += 1;
+            } else {
+                summary.staged += usize::from(entry.staged);
+                summary.unstaged += usize::from(entry.unstaged);
+            }
+        }
+
+        Ok(summary)
+    }
+
     pub(crate) async fn get_diff(
         &self,
         raw_cwd: Option<&str>,
+        raw_base: Option<&str>,
+        raw_head: Option<&str>,
     ) -> Result<GitDiffResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
-        let entries = self.get_porcelain_status_entries(&repo_path).await?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        if let Some(args) = build_ref_diff_args(raw_base, raw_head)? {
+            let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+            let diff = self
+                .run_git_diff_command(&repo_path, &args, false, "git diff for ref range failed")
+                .await?;
+            return Ok(GitDiffResponse {
+                diff,
+                cwd: repo_path.to_string_lossy().to_string(),
+            });
+        }
+
+        let entries = self.get_porcelain_status_entries(&repo_path, false).await?;
         let mut sections = Vec::new();
 
         for entry in entries {
@@ -198,12 +473,31 @@ impl GitService {
         })
     }
 
+    pub(crate) async fn get_staged_paths(
+        &self,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitStagedPathsResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let output = self
+            .run_git_stdout(
+                &repo_path,
+                &["diff", "--cached", "--name-status", "-z"],
+                "git diff --cached failed",
+            )
+            .await?;
+
+        Ok(GitStagedPathsResponse {
+            staged: parse_staged_name_status(&output),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
     pub(crate) async fn get_history(
         &self,
         raw_cwd: Option<&str>,
         limit: Option<usize>,
     ) -> Result<GitHistoryResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
         let history_limit = limit.unwrap_or(12).clamp(1, 30);
         let args = vec![
             "-C".to_string(),
@@ -213,7 +507,7 @@ impl GitService {
             "--decorate=short".to_string(),
             "--date=iso-strict".to_string(),
             format!("--max-count={history_limit}"),
-            "--pretty=format:%H\x1f%h\x1f%an\x1f%aI\x1f%D\x1f%s\x1e".to_string(),
+            GIT_HISTORY_PRETTY_FORMAT.to_string(),
             "HEAD".to_string(),
         ];
 
@@ -240,11 +534,196 @@ impl GitService {
         })
     }
 
+    /// Commits touching one repo-relative file, following renames.
+    pub(crate) async fn get_file_history(
+        &self,
+        path: &str,
+        raw_cwd: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<GitFileLogResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let relative_path = resolve_repo_relative_path(path, &repo_path)?;
+        let log_limit = limit
+            .unwrap_or(DEFAULT_GIT_FILE_LOG_LIMIT)
+            .clamp(1, MAX_GIT_FILE_LOG_LIMIT);
+        let args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "log".to_string(),
+            "--follow".to_string(),
+            "-z".to_string(),
+            "--decorate=short".to_string(),
+            format!("--max-count={log_limit}"),
+            GIT_HISTORY_PRETTY_FORMAT.to_string(),
+            "--".to_string(),
+            relative_path.clone(),
+        ];
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+
+        if result.code != Some(0) {
+            return Err(BridgeError::server(
+                &(if !result.stderr.is_empty() {
+                    result.stderr
+                } else if !result.stdout.is_empty() {
+                    result.stdout
+                } else {
+                    "git log failed".to_string()
+                }),
+            ));
+        }
+
+        Ok(GitFileLogResponse {
+            entries: parse_git_history(&result.stdout),
+            path: relative_path,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Blames lines `start..=end` (1-based) of one repo-relative file, for clients that only
+    /// show blame for the visible part of a file. An `end` past the last line is clamped to
+    /// it; a `start` past it is invalid params.
+    pub(crate) async fn blame_range(
+        &self,
+        path: &str,
+        start: usize,
+        end: usize,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitBlameRangeResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let relative_path = resolve_repo_relative_path(path, &repo_path)?;
+        let args = build_blame_range_args(&repo_path, &relative_path, start, end)?;
+
+        let mut result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+        // Some git versions reject an `-L` end past EOF instead of clamping it.
+        if let Some(line_count) = parse_blame_line_count_error(&result.stderr) {
+            if start > line_count {
+                return Err(BridgeError::invalid_params(&format!(
+                    "start is past the end of the file ({line_count} lines)"
+                )));
+            }
+            if end > line_count {
+                let args = build_blame_range_args(&repo_path, &relative_path, start, line_count)?;
+                result = self
+                    .terminal
+                    .execute_binary("git", &args, repo_path.clone(), None)
+                    .await?;
+            }
+        }
+
+        if result.code != Some(0) {
+            return Err(BridgeError::server(
+                &(if !result.stderr.is_empty() {
+                    result.stderr
+                } else if !result.stdout.is_empty() {
+                    result.stdout
+                } else {
+                    "git blame failed".to_string()
+                }),
+            ));
+        }
+
+        Ok(GitBlameRangeResponse {
+            lines: parse_git_blame_porcelain(&result.stdout),
+            path: relative_path,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Lists recent `HEAD` reflog entries so a client can find the commit a reset or
+    /// rebase moved away from. Read-only.
+    pub(crate) async fn reflog(
+        &self,
+        raw_cwd: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<GitReflogResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let reflog_limit = limit
+            .unwrap_or(DEFAULT_GIT_REFLOG_LIMIT)
+            .clamp(1, MAX_GIT_REFLOG_LIMIT);
+        let max_count = format!("--max-count={reflog_limit}");
+        let output = self
+            .run_git_stdout(
+                &repo_path,
+                &[
+                    "reflog",
+                    &max_count,
+                    "--pretty=format:%H%x00%gd%x00%gs",
+                    "-z",
+                ],
+                "git reflog failed",
+            )
+            .await?;
+
+        Ok(GitReflogResponse {
+            entries: parse_git_reflog(&output),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub(crate) async fn rev_parse(
+        &self,
+        raw_ref: &str,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitRevParseResponse, BridgeError> {
+        let git_ref = normalize_git_ref(raw_ref, "ref")?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "rev-parse".to_string(),
+            "--verify".to_string(),
+            "--quiet".to_string(),
+            git_ref.clone(),
+        ];
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+        let hash = result.stdout.trim();
+        if result.code != Some(0) || hash.is_empty() {
+            return Err(BridgeError::server(&format!(
+                "ref \"{git_ref}\" does not resolve to an object"
+            )));
+        }
+
+        Ok(GitRevParseResponse {
+            hash: hash.to_string(),
+            git_ref,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub(crate) async fn worktrees(
+        &self,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitWorktreesResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let output = self
+            .run_git_stdout(
+                &repo_path,
+                &["worktree", "list", "--porcelain"],
+                "git worktree list failed",
+            )
+            .await?;
+
+        Ok(GitWorktreesResponse {
+            worktrees: parse_git_worktree_list(&output),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
     pub(crate) async fn get_branches(
         &self,
         raw_cwd: Option<&str>,
     ) -> Result<GitBranchesResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
         let output = self
             .run_git_stdout(
                 &repo_path,
@@ -274,7 +753,8 @@ impl GitService {
         branch: String,
         raw_cwd: Option<&str>,
     ) -> Result<GitSwitchResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
         let target = normalize_git_branch_target(&branch)?;
         let known_branches = self.get_branches(raw_cwd).await?.branches;
         let switch_target = resolve_switch_target(&target, &known_branches);
@@ -309,7 +789,7 @@ impl GitService {
         raw_parent_path: Option<&str>,
         directory_name: &str,
     ) -> Result<GitCloneResponse, BridgeError> {
-        let parent_path = self.resolve_repo_path(raw_parent_path)?;
+        let parent_path = self.resolve_git_path(raw_parent_path)?;
         if !parent_path.exists() {
             return Err(BridgeError::invalid_params(
                 "destination parent path must exist",
@@ -356,7 +836,8 @@ impl GitService {
         path: &str,
         raw_cwd: Option<&str>,
     ) -> Result<GitStageResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
         let relative_path = resolve_repo_relative_path(path, &repo_path)?;
         let args = vec![
             "-C".to_string(),
@@ -381,16 +862,22 @@ impl GitService {
         })
     }
 
-    pub(crate) async fn stage_all(
+    pub(crate) async fn move_path(
         &self,
+        from: &str,
+        to: &str,
         raw_cwd: Option<&str>,
-    ) -> Result<GitStageAllResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+    ) -> Result<GitMoveResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let (from, to) = resolve_git_move_paths(from, to, &repo_path)?;
         let args = vec![
             "-C".to_string(),
             repo_path.to_string_lossy().to_string(),
-            "add".to_string(),
-            "-A".to_string(),
+            "mv".to_string(),
+            "--".to_string(),
+            from.clone(),
+            to.clone(),
         ];
 
         let result = self
@@ -398,72 +885,265 @@ impl GitService {
             .execute_binary("git", &args, repo_path.clone(), None)
             .await?;
 
-        Ok(GitStageAllResponse {
+        Ok(GitMoveResponse {
             code: result.code,
             stdout: result.stdout,
             stderr: result.stderr,
-            staged: result.code == Some(0),
+            moved: result.code == Some(0),
+            from,
+            to,
             cwd: repo_path.to_string_lossy().to_string(),
         })
     }
 
-    pub(crate) async fn unstage_file(
+    pub(crate) async fn config_get(
         &self,
-        path: &str,
+        raw_key: &str,
         raw_cwd: Option<&str>,
-    ) -> Result<GitUnstageResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
-        let relative_path = resolve_repo_relative_path(path, &repo_path)?;
+    ) -> Result<GitConfigResponse, BridgeError> {
+        let key = normalize_git_config_key(raw_key)?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let value = self.read_config_value(&repo_path, key).await?;
+
+        Ok(GitConfigResponse {
+            key: key.to_string(),
+            value,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Writes an allowlisted key to the repository's local config and returns the value git
+    /// reports afterwards.
+    pub(crate) async fn config_set(
+        &self,
+        raw_key: &str,
+        raw_value: &str,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitConfigResponse, BridgeError> {
+        let key = normalize_git_config_key(raw_key)?;
+        let value = normalize_git_config_value(key, raw_value)?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
         let args = vec![
             "-C".to_string(),
             repo_path.to_string_lossy().to_string(),
-            "reset".to_string(),
-            "HEAD".to_string(),
-            "--".to_string(),
-            relative_path.clone(),
+            "config".to_string(),
+            key.to_string(),
+            value,
         ];
 
         let result = self
             .terminal
             .execute_binary("git", &args, repo_path.clone(), None)
             .await?;
+        if result.code != Some(0) {
+            return Err(BridgeError::server(if result.stderr.is_empty() {
+                "git config failed"
+            } else {
+                &result.stderr
+            }));
+        }
 
-        Ok(GitUnstageResponse {
-            code: result.code,
-            stdout: result.stdout,
-            stderr: result.stderr,
-            unstaged: result.code == Some(0),
-            path: relative_path,
+        let value = self.read_config_value(&repo_path, key).await?;
+        Ok(GitConfigResponse {
+            key: key.to_string(),
+            value,
             cwd: repo_path.to_string_lossy().to_string(),
         })
     }
 
-    pub(crate) async fn unstage_all(
+    async fn read_config_value(
         &self,
-        raw_cwd: Option<&str>,
-    ) -> Result<GitUnstageAllResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+        repo_path: &Path,
+        key: &str,
+    ) -> Result<Option<String>, BridgeError> {
         let args = vec![
             "-C".to_string(),
             repo_path.to_string_lossy().to_string(),
-            "reset".to_string(),
-            "HEAD".to_string(),
-            "--".to_string(),
-            ".".to_string(),
+            "config".to_string(),
+            "--get".to_string(),
+            key.to_string(),
         ];
-
         let result = self
             .terminal
-            .execute_binary("git", &args, repo_path.clone(), None)
+            .execute_binary("git", &args, repo_path.to_path_buf(), None)
             .await?;
 
-        Ok(GitUnstageAllResponse {
-            code: result.code,
-            stdout: result.stdout,
-            stderr: result.stderr,
-            unstaged: result.code == Some(0),
-            cwd: repo_path.to_string_lossy().to_string(),
-        })
+        // `git config --get` exits 1 when the key is unset.
+        match result.code {
+            Some(0) => Ok(Some(result.stdout.trim_end_matches('\n').to_string())),
+            Some(1) => Ok(None),
+            _ => Err(BridgeError::server(if result.stderr.is_empty() {
+                "git config --get failed"
+            } else {
+                &result.stderr
+            })),
+        }
+    }
+
+    pub(crate) async fn stage_all(
+        &self,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitStageAllResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "add".to_string(),
+            "-A".to_string(),
+        ];
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+
+        Ok(GitStageAllResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            staged: result.code == Some(0),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub(crate) async fn apply_cached_patch(
+        &self,
+        patch: &str,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitApplyPatchResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let patch = normalize_git_patch(patch)?;
+        let args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "apply".to_string(),
+            "--cached".to_string(),
+            "-".to_string(),
+        ];
+
+        let result = self
+            .terminal
+            .execute_binary_with_input(
+                "git",
+                &args,
+                repo_path.clone(),
+                None,
+                Some(patch.into_bytes()),
+            )
+            .await?;
+
+        Ok(GitApplyPatchResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            applied: result.code == Some(0),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub(crate) async fn unstage_file(
+        &self,
+        path: &str,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitUnstageResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let relative_path = resolve_repo_relative_path(path, &repo_path)?;
+        let args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "reset".to_string(),
+            "HEAD".to_string(),
+            "--".to_string(),
+            relative_path.clone(),
+        ];
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+
+        Ok(GitUnstageResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            unstaged: result.code == Some(0),
+            path: relative_path,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Unstages `path` with `git restore --staged`, falling back to `reset HEAD` on
+    /// git versions that predate `restore`.
+    pub(crate) async fn restore_staged(
+        &self,
+        path: &str,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitUnstageResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let relative_path = resolve_repo_relative_path(path, &repo_path)?;
+        let args = build_restore_staged_args(&repo_path, &relative_path);
+
+        let mut result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+        if should_fall_back_to_reset(result.code, &result.stderr) {
+            let args = vec![
+                "-C".to_string(),
+                repo_path.to_string_lossy().to_string(),
+                "reset".to_string(),
+                "HEAD".to_string(),
+                "--".to_string(),
+                relative_path.clone(),
+            ];
+            result = self
+                .terminal
+                .execute_binary("git", &args, repo_path.clone(), None)
+                .await?;
+        }
+
+        Ok(GitUnstageResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            unstaged: result.code == Some(0),
+            path: relative_path,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub(crate) async fn unstage_all(
+        &self,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitUnstageAllResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "reset".to_string(),
+            "HEAD".to_string(),
+            "--".to_string(),
+            ".".to_string(),
+        ];
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+
+        Ok(GitUnstageAllResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            unstaged: result.code == Some(0),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
     }
 
     pub(crate) async fn commit(
@@ -471,7 +1151,8 @@ impl GitService {
         message: String,
         raw_cwd: Option<&str>,
     ) -> Result<GitCommitResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
         let args = vec![
             "-C".to_string(),
             repo_path.to_string_lossy().to_string(),
@@ -484,18 +1165,103 @@ impl GitService {
             .terminal
             .execute_binary("git", &args, repo_path.clone(), None)
             .await?;
+        let committed = result.code == Some(0);
+        let hash = if committed {
+            self.committed_head(&repo_path).await
+        } else {
+            None
+        };
 
         Ok(GitCommitResponse {
             code: result.code,
             stdout: result.stdout,
             stderr: result.stderr,
-            committed: result.code == Some(0),
+            committed,
+            hash,
             cwd: repo_path.to_string_lossy().to_string(),
         })
     }
 
+    /// `git add -A` then `git commit -m` under one repo lock. A clean tree is not an error:
+    /// the response has `committed: false` and git's "nothing to commit" output.
+    pub(crate) async fn commit_all(
+        &self,
+        message: String,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitCommitAllResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let repo_arg = repo_path.to_string_lossy().to_string();
+        let stage_args = vec![
+            "-C".to_string(),
+            repo_arg.clone(),
+            "add".to_string(),
+            "-A".to_string(),
+        ];
+
+        let staged = self
+            .terminal
+            .execute_binary("git", &stage_args, repo_path.clone(), None)
+            .await?;
+        if staged.code != Some(0) {
+            return Ok(GitCommitAllResponse {
+                code: staged.code,
+                stdout: staged.stdout,
+                stderr: staged.stderr,
+                staged: false,
+                committed: false,
+                hash: None,
+                cwd: repo_arg,
+            });
+        }
+
+        let commit_args = vec![
+            "-C".to_string(),
+            repo_arg.clone(),
+            "commit".to_string(),
+            "-m".to_string(),
+            message,
+        ];
+        let result = self
+            .terminal
+            .execute_binary("git", &commit_args, repo_path.clone(), None)
+            .await?;
+        let committed = result.code == Some(0);
+        let hash = if committed {
+            self.committed_head(&repo_path).await
+        } else {
+            None
+        };
+
+        Ok(GitCommitAllResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            staged: true,
+            committed,
+            hash,
+            cwd: repo_arg,
+        })
+    }
+
+    /// `HEAD` right after a commit. Callers still hold the repo lock, so no other mutation
+    /// can move it before it is read.
+    async fn committed_head(&self, repo_path: &Path) -> Option<String> {
+        match self
+            .run_git_stdout(repo_path, &["rev-parse", "HEAD"], "git rev-parse failed")
+            .await
+        {
+            Ok(stdout) => Some(stdout.trim().to_string()).filter(|hash| !hash.is_empty()),
+            Err(error) => {
+                eprintln!("failed to resolve HEAD after commit: {}", error.message);
+                None
+            }
+        }
+    }
+
     pub(crate) async fn push(&self, raw_cwd: Option<&str>) -> Result<GitPushResponse, BridgeError> {
-        let repo_path = self.resolve_repo_path(raw_cwd)?;
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
         let status_output = self
             .run_git_stdout(
                 &repo_path,
@@ -539,19 +1305,299 @@ impl GitService {
         })
     }
 
+    pub(crate) async fn list_tags(
+        &self,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitTagsResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let output = self
+            .run_git_stdout(
+                &repo_path,
+                &["tag", "--list", "--sort=-creatordate"],
+                "git tag --list failed",
+            )
+            .await?;
+
+        Ok(GitTagsResponse {
+            tags: output
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect(),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub(crate) async fn create_tag(
+        &self,
+        name: &str,
+        message: &str,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitTagCreateResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let tag = normalize_git_tag_name(name)?;
+        let message = normalize_git_tag_message(message)?;
+        let args = build_tag_create_args(&repo_path, &tag, &message);
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+
+        Ok(GitTagCreateResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            created: result.code == Some(0),
+            tag,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub(crate) async fn cherry_pick(
+        &self,
+        raw_hash: Option<&str>,
+        abort: bool,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitCherryPickResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let hash = if abort {
+            None
+        } else {
+            let raw_hash =
+                raw_hash.ok_or_else(|| BridgeError::invalid_params("hash is required"))?;
+            Some(normalize_git_commit_hash(raw_hash)?)
+        };
+        let args = build_cherry_pick_args(&repo_path, hash.as_deref());
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+        let success = result.code == Some(0);
+        // A failed pick that stopped on conflicts is reported, not raised.
+        let conflicts = if success || abort {
+            Vec::new()
+        } else {
+            self.list_conflicts(&repo_path).await.unwrap_or_default()
+        };
+
+        Ok(GitCherryPickResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            success,
+            aborted: abort && success,
+            hash,
+            conflicts,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    pub(crate) async fn stash_list(
+        &self,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitStashListResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let output = self
+            .run_git_stdout(
+                &repo_path,
+                &["stash", "list", "--pretty=format:%gd%x00%s", "-z"],
+                "git stash list failed",
+            )
+            .await?;
+
+        Ok(GitStashListResponse {
+            stashes: parse_git_stash_list(&output),
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Runs `git stash apply` or `git stash drop` for one `stash@{N}` entry. An apply that
+    /// stops on conflicts is reported with `success: false` and the conflicted paths.
+    pub(crate) async fn stash_action(
+        &self,
+        action: GitStashAction,
+        raw_ref: &str,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitStashActionResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
+        let _repo_lock = self.lock_repo(&repo_path).await;
+        let stash_ref = normalize_git_stash_ref(raw_ref)?;
+        let args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "stash".to_string(),
+            action.as_str().to_string(),
+            stash_ref.clone(),
+        ];
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+        let success = result.code == Some(0);
+        let conflicts = if success || action == GitStashAction::Drop {
+            Vec::new()
+        } else {
+            self.list_conflicts(&repo_path).await.unwrap_or_default()
+        };
+
+        Ok(GitStashActionResponse {
+            code: result.code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            success,
+            stash_ref,
+            conflicts,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    /// Drops root-relative paths that `.gitignore` rules exclude, so watcher events for build
+    /// output do not trigger status refreshes.
+    pub(crate) async fn filter_unignored_paths(
+        &self,
+        repo_path: &Path,
+        paths: &[String],
+    ) -> Result<Vec<String>, BridgeError> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "check-ignore".to_string(),
+            "--stdin".to_string(),
+            // NUL-separated both ways, so newlines in names and quoted output don't
+            // misclassify paths.
+            "-z".to_string(),
+        ];
+        let result = self
+            .terminal
+            .execute_binary_with_input(
+                "git",
+                &args,
+                repo_path.to_path_buf(),
+                None,
+                Some(format!("{}\0", paths.join("\0")).into_bytes()),
+            )
+            .await?;
+
+        // check-ignore exits 1 when none of the paths are ignored.
+        let ignored = match result.code {
+            Some(0) => result
+                .stdout
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .collect::<HashSet<_>>(),
+            Some(1) => HashSet::new(),
+            _ => {
+                return Err(BridgeError::server(if result.stderr.is_empty() {
+                    "git check-ignore failed"
+                } else {
+                    &result.stderr
+                }))
+            }
+        };
+
+        Ok(paths
+            .iter()
+            .filter(|path| !ignored.contains(path.as_str()))
+            .cloned()
+            .collect())
+    }
+
+    pub(crate) async fn list_conflicts(
+        &self,
+        repo_path: &Path,
+    ) -> Result<Vec<String>, BridgeError> {
+        let output = self
+            .run_git_stdout(
+                repo_path,
+                &["diff", "--name-only", "--diff-filter=U", "-z"],
+                "git diff --diff-filter=U failed",
+            )
+            .await?;
+
+        Ok(output
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
     async fn get_porcelain_status_entries(
         &self,
         repo_path: &Path,
+        include_ignored: bool,
     ) -> Result<Vec<GitStatusEntry>, BridgeError> {
+        let raw = self
+            .run_porcelain_status(repo_path, include_ignored)
+            .await?;
+        parse_porcelain_status_entries(&raw)
+    }
+
+    /// Yes/no working tree check for pre-action guards; skips branch info and entry parsing.
+    pub(crate) async fn is_clean(
+        &self,
+        raw_cwd: Option<&str>,
+    ) -> Result<GitIsCleanResponse, BridgeError> {
+        let repo_path = self.resolve_repo_path(raw_cwd).await?;
         let args = vec![
             "-C".to_string(),
             repo_path.to_string_lossy().to_string(),
+            "--no-optional-locks".to_string(),
+            "status".to_string(),
+            "--porcelain".to_string(),
+            "-z".to_string(),
+        ];
+
+        let result = self
+            .terminal
+            .execute_binary("git", &args, repo_path.clone(), None)
+            .await?;
+
+        if result.code != Some(0) {
+            return Err(BridgeError::server(
+                &(if !result.stderr.is_empty() {
+                    result.stderr
+                } else {
+                    "git status --porcelain failed".to_string()
+                }),
+            ));
+        }
+
+        let changed_count = count_porcelain_entries(&result.stdout);
+        Ok(GitIsCleanResponse {
+            clean: changed_count == 0,
+            changed_count,
+            cwd: repo_path.to_string_lossy().to_string(),
+        })
+    }
+
+    async fn run_porcelain_status(
+        &self,
+        repo_path: &Path,
+        include_ignored: bool,
+    ) -> Result<String, BridgeError> {
+        let mut args = vec![
+            "-C".to_string(),
+            repo_path.to_string_lossy().to_string(),
+            "--no-optional-locks".to_string(),
             "status".to_string(),
             "--porcelain=v1".to_string(),
             "--branch".to_string(),
             "-uall".to_string(),
             "-z".to_string(),
         ];
+        if include_ignored {
+            // Only paths a pattern matches, not every file inside an ignored directory.
+            args.push("--ignored=matching".to_string());
+        }
 
         let result = self
             .terminal
@@ -570,7 +1616,7 @@ impl GitService {
             ));
         }
 
-        parse_porcelain_status_entries(&result.stdout)
+        Ok(result.stdout)
     }
 
     async fn run_git_diff_command(
@@ -645,6 +1691,18 @@ impl GitService {
     }
 }
 
+/// Whether a cached top level `root` still owns `path`: `<root>/.git` exists and no
+/// `git init` below it has made a nested repository own `path` instead.
+fn repo_root_still_owns(root: &Path, path: &Path) -> bool {
+    let nested_repo = path.strip_prefix(root).is_ok_and(|relative| {
+        relative
+            .ancestors()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .any(|dir| root.join(dir).join(".git").exists())
+    });
+    root.join(".git").exists() && !nested_repo
+}
+
 fn parse_porcelain_status_entries(raw: &str) -> Result<Vec<GitStatusEntry>, BridgeError> {
     let tokens = raw
         .split('\0')
@@ -679,8 +1737,9 @@ fn parse_porcelain_status_entries(raw: &str) -> Result<Vec<GitStatusEntry>, Brid
         }
 
         let untracked = index_status == '?' && worktree_status == '?';
-        let staged = !matches!(index_status, ' ' | '?');
-        let unstaged = untracked || worktree_status != ' ';
+        let ignored = index_status == '!' && worktree_status == '!';
+        let staged = !ignored && !matches!(index_status, ' ' | '?');
+        let unstaged = untracked || (!ignored && worktree_status != ' ');
 
         entries.push(GitStatusEntry {
             path,
@@ -690,12 +1749,100 @@ fn parse_porcelain_status_entries(raw: &str) -> Result<Vec<GitStatusEntry>, Brid
             staged,
             unstaged,
             untracked,
+            ignored,
         });
     }
 
     Ok(entries)
 }
 
+#[derive(Debug, Default, PartialEq, Eq)]
+struct GitBranchHeader {
+    branch: String,
+    upstream: Option<String>,
+    ahead: u32,
+    behind: u32,
+    gone: bool,
+}
+
+/// Parses the `## branch...upstream [ahead N, behind M]` line from `git status --branch`
+/// (without the leading `## `).
+fn parse_porcelain_branch_header(raw: &str) -> GitBranchHeader {
+    let raw = raw.trim();
+    let (refs, tracking) = match raw
+        .strip_suffix(']')
+        .and_then(|rest| rest.rsplit_once(" ["))
+    {
+        Some((refs, tracking)) => (refs, Some(tracking)),
+        None => (raw, None),
+    };
+    let refs = refs
+        .strip_prefix("No commits yet on ")
+        .or_else(|| refs.strip_prefix("Initial commit on "))
+        .unwrap_or(refs);
+    let (branch, upstream) = match refs.split_once("...") {
+        Some((branch, upstream)) => (branch, Some(upstream.to_string())),
+        None => (refs, None),
+    };
+
+    let mut header = GitBranchHeader {
+        branch: branch.to_string(),
+        upstream,
+        ..GitBranchHeader::default()
+    };
+    for part in tracking
+        .into_iter()
+        .flat_map(|tracking| tracking.split(", "))
+    {
+        if part == "gone" {
+            header.gone = true;
+        } else if let Some(count) = part.strip_prefix("ahead ") {
+            header.ahead = count.parse().unwrap_or(0);
+        } else if let Some(count) = part.strip_prefix("behind ") {
+            header.behind = count.parse().unwrap_or(0);
+        }
+    }
+
+    header
+}
+
+fn is_conflicted_status(index_status: &str, worktree_status: &str) -> bool {
+    index_status == "U"
+        || worktree_status == "U"
+        || (index_status == "A" && worktree_status == "A")
+        || (index_status == "D" && worktree_status == "D")
+}
+
+fn parse_staged_name_status(raw: &str) -> Vec<GitStagedPath> {
+    let mut tokens = raw.split('\0').filter(|token| !token.is_empty());
+    let mut entries = Vec::new();
+
+    while let Some(status_token) = tokens.next() {
+        // Renames and copies carry a similarity score (e.g. `R100`) followed by both paths.
+        let status = status_token
+            .chars()
+            .next()
+            .map(|status| status.to_string())
+            .unwrap_or_default();
+        let original_path = if matches!(status.as_str(), "R" | "C") {
+            tokens.next().map(str::to_string)
+        } else {
+            None
+        };
+        let Some(path) = tokens.next() else {
+            break;
+        };
+
+        entries.push(GitStagedPath {
+            path: path.to_string(),
+            original_path,
+            status,
+        });
+    }
+
+    entries
+}
+
 fn parse_status_has_upstream(raw: &str) -> bool {
     raw.lines()
         .map(str::trim)
@@ -704,10 +1851,32 @@ fn parse_status_has_upstream(raw: &str) -> bool {
         .unwrap_or(false)
 }
 
+/// Counts `status --porcelain -z` entries. Renames and copies carry their source path as an
+/// extra NUL-separated field, which is skipped.
+fn count_porcelain_entries(raw: &str) -> usize {
+    let mut tokens = raw.split('\0').filter(|token| !token.is_empty());
+    let mut count = 0;
+    while let Some(token) = tokens.next() {
+        count += 1;
+        // A rename or copy in either the index (X) or worktree (Y) column is followed by
+        // its original path.
+        if token
+            .as_bytes()
+            .iter()
+            .take(2)
+            .any(|status| matches!(status, b'R' | b'C'))
+        {
+            tokens.next();
+        }
+    }
+    count
+}
+
 fn parse_git_history(raw: &str) -> Vec<GitHistoryCommit> {
     raw.split('\x1e')
         .filter_map(|record| {
-            let trimmed = record.trim();
+            // `log -z` puts a NUL between records.
+            let trimmed = record.trim_matches(|char: char| char.is_whitespace() || char == '\0');
             if trimmed.is_empty() {
                 return None;
             }
@@ -864,6 +2033,402 @@ fn normalize_git_branch_target(raw_branch: &str) -> Result<String, BridgeError>
     Ok(target.to_string())
 }
 
+fn normalize_git_ref(raw_ref: &str, label: &str) -> Result<String, BridgeError> {
+    let value = raw_ref.trim();
+    if value.is_empty() {
+        return Err(BridgeError::invalid_params(&format!(
+            "{label} must not be empty"
+        )));
+    }
+    if value.starts_with('-') {
+        return Err(BridgeError::invalid_params(&format!(
+            "{label} must not start with a dash"
+        )));
+    }
+    if value.contains("..") {
+        return Err(BridgeError::invalid_params(&format!(
+            "{label} must be a single ref; pass base and head separately"
+        )));
+    }
+    if value.chars().any(|ch| {
+        ch.is_whitespace()
+            || ch.is_control()
+            || matches!(
+                ch,
+                ':' | '?' | '*' | '[' | '\\' | ';' | '|' | '&' | '$' | '`' | '<' | '>' | '\'' | '"'
+            )
+    }) {
+        return Err(BridgeError::invalid_params(&format!(
+            "{label} contains invalid characters"
+        )));
+    }
+
+    Ok(value.to_string())
+}
+
+/// Builds `git diff` args for an explicit `base[..head]` comparison, or `None`
+/// when no base was requested and the working-tree diff should be used.
+fn build_ref_diff_args(
+    raw_base: Option<&str>,
+    raw_head: Option<&str>,
+) -> Result<Option<Vec<String>>, BridgeError> {
+    let raw_base = raw_base.filter(|value| !value.trim().is_empty());
+    let raw_head = raw_head.filter(|value| !value.trim().is_empty());
+    let Some(raw_base) = raw_base else {
+        if raw_head.is_some() {
+            return Err(BridgeError::invalid_params("head requires base"));
+        }
+        return Ok(None);
+    };
+
+    let base = normalize_git_ref(raw_base, "base")?;
+    let range = match raw_head {
+        Some(raw_head) => format!("{base}..{}", normalize_git_ref(raw_head, "head")?),
+        None => base,
+    };
+
+    Ok(Some(vec![
+        "diff".to_string(),
+        "--no-color".to_string(),
+        "--patch".to_string(),
+        range,
+        "--".to_string(),
+    ]))
+}
+
+fn normalize_git_patch(raw_patch: &str) -> Result<String, BridgeError> {
+    if raw_patch.trim().is_empty() {
+        return Err(BridgeError::invalid_params("patch must not be empty"));
+    }
+    if raw_patch.len() > MAX_GIT_PATCH_BYTES {
+        return Err(BridgeError::invalid_params(&format!(
+            "patch exceeds {MAX_GIT_PATCH_BYTES} bytes"
+        )));
+    }
+    if raw_patch.contains('\0') {
+        return Err(BridgeError::invalid_params(
+            "patch contains invalid characters",
+        ));
+    }
+
+    // git apply rejects a final hunk line without its trailing newline.
+    let mut patch = raw_patch.to_string();
+    if !patch.ends_with('\n') {
+        patch.push('\n');
+    }
+    Ok(patch)
+}
+
+fn normalize_git_tag_name(raw_name: &str) -> Result<String, BridgeError> {
+    let name = raw_name.trim();
+    if name.is_empty() {
+        return Err(BridgeError::invalid_params("tag name must not be empty"));
+    }
+    if name.starts_with('-') {
+        return Err(BridgeError::invalid_params(
+            "tag name must not start with a dash",
+        ));
+    }
+
+    // Mirrors the `git check-ref-format` rules that matter for a single tag name.
+    let has_invalid_char = name.chars().any(|ch| {
+        ch.is_control()
+            || ch.is_whitespace()
+            || matches!(ch, '~' | '^' | ':' | '?' | '*' | '[' | '\\')
+    });
+    if has_invalid_char
+        || name.contains("..")
+        || name.contains("@{")
+        || name == "@"
+        || name.starts_with('/')
+        || name.ends_with('/')
+        || name.ends_with('.')
+        || name.ends_with(".lock")
+        || name.contains("//")
+        || name.split('/').any(|segment| segment.starts_with('.'))
+    {
+        return Err(BridgeError::invalid_params(
+            "tag name contains invalid characters",
+        ));
+    }
+
+    Ok(name.to_string())
+}
+
+fn normalize_git_tag_message(raw_message: &str) -> Result<String, BridgeError> {
+    let message = raw_message.trim();
+    if message.is_empty() {
+        return Err(BridgeError::invalid_params("message must not be empty"));
+    }
+    if message.contains('\0') {
+        return Err(BridgeError::invalid_params(
+            "message contains invalid characters",
+        ));
+    }
+
+    Ok(message.to_string())
+}
+
+fn build_tag_create_args(repo_path: &Path, tag: &str, message: &str) -> Vec<String> {
+    vec![
+        "-C".to_string(),
+        repo_path.to_string_lossy().to_string(),
+        "tag".to_string(),
+        "-a".to_string(),
+        tag.to_string(),
+        "-m".to_string(),
+        message.to_string(),
+    ]
+}
+
+fn build_restore_staged_args(repo_path: &Path, relative_path: &str) -> Vec<String> {
+    vec![
+        "-C".to_string(),
+        repo_path.to_string_lossy().to_string(),
+        "restore".to_string(),
+        "--staged".to_string(),
+        "--".to_string(),
+        relative_path.to_string(),
+    ]
+}
+
+/// True when `git restore` itself is missing (git < 2.23), as opposed to failing on the path.
+fn should_fall_back_to_reset(code: Option<i32>, stderr: &str) -> bool {
+    if code == Some(0) {
+        return false;
+    }
+
+    let stderr = stderr.to_ascii_lowercase();
+    stderr.contains("'restore' is not a git command")
+        || stderr.contains("unknown option `staged'")
+        || stderr.contains("unknown option 'staged'")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum GitStashAction {
+    Apply,
+    Drop,
+}
+
+impl GitStashAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Apply => "apply",
+            Self::Drop => "drop",
+        }
+    }
+}
+
+/// Parses `git worktree list --porcelain`: one blank-line-separated record per worktree,
+/// starting with a `worktree <path>` line.
+fn parse_git_worktree_list(raw: &str) -> Vec<GitWorktreeEntry> {
+    let mut worktrees = Vec::new();
+    let mut current: Option<GitWorktreeEntry> = None;
+    for line in raw.lines() {
+        if line.trim().is_empty() {
+            worktrees.extend(current.take());
+            continue;
+        }
+        if let Some(path) = line.strip_prefix("worktree ") {
+            worktrees.extend(current.take());
+            current = Some(GitWorktreeEntry {
+                path: path.to_string(),
+                head: None,
+                branch: None,
+                bare: false,
+                detached: false,
+            });
+            continue;
+        }
+        let Some(entry) = current.as_mut() else {
+            continue;
+        };
+        if let Some(head) = line.strip_prefix("HEAD ") {
+            entry.head = Some(head.to_string());
+        } else if let Some(branch) = line.strip_prefix("branch ") {
+            entry.branch = Some(
+                branch
+                    .strip_prefix("refs/heads/")
+                    .unwrap_or(branch)
+                    .to_string(),
+            );
+        } else if line == "bare" {
+            entry.bare = true;
+        } else if line == "detached" {
+            entry.detached = true;
+        }
+    }
+    worktrees.extend(current);
+    worktrees
+}
+
+/// Parses `git reflog --pretty=format:%H%x00%gd%x00%gs -z`: three NUL-separated fields
+/// per entry, with entries also separated by NUL.
+fn parse_git_reflog(raw: &str) -> Vec<GitReflogEntry> {
+    let fields = raw.split('\0').collect::<Vec<_>>();
+    fields
+        .chunks(3)
+        .filter_map(|chunk| {
+            let hash = chunk[0].trim();
+            if hash.is_empty() {
+                return None;
+            }
+            Some(GitReflogEntry {
+                hash: hash.to_string(),
+                selector: chunk.get(1).copied().unwrap_or_default().to_string(),
+                subject: chunk.get(2).copied().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Parses `git stash list --pretty=format:%gd%x00%s -z`, where both the ref/subject
+/// separator and the record terminator are NUL.
+fn parse_git_stash_list(raw: &str) -> Vec<GitStashEntry> {
+    let fields = raw.split('\0').collect::<Vec<_>>();
+    fields
+        .chunks(2)
+        .filter_map(|chunk| {
+            let stash_ref = chunk[0].trim();
+            if stash_ref.is_empty() {
+                return None;
+            }
+            Some(GitStashEntry {
+                stash_ref: stash_ref.to_string(),
+                message: chunk.get(1).copied().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn normalize_git_stash_ref(raw_ref: &str) -> Result<String, BridgeError> {
+    let stash_ref = raw_ref.trim();
+    let index = stash_ref
+        .strip_prefix("stash@{")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .filter(|index| {
+            (1..=6).contains(&index.len()) && index.chars().all(|ch| ch.is_ascii_digit())
+        });
+    if index.is_none() {
+        return Err(BridgeError::invalid_params("ref must look like stash@{N}"));
+    }
+
+    Ok(stash_ref.to_string())
+}
+
+fn normalize_git_commit_hash(raw_hash: &str) -> Result<String, BridgeError> {
+    let hash = raw_hash.trim();
+    if hash.is_empty() {
+        return Err(BridgeError::invalid_params("hash must not be empty"));
+    }
+    if !(4..=64).contains(&hash.len()) || !hash.chars().all(|ch| ch.is_ascii_hexdigit()) {
+        return Err(BridgeError::invalid_params(
+            "hash must be a 4-64 character hexadecimal commit id",
+        ));
+    }
+
+    Ok(hash.to_ascii_lowercase())
+}
+
+fn build_blame_range_args(
+    repo_path: &Path,
+    relative_path: &str,
+    start: usize,
+    end: usize,
+) -> Result<Vec<String>, BridgeError> {
+    if start == 0 || end == 0 {
+        return Err(BridgeError::invalid_params(
+            "start and end must be positive line numbers",
+        ));
+    }
+    if start > end {
+        return Err(BridgeError::invalid_params("start must not be after end"));
+    }
+    if end - start >= MAX_GIT_BLAME_RANGE_LINES {
+        return Err(BridgeError::invalid_params(&format!(
+            "blame range must cover at most {MAX_GIT_BLAME_RANGE_LINES} lines"
+        )));
+    }
+
+    Ok(vec![
+        "-C".to_string(),
+        repo_path.to_string_lossy().to_string(),
+        "blame".to_string(),
+        "-L".to_string(),
+        format!("{start},{end}"),
+        "--line-porcelain".to_string(),
+        "--".to_string(),
+        relative_path.to_string(),
+    ])
+}
+
+/// Line count from git blame's "file <path> has only N lines" error.
+fn parse_blame_line_count_error(stderr: &str) -> Option<usize> {
+    let (_, rest) = stderr.rsplit_once(" has only ")?;
+    let (count, _) = rest.split_once(" line")?;
+    count.trim().parse().ok()
+}
+
+/// Parses `git blame --line-porcelain`, where every line repeats its commit's headers.
+fn parse_git_blame_porcelain(raw: &str) -> Vec<GitBlameLine> {
+    let mut lines = Vec::new();
+    let mut current: Option<GitBlameLine> = None;
+
+    for line in raw.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            if let Some(mut entry) = current.take() {
+                entry.content = content.to_string();
+                lines.push(entry);
+            }
+            continue;
+        }
+
+        let Some(entry) = current.as_mut() else {
+            let mut fields = line.split(' ');
+            let hash = fields.next().unwrap_or_default();
+            let original_line_number = fields.next().and_then(|value| value.parse().ok());
+            let line_number = fields.next().and_then(|value| value.parse().ok());
+            if let (Some(original_line_number), Some(line_number)) =
+                (original_line_number, line_number)
+            {
+                current = Some(GitBlameLine {
+                    hash: hash.to_string(),
+                    line_number,
+                    original_line_number,
+                    author: String::new(),
+                    author_time: None,
+                    summary: String::new(),
+                    content: String::new(),
+                });
+            }
+            continue;
+        };
+
+        if let Some(author) = line.strip_prefix("author ") {
+            entry.author = author.to_string();
+        } else if let Some(author_time) = line.strip_prefix("author-time ") {
+            entry.author_time = author_time.trim().parse().ok();
+        } else if let Some(summary) = line.strip_prefix("summary ") {
+            entry.summary = summary.to_string();
+        }
+    }
+
+    lines
+}
+
+fn build_cherry_pick_args(repo_path: &Path, hash: Option<&str>) -> Vec<String> {
+    let mut args = vec![
+        "-C".to_string(),
+        repo_path.to_string_lossy().to_string(),
+        "cherry-pick".to_string(),
+    ];
+    match hash {
+        Some(hash) => args.push(hash.to_string()),
+        None => args.push("--abort".to_string()),
+    }
+    args
+}
+
 fn select_default_remote_name(raw: &str) -> Option<String> {
     let remotes = raw
         .lines()
@@ -884,7 +2449,7 @@ fn select_default_remote_name(raw: &str) -> Option<String> {
 
 fn resolve_git_cwd(
     raw_cwd: Option<&str>,
-    root: &PathBuf,
+    root: &Path,
     allow_outside_root: bool,
 ) -> Result<PathBuf, BridgeError> {
     let normalized_root = normalize_path(root);
@@ -941,6 +2506,64 @@ fn resolve_repo_relative_path(raw_path: &str, repo_path: &Path) -> Result<String
     Ok(relative.to_string_lossy().to_string())
 }
 
+fn resolve_git_move_paths(
+    raw_from: &str,
+    raw_to: &str,
+    repo_path: &Path,
+) -> Result<(String, String), BridgeError> {
+    let from = resolve_repo_relative_path(raw_from, repo_path)
+        .map_err(|error| BridgeError::invalid_params(&format!("from: {}", error.message)))?;
+    let to = resolve_repo_relative_path(raw_to, repo_path)
+        .map_err(|error| BridgeError::invalid_params(&format!("to: {}", error.message)))?;
+    if from == to {
+        return Err(BridgeError::invalid_params("from and to must differ"));
+    }
+
+    Ok((from, to))
+}
+
+fn normalize_git_config_key(raw_key: &str) -> Result<&'static str, BridgeError> {
+    let key = raw_key.trim().to_ascii_lowercase();
+    GIT_CONFIG_SAFE_KEYS
+        .iter()
+        .copied()
+        .find(|safe_key| safe_key.to_ascii_lowercase() == key)
+        .ok_or_else(|| {
+            BridgeError::invalid_params(&format!(
+                "key must be one of: {}",
+                GIT_CONFIG_SAFE_KEYS.join(", ")
+            ))
+        })
+}
+
+fn normalize_git_config_value(key: &str, raw_value: &str) -> Result<String, BridgeError> {
+    let value = raw_value.trim();
+    if value.is_empty() {
+        return Err(BridgeError::invalid_params("value must not be empty"));
+    }
+    if value.len() > MAX_GIT_CONFIG_VALUE_LEN {
+        return Err(BridgeError::invalid_params(&format!(
+            "value must be at most {MAX_GIT_CONFIG_VALUE_LEN} bytes"
+        )));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(BridgeError::invalid_params(
+            "value must not contain control characters",
+        ));
+    }
+    if key == "commit.gpgsign" {
+        let normalized = value.to_ascii_lowercase();
+        if normalized != "true" && normalized != "false" {
+            return Err(BridgeError::invalid_params(
+                "commit.gpgsign must be true or false",
+            ));
+        }
+        return Ok(normalized);
+    }
+
+    Ok(value.to_string())
+}
+
 fn resolve_clone_directory_name(raw_name: &str) -> Result<String, BridgeError> {
     let trimmed = raw_name.trim();
     if trimmed.is_empty() {
@@ -979,13 +2602,107 @@ fn resolve_clone_directory_name(raw_name: &str) -> Result<String, BridgeError> {
 #[cfg(test)]
 mod tests {
     use super::{
-        normalize_git_branch_target, parse_git_branches, parse_git_history,
-        parse_porcelain_status_entries, parse_status_has_upstream, resolve_clone_directory_name,
-        resolve_git_cwd, resolve_repo_relative_path, resolve_switch_target,
-        select_default_remote_name, GitSwitchTarget,
+        build_blame_range_args, build_cherry_pick_args, build_ref_diff_args,
+        build_restore_staged_args, build_tag_create_args, count_porcelain_entries,
+        is_conflicted_status, normalize_git_branch_target, normalize_git_commit_hash,
+        normalize_git_config_key, normalize_git_config_value, normalize_git_patch,
+        normalize_git_ref, normalize_git_stash_ref, normalize_git_tag_message,
+        normalize_git_tag_name, parse_blame_line_count_error, parse_git_blame_porcelain,
+        parse_git_branches, parse_git_history, parse_git_reflog, parse_git_stash_list,
+        parse_git_worktree_list, parse_porcelain_branch_header, parse_porcelain_status_entries,
+        parse_staged_name_status, parse_status_has_upstream, resolve_clone_directory_name,
+        resolve_git_cwd, resolve_git_move_paths, resolve_repo_relative_path, resolve_switch_target,
+        select_default_remote_name, should_fall_back_to_reset, CachedRepoRoot, GitBranchHeader,
+        GitService, GitStashAction, GitSwitchTarget, MAX_CACHED_REPO_ROOTS,
+        MAX_GIT_BLAME_RANGE_LINES, MAX_GIT_PATCH_BYTES,
+    };
+    use crate::{services::TerminalService, GitBranchSummary};
+    use std::{
+        collections::{HashMap, HashSet},
+        env,
+        path::{Path, PathBuf},
+        process::Command,
+        sync::Arc,
+        time::{Duration, Instant, SystemTime, UNIX_EPOCH},
     };
-    use crate::GitBranchSummary;
-    use std::path::{Path, PathBuf};
+    use tokio::time::timeout;
+
+    fn run_test_git(repo: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(args)
+            .output()
+            .expect("run git");
+        assert!(
+            output.status.success(),
+            "git {args:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).to_string()
+    }
+
+    fn create_test_repo(label: &str) -> PathBuf {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let repo = crate::normalize_path(&env::temp_dir().join(format!(
+            "clawdex-git-{label}-{}-{nonce}",
+            std::process::id()
+        )));
+        std::fs::create_dir_all(&repo).expect("create test repo dir");
+        run_test_git(&repo, &["init", "--quiet"]);
+        run_test_git(&repo, &["config", "user.email", "bridge@example.com"]);
+        run_test_git(&repo, &["config", "user.name", "Bridge Test"]);
+        repo
+    }
+
+    fn test_git_service(root: &Path) -> GitService {
+        let terminal = Arc::new(TerminalService::new(
+            root.to_path_buf(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            0,
+        ));
+        GitService::new(terminal, root.to_path_buf(), false)
+    }
+
+    #[tokio::test]
+    async fn diff_and_status_past_the_output_cap_return_truncated_output() {
+        let repo = create_test_repo("large-output");
+        std::fs::write(repo.join("big.txt"), "old\n").expect("write file");
+        run_test_git(&repo, &["add", "big.txt"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+        std::fs::write(repo.join("big.txt"), "changed line\n".repeat(20_000)).expect("grow file");
+        for index in 0..400 {
+            std::fs::write(repo.join(format!("untracked-{index:03}.txt")), "x")
+                .expect("write untracked file");
+        }
+        let terminal = Arc::new(
+            TerminalService::new(
+                repo.clone(),
+                HashSet::new(),
+                HashMap::new(),
+                false,
+                false,
+                0,
+            )
+            .with_max_output_bytes(4 * 1024),
+        );
+        let git = GitService::new(terminal, repo.clone(), false);
+
+        let diff = git
+            .get_diff(None, Some("HEAD"), None)
+            .await
+            .expect("large diff succeeds");
+        assert!(diff.diff.contains("[output truncated]"));
+        git.get_status(None).await.expect("large status succeeds");
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
 
     #[test]
     fn resolves_relative_cwd_against_root() {
@@ -1038,6 +2755,65 @@ mod tests {
         assert_eq!(error.code, -32602);
     }
 
+    #[test]
+    fn builds_restore_staged_args_for_relative_path() {
+        let repo = Path::new("/bridge/root/repo");
+        let relative = resolve_repo_relative_path("./src/../README.md", repo).expect("path");
+        assert_eq!(
+            build_restore_staged_args(repo, &relative),
+            vec![
+                "-C",
+                "/bridge/root/repo",
+                "restore",
+                "--staged",
+                "--",
+                "README.md"
+            ]
+        );
+        assert!(resolve_repo_relative_path("/etc/passwd", repo).is_err());
+    }
+
+    #[test]
+    fn validates_both_git_move_paths() {
+        let repo = Path::new("/bridge/root/repo");
+        assert_eq!(
+            resolve_git_move_paths("src/old.rs", "./src/new.rs", repo).expect("move paths"),
+            ("src/old.rs".to_string(), "src/new.rs".to_string())
+        );
+
+        let from_error =
+            resolve_git_move_paths("../outside.rs", "src/new.rs", repo).expect_err("from escape");
+        assert_eq!(from_error.code, -32602);
+        assert!(from_error.message.starts_with("from:"));
+
+        let to_error =
+            resolve_git_move_paths("src/old.rs", "/etc/passwd", repo).expect_err("to absolute");
+        assert!(to_error.message.starts_with("to:"));
+        let to_error =
+            resolve_git_move_paths("src/old.rs", "src/../../x.rs", repo).expect_err("to escape");
+        assert!(to_error.message.starts_with("to:"));
+
+        assert!(resolve_git_move_paths("src/a.rs", "src/./a.rs", repo).is_err());
+        assert!(resolve_git_move_paths(" ", "src/a.rs", repo).is_err());
+    }
+
+    #[test]
+    fn falls_back_to_reset_only_when_restore_is_unavailable() {
+        assert!(should_fall_back_to_reset(
+            Some(1),
+            "git: 'restore' is not a git command. See 'git --help'."
+        ));
+        assert!(should_fall_back_to_reset(
+            Some(129),
+            "error: unknown option `staged'"
+        ));
+        assert!(!should_fall_back_to_reset(Some(0), ""));
+        assert!(!should_fall_back_to_reset(
+            Some(1),
+            "error: pathspec 'missing.txt' did not match any file(s) known to git"
+        ));
+    }
+
     #[test]
     fn resolves_clone_directory_name_from_single_segment() {
         let resolved =
@@ -1074,6 +2850,45 @@ mod tests {
         assert!(!untracked.staged);
         assert!(untracked.unstaged);
         assert!(untracked.untracked);
+        assert!(!untracked.ignored);
+    }
+
+    #[test]
+    fn parses_porcelain_ignored_entries() {
+        let raw = "## main\0 M src/lib.rs\0!! target/debug/app\0!! .env\0";
+        let entries = parse_porcelain_status_entries(raw).expect("parse status entries");
+        assert_eq!(entries.len(), 3);
+        assert!(!entries[0].ignored);
+        assert!(entries[0].unstaged);
+
+        let ignored = &entries[1];
+        assert_eq!(ignored.path, "target/debug/app");
+        assert_eq!(ignored.index_status, "!");
+        assert_eq!(ignored.worktree_status, "!");
+        assert!(ignored.ignored);
+        assert!(!ignored.staged);
+        assert!(!ignored.unstaged);
+        assert!(!ignored.untracked);
+        assert_eq!(entries[2].path, ".env");
+        assert!(entries[2].ignored);
+    }
+
+    #[test]
+    fn parses_staged_name_status_for_rename_and_deletion() {
+        let raw = "R100\0old/name.ts\0new/name.ts\0D\0removed.txt\0M\0src/lib.rs\0";
+        let entries = parse_staged_name_status(raw);
+        assert_eq!(entries.len(), 3);
+
+        assert_eq!(entries[0].status, "R");
+        assert_eq!(entries[0].path, "new/name.ts");
+        assert_eq!(entries[0].original_path.as_deref(), Some("old/name.ts"));
+
+        assert_eq!(entries[1].status, "D");
+        assert_eq!(entries[1].path, "removed.txt");
+        assert_eq!(entries[1].original_path, None);
+
+        assert_eq!(entries[2].status, "M");
+        assert_eq!(entries[2].path, "src/lib.rs");
     }
 
     #[test]
@@ -1084,6 +2899,55 @@ mod tests {
         assert!(!parse_status_has_upstream("## feature/local-only\n"));
     }
 
+    #[test]
+    fn parses_porcelain_branch_header_tracking_variants() {
+        assert_eq!(
+            parse_porcelain_branch_header("main...origin/main [ahead 2, behind 3]"),
+            GitBranchHeader {
+                branch: "main".to_string(),
+                upstream: Some("origin/main".to_string()),
+                ahead: 2,
+                behind: 3,
+                gone: false,
+            }
+        );
+        let behind = parse_porcelain_branch_header("main...origin/main [behind 5]");
+        assert_eq!((behind.ahead, behind.behind), (0, 5));
+        let ahead = parse_porcelain_branch_header("feature/x...origin/feature/x [ahead 1]");
+        assert_eq!(ahead.branch, "feature/x");
+        assert_eq!((ahead.ahead, ahead.behind), (1, 0));
+
+        let gone = parse_porcelain_branch_header("topic...origin/topic [gone]");
+        assert!(gone.gone);
+        assert_eq!(gone.upstream.as_deref(), Some("origin/topic"));
+        assert_eq!((gone.ahead, gone.behind), (0, 0));
+
+        assert_eq!(
+            parse_porcelain_branch_header("local-only"),
+            GitBranchHeader {
+                branch: "local-only".to_string(),
+                ..GitBranchHeader::default()
+            }
+        );
+        assert_eq!(
+            parse_porcelain_branch_header("No commits yet on main").branch,
+            "main"
+        );
+        assert_eq!(
+            parse_porcelain_branch_header("HEAD (no branch)").branch,
+            "HEAD (no branch)"
+        );
+    }
+
+    #[test]
+    fn detects_conflicted_porcelain_statuses() {
+        for (index, worktree) in [("U", "U"), ("A", "A"), ("D", "D"), ("A", "U"), ("U", "D")] {
+            assert!(is_conflicted_status(index, worktree), "{index}{worktree}");
+        }
+        assert!(!is_conflicted_status("M", "M"));
+        assert!(!is_conflicted_status("A", " "));
+    }
+
     #[test]
     fn prefers_origin_as_default_remote() {
         assert_eq!(
@@ -1120,6 +2984,31 @@ mod tests {
         assert!(!commits[1].is_head);
     }
 
+    #[test]
+    fn parses_follow_style_file_log_records() {
+        let raw = concat!(
+            "abc123\x1fabc123\x1fMohit\x1f2026-04-05T10:00:00+05:30\x1fHEAD -> main\x1fRename notes\x1e",
+            "\0def456\x1fdef456\x1fMohit\x1f2026-04-04T09:00:00+05:30\x1f\x1fCreate notes\x1e"
+        );
+
+        let entries = parse_git_history(raw);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].subject, "Rename notes");
+        assert!(entries[0].is_head);
+        assert_eq!(entries[1].hash, "def456");
+        assert_eq!(entries[1].subject, "Create notes");
+        assert!(entries[1].ref_names.is_empty());
+
+        let repo = Path::new("/tmp/repo");
+        assert_eq!(
+            resolve_repo_relative_path("docs/../notes.md", repo).expect("relative path"),
+            "notes.md"
+        );
+        assert!(resolve_repo_relative_path("../notes.md", repo).is_err());
+        assert!(resolve_repo_relative_path("/tmp/repo/notes.md", repo).is_err());
+        assert!(resolve_repo_relative_path(".", repo).is_err());
+    }
+
     #[test]
     fn parses_local_and_remote_git_branches() {
         let raw = concat!(
@@ -1189,4 +3078,832 @@ mod tests {
         let error = normalize_git_branch_target("--detach").expect_err("reject option-like name");
         assert_eq!(error.code, -32602);
     }
+
+    #[test]
+    fn validates_git_tag_names() {
+        assert_eq!(
+            normalize_git_tag_name(" v1.2.0 ").expect("plain tag"),
+            "v1.2.0"
+        );
+        assert!(normalize_git_tag_name("release/2026-04").is_ok());
+
+        for invalid in [
+            "", "-d", "v1 2", "v1..2", "v1^", "v1~1", "a:b", "v*", "v[1]", "x@{1}", "v1.lock",
+            "v1.", "/v1", "v1/", "a//b", ".hidden", "a/.b", "v1\\2",
+        ] {
+            let error = normalize_git_tag_name(invalid).expect_err(invalid);
+            assert_eq!(error.code, -32602, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn builds_annotated_tag_create_args() {
+        let message = normalize_git_tag_message("  Release 1.2.0\n\nNotes  ").expect("message");
+        let args = build_tag_create_args(Path::new("/bridge/root/repo"), "v1.2.0", &message);
+        assert_eq!(
+            args,
+            vec![
+                "-C",
+                "/bridge/root/repo",
+                "tag",
+                "-a",
+                "v1.2.0",
+                "-m",
+                "Release 1.2.0\n\nNotes",
+            ]
+        );
+        assert!(normalize_git_tag_message("   ").is_err());
+    }
+
+    #[test]
+    fn validates_cherry_pick_hashes() {
+        assert_eq!(
+            normalize_git_commit_hash(" ABC1234 ").expect("hash"),
+            "abc1234"
+        );
+        assert!(normalize_git_commit_hash("").is_err());
+        assert!(normalize_git_commit_hash("abc").is_err());
+        assert!(normalize_git_commit_hash("--abort").is_err());
+        assert!(normalize_git_commit_hash("main").is_err());
+        assert!(normalize_git_commit_hash(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn validates_blame_ranges_and_builds_line_porcelain_args() {
+        let repo = Path::new("/bridge/root/repo");
+        let relative = resolve_repo_relative_path("./src/lib.rs", repo).expect("path");
+        assert_eq!(
+            build_blame_range_args(repo, &relative, 10, 20).expect("args"),
+            vec![
+                "-C",
+                "/bridge/root/repo",
+                "blame",
+                "-L",
+                "10,20",
+                "--line-porcelain",
+                "--",
+                "src/lib.rs"
+            ]
+        );
+        assert_eq!(
+            build_blame_range_args(repo, &relative, 7, 7).expect("one line")[4],
+            "7,7"
+        );
+        for (start, end) in [(0, 5), (5, 0), (6, 5)] {
+            let error =
+                build_blame_range_args(repo, &relative, start, end).expect_err("invalid range");
+            assert_eq!(error.code, -32602);
+        }
+        assert!(build_blame_range_args(repo, &relative, 1, MAX_GIT_BLAME_RANGE_LINES).is_ok());
+        assert!(build_blame_range_args(repo, &relative, 1, MAX_GIT_BLAME_RANGE_LINES + 1).is_err());
+        assert!(resolve_repo_relative_path("../outside.rs", repo).is_err());
+    }
+
+    #[tokio::test]
+    async fn blame_range_clamps_end_and_rejects_start_past_the_last_line() {
+        let repo = create_test_repo("blame-eof");
+        std::fs::write(repo.join("three.txt"), "a\nb\nc\n").expect("write file");
+        run_test_git(&repo, &["add", "three.txt"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+        let git = test_git_service(&repo);
+
+        let blame = git
+            .blame_range("three.txt", 2, 10, None)
+            .await
+            .expect("end past the last line");
+        let contents = blame
+            .lines
+            .iter()
+            .map(|line| line.content.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, ["b", "c"]);
+
+        let error = git
+            .blame_range("three.txt", 5, 10, None)
+            .await
+            .expect_err("start past the last line");
+        assert_eq!(error.code, -32602);
+        assert!(error.message.contains("3 lines"));
+
+        assert_eq!(
+            parse_blame_line_count_error("fatal: file three.txt has only 3 lines\n"),
+            Some(3)
+        );
+        assert_eq!(parse_blame_line_count_error("fatal: no such path"), None);
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[test]
+    fn parses_line_porcelain_blame_output() {
+        let raw = "\
+abc123 3 10 2
+author Ada
+author-mail <ada@example.com>
+author-time 1700000000
+author-tz +0000
+summary Add parser
+filename src/lib.rs
+\tfn parse() {
+abc123 4 11
+author Ada
+author-time 1700000000
+summary Add parser
+filename src/lib.rs
+\t}
+";
+        let lines = parse_git_blame_porcelain(raw);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].hash, "abc123");
+        assert_eq!(lines[0].line_number, 10);
+        assert_eq!(lines[0].original_line_number, 3);
+        assert_eq!(lines[0].author, "Ada");
+        assert_eq!(lines[0].author_time, Some(1_700_000_000));
+        assert_eq!(lines[0].summary, "Add parser");
+        assert_eq!(lines[0].content, "fn parse() {");
+        assert_eq!(lines[1].line_number, 11);
+        assert_eq!(lines[1].content, "}");
+    }
+
+    #[test]
+    fn builds_cherry_pick_and_abort_args() {
+        let repo = Path::new("/repo");
+        assert_eq!(
+            build_cherry_pick_args(repo, Some("abc1234")),
+            vec!["-C", "/repo", "cherry-pick", "abc1234"]
+        );
+        assert_eq!(
+            build_cherry_pick_args(repo, None),
+            vec!["-C", "/repo", "cherry-pick", "--abort"]
+        );
+    }
+
+    #[test]
+    fn validates_git_diff_refs() {
+        assert_eq!(normalize_git_ref(" main ", "base").expect("ref"), "main");
+        assert_eq!(normalize_git_ref("HEAD~2", "base").expect("ref"), "HEAD~2");
+        assert!(normalize_git_ref("--output=/tmp/x", "base").is_err());
+        assert!(normalize_git_ref("main..feature", "base").is_err());
+        assert!(normalize_git_ref("main;rm", "base").is_err());
+        assert!(normalize_git_ref("main feature", "base").is_err());
+        assert!(normalize_git_ref("$(id)", "head").is_err());
+    }
+
+    #[test]
+    fn builds_ref_diff_args_for_two_dot_range() {
+        assert_eq!(
+            build_ref_diff_args(Some("main"), Some("feature/login")).expect("args"),
+            Some(vec![
+                "diff".to_string(),
+                "--no-color".to_string(),
+                "--patch".to_string(),
+                "main..feature/login".to_string(),
+                "--".to_string(),
+            ])
+        );
+        assert_eq!(
+            build_ref_diff_args(Some("abc123"), None)
+                .expect("args")
+                .expect("single ref")[3],
+            "abc123"
+        );
+        assert_eq!(build_ref_diff_args(None, None).expect("args"), None);
+        assert_eq!(build_ref_diff_args(Some(" "), None).expect("args"), None);
+        assert!(build_ref_diff_args(None, Some("feature")).is_err());
+    }
+
+    #[test]
+    fn validates_git_patch_payloads() {
+        assert_eq!(
+            normalize_git_patch("diff --git a/x b/x").expect("patch"),
+            "diff --git a/x b/x\n"
+        );
+        assert!(normalize_git_patch("  \n").is_err());
+        assert!(normalize_git_patch(&"x".repeat(MAX_GIT_PATCH_BYTES + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn apply_cached_patch_stages_only_selected_hunk() {
+        let repo = create_test_repo("apply-patch");
+        let original = (1..=20).map(|n| format!("line {n}\n")).collect::<String>();
+        std::fs::write(repo.join("notes.txt"), &original).expect("write file");
+        run_test_git(&repo, &["add", "notes.txt"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+
+        let edited = original
+            .replace("line 2\n", "line two\n")
+            .replace("line 19\n", "line nineteen\n");
+        std::fs::write(repo.join("notes.txt"), edited).expect("edit file");
+
+        let patch = concat!(
+            "diff --git a/notes.txt b/notes.txt\n",
+            "--- a/notes.txt\n",
+            "+++ b/notes.txt\n",
+            "@@ -1,3 +1,3 @@\n",
+            " line 1\n",
+            "-line 2\n",
+            "+line two\n",
+            " line 3\n",
+        );
+        let git = test_git_service(&repo);
+        let applied = git
+            .apply_cached_patch(patch, None)
+            .await
+            .expect("apply patch");
+        assert!(applied.applied, "{}", applied.stderr);
+
+        let staged = run_test_git(&repo, &["diff", "--cached"]);
+        assert!(staged.contains("+line two"));
+        assert!(!staged.contains("+line nineteen"));
+        let unstaged = run_test_git(&repo, &["diff"]);
+        assert!(unstaged.contains("+line nineteen"));
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn filter_unignored_paths_handles_newlines_and_quoted_names() {
+        let repo = create_test_repo("check-ignore");
+        std::fs::write(repo.join(".gitignore"), "*.log\n").expect("write gitignore");
+        let git = test_git_service(&repo);
+        let paths = [
+            "build.log",
+            "notes.txt",
+            "line\nbreak.txt",
+            "tab\tname.log",
+            "caf\u{e9}.txt",
+        ]
+        .map(str::to_string);
+
+        let kept = git
+            .filter_unignored_paths(&repo, &paths)
+            .await
+            .expect("check-ignore");
+        assert_eq!(kept, ["notes.txt", "line\nbreak.txt", "caf\u{e9}.txt"]);
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn rev_parse_resolves_refs_and_reports_unknown_ones() {
+        let repo = create_test_repo("rev-parse");
+        std::fs::write(repo.join("tracked.txt"), "one\n").expect("write file");
+        run_test_git(&repo, &["add", "tracked.txt"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+        let head = run_test_git(&repo, &["rev-parse", "HEAD"]);
+        let git = test_git_service(&repo);
+
+        let resolved = git.rev_parse(" HEAD ", None).await.expect("resolve HEAD");
+        assert_eq!(resolved.git_ref, "HEAD");
+        assert_eq!(resolved.hash, head.trim());
+
+        let missing = git
+            .rev_parse("no-such-branch", None)
+            .await
+            .expect_err("unknown ref");
+        assert_eq!(missing.code, -32000);
+        assert!(missing.message.contains("no-such-branch"));
+
+        let invalid = git
+            .rev_parse("--output=/tmp/x", None)
+            .await
+            .expect_err("leading dash");
+        assert_eq!(invalid.code, -32602);
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn mutations_on_the_same_repo_serialize_but_reads_do_not() {
+        let repo = create_test_repo("repo-lock");
+        std::fs::write(repo.join("a.txt"), "a\n").expect("write file");
+        let git = test_git_service(&repo);
+
+        let held = git.lock_repo(&repo).await;
+        let blocked = tokio::spawn({
+            let git = git.clone();
+            async move { git.stage_all(None).await }
+        });
+        let status = timeout(Duration::from_secs(5), git.get_status(None))
+            .await
+            .expect("reads do not wait on the repo lock");
+        assert!(status.is_ok());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(
+            !blocked.is_finished(),
+            "stage_all ran while the repo was locked"
+        );
+
+        drop(held);
+        let staged = timeout(Duration::from_secs(5), blocked)
+            .await
+            .expect("stage_all resumes once unlocked")
+            .expect("join stage_all")
+            .expect("stage_all");
+        assert!(staged.staged);
+
+        std::fs::write(repo.join("b.txt"), "b\n").expect("write file");
+        let (first, second) = tokio::join!(git.stage_all(None), git.stage_all(None));
+        assert!(first.expect("first stage_all").staged);
+        assert!(second.expect("second stage_all").staged);
+        assert!(run_test_git(&repo, &["diff", "--cached", "--name-only"]).contains("b.txt"));
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn mutations_from_different_subdirectories_share_the_repo_lock() {
+        let repo = create_test_repo("repo-lock-subdirs");
+        let first = repo.join("first");
+        let second = repo.join("second");
+        std::fs::create_dir_all(&first).expect("create first subdir");
+        std::fs::create_dir_all(&second).expect("create second subdir");
+        std::fs::write(second.join("b.txt"), "b\n").expect("write file");
+        let git = test_git_service(&repo);
+
+        let held = git.lock_repo(&first).await;
+        let second_cwd = second.to_string_lossy().to_string();
+        let blocked = tokio::spawn({
+            let git = git.clone();
+            async move { git.stage_all(Some(second_cwd.as_str())).await }
+        });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(
+            !blocked.is_finished(),
+            "stage_all from another subdirectory ran while the repo was locked"
+        );
+
+        drop(held);
+        let staged = timeout(Duration::from_secs(5), blocked)
+            .await
+            .expect("stage_all resumes once unlocked")
+            .expect("join stage_all")
+            .expect("stage_all");
+        assert!(staged.staged);
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn status_with_ignored_lists_matching_ignored_paths_only_as_entries() {
+        let repo = create_test_repo("status-ignored");
+        std::fs::write(repo.join(".gitignore"), "*.log\nbuild/\n").expect("write gitignore");
+        run_test_git(&repo, &["add", ".gitignore"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+        std::fs::write(repo.join("debug.log"), "log\n").expect("write log");
+        std::fs::create_dir_all(repo.join("build/out")).expect("create build dir");
+        std::fs::write(repo.join("build/out/app"), "bin").expect("write build output");
+        let git = test_git_service(&repo);
+
+        let status = git
+            .get_status_with_ignored(None, true)
+            .await
+            .expect("status with ignored");
+        let mut ignored = status
+            .files
+            .iter()
+            .filter(|entry| entry.ignored)
+            .map(|entry| entry.path.as_str())
+            .collect::<Vec<_>>();
+        ignored.sort_unstable();
+        assert_eq!(ignored, ["build/", "debug.log"]);
+        assert!(status.clean);
+        assert!(!status.raw.contains("!!"));
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn is_clean_counts_changes_in_working_tree() {
+        let repo = create_test_repo("is-clean");
+        std::fs::write(repo.join("tracked.txt"), "one\n").expect("write file");
+        run_test_git(&repo, &["add", "tracked.txt"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+        let git = test_git_service(&repo);
+
+        let clean = git.is_clean(None).await.expect("clean status");
+        assert!(clean.clean);
+        assert_eq!(clean.changed_count, 0);
+
+        std::fs::write(repo.join("tracked.txt"), "two\n").expect("edit file");
+        std::fs::write(repo.join("new.txt"), "new\n").expect("write untracked");
+        run_test_git(&repo, &["mv", "tracked.txt", "renamed.txt"]);
+        let dirty = git.is_clean(None).await.expect("dirty status");
+        assert!(!dirty.clean);
+        assert_eq!(dirty.changed_count, 2);
+
+        assert_eq!(
+            count_porcelain_entries("R  new.txt\0old.txt\0?? notes.md\0 M a.rs\0"),
+            3
+        );
+        assert_eq!(
+            count_porcelain_entries(" R moved.txt\0orig.txt\0MC copy.rs\0src.rs\0"),
+            2
+        );
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn commit_all_stages_and_commits_every_change() {
+        let repo = create_test_repo("commit-all");
+        std::fs::write(repo.join("tracked.txt"), "one\n").expect("write file");
+        run_test_git(&repo, &["add", "tracked.txt"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+        let git = test_git_service(&repo);
+
+        std::fs::write(repo.join("tracked.txt"), "two\n").expect("edit file");
+        std::fs::write(repo.join("new.txt"), "new\n").expect("write untracked");
+        let committed = git
+            .commit_all("Update everything".to_string(), None)
+            .await
+            .expect("commit all");
+        assert!(committed.staged);
+        assert!(committed.committed, "{}", committed.stderr);
+        assert_eq!(
+            run_test_git(&repo, &["log", "-1", "--pretty=%s"]).trim(),
+            "Update everything"
+        );
+        assert!(run_test_git(&repo, &["status", "--porcelain"]).is_empty());
+
+        let clean = git
+            .commit_all("Nothing here".to_string(), None)
+            .await
+            .expect("commit all on a clean tree");
+        assert!(clean.staged);
+        assert!(!clean.committed);
+        assert!(
+            clean.stdout.contains("nothing to commit"),
+            "{}",
+            clean.stdout
+        );
+        assert_eq!(
+            run_test_git(&repo, &["rev-list", "--count", "HEAD"]).trim(),
+            "2"
+        );
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn move_path_renames_tracked_file() {
+        let repo = create_test_repo("move");
+        std::fs::write(repo.join("old.txt"), "content\n").expect("write file");
+        run_test_git(&repo, &["add", "old.txt"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+
+        let git = test_git_service(&repo);
+        let moved = git
+            .move_path("old.txt", "new.txt", None)
+            .await
+            .expect("git mv");
+        assert!(moved.moved, "{}", moved.stderr);
+        assert_eq!(moved.from, "old.txt");
+        assert_eq!(moved.to, "new.txt");
+        assert!(repo.join("new.txt").is_file());
+        assert!(!repo.join("old.txt").exists());
+        let staged = run_test_git(&repo, &["diff", "--cached", "--name-status", "-M"]);
+        assert!(staged.contains("old.txt") && staged.contains("new.txt"));
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[test]
+    fn parses_worktree_list_porcelain_records() {
+        let raw = concat!(
+            "worktree /srv/repo.git\n",
+            "bare\n",
+            "\n",
+            "worktree /srv/repo\n",
+            "HEAD 8fc6dc51e2438be7002608dec9c0dda6f1e5904a\n",
+            "branch refs/heads/main\n",
+            "\n",
+            "worktree /srv/repo-review\n",
+            "HEAD 1d2f3a4b5c6d7e8f90a1b2c3d4e5f60718293a4b\n",
+            "detached\n",
+            "locked\n",
+            "\n",
+            "worktree /srv/repo-feature\n",
+            "HEAD 0a1b2c3d4e5f60718293a4b5c6d7e8f901234567\n",
+            "branch refs/heads/feature/worktrees\n",
+        );
+        let worktrees = parse_git_worktree_list(raw);
+        assert_eq!(worktrees.len(), 4);
+
+        assert_eq!(worktrees[0].path, "/srv/repo.git");
+        assert!(worktrees[0].bare);
+        assert_eq!(worktrees[0].head, None);
+        assert_eq!(worktrees[0].branch, None);
+
+        assert_eq!(worktrees[1].path, "/srv/repo");
+        assert_eq!(
+            worktrees[1].head.as_deref(),
+            Some("8fc6dc51e2438be7002608dec9c0dda6f1e5904a")
+        );
+        assert_eq!(worktrees[1].branch.as_deref(), Some("main"));
+        assert!(!worktrees[1].detached);
+
+        assert_eq!(worktrees[2].path, "/srv/repo-review");
+        assert!(worktrees[2].detached);
+        assert_eq!(worktrees[2].branch, None);
+        assert!(!worktrees[2].bare);
+
+        assert_eq!(worktrees[3].branch.as_deref(), Some("feature/worktrees"));
+        assert!(parse_git_worktree_list("").is_empty());
+    }
+
+    #[test]
+    fn parses_nul_separated_reflog_entries() {
+        let raw = concat!(
+            "8fc6dc51e2438be7002608dec9c0dda6f1e5904a\0HEAD@{0}\0reset: moving to HEAD~2\0",
+            "1d2f3a4b5c6d7e8f90a1b2c3d4e5f60718293a4b\0HEAD@{1}\0commit: add parser\0",
+            "0a1b2c3d4e5f60718293a4b5c6d7e8f901234567\0HEAD@{2}\0commit (initial): init",
+        );
+        let entries = parse_git_reflog(raw);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].hash, "8fc6dc51e2438be7002608dec9c0dda6f1e5904a");
+        assert_eq!(entries[0].selector, "HEAD@{0}");
+        assert_eq!(entries[0].subject, "reset: moving to HEAD~2");
+        assert_eq!(entries[1].subject, "commit: add parser");
+        assert_eq!(entries[2].selector, "HEAD@{2}");
+        assert_eq!(entries[2].subject, "commit (initial): init");
+        assert!(parse_git_reflog("").is_empty());
+    }
+
+    #[test]
+    fn parses_nul_separated_stash_list() {
+        let stashes = parse_git_stash_list(
+            "stash@{0}\0On main: wip parser\0stash@{1}\0\0stash@{2}\0WIP on main: 8fc6dc5 init",
+        );
+        let parsed = stashes
+            .iter()
+            .map(|entry| (entry.stash_ref.as_str(), entry.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            parsed,
+            vec![
+                ("stash@{0}", "On main: wip parser"),
+                ("stash@{1}", ""),
+                ("stash@{2}", "WIP on main: 8fc6dc5 init"),
+            ]
+        );
+        assert!(parse_git_stash_list("").is_empty());
+    }
+
+    #[test]
+    fn stash_ref_validation_requires_indexed_stash_refs() {
+        assert_eq!(normalize_git_stash_ref(" stash@{0} ").unwrap(), "stash@{0}");
+        assert_eq!(normalize_git_stash_ref("stash@{12}").unwrap(), "stash@{12}");
+        for raw in [
+            "",
+            "stash",
+            "stash@{}",
+            "stash@{-1}",
+            "stash@{1} --index",
+            "stash@{2.days.ago}",
+            "refs/stash",
+            "--all",
+        ] {
+            let error = normalize_git_stash_ref(raw).unwrap_err();
+            assert_eq!(error.code, -32602, "{raw}");
+        }
+    }
+
+    #[tokio::test]
+    async fn stash_list_apply_and_drop_round_trip() {
+        let repo = create_test_repo("stash");
+        let service = test_git_service(&repo);
+        std::fs::write(repo.join("README.md"), "initial\n").expect("write file");
+        run_test_git(&repo, &["add", "README.md"]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "initial"]);
+        std::fs::write(repo.join("README.md"), "first change\n").expect("edit file");
+        run_test_git(&repo, &["stash", "push", "-m", "first"]);
+        std::fs::write(repo.join("README.md"), "second change\n").expect("edit file");
+        run_test_git(&repo, &["stash", "push", "-m", "second"]);
+
+        let listed = service.stash_list(None).await.expect("stash list");
+        let messages = listed
+            .stashes
+            .iter()
+            .map(|entry| entry.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].ends_with("second") && messages[1].ends_with("first"));
+
+        let applied = service
+            .stash_action(GitStashAction::Apply, "stash@{1}", None)
+            .await
+            .expect("stash apply");
+        assert!(applied.success, "{}", applied.stderr);
+        assert_eq!(
+            std::fs::read_to_string(repo.join("README.md")).expect("read file"),
+            "first change\n"
+        );
+
+        let dropped = service
+            .stash_action(GitStashAction::Drop, "stash@{0}", None)
+            .await
+            .expect("stash drop");
+        assert!(dropped.success, "{}", dropped.stderr);
+        let remaining = service.stash_list(None).await.expect("stash list");
+        assert_eq!(remaining.stashes.len(), 1);
+        assert!(remaining.stashes[0].message.ends_with("first"));
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[test]
+    fn git_config_key_allowlist_rejects_unsafe_keys() {
+        assert_eq!(
+            normalize_git_config_key(" user.name ").unwrap(),
+            "user.name"
+        );
+        assert_eq!(
+            normalize_git_config_key("User.Email").unwrap(),
+            "user.email"
+        );
+        assert_eq!(
+            normalize_git_config_key("commit.gpgSign").unwrap(),
+            "commit.gpgsign"
+        );
+        for key in ["core.sshCommand", "core.hooksPath", "alias.x", "user", ""] {
+            let error = normalize_git_config_key(key).unwrap_err();
+            assert_eq!(error.code, -32602, "{key}");
+        }
+    }
+
+    #[test]
+    fn git_config_value_validation_rejects_control_characters() {
+        assert_eq!(
+            normalize_git_config_value("user.name", "  Ada Lovelace ").unwrap(),
+            "Ada Lovelace"
+        );
+        assert_eq!(
+            normalize_git_config_value("commit.gpgsign", "TRUE").unwrap(),
+            "true"
+        );
+        assert!(normalize_git_config_value("commit.gpgsign", "sometimes").is_err());
+        assert!(normalize_git_config_value("user.name", "   ").is_err());
+        assert!(normalize_git_config_value("user.name", "Ada\nLovelace").is_err());
+        assert!(normalize_git_config_value("user.email", "ada\u{1b}[31m@example.com").is_err());
+        assert!(normalize_git_config_value("user.name", &"a".repeat(257)).is_err());
+    }
+
+    #[tokio::test]
+    async fn config_set_writes_local_value_and_get_reads_it_back() {
+        let repo = create_test_repo("config");
+        let git = test_git_service(&repo);
+
+        let updated = git
+            .config_set("user.name", "Mobile Author", None)
+            .await
+            .expect("config set");
+        assert_eq!(updated.key, "user.name");
+        assert_eq!(updated.value.as_deref(), Some("Mobile Author"));
+        assert_eq!(
+            run_test_git(&repo, &["config", "--local", "user.name"]).trim(),
+            "Mobile Author"
+        );
+
+        let read = git
+            .config_get("user.email", None)
+            .await
+            .expect("config get");
+        assert_eq!(read.value.as_deref(), Some("bridge@example.com"));
+
+        let error = git
+            .config_set("core.sshCommand", "touch /tmp/pwned", None)
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, -32602);
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn status_summary_counts_entries_by_kind() {
+        let repo = create_test_repo("status-summary");
+        std::fs::write(repo.join("tracked.txt"), "one\n").expect("write tracked");
+        std::fs::write(repo.join("staged.txt"), "one\n").expect("write staged");
+        run_test_git(&repo, &["add", "."]);
+        run_test_git(&repo, &["commit", "--quiet", "-m", "init"]);
+
+        std::fs::write(repo.join("tracked.txt"), "two\n").expect("edit tracked");
+        std::fs::write(repo.join("staged.txt"), "two\n").expect("edit staged");
+        run_test_git(&repo, &["add", "staged.txt"]);
+        std::fs::write(repo.join("new.txt"), "new\n").expect("write untracked");
+
+        let summary = test_git_service(&repo)
+            .get_status_summary(None)
+            .await
+            .expect("status summary");
+        assert_eq!(summary.staged, 1);
+        assert_eq!(summary.unstaged, 1);
+        assert_eq!(summary.untracked, 1);
+        assert_eq!(summary.conflicts, 0);
+        assert_eq!((summary.ahead, summary.behind), (0, 0));
+        assert!(summary.upstream.is_none());
+        assert!(!summary.clean);
+        assert!(!summary.branch.is_empty());
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn init_turns_plain_directory_into_repository() {
+        let nonce = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system clock after unix epoch")
+            .as_nanos();
+        let dir = crate::normalize_path(
+            &env::temp_dir().join(format!("clawdex-git-init-{}-{nonce}", std::process::id())),
+        );
+        std::fs::create_dir_all(&dir).expect("create test dir");
+        let git = test_git_service(&dir);
+
+        assert!(!git.is_repo(&dir).await);
+        assert_eq!(git.workdir_repo_root().await, None);
+        let error = git.get_status(None).await.expect_err("not a repo yet");
+        assert_eq!(error.code, -32000);
+        assert_eq!(
+            error.data.as_ref().and_then(|data| data.get("error")),
+            Some(&serde_json::json!("not_a_git_repository"))
+        );
+
+        let initialized = git.init(None, Some("main")).await.expect("git init");
+        assert!(initialized.initialized, "{}", initialized.stderr);
+        assert!(git.is_repo(&dir).await);
+        assert_eq!(git.workdir_repo_root().await, Some(dir.clone()));
+
+        let status = git.get_status(None).await.expect("status after init");
+        assert!(status.clean);
+        assert!(status.branch.ends_with("main"));
+
+        // The root is cached after the first lookup and dropped once `.git` is gone.
+        assert_eq!(
+            git.repo_roots
+                .lock()
+                .expect("repo roots")
+                .get(&dir)
+                .map(|cached| &cached.root),
+            Some(&dir)
+        );
+        std::fs::remove_dir_all(dir.join(".git")).expect("remove .git");
+        assert!(!git.is_repo(&dir).await);
+        assert!(git.repo_roots.lock().expect("repo roots").is_empty());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn init_in_a_subdirectory_moves_its_cached_root_to_the_new_repo() {
+        let repo = create_test_repo("nested-init");
+        let nested = repo.join("packages").join("app");
+        std::fs::create_dir_all(&nested).expect("create nested dir");
+        let git = test_git_service(&repo);
+
+        assert_eq!(
+            git.repo_root(Some("packages/app"))
+                .await
+                .expect("parent root"),
+            repo
+        );
+        let initialized = git
+            .init(Some("packages/app"), None)
+            .await
+            .expect("nested git init");
+        assert!(initialized.initialized, "{}", initialized.stderr);
+        assert_eq!(
+            git.repo_root(Some("packages/app"))
+                .await
+                .expect("nested root"),
+            nested
+        );
+        assert_eq!(git.repo_root(None).await.expect("outer root"), repo);
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn full_repo_root_cache_evicts_only_the_least_recently_used_entry() {
+        let repo = create_test_repo("root-cache-eviction");
+        let git = test_git_service(&repo);
+        let stale = Instant::now() - Duration::from_secs(60);
+        {
+            let mut roots = git.repo_roots.lock().expect("repo roots");
+            for index in 0..MAX_CACHED_REPO_ROOTS {
+                roots.insert(
+                    repo.join(format!("dir-{index}")),
+                    CachedRepoRoot {
+                        root: repo.clone(),
+                        last_used: stale + Duration::from_millis(index as u64),
+                    },
+                );
+            }
+        }
+
+        assert_eq!(git.repo_root(None).await.expect("repo root"), repo);
+        let roots = git.repo_roots.lock().expect("repo roots");
+        assert_eq!(roots.len(), MAX_CACHED_REPO_ROOTS);
+        assert!(roots.contains_key(&repo));
+        assert!(!roots.contains_key(&repo.join("dir-0")));
+        assert!(roots.contains_key(&repo.join("dir-1")));
+        drop(roots);
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
 }
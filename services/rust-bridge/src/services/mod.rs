@@ -2,6 +2,6 @@ pub mod git;
 pub mod terminal;
 pub mod update;
 
-pub(crate) use git::GitService;
-pub(crate) use terminal::TerminalService;
+pub(crate) use git::{GitService, GitStashAction};
+pub(crate) use terminal::{ReapingChild, TerminalService};
 pub(crate) use update::UpdateService;
@@ -1,52 +1,251 @@
 use std::{
-    collections::HashSet,
-    path::PathBuf,
-    process::Stdio,
-    sync::Arc,
+    collections::{HashMap, HashSet, VecDeque},
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+    process::{ExitStatus, Stdio},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use ring::hmac;
 use tokio::{
-    io::{AsyncRead, AsyncReadExt},
-    process::Command,
-    sync::Semaphore,
+    fs,
+    io::{AsyncRead, AsyncReadExt, AsyncSeekExt, AsyncWriteExt},
+    process::{Child, Command},
+    sync::{Mutex, Notify, Semaphore},
     time::timeout,
 };
 
 use crate::{
-    contains_disallowed_control_chars, normalize_path, BridgeError, TerminalExecRequest,
-    TerminalExecResponse,
+    constant_time_eq, contains_disallowed_control_chars, normalize_path, now_iso, BridgeError,
+    TerminalExecRequest, TerminalExecResponse, TerminalHistoryEntry, TerminalHistoryResponse,
+    TerminalOutputReadRequest, TerminalOutputReadResponse, DEFAULT_TERMINAL_MAX_OUTPUT_BYTES,
+    MOBILE_ATTACHMENTS_DIR,
 };
 
 const DEFAULT_TERMINAL_MAX_CONCURRENT: usize = 4;
-const DEFAULT_TERMINAL_MAX_OUTPUT_BYTES: usize = 256 * 1024;
 const OUTPUT_READ_CHUNK_SIZE: usize = 8 * 1024;
+const CAPTURED_OUTPUT_PREVIEW_BYTES: usize = 4 * 1024;
+const CAPTURED_OUTPUT_MAX_FILE_BYTES: usize = 64 * 1024 * 1024;
+const CAPTURED_OUTPUT_DIR: &str = "terminal-output";
+const CAPTURED_OUTPUT_READ_DEFAULT_BYTES: usize = 256 * 1024;
+const CAPTURED_OUTPUT_READ_MAX_BYTES: usize = 1024 * 1024;
+/// Captured output files older than this are deleted the next time output is captured.
+const CAPTURED_OUTPUT_TTL: Duration = Duration::from_secs(60 * 60);
+/// Distinguishes captures started in the same millisecond, which share timestamp and pid.
+static CAPTURED_OUTPUT_SEQ: AtomicU64 = AtomicU64::new(0);
+/// Binaries `BRIDGE_TERMINAL_READONLY` lets through `execute_shell`; `git` only for `status`.
+const READONLY_COMMANDS: &[&str] = &["pwd", "ls", "cat"];
+const READONLY_GIT_SUBCOMMANDS: &[&str] = &["status"];
+/// Git global options whose value is the next argument, skipped when finding the subcommand.
+const GIT_GLOBAL_OPTIONS_WITH_VALUE: &[&str] = &[
+    "-C",
+    "-c",
+    "--git-dir",
+    "--work-tree",
+    "--namespace",
+    "--config-env",
+];
+/// Alias hops followed before giving up; git itself refuses deeper alias loops.
+const MAX_GIT_ALIAS_DEPTH: usize = 8;
+
+#[derive(Default)]
+struct ExecIo {
+    stdin_input: Option<Vec<u8>>,
+    /// When set, stdout is streamed to this file and only a preview is kept in memory.
+    stdout_file: Option<PathBuf>,
+    /// Kill the command once its output passes the cap (`bridge/terminal/exec`). Otherwise
+    /// the rest is read and discarded so the exit code stays meaningful, as the git
+    /// service needs.
+    kill_on_overflow: bool,
+}
+
+/// Tripped by a capture task once its stream exceeds the cap, so the command can be
+/// stopped instead of being drained indefinitely.
+#[derive(Default)]
+struct OutputOverflow {
+    tripped: AtomicBool,
+    notify: Notify,
+}
+
+impl OutputOverflow {
+    fn trip(&self) {
+        if !self.tripped.swap(true, Ordering::SeqCst) {
+            self.notify.notify_one();
+        }
+    }
+
+    fn is_tripped(&self) -> bool {
+        self.tripped.load(Ordering::SeqCst)
+    }
+}
+
+/// Owns a spawned child until it has been waited on. If it is dropped first (an early `?`
+/// return or a cancelled caller), the child is killed and reaped on a background task so it
+/// does not linger as a zombie.
+pub(crate) struct ReapingChild {
+    child: Option<Child>,
+    reaped: bool,
+}
+
+impl ReapingChild {
+    pub(crate) fn new(child: Child) -> Self {
+        Self {
+            child: Some(child),
+            reaped: false,
+        }
+    }
+
+    pub(crate) fn child_mut(&mut self) -> &mut Child {
+        self.child.as_mut().expect("child is present until dropped")
+    }
+
+    pub(crate) async fn wait(&mut self) -> io::Result<ExitStatus> {
+        let status = self.child_mut().wait().await;
+        if status.is_ok() {
+            self.reaped = true;
+        }
+        status
+    }
+
+    pub(crate) async fn kill_and_wait(&mut self) {
+        let _ = self.child_mut().kill().await;
+        if self.child_mut().wait().await.is_ok() {
+            self.reaped = true;
+        }
+    }
+
+    /// Hands the child to a long-lived owner that takes over reaping it.
+    pub(crate) fn into_inner(mut self) -> Child {
+        self.reaped = true;
+        self.child.take().expect("child is present until dropped")
+    }
+}
+
+impl Drop for ReapingChild {
+    fn drop(&mut self) {
+        if self.reaped {
+            return;
+        }
+        let Some(mut child) = self.child.take() else {
+            return;
+        };
+        let _ = child.start_kill();
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    let _ = child.wait().await;
+                });
+            }
+            Err(_) => {
+                let _ = child.try_wait();
+            }
+        }
+    }
+}
 
 #[derive(Clone)]
 pub(crate) struct TerminalService {
     root: PathBuf,
+    attachments_root: PathBuf,
     allowed_commands: HashSet<String>,
+    blocked_args: HashMap<String, HashSet<String>>,
+    /// `BRIDGE_COMMAND_SIGNING_KEY`; without it `commandToken` is rejected.
+    command_signing_key: Option<hmac::Key>,
     disabled: bool,
+    /// `BRIDGE_TERMINAL_READONLY`: `execute_shell` only runs `READONLY_COMMANDS`.
+    readonly: bool,
     allow_outside_root: bool,
     concurrency_limiter: Arc<Semaphore>,
+    history: Arc<Mutex<VecDeque<TerminalHistoryEntry>>>,
+    history_capacity: usize,
+    max_output_bytes: usize,
 }
 
 impl TerminalService {
     pub(crate) fn new(
         root: PathBuf,
         allowed_commands: HashSet<String>,
+        blocked_args: HashMap<String, HashSet<String>>,
         disabled: bool,
         allow_outside_root: bool,
+        history_capacity: usize,
     ) -> Self {
         Self {
+            attachments_root: root.join(MOBILE_ATTACHMENTS_DIR),
             root,
             allowed_commands,
+            blocked_args,
+            command_signing_key: None,
             disabled,
+            readonly: false,
             allow_outside_root,
             concurrency_limiter: Arc::new(Semaphore::new(DEFAULT_TERMINAL_MAX_CONCURRENT)),
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            history_capacity,
+            max_output_bytes: DEFAULT_TERMINAL_MAX_OUTPUT_BYTES,
         }
     }
 
+    /// `BRIDGE_TERMINAL_MAX_OUTPUT_BYTES`; caps in-memory capture per stream.
+    pub(crate) fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Restricts `execute_shell` to the read-only subset. A full disable still wins, and
+    /// `execute_binary` (used by the git service) is unaffected.
+    pub(crate) fn with_readonly(mut self, readonly: bool) -> Self {
+        self.readonly = readonly;
+        self
+    }
+
+    /// Stores captured output under `attachments_root` instead of the workdir default.
+    pub(crate) fn with_attachments_root(mut self, attachments_root: PathBuf) -> Self {
+        self.attachments_root = attachments_root;
+        self
+    }
+
+    pub(crate) fn with_command_signing_key(mut self, signing_key: Option<&str>) -> Self {
+        self.command_signing_key =
+            signing_key.map(|key| hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes()));
+        self
+    }
+
+    /// Returns recent `execute_shell` runs, newest first. Output is never retained.
+    pub(crate) async fn history(&self, limit: Option<usize>) -> TerminalHistoryResponse {
+        let history = self.history.lock().await;
+        let limit = limit.unwrap_or(self.history_capacity);
+        TerminalHistoryResponse {
+            entries: history.iter().rev().take(limit).cloned().collect(),
+            capacity: self.history_capacity,
+        }
+    }
+
+    async fn record_history(&self, result: &TerminalExecResponse) {
+        if self.history_capacity == 0 {
+            return;
+        }
+
+        let mut history = self.history.lock().await;
+        while history.len() >= self.history_capacity {
+            history.pop_front();
+        }
+        history.push_back(TerminalHistoryEntry {
+            command: result.command.clone(),
+            cwd: result.cwd.clone(),
+            code: result.code,
+            timed_out: result.timed_out,
+            duration_ms: result.duration_ms,
+            executed_at: now_iso(),
+        });
+    }
+
     pub(crate) async fn execute_shell(
         &self,
         request: TerminalExecRequest,
@@ -75,8 +274,30 @@ impl TerminalService {
             return Err(BridgeError::invalid_params("command must not be empty"));
         }
 
+        // A valid token authorizes this exact command past the binary allowlist only;
+        // blocked arguments and the cwd sandbox still apply.
+        let signed = match request.command_token.as_deref() {
+            Some(token) => {
+                self.verify_command_token(command, token)?;
+                true
+            }
+            None => false,
+        };
+
+        // Read-only mode is checked before the allowlist and is not lifted by a command token.
+        if self.readonly && !is_readonly_command(&tokens) {
+            return Err(BridgeError::forbidden(
+                "terminal_readonly",
+                &format!(
+                    "Terminal is read-only on this bridge; only {} and git status are allowed.",
+                    READONLY_COMMANDS.join(", ")
+                ),
+            ));
+        }
+
         let binary = tokens[0].clone();
-        if !self.allowed_commands.is_empty() && !self.allowed_commands.contains(&binary) {
+        if !signed && !self.allowed_commands.is_empty() && !self.allowed_commands.contains(&binary)
+        {
             let mut allowed = self.allowed_commands.iter().cloned().collect::<Vec<_>>();
             allowed.sort();
             return Err(BridgeError::invalid_params(&format!(
@@ -86,16 +307,147 @@ impl TerminalService {
         }
 
         let args = tokens[1..].to_vec();
+        if let Some(blocked) = self.find_blocked_arg(&binary, &args) {
+            return Err(BridgeError::invalid_params(&format!(
+                "Argument \"{blocked}\" is not allowed for command \"{binary}\""
+            )));
+        }
+
         let cwd = resolve_exec_cwd(request.cwd.as_deref(), &self.root, self.allow_outside_root)?;
+        self.check_blocked_git_alias(&binary, &args, &cwd).await?;
 
-        self.execute_binary_internal(
-            binary.as_str(),
-            &args,
-            command.to_string(),
-            cwd,
-            request.timeout_ms,
-        )
-        .await
+        let stdout_file = if request.capture_to_file.unwrap_or(false) {
+            Some(self.prepare_captured_output_path().await?)
+        } else {
+            None
+        };
+
+        let result = self
+            .execute_binary_internal(
+                binary.as_str(),
+                &args,
+                command.to_string(),
+                cwd,
+                request.timeout_ms,
+                ExecIo {
+                    stdin_input: None,
+                    stdout_file,
+                    kill_on_overflow: true,
+                },
+            )
+            .await?;
+        self.record_history(&result).await;
+
+        Ok(result)
+    }
+
+    fn verify_command_token(&self, command: &str, token: &str) -> Result<(), BridgeError> {
+        let Some(key) = self.command_signing_key.as_ref() else {
+            return Err(BridgeError::forbidden(
+                "command_token_disabled",
+                "Command tokens are disabled on this bridge.",
+            ));
+        };
+        let expected = hmac::sign(key, command.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect::<String>();
+        if !constant_time_eq(&expected, &token.trim().to_ascii_lowercase()) {
+            return Err(BridgeError::forbidden(
+                "invalid_command_token",
+                "commandToken does not match the command.",
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn find_blocked_arg<'a>(&self, binary: &str, args: &'a [String]) -> Option<&'a str> {
+        let binary_name = Path::new(binary)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_ascii_lowercase())?;
+        let blocked = self.blocked_args.get(&binary_name)?;
+        if binary_name == "git" {
+            // `-c alias.p=push` would rename a blocked subcommand for this one run.
+            let global_options = &args[..git_subcommand_index(args).unwrap_or(args.len())];
+            if let Some(config_override) = global_options
+                .iter()
+                .find(|arg| is_git_config_override(arg))
+            {
+                return Some(config_override);
+            }
+        }
+        args.iter()
+            .find(|arg| blocked.contains(arg.as_str()))
+            .map(String::as_str)
+    }
+
+    /// Expands the alias a git command runs, so `git p` with `alias.p = push` in the repo
+    /// config is checked as `git push`. Shell aliases (`!...`) can run anything and are
+    /// refused while git has blocked arguments.
+    async fn check_blocked_git_alias(
+        &self,
+        binary: &str,
+        args: &[String],
+        cwd: &Path,
+    ) -> Result<(), BridgeError> {
+        let is_git = Path::new(binary)
+            .file_name()
+            .is_some_and(|name| name.eq_ignore_ascii_case("git"));
+        let Some(blocked) = self.blocked_args.get("git").filter(|_| is_git) else {
+            return Ok(());
+        };
+        let Some(index) = git_subcommand_index(args) else {
+            return Ok(());
+        };
+        let global_options = &args[..index];
+        let mut subcommand = args[index].clone();
+
+        for _ in 0..MAX_GIT_ALIAS_DEPTH {
+            let mut lookup = global_options.to_vec();
+            lookup.extend([
+                "config".to_string(),
+                "--get".to_string(),
+                format!("alias.{subcommand}"),
+            ]);
+            let result = self
+                .execute_binary(binary, &lookup, cwd.to_path_buf(), None)
+                .await?;
+            if result.code != Some(0) {
+                // Not an alias.
+                return Ok(());
+            }
+
+            let expansion = result.stdout.trim();
+            if expansion.starts_with('!') {
+                return Err(BridgeError::invalid_params(&format!(
+                    "Git alias \"{subcommand}\" runs a shell command, which is not allowed for \
+                     command \"git\""
+                )));
+            }
+            let tokens = shlex::split(expansion).unwrap_or_default();
+            let alias_index = git_subcommand_index(&tokens).unwrap_or(tokens.len());
+            if let Some(token) = tokens.iter().enumerate().find_map(|(position, token)| {
+                (blocked.contains(token.as_str())
+                    || (position < alias_index && is_git_config_override(token)))
+                .then_some(token)
+            }) {
+                return Err(BridgeError::invalid_params(&format!(
+                    "Argument \"{token}\" (from git alias \"{subcommand}\") is not allowed for \
+                     command \"git\""
+                )));
+            }
+            let Some(next) = tokens.get(alias_index) else {
+                return Ok(());
+            };
+            subcommand = next.clone();
+        }
+
+        Err(BridgeError::invalid_params(&format!(
+            "Git alias \"{}\" expands too deeply to check",
+            args[index]
+        )))
     }
 
     pub(crate) async fn execute_binary(
@@ -104,6 +456,18 @@ impl TerminalService {
         args: &[String],
         cwd: PathBuf,
         timeout_ms: Option<u64>,
+    ) -> Result<TerminalExecResponse, BridgeError> {
+        self.execute_binary_with_input(binary, args, cwd, timeout_ms, None)
+            .await
+    }
+
+    pub(crate) async fn execute_binary_with_input(
+        &self,
+        binary: &str,
+        args: &[String],
+        cwd: PathBuf,
+        timeout_ms: Option<u64>,
+        stdin_input: Option<Vec<u8>>,
     ) -> Result<TerminalExecResponse, BridgeError> {
         let cwd = normalize_path(&cwd);
         if !self.allow_outside_root {
@@ -120,8 +484,87 @@ impl TerminalService {
             .collect::<Vec<_>>()
             .join(" ");
 
-        self.execute_binary_internal(binary, args, display, cwd, timeout_ms)
+        self.execute_binary_internal(
+            binary,
+            args,
+            display,
+            cwd,
+            timeout_ms,
+            ExecIo {
+                stdin_input,
+                stdout_file: None,
+                kill_on_overflow: false,
+            },
+        )
+        .await
+    }
+
+    async fn prepare_captured_output_path(&self) -> Result<PathBuf, BridgeError> {
+        let dir = normalize_path(&self.attachments_root.join(CAPTURED_OUTPUT_DIR));
+        fs::create_dir_all(&dir).await.map_err(|error| {
+            BridgeError::server(&format!("failed to create output directory: {error}"))
+        })?;
+        remove_expired_captured_output(&dir, CAPTURED_OUTPUT_TTL).await;
+
+        let timestamp = Utc::now().format("%Y%m%d-%H%M%S-%3f").to_string();
+        let seq = CAPTURED_OUTPUT_SEQ.fetch_add(1, Ordering::Relaxed);
+        Ok(dir.join(format!(
+            "{timestamp}-{}-{seq}-stdout.txt",
+            std::process::id()
+        )))
+    }
+
+    /// Reads a chunk of a file written by `captureToFile`. Only regular files directly inside
+    /// the captured output directory can be read, so the path cannot reach other attachments.
+    pub(crate) async fn read_captured_output(
+        &self,
+        request: TerminalOutputReadRequest,
+    ) -> Result<TerminalOutputReadResponse, BridgeError> {
+        let not_captured = || BridgeError::invalid_params("path is not a captured output file");
+        let raw_path = request.path.trim();
+        if !Path::new(raw_path).is_absolute() {
+            return Err(not_captured());
+        }
+        let dir = fs::canonicalize(self.attachments_root.join(CAPTURED_OUTPUT_DIR))
             .await
+            .map_err(|_| not_captured())?;
+        let path = fs::canonicalize(raw_path)
+            .await
+            .map_err(|_| not_captured())?;
+        if path.parent() != Some(dir.as_path()) {
+            return Err(not_captured());
+        }
+
+        let mut file = fs::File::open(&path).await.map_err(|_| not_captured())?;
+        let metadata = file.metadata().await.map_err(|_| not_captured())?;
+        if !metadata.is_file() {
+            return Err(not_captured());
+        }
+        let size = metadata.len();
+        let offset = request.offset.unwrap_or(0).min(size);
+        let limit = request
+            .limit
+            .unwrap_or(CAPTURED_OUTPUT_READ_DEFAULT_BYTES)
+            .clamp(1, CAPTURED_OUTPUT_READ_MAX_BYTES);
+        let read_error = |error: io::Error| {
+            BridgeError::server(&format!("failed to read captured output: {error}"))
+        };
+        file.seek(SeekFrom::Start(offset))
+            .await
+            .map_err(read_error)?;
+        let mut data = Vec::new();
+        file.take(limit as u64)
+            .read_to_end(&mut data)
+            .await
+            .map_err(read_error)?;
+
+        Ok(TerminalOutputReadResponse {
+            path: raw_path.to_string(),
+            offset,
+            eof: offset + data.len() as u64 >= size,
+            data_base64: general_purpose::STANDARD.encode(&data),
+            size,
+        })
     }
 
     async fn execute_binary_internal(
@@ -131,7 +574,13 @@ impl TerminalService {
         display_command: String,
         cwd: PathBuf,
         timeout_ms: Option<u64>,
+        io: ExecIo,
     ) -> Result<TerminalExecResponse, BridgeError> {
+        let ExecIo {
+            stdin_input,
+            stdout_file,
+            kill_on_overflow,
+        } = io;
         let _permit = self
             .concurrency_limiter
             .clone()
@@ -141,52 +590,116 @@ impl TerminalService {
         let timeout_ms = timeout_ms.unwrap_or(30_000).clamp(100, 120_000);
         let started_at = Instant::now();
 
-        let mut child = Command::new(binary)
-            .args(args)
-            .current_dir(&cwd)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|error| BridgeError::server(&format!("failed to spawn command: {error}")))?;
+        let mut child = ReapingChild::new(
+            Command::new(binary)
+                .args(args)
+                .current_dir(&cwd)
+                .stdin(if stdin_input.is_some() {
+                    Stdio::piped()
+                } else {
+                    Stdio::null()
+                })
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .map_err(|error| {
+                    BridgeError::server(&format!("failed to spawn command: {error}"))
+                })?,
+        );
 
         let stdout = child
+            .child_mut()
             .stdout
             .take()
             .ok_or_else(|| BridgeError::server("failed to capture stdout"))?;
         let stderr = child
+            .child_mut()
             .stderr
             .take()
             .ok_or_else(|| BridgeError::server("failed to capture stderr"))?;
 
-        let stdout_task = tokio::spawn(async move {
-            read_stream_limited(stdout, DEFAULT_TERMINAL_MAX_OUTPUT_BYTES).await
-        });
+        if let (Some(input), Some(mut stdin)) = (stdin_input, child.child_mut().stdin.take()) {
+            tokio::spawn(async move {
+                // Dropping stdin after the write signals EOF to the child.
+                let _ = stdin.write_all(&input).await;
+            });
+        }
 
+        let max_output_bytes = self.max_output_bytes;
+        let overflow = Arc::new(OutputOverflow::default());
+        let stdout_overflow = overflow.clone();
+        let stdout_task = match stdout_file.clone() {
+            Some(path) => tokio::spawn(async move {
+                copy_stream_to_file(
+                    stdout,
+                    &path,
+                    CAPTURED_OUTPUT_MAX_FILE_BYTES,
+                    CAPTURED_OUTPUT_PREVIEW_BYTES,
+                    &stdout_overflow,
+                )
+                .await
+            }),
+            None => tokio::spawn(async move {
+                Ok(read_stream_limited(
+                    stdout,
+                    max_output_bytes,
+                    &stdout_overflow,
+                    !kill_on_overflow,
+                )
+                .await)
+            }),
+        };
+
+        let stderr_overflow = overflow.clone();
         let stderr_task = tokio::spawn(async move {
-            read_stream_limited(stderr, DEFAULT_TERMINAL_MAX_OUTPUT_BYTES).await
+            read_stream_limited(
+                stderr,
+                max_output_bytes,
+                &stderr_overflow,
+                !kill_on_overflow,
+            )
+            .await
         });
 
         let mut timed_out = false;
         let mut exit_code = None;
         let mut wait_error: Option<String> = None;
 
-        match timeout(Duration::from_millis(timeout_ms), child.wait()).await {
-            Ok(Ok(status)) => {
+        let waited = timeout(Duration::from_millis(timeout_ms), async {
+            tokio::select! {
+                biased;
+                status = child.wait() => Some(status),
+                _ = overflow.notify.notified(), if kill_on_overflow => None,
+            }
+        })
+        .await;
+        match waited {
+            Ok(Some(Ok(status))) => {
                 exit_code = status.code();
             }
-            Ok(Err(error)) => {
+            Ok(Some(Err(error))) => {
                 wait_error = Some(error.to_string());
                 exit_code = Some(-1);
             }
+            Ok(None) => {
+                // Output went past the cap; stop the command rather than letting it run on.
+                child.kill_and_wait().await;
+            }
             Err(_) => {
                 timed_out = true;
-                let _ = child.kill().await;
-                let _ = child.wait().await;
+                child.kill_and_wait().await;
             }
         }
 
-        let (stdout_bytes, stdout_truncated) = stdout_task.await.unwrap_or_default();
+        let (stdout_bytes, stdout_truncated) = match stdout_task.await {
+            Ok(Ok(output)) => output,
+            Ok(Err(error)) => {
+                return Err(BridgeError::server(&format!(
+                    "failed to write captured output: {error}"
+                )))
+            }
+            Err(_) => Default::default(),
+        };
         let (stderr_bytes, stderr_truncated) = stderr_task.await.unwrap_or_default();
 
         let stdout_text = finalize_output(stdout_bytes, stdout_truncated);
@@ -204,7 +717,9 @@ impl TerminalService {
             code: exit_code,
             stdout: stdout_text,
             stderr: stderr_text,
+            stdout_path: stdout_file.map(|path| path.to_string_lossy().to_string()),
             timed_out,
+            truncated: overflow.is_tripped(),
             duration_ms: started_at.elapsed().as_millis() as u64,
         })
     }
@@ -212,7 +727,7 @@ impl TerminalService {
 
 fn resolve_exec_cwd(
     raw_cwd: Option<&str>,
-    root: &PathBuf,
+    root: &Path,
     allow_outside_root: bool,
 ) -> Result<PathBuf, BridgeError> {
     let normalized_root = normalize_path(root);
@@ -238,7 +753,36 @@ fn resolve_exec_cwd(
     Ok(normalized)
 }
 
-async fn read_stream_limited<R>(mut reader: R, max_bytes: usize) -> (Vec<u8>, bool)
+/// Deletes files in `dir` last modified more than `ttl` ago. Best effort: files that
+/// can't be read or removed are left for the next sweep.
+async fn remove_expired_captured_output(dir: &Path, ttl: Duration) {
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        let expired = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > ttl);
+        if metadata.is_file() && expired {
+            let _ = fs::remove_file(entry.path()).await;
+        }
+    }
+}
+
+/// Reads `reader` until EOF or until it produces more than `max_bytes`, in which case
+/// `overflow` is tripped and reading stops, or with `drain` continues to EOF discarding
+/// everything past the cap.
+async fn read_stream_limited<R>(
+    mut reader: R,
+    max_bytes: usize,
+    overflow: &OutputOverflow,
+    drain: bool,
+) -> (Vec<u8>, bool)
 where
     R: AsyncRead + Unpin,
 {
@@ -253,21 +797,63 @@ where
             Err(_) => break,
         };
 
-        if bytes.len() < max_bytes {
-            let remaining = max_bytes - bytes.len();
-            let to_take = remaining.min(read);
-            bytes.extend_from_slice(&buffer[..to_take]);
-            if to_take < read {
-                truncated = true;
-            }
-        } else {
+        let to_take = (max_bytes - bytes.len()).min(read);
+        bytes.extend_from_slice(&buffer[..to_take]);
+        if to_take < read {
+            overflow.trip();
             truncated = true;
+            if !drain {
+                break;
+            }
         }
     }
 
     (bytes, truncated)
 }
 
+/// Streams `reader` into `path` and returns the leading `preview_bytes` plus whether the
+/// preview is shorter than the captured output. Output past `max_file_bytes` trips
+/// `overflow`, which the caller reports as `truncated`.
+async fn copy_stream_to_file<R>(
+    mut reader: R,
+    path: &Path,
+    max_file_bytes: usize,
+    preview_bytes: usize,
+    overflow: &OutputOverflow,
+) -> std::io::Result<(Vec<u8>, bool)>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut file = fs::File::create(path).await?;
+    let mut preview = Vec::new();
+    let mut written = 0usize;
+    let mut buffer = [0_u8; OUTPUT_READ_CHUNK_SIZE];
+
+    loop {
+        let read = match reader.read(&mut buffer).await {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(_) => break,
+        };
+
+        if preview.len() < preview_bytes {
+            let to_take = (preview_bytes - preview.len()).min(read);
+            preview.extend_from_slice(&buffer[..to_take]);
+        }
+        let to_write = (max_file_bytes - written).min(read);
+        file.write_all(&buffer[..to_write]).await?;
+        written += to_write;
+        if to_write < read {
+            overflow.trip();
+            break;
+        }
+    }
+    file.flush().await?;
+
+    let truncated = written > preview.len();
+    Ok((preview, truncated))
+}
+
 fn finalize_output(bytes: Vec<u8>, truncated: bool) -> String {
     let mut output = String::from_utf8_lossy(&bytes).trim_end().to_string();
     if truncated {
@@ -279,10 +865,75 @@ fn finalize_output(bytes: Vec<u8>, truncated: bool) -> String {
     output
 }
 
+fn is_readonly_command(tokens: &[String]) -> bool {
+    match tokens.first().map(String::as_str) {
+        Some("git") => tokens
+            .get(1)
+            .is_some_and(|subcommand| READONLY_GIT_SUBCOMMANDS.contains(&subcommand.as_str())),
+        Some(binary) => READONLY_COMMANDS.contains(&binary),
+        None => false,
+    }
+}
+
+/// Index of the git subcommand in `args`, past any global options.
+fn git_subcommand_index(args: &[String]) -> Option<usize> {
+    let mut index = 0;
+    while let Some(arg) = args.get(index) {
+        if !arg.starts_with('-') {
+            return Some(index);
+        }
+        index += if GIT_GLOBAL_OPTIONS_WITH_VALUE.contains(&arg.as_str()) {
+            2
+        } else {
+            1
+        };
+    }
+    None
+}
+
+/// `-c` and `--config-env` set config for a single run, aliases included.
+fn is_git_config_override(arg: &str) -> bool {
+    arg.starts_with("-c") || arg == "--config-env" || arg.starts_with("--config-env=")
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{finalize_output, resolve_exec_cwd};
-    use std::path::PathBuf;
+    #[cfg(unix)]
+    use super::ReapingChild;
+    use super::{
+        copy_stream_to_file, finalize_output, remove_expired_captured_output, resolve_exec_cwd,
+        OutputOverflow, TerminalService,
+    };
+    use crate::{
+        normalize_path, BridgeError, TerminalExecRequest, TerminalExecResponse,
+        TerminalOutputReadRequest, MOBILE_ATTACHMENTS_DIR,
+    };
+    use base64::{engine::general_purpose, Engine as _};
+    use ring::hmac;
+    use std::{
+        collections::{HashMap, HashSet},
+        env,
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+    #[cfg(unix)]
+    use std::{process::Stdio, time::Instant};
+    #[cfg(unix)]
+    use tokio::process::Command;
+
+    fn test_exec_response(command: &str) -> TerminalExecResponse {
+        TerminalExecResponse {
+            command: command.to_string(),
+            cwd: "/bridge/root".to_string(),
+            code: Some(0),
+            stdout: "secret output".to_string(),
+            stderr: String::new(),
+            stdout_path: None,
+            timed_out: false,
+            truncated: false,
+            duration_ms: 5,
+        }
+    }
 
     #[test]
     fn resolves_relative_exec_cwd_against_root() {
@@ -323,4 +974,624 @@ mod tests {
             "hello\n[output truncated]"
         );
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn executed_commands_are_recorded_in_history() {
+        let root = normalize_path(&env::temp_dir());
+        let terminal = TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            4,
+        );
+
+        terminal
+            .execute_shell(TerminalExecRequest {
+                command: "pwd".to_string(),
+                cwd: None,
+                timeout_ms: None,
+                capture_to_file: None,
+                command_token: None,
+            })
+            .await
+            .expect("execute pwd");
+
+        let history = terminal.history(None).await;
+        assert_eq!(history.entries.len(), 1);
+        assert_eq!(history.entries[0].command, "pwd");
+        assert_eq!(history.entries[0].cwd, root.to_string_lossy());
+        assert_eq!(history.entries[0].code, Some(0));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn runaway_output_is_truncated_and_the_command_killed() {
+        let root = normalize_path(&env::temp_dir());
+        let terminal = TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            0,
+        )
+        .with_max_output_bytes(1024);
+
+        let result = terminal
+            .execute_shell(TerminalExecRequest {
+                command: "yes".to_string(),
+                cwd: None,
+                timeout_ms: Some(10_000),
+                capture_to_file: None,
+                command_token: None,
+            })
+            .await
+            .expect("execute yes");
+
+        assert!(result.truncated);
+        assert!(!result.timed_out);
+        assert_eq!(result.code, None);
+        assert!(result.stdout.ends_with("[output truncated]"));
+        assert!(result.stdout.len() <= 1024 + "\n[output truncated]".len());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn internal_commands_drain_past_the_cap_and_keep_their_exit_code() {
+        let root = normalize_path(&env::temp_dir());
+        let terminal = TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            0,
+        )
+        .with_max_output_bytes(1024);
+
+        let args = ["-c".to_string(), "yes | head -c 200000".to_string()];
+        let result = terminal
+            .execute_binary("sh", &args, root, Some(10_000))
+            .await
+            .expect("execute sh");
+
+        assert!(result.truncated);
+        assert!(!result.timed_out);
+        assert_eq!(result.code, Some(0));
+        assert!(result.stdout.ends_with("[output truncated]"));
+    }
+
+    #[tokio::test]
+    async fn history_evicts_oldest_entries_past_capacity() {
+        let terminal = TerminalService::new(
+            PathBuf::from("/bridge/root"),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            2,
+        );
+
+        for command in ["ls", "pwd", "git status"] {
+            terminal.record_history(&test_exec_response(command)).await;
+        }
+
+        let history = terminal.history(None).await;
+        assert_eq!(history.capacity, 2);
+        let commands = history
+            .entries
+            .iter()
+            .map(|entry| entry.command.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(commands, vec!["git status", "pwd"]);
+
+        let limited = terminal.history(Some(1)).await;
+        assert_eq!(limited.entries.len(), 1);
+        assert_eq!(limited.entries[0].command, "git status");
+    }
+
+    fn blocked_git_terminal() -> TerminalService {
+        let blocked = HashMap::from([(
+            "git".to_string(),
+            HashSet::from(["push".to_string(), "reset".to_string()]),
+        )]);
+        TerminalService::new(
+            normalize_path(&env::temp_dir()),
+            HashSet::new(),
+            blocked,
+            false,
+            false,
+            0,
+        )
+    }
+
+    fn git_only_terminal(signing_key: Option<&str>) -> TerminalService {
+        TerminalService::new(
+            normalize_path(&env::temp_dir()),
+            HashSet::from(["git".to_string()]),
+            HashMap::new(),
+            false,
+            false,
+            0,
+        )
+        .with_command_signing_key(signing_key)
+    }
+
+    fn sign_command(key: &str, command: &str) -> String {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, key.as_bytes());
+        hmac::sign(&key, command.as_bytes())
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+
+    fn signed_request(command: &str, token: Option<String>) -> TerminalExecRequest {
+        TerminalExecRequest {
+            command: command.to_string(),
+            cwd: None,
+            timeout_ms: None,
+            capture_to_file: None,
+            command_token: token,
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn valid_command_token_bypasses_binary_allowlist() {
+        let terminal = git_only_terminal(Some("automation-key"));
+
+        let unsigned = terminal
+            .execute_shell(signed_request("pwd", None))
+            .await
+            .expect_err("pwd is outside the allowlist");
+        assert_eq!(unsigned.code, -32602);
+
+        let token = sign_command("automation-key", "pwd").to_ascii_uppercase();
+        let result = terminal
+            .execute_shell(signed_request("  pwd  ", Some(token)))
+            .await
+            .expect("signed pwd runs");
+        assert_eq!(result.code, Some(0));
+
+        let outside_cwd = terminal
+            .execute_shell(TerminalExecRequest {
+                cwd: Some("../..".to_string()),
+                ..signed_request("pwd", Some(sign_command("automation-key", "pwd")))
+            })
+            .await
+            .expect_err("signed commands still honor the cwd sandbox");
+        assert_eq!(outside_cwd.code, -32602);
+    }
+
+    #[tokio::test]
+    async fn invalid_or_unconfigured_command_token_is_rejected() {
+        let terminal = git_only_terminal(Some("automation-key"));
+        for token in [
+            sign_command("other-key", "pwd"),
+            sign_command("automation-key", "pwd -P"),
+            String::new(),
+        ] {
+            let error = terminal
+                .execute_shell(signed_request("pwd", Some(token)))
+                .await
+                .expect_err("mismatched token is rejected");
+            assert_eq!(error.code, -32003);
+            assert_eq!(
+                error.data.as_ref().map(|data| data["error"].clone()),
+                Some(serde_json::json!("invalid_command_token"))
+            );
+        }
+
+        let error = git_only_terminal(None)
+            .execute_shell(signed_request(
+                "pwd",
+                Some(sign_command("automation-key", "pwd")),
+            ))
+            .await
+            .expect_err("tokens are disabled without a signing key");
+        assert_eq!(
+            error.data.as_ref().map(|data| data["error"].clone()),
+            Some(serde_json::json!("command_token_disabled"))
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_blocked_subcommand_for_allowed_binary() {
+        let error = blocked_git_terminal()
+            .execute_shell(TerminalExecRequest {
+                command: "git push --force origin main".to_string(),
+                cwd: None,
+                timeout_ms: None,
+                capture_to_file: None,
+                command_token: None,
+            })
+            .await
+            .expect_err("git push should be blocked");
+
+        assert_eq!(error.code, -32602);
+        assert!(error.message.contains("\"push\""));
+    }
+
+    #[tokio::test]
+    async fn git_aliases_and_config_overrides_cannot_rename_a_blocked_subcommand() {
+        let nonce = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let repo_name = format!("clawdex-blocked-alias-{}-{nonce}", std::process::id());
+        let repo = normalize_path(&env::temp_dir()).join(&repo_name);
+        std::fs::create_dir_all(&repo).expect("create repo dir");
+        for args in [
+            &["init", "--quiet"][..],
+            &["config", "alias.p", "push"],
+            &["config", "alias.pp", "p --force"],
+            &["config", "alias.sh", "!git push"],
+            &["config", "alias.st", "status --short"],
+        ] {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(&repo)
+                .args(args)
+                .status()
+                .expect("run git");
+            assert!(status.success(), "git {args:?} failed");
+        }
+        let terminal = blocked_git_terminal();
+        let exec = |command: &str| {
+            terminal.execute_shell(TerminalExecRequest {
+                command: command.to_string(),
+                cwd: Some(repo_name.clone()),
+                timeout_ms: None,
+                capture_to_file: None,
+                command_token: None,
+            })
+        };
+
+        for (command, mentions) in [
+            ("git -c alias.q=push q", "\"-c\""),
+            ("git --config-env=alias.q=ALIAS q", "--config-env"),
+            ("git p origin main", "\"push\""),
+            ("git pp", "\"push\""),
+            ("git sh", "shell command"),
+        ] {
+            let error = exec(command).await.expect_err(command);
+            assert_eq!(error.code, -32602, "{command}");
+            assert!(
+                error.message.contains(mentions),
+                "{command}: {}",
+                error.message
+            );
+        }
+
+        let status = exec("git st").await.expect("non-blocked alias runs");
+        assert_eq!(status.code, Some(0), "{}", status.stderr);
+        let commit = exec("git -C . commit -c HEAD --dry-run").await;
+        assert!(
+            commit.is_ok(),
+            "-c after the subcommand is not a config override"
+        );
+
+        std::fs::remove_dir_all(&repo).ok();
+    }
+
+    #[tokio::test]
+    async fn runs_binary_when_args_are_not_blocked() {
+        let result = blocked_git_terminal()
+            .execute_shell(TerminalExecRequest {
+                command: "git --version".to_string(),
+                cwd: None,
+                timeout_ms: None,
+                capture_to_file: None,
+                command_token: None,
+            })
+            .await
+            .expect("git --version is allowed");
+
+        assert_eq!(result.code, Some(0));
+        assert!(result.stdout.starts_with("git version"));
+    }
+
+    fn readonly_terminal() -> TerminalService {
+        TerminalService::new(
+            normalize_path(&env::temp_dir()),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            0,
+        )
+        .with_readonly(true)
+        .with_command_signing_key(Some("automation-key"))
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn readonly_mode_runs_read_only_commands() {
+        let terminal = readonly_terminal();
+        for command in ["ls", "pwd"] {
+            let result = terminal
+                .execute_shell(signed_request(command, None))
+                .await
+                .expect("read-only command runs");
+            assert_eq!(result.code, Some(0));
+        }
+    }
+
+    #[tokio::test]
+    async fn readonly_mode_rejects_write_commands_even_when_signed() {
+        let terminal = readonly_terminal();
+        for command in ["touch clawdex-readonly-probe", "git commit -m probe", "git"] {
+            let error = terminal
+                .execute_shell(signed_request(command, None))
+                .await
+                .expect_err("write command is rejected");
+            assert_eq!(error.code, -32003);
+            assert_eq!(
+                error.data.as_ref().map(|data| data["error"].clone()),
+                Some(serde_json::json!("terminal_readonly"))
+            );
+        }
+
+        let signed = terminal
+            .execute_shell(signed_request(
+                "touch clawdex-readonly-probe",
+                Some(sign_command(
+                    "automation-key",
+                    "touch clawdex-readonly-probe",
+                )),
+            ))
+            .await
+            .expect_err("a command token does not lift read-only mode");
+        assert_eq!(signed.code, -32003);
+    }
+
+    #[cfg(unix)]
+    fn process_exists(pid: u32) -> bool {
+        // Signal 0 only checks for existence; zombies still count until reaped.
+        unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+    }
+
+    #[cfg(unix)]
+    async fn spawn_then_fail(pid_out: &mut Option<u32>) -> Result<(), BridgeError> {
+        let mut child = ReapingChild::new(
+            Command::new("sleep")
+                .arg("30")
+                .stdout(Stdio::null())
+                .spawn()
+                .expect("spawn sleep"),
+        );
+        *pid_out = child.child_mut().id();
+        // stdout was not piped, so this fails like a capture error after spawn.
+        child
+            .child_mut()
+            .stdout
+            .take()
+            .ok_or_else(|| BridgeError::server("failed to capture stdout"))?;
+        child
+            .wait()
+            .await
+            .map(|_| ())
+            .map_err(|error| BridgeError::server(&error.to_string()))
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn error_after_spawn_kills_and_reaps_child() {
+        let mut pid = None;
+        let error = spawn_then_fail(&mut pid).await.expect_err("capture fails");
+        assert_eq!(error.message, "failed to capture stdout");
+        let pid = pid.expect("child pid");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while process_exists(pid) && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert!(
+            !process_exists(pid),
+            "child {pid} was left running or unreaped"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn captured_output_is_written_to_file_with_preview() {
+        let root = normalize_path(&env::temp_dir());
+        let terminal = TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            0,
+        );
+
+        let result = terminal
+            .execute_shell(TerminalExecRequest {
+                command: "seq 1 5000".to_string(),
+                cwd: None,
+                timeout_ms: None,
+                capture_to_file: Some(true),
+                command_token: None,
+            })
+            .await
+            .expect("execute seq");
+
+        let stdout_path = result.stdout_path.expect("stdout path");
+        let path = PathBuf::from(&stdout_path);
+        assert!(path.starts_with(root.join(MOBILE_ATTACHMENTS_DIR)));
+        let captured = std::fs::read_to_string(&path).expect("read captured output");
+        let tail = terminal
+            .read_captured_output(TerminalOutputReadRequest {
+                path: stdout_path,
+                offset: Some(captured.len() as u64 - 10),
+                limit: None,
+            })
+            .await
+            .expect("read captured output through the bridge");
+        std::fs::remove_file(&path).ok();
+        assert_eq!(
+            general_purpose::STANDARD
+                .decode(&tail.data_base64)
+                .expect("base64 chunk"),
+            b"4999\n5000\n"
+        );
+        assert_eq!(tail.size, captured.len() as u64);
+        assert!(tail.eof);
+
+        assert!(captured.ends_with("4999\n5000\n"));
+        assert!(result.stdout.len() < captured.len());
+        assert!(result.stdout.starts_with("1\n2\n3"));
+        assert!(result.stdout.ends_with("[output truncated]"));
+    }
+
+    #[tokio::test]
+    async fn captured_output_paths_are_unique_within_one_millisecond() {
+        let nonce = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let root = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-captured-output-paths-{}-{nonce}",
+            std::process::id()
+        )));
+        let terminal = TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            0,
+        );
+
+        let mut paths = HashSet::new();
+        for _ in 0..50 {
+            let path = terminal
+                .prepare_captured_output_path()
+                .await
+                .expect("prepare output path");
+            assert!(paths.insert(path), "captured output path was reused");
+        }
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn captured_output_reads_stay_inside_the_output_directory() {
+        let nonce = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let root = normalize_path(&env::temp_dir().join(format!(
+            "clawdex-captured-output-read-{}-{nonce}",
+            std::process::id()
+        )));
+        let terminal = TerminalService::new(
+            root.clone(),
+            HashSet::new(),
+            HashMap::new(),
+            false,
+            false,
+            0,
+        );
+        let captured = terminal
+            .prepare_captured_output_path()
+            .await
+            .expect("prepare output path");
+        std::fs::write(&captured, "0123456789").expect("write captured output");
+        let attachment = root.join(MOBILE_ATTACHMENTS_DIR).join("photo.png");
+        std::fs::write(&attachment, "png").expect("write attachment");
+        let read = |path: String, offset: Option<u64>, limit: Option<usize>| {
+            terminal.read_captured_output(TerminalOutputReadRequest {
+                path,
+                offset,
+                limit,
+            })
+        };
+
+        let chunk = read(captured.to_string_lossy().to_string(), Some(2), Some(3))
+            .await
+            .expect("read chunk");
+        assert_eq!(chunk.data_base64, general_purpose::STANDARD.encode("234"));
+        assert_eq!((chunk.offset, chunk.size, chunk.eof), (2, 10, false));
+
+        let traversal = captured
+            .parent()
+            .expect("output dir")
+            .join("..")
+            .join("photo.png");
+        for path in [
+            attachment.to_string_lossy().to_string(),
+            traversal.to_string_lossy().to_string(),
+            captured
+                .parent()
+                .expect("output dir")
+                .to_string_lossy()
+                .to_string(),
+            "terminal-output/missing.txt".to_string(),
+        ] {
+            let error = read(path.clone(), None, None)
+                .await
+                .expect_err("path outside the output directory");
+            assert_eq!(error.code, -32602, "{path}");
+        }
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[tokio::test]
+    async fn captured_output_past_the_file_cap_is_reported_as_truncated() {
+        let nonce = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let dir = env::temp_dir().join(format!(
+            "clawdex-captured-output-{}-{nonce}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join("stdout.txt");
+        let overflow = OutputOverflow::default();
+
+        let (preview, _) = copy_stream_to_file(&[b'x'; 100][..], &path, 64, 16, &overflow)
+            .await
+            .expect("copy stream");
+        assert_eq!(preview.len(), 16);
+        assert_eq!(std::fs::metadata(&path).expect("metadata").len(), 64);
+        assert!(overflow.is_tripped());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn expired_captured_output_files_are_removed() {
+        let nonce = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .expect("valid time")
+            .as_nanos();
+        let dir = env::temp_dir().join(format!(
+            "clawdex-captured-output-ttl-{}-{nonce}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let fresh = dir.join("fresh-stdout.txt");
+        let stale = dir.join("stale-stdout.txt");
+        std::fs::write(&fresh, "fresh").expect("write fresh");
+        std::fs::write(&stale, "stale").expect("write stale");
+        std::fs::File::options()
+            .write(true)
+            .open(&stale)
+            .expect("open stale")
+            .set_modified(SystemTime::now() - Duration::from_secs(2 * 60 * 60))
+            .expect("backdate stale");
+
+        remove_expired_captured_output(&dir, Duration::from_secs(60 * 60)).await;
+
+        assert!(fresh.exists());
+        assert!(!stale.exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }